@@ -0,0 +1,35 @@
+/* C ABI for the pexels client (build with `cargo build -p pexels --features ffi`).
+ *
+ * Every call returning `char *` yields a JSON envelope: {"data": ...} on
+ * success or {"error": ...} on failure. Release it with pexels_string_free.
+ */
+#ifndef PEXELS_H
+#define PEXELS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PexelsClient PexelsClient;
+
+/* host may be NULL for https://api.pexels.com; returns NULL on failure. */
+PexelsClient *pexels_client_new(const char *token, const char *host);
+void pexels_client_free(PexelsClient *client);
+
+/* per_page/limit of 0 use API defaults / first page only. */
+char *pexels_search_photos(const PexelsClient *client, const char *query,
+                           uint32_t per_page, uint32_t limit);
+
+/* size: "original", "large2x", "large", ... (NULL = original). */
+char *pexels_download(const PexelsClient *client, const char *photo_id,
+                      const char *size, const char *path);
+
+void pexels_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PEXELS_H */
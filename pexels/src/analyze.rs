@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+
+// `--analyze`: per-quadrant average color and busy-ness of a photo, for
+// picking images with a quiet area to put text on. Busy-ness is edge
+// density: the share of pixels whose Sobel gradient on luma exceeds
+// EDGE_THRESHOLD, measured on a copy downscaled to SAMPLE_SIDE so the
+// number does not depend on the resolution that was downloaded.
+
+// Longest side the image is reduced to before measuring.
+const SAMPLE_SIDE: u32 = 256;
+// Gradient magnitude (0-255 scale) that counts as an edge.
+const EDGE_THRESHOLD: f64 = 24.0;
+
+pub const QUADRANTS: [&str; 4] = ["top_left", "top_right", "bottom_left", "bottom_right"];
+
+// `{quadrants: {top_left: {avg_color, busyness}, ...}, busyness, quietest}`;
+// `quietest` is the quadrant with the lowest busy-ness.
+pub fn analyze(img: &DynamicImage) -> JsonValue {
+    let small = if img.width().max(img.height()) > SAMPLE_SIDE {
+        img.thumbnail(SAMPLE_SIDE, SAMPLE_SIDE).to_rgb8()
+    } else {
+        img.to_rgb8()
+    };
+    let (w, h) = small.dimensions();
+    let luma: Vec<f64> = small
+        .pixels()
+        .map(|p| 0.2126 * p[0] as f64 + 0.7152 * p[1] as f64 + 0.0722 * p[2] as f64)
+        .collect();
+    let at = |x: u32, y: u32| luma[(y * w + x) as usize];
+    // Per quadrant: RGB sums, pixels, edge pixels
+    let mut acc = [([0u64; 3], 0u64, 0u64); 4];
+    for y in 0..h {
+        for x in 0..w {
+            let q = (y >= h / 2) as usize * 2 + (x >= w / 2) as usize;
+            let p = small.get_pixel(x, y).0;
+            for (s, c) in acc[q].0.iter_mut().zip(p) {
+                *s += c as u64;
+            }
+            acc[q].1 += 1;
+            let interior = x > 0 && y > 0 && x + 1 < w && y + 1 < h;
+            if interior {
+                let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x - 1, y)
+                    - at(x - 1, y + 1);
+                let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                    - at(x - 1, y - 1)
+                    - 2.0 * at(x, y - 1)
+                    - at(x + 1, y - 1);
+                // Sobel kernels sum to 4x the step; bring it back to 0-255
+                if (gx * gx + gy * gy).sqrt() / 4.0 > EDGE_THRESHOLD {
+                    acc[q].2 += 1;
+                }
+            }
+        }
+    }
+    let mut quadrants = serde_json::Map::new();
+    let mut quietest = (QUADRANTS[0], f64::MAX);
+    for (name, (sum, n, edges)) in QUADRANTS.iter().zip(acc) {
+        let n1 = n.max(1);
+        let avg = sum.map(|s| (s as f64 / n1 as f64).round() as u8);
+        let busy = round3(edges as f64 / n1 as f64);
+        if busy < quietest.1 {
+            quietest = (name, busy);
+        }
+        quadrants.insert(
+            name.to_string(),
+            serde_json::json!({
+                "avg_color": crate::contrast::hex(avg),
+                "busyness": busy,
+            }),
+        );
+    }
+    let (edges, n) = acc.iter().fold((0, 0), |(e, n), q| (e + q.2, n + q.1));
+    serde_json::json!({
+        "quadrants": quadrants,
+        "busyness": round3(edges as f64 / n.max(1) as f64),
+        "quietest": quietest.0,
+    })
+}
+
+// `photo-1.jpeg` -> `photo-1.analysis.json`
+pub fn sidecar_path(file: &Path) -> PathBuf {
+    file.with_extension("analysis.json")
+}
+
+// Analyze a downloaded photo and write the report next to it; returns the
+// report with `sidecar` set.
+pub fn analyze_file(file: &Path) -> Result<JsonValue> {
+    let img = image::ImageReader::open(file)
+        .context("open downloaded photo")?
+        .with_guessed_format()
+        .context("read downloaded photo")?
+        .decode()
+        .map_err(|e| analysis_error(file, &e.to_string()))?;
+    let mut report = analyze(&img);
+    let sidecar = sidecar_path(file);
+    let tmp = crate::download::part_path(&sidecar);
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&report)?).context("write analysis sidecar")?;
+    std::fs::rename(&tmp, &sidecar).context("write analysis sidecar")?;
+    report["sidecar"] = JsonValue::String(sidecar.display().to_string());
+    Ok(report)
+}
+
+fn analysis_error(path: &Path, reason: &str) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "analysis failed",
+        "path": path.display().to_string(),
+        "reason": reason,
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "analysis failed".into()))
+}
+
+fn round3(v: f64) -> f64 {
+    (v * 1000.0).round() / 1000.0
+}
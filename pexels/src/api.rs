@@ -1,9 +1,16 @@
 use crate::config::Config;
+use crate::download::{Fetched, Transfer, Validators};
 use crate::util::backoff_delay;
-use anyhow::Result;
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, AUTHORIZATION, USER_AGENT};
-use reqwest::{Client, Response, StatusCode, Url};
+use anyhow::{Context, Result};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, AUTHORIZATION, ETAG,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE, USER_AGENT,
+};
+use reqwest::{Client, Method, Response, StatusCode, Url};
 use serde_json::Value as JsonValue;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::warn;
 
@@ -11,8 +18,46 @@ use tracing::warn;
 pub struct PexelsClient {
     cfg: Config,
     http: Client,
+    // Host-profile headers, only ever sent to the API host
+    gateway: HeaderMap,
+    // HTTP requests sent by this client (and its clones)
+    calls: Arc<AtomicU64>,
+    // Latest X-Ratelimit-* window seen by this client (and its clones)
+    window: Arc<Mutex<Option<crate::quota::Window>>>,
+    // Set when a response brought a window not yet written to the state db
+    window_unsaved: Arc<AtomicBool>,
+    // Send attempts so far, for --inject-fault
+    #[cfg(feature = "fault-injection")]
+    attempts: Arc<AtomicU64>,
 }
 
+// A paged list endpoint; `filters` are extra query parameters.
+pub enum Listing<'q> {
+    PhotosSearch {
+        query: &'q str,
+        filters: Vec<(String, String)>,
+    },
+    PhotosCurated,
+    VideosSearch {
+        query: &'q str,
+        filters: Vec<(String, String)>,
+    },
+    VideosPopular {
+        filters: Vec<(String, String)>,
+    },
+    CollectionsList,
+    CollectionsFeatured,
+    CollectionsItems {
+        id: &'q str,
+    },
+}
+
+// Item arrays of a list response as (input key, output key).
+type ItemKeys = &'static [(&'static str, &'static str)];
+
+// Endpoint, query parameters and item keys of a list request.
+type ListRequest = (Url, Vec<(String, String)>, ItemKeys);
+
 impl PexelsClient {
     pub fn new(cfg: Config) -> Result<Self> {
         let mut headers = HeaderMap::new();
@@ -40,46 +85,374 @@ impl PexelsClient {
         );
         headers.insert(USER_AGENT, HeaderValue::from_str(&ua).unwrap());
 
-        let http = Client::builder()
-            .default_headers(headers)
-            .timeout(Duration::from_secs(cfg.timeout_secs))
-            .build()?;
-        Ok(Self { cfg, http })
+        // Gateway headers from --host-profile; sent per request (see
+        // `request`), where they override the defaults above
+        let mut gateway = HeaderMap::new();
+        if let Some(profile) = &cfg.host_profile {
+            for (name, value) in &profile.headers {
+                let key = HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("invalid header name in host profile: {}", name))?;
+                let val = HeaderValue::from_str(value)
+                    .with_context(|| format!("invalid value for header {}", name))?;
+                gateway.insert(key, val);
+            }
+        }
+
+        let builder = Client::builder().default_headers(headers);
+        // The browser fetch backend has no client-wide timeout
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(Duration::from_secs(cfg.timeout_secs));
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = match cfg.host_profile.as_ref().and_then(|p| p.cacert.as_ref()) {
+            Some(path) => {
+                let pem = std::fs::read(path)
+                    .with_context(|| format!("read cacert {}", path.display()))?;
+                reqwest::Certificate::from_pem_bundle(&pem)
+                    .with_context(|| format!("parse cacert {}", path.display()))?
+                    .into_iter()
+                    .fold(builder, |b, cert| b.add_root_certificate(cert))
+            }
+            None => builder,
+        };
+        let http = builder.build()?;
+        Ok(Self {
+            cfg,
+            http,
+            gateway,
+            calls: Arc::new(AtomicU64::new(0)),
+            window: Arc::new(Mutex::new(None)),
+            window_unsaved: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "fault-injection")]
+            attempts: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    // Library convenience: CLI defaults (15s timeout, 3 retries) with a token.
+    pub fn with_token(token: impl Into<String>) -> Result<Self> {
+        Self::new(Config {
+            token: Some(token.into()),
+            timeout_secs: 15,
+            max_retries: 3,
+            ..Default::default()
+        })
+    }
+
+    // Client for a changed config that keeps counting into this one's calls.
+    pub fn with_config(&self, cfg: Config) -> Result<Self> {
+        let mut next = Self::new(cfg)?;
+        next.calls = self.calls.clone();
+        next.window = self.window.clone();
+        next.window_unsaved = self.window_unsaved.clone();
+        #[cfg(feature = "fault-injection")]
+        {
+            next.attempts = self.attempts.clone();
+        }
+        Ok(next)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.cfg
+    }
+
+    // Typed photo endpoints with streaming pagination.
+    pub fn photos(&self) -> crate::pages::Photos<'_> {
+        crate::pages::Photos::new(self)
+    }
+
+    pub fn videos(&self) -> crate::pages::Videos<'_> {
+        crate::pages::Videos::new(self)
+    }
+
+    pub fn collections(&self) -> crate::pages::Collections<'_> {
+        crate::pages::Collections::new(self)
+    }
+
+    // Typed single-resource lookups over the JSON path (`photos_get`, ...).
+    pub async fn photo(&self, id: &str) -> Result<crate::models::Photo> {
+        let v = self.photos_get(id).await?;
+        serde_json::from_value(v).context("parse photo")
+    }
+
+    pub async fn video(&self, id: &str) -> Result<crate::models::Video> {
+        let v = self.videos_get(id).await?;
+        serde_json::from_value(v).context("parse video")
     }
 
     // Download arbitrary URL without query params.
+    // Media transfers get their own timeout and retry budget: files are far
+    // larger than JSON pages and CDN hiccups are more common.
     pub async fn download_url_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let fetched = self
+            .download_url_conditional(url, &Validators::default())
+            .await?;
+        Ok(fetched.body.unwrap_or_default())
+    }
+
+    // Media download revalidating a copy already on disk: `prev` validators
+    // are sent as If-None-Match / If-Modified-Since, and a 304 comes back as
+    // `Fetched { body: None, .. }`.
+    pub async fn download_url_conditional(&self, url: &str, prev: &Validators) -> Result<Fetched> {
         let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!(e))?;
-        self.req_bytes(parsed, vec![]).await
+        let (timeout, retries) = self.download_budget();
+        self.fetch_bytes(
+            parsed,
+            vec![],
+            conditional_headers(prev),
+            Some(timeout),
+            retries,
+        )
+        .await
+    }
+
+    // Stream a media file into `part` without buffering it in memory. An
+    // existing partial file is resumed with a Range request (guarded by
+    // If-Range with `partial`, the validators of the interrupted transfer);
+    // retries continue from wherever the last attempt stopped. `on_start`
+    // runs with the validators of every fresh (non-resumed) body before
+    // bytes are written, so a later run can resume it safely.
+    pub async fn download_to_file(
+        &self,
+        url: &str,
+        prev: &Validators,
+        partial: &Validators,
+        part: &Path,
+        progress: &mut crate::progress::Progress,
+        mut on_start: impl FnMut(&Validators) -> Result<()>,
+    ) -> Result<Transfer> {
+        use futures::StreamExt;
+        use std::io::Write;
+        let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!(e))?;
+        self.online(&parsed)?;
+        let (timeout, retries) = self.download_budget();
+        let file_len = || std::fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+        let mut attempt = 0;
+        let mut resumed_from = None;
+        let mut partial = partial.clone();
+        loop {
+            let offset = file_len();
+            let mut headers = conditional_headers(prev);
+            if offset > 0 {
+                headers.insert(RANGE, HeaderValue::from_str(&format!("bytes={}-", offset))?);
+                if let Some(v) = partial
+                    .if_range()
+                    .and_then(|v| HeaderValue::from_str(v).ok())
+                {
+                    headers.insert(IF_RANGE, v);
+                }
+            }
+            let req = self.request(Method::GET, parsed.clone()).headers(headers);
+            #[cfg(not(target_arch = "wasm32"))]
+            let req = req.timeout(timeout);
+            #[cfg(target_arch = "wasm32")]
+            let _ = timeout;
+            let res = self.send(req).await;
+            self.audit("GET", &parsed, &[], res.as_ref().ok());
+            let resp = match res {
+                Ok(r) => r,
+                Err(e) if attempt < retries => {
+                    attempt += 1;
+                    let delay = backoff_delay(attempt);
+                    warn!(
+                        "http error: {} retrying in {:?}",
+                        redact(&e.to_string()),
+                        delay
+                    );
+                    crate::util::sleep(delay).await;
+                    continue;
+                }
+                Err(e) => return Err(anyhow::anyhow!(e)),
+            };
+            let status = resp.status();
+            let validators = validators_of(&resp);
+            if status == StatusCode::NOT_MODIFIED {
+                return Ok(Transfer {
+                    validators,
+                    ..Default::default()
+                });
+            }
+            if status == StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+                // Stale partial file (longer than the object): start over
+                std::fs::remove_file(part).context("discard partial file")?;
+                continue;
+            }
+            if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+                && attempt < retries
+            {
+                attempt += 1;
+                let delay = retry_after_delay(&resp, attempt, self.cfg.retry_after);
+                warn!("http {} retrying in {:?}", status, delay);
+                crate::util::sleep(delay).await;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(http_error(resp).await);
+            }
+            let append = status == StatusCode::PARTIAL_CONTENT && offset > 0;
+            let start = if append { offset } else { 0 };
+            resumed_from.get_or_insert(start);
+            if !append {
+                on_start(&validators)?;
+                partial = validators.clone();
+            }
+            let total = resp.content_length().map(|n| n + start);
+            let mut file = crate::download::open_part(part, append)?;
+            progress.reset(start, total);
+            let mut body = resp.bytes_stream();
+            let mut failed = None;
+            while let Some(chunk) = body.next().await {
+                match chunk {
+                    Ok(b) => {
+                        file.write_all(&b).context("write partial file")?;
+                        progress.inc(b.len() as u64);
+                    }
+                    Err(e) => {
+                        failed = Some(e);
+                        break;
+                    }
+                }
+            }
+            file.flush()?;
+            file.sync_all().context("sync partial file")?;
+            if let Some(e) = failed {
+                if attempt < retries {
+                    attempt += 1;
+                    let delay = backoff_delay(attempt);
+                    warn!(
+                        "transfer interrupted at {} bytes: {} resuming in {:?}",
+                        file_len(),
+                        redact(&e.to_string()),
+                        delay
+                    );
+                    crate::util::sleep(delay).await;
+                    continue;
+                }
+                progress.finish();
+                return Err(anyhow::anyhow!(e));
+            }
+            progress.finish();
+            return Ok(Transfer {
+                bytes: Some(file_len()),
+                resumed_from: resumed_from.unwrap_or(0),
+                validators,
+            });
+        }
+    }
+
+    // Media transfers get their own timeout and retry budget.
+    fn download_budget(&self) -> (Duration, u32) {
+        let timeout = Duration::from_secs(
+            self.cfg
+                .download_timeout_secs
+                .unwrap_or(DEFAULT_DOWNLOAD_TIMEOUT_SECS),
+        );
+        let retries = self.cfg.download_retries.unwrap_or(self.cfg.max_retries);
+        (timeout, retries)
     }
 
     pub fn base_photos(&self) -> Url {
-        let base = self
-            .cfg
-            .host
-            .clone()
-            .unwrap_or_else(|| "https://api.pexels.com".to_string());
-        Url::parse(&(base + "/v1/")).expect("valid url")
+        Url::parse(&(self.host() + "/v1/")).expect("valid url")
     }
     pub fn base_videos(&self) -> Url {
-        let base = self
-            .cfg
+        Url::parse(&(self.host() + "/videos/")).expect("valid url")
+    }
+
+    pub fn host(&self) -> String {
+        self.cfg
             .host
             .clone()
-            .unwrap_or_else(|| "https://api.pexels.com".to_string());
-        Url::parse(&(base + "/videos/")).expect("valid url")
+            .unwrap_or_else(|| "https://api.pexels.com".to_string())
+    }
+
+    // URL for `pexels api`: a path (`/v1/search`, `videos/popular`) on the
+    // configured host, or a full URL on that same host so the token is never
+    // sent anywhere else.
+    pub fn api_url(&self, path: &str) -> Result<Url> {
+        let base = Url::parse(&(self.host() + "/")).map_err(|e| anyhow::anyhow!(e))?;
+        if path.starts_with("http://") || path.starts_with("https://") {
+            let url = Url::parse(path).map_err(|e| anyhow::anyhow!(e))?;
+            if url.origin() != base.origin() {
+                let err = serde_json::json!({
+                    "error": "URL is not on the API host",
+                    "url": path,
+                    "host": base.origin().ascii_serialization(),
+                    "hint": "pass a path such as /v1/search, or --host to target another host",
+                });
+                return Err(anyhow::anyhow!(serde_yaml::to_string(&err)
+                    .unwrap_or_else(|_| "URL is not on the API host".into())));
+            }
+            return Ok(url);
+        }
+        base.join(path.trim_start_matches('/'))
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    // Body last stored in the result cache for this request, any age.
+    pub fn cached_response(&self, url: &Url, qp: &[(String, String)]) -> Option<JsonValue> {
+        let key = crate::cache::logical_key(
+            url,
+            qp,
+            self.cfg.locale.as_deref(),
+            self.cfg.token.as_deref(),
+        );
+        crate::cache::Cache::new(crate::cache::default_dir(), self.cfg.cache.ttl())
+            .get_offline(&key)
+    }
+
+    // Signed GET of an arbitrary API URL with the usual retries.
+    pub async fn api_get(&self, url: Url, qp: Vec<(String, String)>) -> Result<JsonValue> {
+        self.req(url, qp).await
     }
 
-    async fn req(&self, url: Url, qp: Vec<(String, String)>) -> Result<JsonValue> {
+    // Signed HEAD of an arbitrary API URL: status and response headers.
+    pub async fn api_head(&self, url: Url, qp: Vec<(String, String)>) -> Result<JsonValue> {
+        self.pace().await?;
+        let resp = self
+            .send(self.request(Method::HEAD, url.clone()).query(&qp))
+            .await?;
+        self.audit("HEAD", &url, &qp, Some(&resp));
+        if !resp.status().is_success() {
+            return Err(http_error(resp).await);
+        }
+        let headers: serde_json::Map<String, JsonValue> = resp
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| {
+                let v = v.to_str().ok()?;
+                Some((k.as_str().to_string(), JsonValue::String(v.to_string())))
+            })
+            .collect();
+        Ok(serde_json::json!({
+            "status": resp.status().as_u16(),
+            "headers": headers,
+        }))
+    }
+
+    pub(crate) async fn req(&self, url: Url, qp: Vec<(String, String)>) -> Result<JsonValue> {
         // retries with backoff
         let mut attempt = 0;
+        // malformed bodies (truncated JSON, HTML error pages) get one extra try
+        let mut reparsed = false;
         loop {
-            let res = self.http.get(url.clone()).query(&qp).send().await;
+            self.pace().await?;
+            let res = self
+                .send(self.request(Method::GET, url.clone()).query(&qp))
+                .await;
+            self.audit("GET", &url, &qp, res.as_ref().ok());
             match res {
                 Ok(resp) => {
                     let status = resp.status();
                     if status.is_success() {
-                        return parse_json(resp).await;
+                        match read_json(resp).await? {
+                            Ok(v) => return Ok(v),
+                            Err(bad) if !reparsed => {
+                                reparsed = true;
+                                let delay = backoff_delay(1);
+                                warn!("malformed JSON ({}); retrying in {:?}", bad.reason, delay);
+                                crate::util::sleep(delay).await;
+                                continue;
+                            }
+                            Err(bad) => return Err(bad.into_error()),
+                        }
                     }
                     if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
                         && attempt < self.cfg.max_retries
@@ -87,7 +460,7 @@ impl PexelsClient {
                         attempt += 1;
                         let delay = retry_after_delay(&resp, attempt, self.cfg.retry_after);
                         warn!("http {} retrying in {:?}", status, delay);
-                        tokio::time::sleep(delay).await;
+                        crate::util::sleep(delay).await;
                         continue;
                     }
                     return Err(http_error(resp).await);
@@ -101,7 +474,7 @@ impl PexelsClient {
                             redact(&e.to_string()),
                             delay
                         );
-                        tokio::time::sleep(delay).await;
+                        crate::util::sleep(delay).await;
                         continue;
                     }
                     return Err(anyhow::anyhow!(e));
@@ -110,29 +483,142 @@ impl PexelsClient {
         }
     }
 
+    // One list page through the result cache: served from a fresh entry for
+    // the same logical request unless --refresh, stored after every fetch.
+    async fn req_list(
+        &self,
+        url: Url,
+        qp: Vec<(String, String)>,
+        cli: &crate::cli::Cli,
+    ) -> Result<JsonValue> {
+        let Some(path) = &cli.cursor_file else {
+            return self.req_cached(url, qp, !cli.refresh).await;
+        };
+        let v = match crate::cursor::read(path)? {
+            crate::cursor::Cursor::Start => self.req_cached(url, qp, !cli.refresh).await?,
+            // next_page already carries the query; only the API host is accepted
+            crate::cursor::Cursor::Next(next) => {
+                self.req_cached(self.api_url(&next)?, vec![], !cli.refresh)
+                    .await?
+            }
+            crate::cursor::Cursor::Done => return Err(crate::cursor::done_error(path)),
+        };
+        crate::cursor::write(path, v.get("next_page").and_then(|n| n.as_str()))?;
+        Ok(v)
+    }
+
+    // Response stored in the result cache after every fetch; `reuse` serves
+    // a fresh entry instead of fetching. With --offline only the cache is
+    // consulted (any age) and a miss is an error.
+    async fn req_cached(
+        &self,
+        url: Url,
+        qp: Vec<(String, String)>,
+        reuse: bool,
+    ) -> Result<JsonValue> {
+        let cache = crate::cache::Cache::new(crate::cache::default_dir(), self.cfg.cache.ttl());
+        let key = crate::cache::logical_key(
+            &url,
+            &qp,
+            self.cfg.locale.as_deref(),
+            self.cfg.token.as_deref(),
+        );
+        if self.cfg.offline {
+            return cache
+                .get_offline(&key)
+                .ok_or_else(|| crate::cache::offline_miss(&url, &qp));
+        }
+        if reuse {
+            if let Some(v) = cache.get(&key) {
+                tracing::debug!("cache hit: {}", url.path());
+                return Ok(v);
+            }
+        }
+        let v = self.req(url, qp).await?;
+        if let Err(e) = cache.put(&key, &v) {
+            warn!("result cache: {}", e);
+        }
+        Ok(v)
+    }
+
     pub async fn req_bytes(&self, url: Url, qp: Vec<(String, String)>) -> Result<Vec<u8>> {
+        let fetched = self
+            .fetch_bytes(url, qp, HeaderMap::new(), None, self.cfg.max_retries)
+            .await?;
+        Ok(fetched.body.unwrap_or_default())
+    }
+
+    // `timeout` overrides the client-wide timeout for this request. Body read
+    // failures (connection dropped mid-transfer) are retried like send errors.
+    async fn fetch_bytes(
+        &self,
+        url: Url,
+        qp: Vec<(String, String)>,
+        headers: HeaderMap,
+        timeout: Option<Duration>,
+        retries: u32,
+    ) -> Result<Fetched> {
+        self.online(&url)?;
         let mut attempt = 0;
         loop {
-            let res = self.http.get(url.clone()).query(&qp).send().await;
+            let req = self
+                .request(Method::GET, url.clone())
+                .query(&qp)
+                .headers(headers.clone());
+            #[cfg(not(target_arch = "wasm32"))]
+            let req = match timeout {
+                Some(t) => req.timeout(t),
+                None => req,
+            };
+            #[cfg(target_arch = "wasm32")]
+            let _ = timeout;
+            let res = self.send(req).await;
+            self.audit("GET", &url, &qp, res.as_ref().ok());
             match res {
                 Ok(resp) => {
                     let status = resp.status();
+                    let validators = validators_of(&resp);
+                    if status == StatusCode::NOT_MODIFIED {
+                        return Ok(Fetched {
+                            body: None,
+                            validators,
+                        });
+                    }
                     if status.is_success() {
-                        return Ok(resp.bytes().await?.to_vec());
+                        match resp.bytes().await {
+                            Ok(b) => {
+                                return Ok(Fetched {
+                                    body: Some(b.to_vec()),
+                                    validators,
+                                })
+                            }
+                            Err(e) if attempt < retries => {
+                                attempt += 1;
+                                let delay = backoff_delay(attempt);
+                                warn!(
+                                    "body read failed: {} retrying in {:?}",
+                                    redact(&e.to_string()),
+                                    delay
+                                );
+                                crate::util::sleep(delay).await;
+                                continue;
+                            }
+                            Err(e) => return Err(anyhow::anyhow!(e)),
+                        }
                     }
                     if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
-                        && attempt < self.cfg.max_retries
+                        && attempt < retries
                     {
                         attempt += 1;
                         let delay = retry_after_delay(&resp, attempt, self.cfg.retry_after);
                         warn!("http {} retrying in {:?}", status, delay);
-                        tokio::time::sleep(delay).await;
+                        crate::util::sleep(delay).await;
                         continue;
                     }
                     return Err(http_error(resp).await);
                 }
                 Err(e) => {
-                    if attempt < self.cfg.max_retries {
+                    if attempt < retries {
                         attempt += 1;
                         let delay = backoff_delay(attempt);
                         warn!(
@@ -140,7 +626,7 @@ impl PexelsClient {
                             redact(&e.to_string()),
                             delay
                         );
-                        tokio::time::sleep(delay).await;
+                        crate::util::sleep(delay).await;
                         continue;
                     }
                     return Err(anyhow::anyhow!(e));
@@ -155,12 +641,18 @@ impl PexelsClient {
             .base_photos()
             .join("curated")
             .map_err(|e| anyhow::anyhow!(e))?;
-        let resp = self.http.get(url).send().await?;
+        self.pace().await?;
+        let resp = self.send(self.request(Method::GET, url.clone())).await?;
+        self.audit("GET", &url, &[], Some(&resp));
         if !resp.status().is_success() {
             return Err(http_error(resp).await);
         }
         let headers = resp.headers().clone();
-        let body = parse_json(resp).await.unwrap_or(JsonValue::Null);
+        let body = read_json(resp)
+            .await
+            .ok()
+            .and_then(|r| r.ok())
+            .unwrap_or(JsonValue::Null);
         let mut out = serde_json::Map::new();
         for (k, v) in headers.iter() {
             let key = k.as_str().to_ascii_lowercase();
@@ -170,144 +662,304 @@ impl PexelsClient {
                 }
             }
         }
+        out.insert("source".into(), "live".into());
         out.insert("sample".into(), body);
         Ok(JsonValue::Object(out))
     }
 
-    pub async fn photos_search(&self, query: &str, cli: &crate::cli::Cli) -> Result<JsonValue> {
+    // `filters` are extra search parameters (orientation, size, color).
+    pub async fn photos_search(
+        &self,
+        query: &str,
+        filters: &[(String, String)],
+        cli: &crate::cli::Cli,
+    ) -> Result<JsonValue> {
+        self.list(
+            Listing::PhotosSearch {
+                query,
+                filters: filters.to_vec(),
+            },
+            cli,
+        )
+        .await
+    }
+
+    pub async fn photos_curated(&self, cli: &crate::cli::Cli) -> Result<JsonValue> {
+        self.list(Listing::PhotosCurated, cli).await
+    }
+
+    // First page of curated photos, or of a search when `query` is set.
+    pub async fn photos_pool(&self, query: Option<&str>, per_page: u32) -> Result<JsonValue> {
+        let path = if query.is_some() { "search" } else { "curated" };
         let url = self
             .base_photos()
-            .join("search")
+            .join(path)
             .map_err(|e| anyhow::anyhow!(e))?;
-        let mut qp = self.pagination_qp(cli);
-        qp.push(("query".into(), query.into()));
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("photos", "photos")])
-                .await
-        } else {
-            self.req(url, qp).await
+        let mut qp = vec![("per_page".to_string(), per_page.to_string())];
+        if let Some(q) = query {
+            qp.push(("query".into(), q.into()));
         }
+        self.req(url, qp).await
     }
 
-    pub async fn photos_curated(&self, cli: &crate::cli::Cli) -> Result<JsonValue> {
+    // One page of curated photos, or of a search when `query` is set.
+    pub async fn photos_page(
+        &self,
+        query: Option<&str>,
+        page: u32,
+        per_page: u32,
+    ) -> Result<JsonValue> {
+        let path = if query.is_some() { "search" } else { "curated" };
         let url = self
             .base_photos()
-            .join("curated")
+            .join(path)
             .map_err(|e| anyhow::anyhow!(e))?;
-        let qp = self.pagination_qp(cli);
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("photos", "photos")])
-                .await
-        } else {
-            self.req(url, qp).await
+        let mut qp = vec![
+            ("page".to_string(), page.to_string()),
+            ("per_page".to_string(), per_page.to_string()),
+        ];
+        if let Some(q) = query {
+            qp.push(("query".into(), q.into()));
         }
+        self.req(url, qp).await
     }
 
     pub async fn photos_get(&self, id: &str) -> Result<JsonValue> {
+        self.photos_lookup(id, false).await
+    }
+
+    // A photo, served from a fresh result-cache entry when `reuse` is set.
+    pub async fn photos_lookup(&self, id: &str, reuse: bool) -> Result<JsonValue> {
         let url = self
             .base_photos()
             .join(&format!("photos/{}", id))
             .map_err(|e| anyhow::anyhow!(e))?;
-        self.req(url, vec![]).await
+        self.req_cached(url, vec![], reuse).await
     }
 
-    pub async fn videos_search(&self, query: &str, cli: &crate::cli::Cli) -> Result<JsonValue> {
-        let url = self
-            .base_videos()
-            .join("search")
-            .map_err(|e| anyhow::anyhow!(e))?;
-        let mut qp = self.pagination_qp(cli);
-        qp.push(("query".into(), query.into()));
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("videos", "videos")])
-                .await
-        } else {
-            self.req(url, qp).await
-        }
+    pub async fn videos_search(
+        &self,
+        query: &str,
+        filters: &[(String, String)],
+        cli: &crate::cli::Cli,
+    ) -> Result<JsonValue> {
+        self.list(
+            Listing::VideosSearch {
+                query,
+                filters: filters.to_vec(),
+            },
+            cli,
+        )
+        .await
     }
-    pub async fn videos_popular(&self, cli: &crate::cli::Cli) -> Result<JsonValue> {
-        let url = self
-            .base_videos()
-            .join("popular")
-            .map_err(|e| anyhow::anyhow!(e))?;
-        let qp = self.pagination_qp(cli);
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("videos", "videos")])
-                .await
-        } else {
-            self.req(url, qp).await
-        }
+    pub async fn videos_popular(
+        &self,
+        filters: &[(String, String)],
+        cli: &crate::cli::Cli,
+    ) -> Result<JsonValue> {
+        self.list(
+            Listing::VideosPopular {
+                filters: filters.to_vec(),
+            },
+            cli,
+        )
+        .await
     }
     pub async fn videos_get(&self, id: &str) -> Result<JsonValue> {
         let url = self
             .base_videos()
             .join(&format!("videos/{}", id))
             .map_err(|e| anyhow::anyhow!(e))?;
-        self.req(url, vec![]).await
+        self.req_cached(url, vec![], false).await
     }
 
     pub async fn collections_list(&self, cli: &crate::cli::Cli) -> Result<JsonValue> {
-        let url = self
-            .base_photos()
-            .join("collections")
-            .map_err(|e| anyhow::anyhow!(e))?;
-        let qp = self.pagination_qp(cli);
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("collections", "collections")])
-                .await
-        } else {
-            self.req(url, qp).await
-        }
+        self.list(Listing::CollectionsList, cli).await
     }
     pub async fn collections_featured(&self, cli: &crate::cli::Cli) -> Result<JsonValue> {
+        self.list(Listing::CollectionsFeatured, cli).await
+    }
+    pub async fn collections_get(&self, id: &str) -> Result<JsonValue> {
         let url = self
             .base_photos()
-            .join("collections/featured")
+            .join(&format!("collections/{}", id))
             .map_err(|e| anyhow::anyhow!(e))?;
-        let qp = self.pagination_qp(cli);
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("collections", "collections")])
-                .await
+        self.req_cached(url, vec![], false).await
+    }
+    pub async fn collections_items(&self, id: &str, cli: &crate::cli::Cli) -> Result<JsonValue> {
+        self.list(Listing::CollectionsItems { id }, cli).await
+    }
+
+    // One page of `listing`, or the merged walk with --all/--limit/--max-pages.
+    pub async fn list(&self, listing: Listing<'_>, cli: &crate::cli::Cli) -> Result<JsonValue> {
+        let (url, qp, item_keys) = self.listing_request(&listing, cli)?;
+        if cli.walks_pages() {
+            self.req_paginated(url, qp, cli, item_keys).await
         } else {
-            self.req(url, qp).await
+            self.req_list(url, qp, cli).await
         }
     }
-    pub async fn collections_get(&self, id: &str) -> Result<JsonValue> {
+
+    // The walk over `listing` chunk by chunk (see `paginate`).
+    pub fn list_pages<'a>(
+        &'a self,
+        listing: Listing<'_>,
+        cli: &'a crate::cli::Cli,
+    ) -> Result<impl futures::Stream<Item = Result<serde_json::Map<String, JsonValue>>> + 'a> {
+        let (url, qp, item_keys) = self.listing_request(&listing, cli)?;
+        self.paginate(url, qp, cli, item_keys)
+    }
+
+    // `total_results` of `listing` from a single one-item page (--count).
+    pub async fn count(&self, listing: Listing<'_>, cli: &crate::cli::Cli) -> Result<u64> {
+        let (url, mut qp, _) = self.listing_request(&listing, cli)?;
+        qp.retain(|(k, _)| k != "page" && k != "per_page");
+        qp.push(("per_page".into(), "1".into()));
+        let v = self.req_cached(url.clone(), qp, !cli.refresh).await?;
+        v.get("total_results")
+            .and_then(|t| t.as_u64())
+            .ok_or_else(|| {
+                let err = serde_json::json!({
+                    "error": "no total_results in the response",
+                    "endpoint": url.path(),
+                });
+                anyhow::anyhow!(
+                    serde_yaml::to_string(&err).unwrap_or_else(|_| "no total_results".into())
+                )
+            })
+    }
+
+    // The request behind a list command.
+    fn listing_request(&self, listing: &Listing<'_>, cli: &crate::cli::Cli) -> Result<ListRequest> {
+        let (base, path, item_keys): (Url, String, ItemKeys) = match listing {
+            Listing::PhotosSearch { .. } => {
+                (self.base_photos(), "search".into(), &[("photos", "photos")])
+            }
+            Listing::PhotosCurated => (
+                self.base_photos(),
+                "curated".into(),
+                &[("photos", "photos")],
+            ),
+            Listing::VideosSearch { .. } => {
+                (self.base_videos(), "search".into(), &[("videos", "videos")])
+            }
+            Listing::VideosPopular { .. } => (
+                self.base_videos(),
+                "popular".into(),
+                &[("videos", "videos")],
+            ),
+            Listing::CollectionsList => (
+                self.base_photos(),
+                "collections".into(),
+                &[("collections", "collections")],
+            ),
+            Listing::CollectionsFeatured => (
+                self.base_photos(),
+                "collections/featured".into(),
+                &[("collections", "collections")],
+            ),
+            Listing::CollectionsItems { id } => (
+                self.base_photos(),
+                format!("collections/{}/media", id),
+                &[("media", "media")],
+            ),
+        };
+        let url = base.join(&path).map_err(|e| anyhow::anyhow!(e))?;
+        let mut qp = self.pagination_qp(cli);
+        match listing {
+            Listing::PhotosSearch { query, filters } | Listing::VideosSearch { query, filters } => {
+                qp.push(("query".into(), (*query).into()));
+                qp.extend(filters.iter().cloned());
+            }
+            Listing::VideosPopular { filters } => qp.extend(filters.iter().cloned()),
+            _ => {}
+        }
+        Ok((url, qp, item_keys))
+    }
+
+    // One page of a collection's media with explicit query parameters.
+    pub async fn collection_media_page(
+        &self,
+        id: &str,
+        qp: Vec<(String, String)>,
+    ) -> Result<JsonValue> {
         let url = self
             .base_photos()
-            .join(&format!("collections/{}", id))
+            .join(&format!("collections/{}/media", id))
             .map_err(|e| anyhow::anyhow!(e))?;
-        self.req(url, vec![]).await
+        self.req(url, qp).await
     }
-    pub async fn collections_items(&self, id: &str, cli: &crate::cli::Cli) -> Result<JsonValue> {
+
+    // Every page of a collection's media (still bounded by --limit/--max-pages).
+    pub async fn collections_items_all(
+        &self,
+        id: &str,
+        cli: &crate::cli::Cli,
+    ) -> Result<JsonValue> {
         let url = self
             .base_photos()
             .join(&format!("collections/{}/media", id))
             .map_err(|e| anyhow::anyhow!(e))?;
-        let qp = self.pagination_qp(cli);
-        if cli.all || cli.limit.is_some() || cli.max_pages.is_some() {
-            self.req_paginated(url, qp, cli, &[("media", "media")])
-                .await
-        } else {
-            self.req(url, qp).await
+        let mut qp = self.pagination_qp(cli);
+        if !qp.iter().any(|(k, _)| k == "per_page") {
+            qp.push(("per_page".into(), "80".into()));
         }
+        self.req_paginated(url, qp, cli, &[("media", "media")])
+            .await
     }
 
     pub async fn util_inspect(&self) -> Result<JsonValue> {
         Ok(serde_json::json!({
-            "host": self.cfg.host.clone().unwrap_or_else(|| "https://api.pexels.com".into()),
+            "host": self.host(),
             "timeout": self.cfg.timeout_secs,
             "locale": self.cfg.locale,
             "max_retries": self.cfg.max_retries,
         }))
     }
+    // Content-Length of a media URL via HEAD (None when the server omits it).
+    pub async fn head_content_length(&self, url: &str) -> Result<Option<u64>> {
+        let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!(e))?;
+        self.online(&parsed)?;
+        let resp = self
+            .send(self.request(Method::HEAD, parsed.clone()))
+            .await?;
+        self.audit("HEAD", &parsed, &[], Some(&resp));
+        if !resp.status().is_success() {
+            return Err(http_error(resp).await);
+        }
+        Ok(resp
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.parse().ok()))
+    }
+
+    pub async fn head_content_type(&self, url: &str) -> Result<Option<String>> {
+        let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!(e))?;
+        self.online(&parsed)?;
+        let resp = self
+            .send(self.request(Method::HEAD, parsed.clone()))
+            .await?;
+        self.audit("HEAD", &parsed, &[], Some(&resp));
+        if !resp.status().is_success() {
+            return Err(http_error(resp).await);
+        }
+        Ok(resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from))
+    }
+
     pub async fn util_ping(&self) -> Result<()> {
         // lightweight: HEAD curated
         let url = self
             .base_photos()
             .join("curated")
             .map_err(|e| anyhow::anyhow!(e))?;
-        let resp = self.http.head(url).send().await?;
+        self.pace().await?;
+        let resp = self.send(self.request(Method::HEAD, url.clone())).await?;
+        self.audit("HEAD", &url, &[], Some(&resp));
         if resp.status().is_success() {
             Ok(())
         } else {
@@ -315,17 +967,235 @@ impl PexelsClient {
         }
     }
 
+    // `auth login` check: the cheapest authenticated request
+    // (`curated?per_page=1`). Returns the rate-limit window it reported.
+    pub async fn verify_token(&self) -> Result<Option<crate::quota::Window>> {
+        let url = self
+            .base_photos()
+            .join("curated")
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let qp = vec![("per_page".to_string(), "1".to_string())];
+        self.pace().await?;
+        let resp = self
+            .send(self.request(Method::GET, url.clone()).query(&qp))
+            .await?;
+        self.audit("GET", &url, &qp, Some(&resp));
+        if !resp.status().is_success() {
+            return Err(http_error(resp).await);
+        }
+        Ok(self.quota_window())
+    }
+
+    // Number of HTTP requests sent so far, retries included.
+    pub fn api_calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    // Rate-limit window from the most recent API response, if any.
+    pub fn quota_window(&self) -> Option<crate::quota::Window> {
+        *self.window.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Hold off while the rate-limit window is exhausted: sleep until
+    // X-Ratelimit-Reset, or with `no_wait` return the window so the caller
+    // can stop and report when to resume.
+    pub async fn await_quota(&self, no_wait: bool) -> Option<crate::quota::Window> {
+        // Offline pages come from the cache and cost nothing
+        if self.cfg.offline {
+            return None;
+        }
+        let window = self.quota_window()?;
+        let wait = window.wait(chrono::Utc::now().timestamp())?;
+        if no_wait {
+            return Some(window);
+        }
+        warn!(
+            "rate limit window exhausted; waiting until {} ({}s)",
+            window.resume_at(),
+            wait.as_secs()
+        );
+        crate::util::sleep(wait).await;
+        *self.window.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        None
+    }
+
+    // Client-side rate limiter, run before every API request: waits out an
+    // exhausted window (or fails with --no-wait) and spaces requests out
+    // once few remain. Media transfers from the CDN are not metered.
+    async fn pace(&self) -> Result<()> {
+        if self.cfg.offline {
+            return Err(crate::cache::offline_error(&Url::parse(&self.host())?));
+        }
+        let Some(w) = self.quota_window() else {
+            return Ok(());
+        };
+        let now = chrono::Utc::now().timestamp();
+        if w.exhausted(now) {
+            if self.cfg.no_wait {
+                return Err(crate::quota::exhausted_error(&w));
+            }
+            self.await_quota(false).await;
+            return Ok(());
+        }
+        let delay = w.pace(now);
+        if !delay.is_zero() {
+            tracing::debug!(
+                "{} requests left in window; pacing {:?}",
+                w.remaining,
+                delay
+            );
+            crate::util::sleep(delay).await;
+        }
+        Ok(())
+    }
+
+    // Every request goes out through here so --inject-fault can stand in
+    // for the network on the scheduled attempts.
+    // Request builder for `url`. Gateway headers go only to the API host, so
+    // media downloads from the CDN never carry gateway credentials.
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        let on_api = Url::parse(&self.host()).is_ok_and(|h| h.origin() == url.origin());
+        let rb = self.http.request(method, url);
+        if on_api && !self.gateway.is_empty() {
+            rb.headers(self.gateway.clone())
+        } else {
+            rb
+        }
+    }
+
+    async fn send(&self, rb: reqwest::RequestBuilder) -> reqwest::Result<Response> {
+        #[cfg(feature = "fault-injection")]
+        {
+            use crate::fault::FaultKind;
+            let attempt = self.attempts.fetch_add(1, Ordering::Relaxed) + 1;
+            match crate::fault::at(&self.cfg.faults, attempt) {
+                Some(FaultKind::Status(code)) => {
+                    warn!("injected fault: HTTP {} on attempt {}", code, attempt);
+                    let resp = http::Response::builder()
+                        .status(code)
+                        .body(Vec::<u8>::new())
+                        .expect("valid status");
+                    return Ok(Response::from(resp));
+                }
+                Some(FaultKind::Timeout) => {
+                    warn!("injected fault: timeout on attempt {}", attempt);
+                    return rb.timeout(Duration::from_nanos(1)).send().await;
+                }
+                Some(FaultKind::Connect) => {
+                    warn!("injected fault: connection refused on attempt {}", attempt);
+                    // Port 1 on loopback: nothing listens, so the connect fails
+                    return self.http.get("http://127.0.0.1:1/").send().await;
+                }
+                None => {}
+            }
+        }
+        rb.send().await
+    }
+
+    // Media transfers are refused under --offline.
+    fn online(&self, url: &Url) -> Result<()> {
+        if self.cfg.offline {
+            return Err(crate::cache::offline_error(url));
+        }
+        Ok(())
+    }
+
+    // Seed the window from an earlier run (kept in the state db) unless it
+    // has already reset.
+    pub fn restore_window(&self, st: &crate::state::State) {
+        let saved = crate::quota::saved(st, &self.host())
+            .map(|(w, _)| w)
+            .filter(|w| w.reset > chrono::Utc::now().timestamp());
+        let mut cur = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        if cur.is_none() {
+            *cur = saved;
+        }
+    }
+
+    // Write the latest window to the state db for `quota view` and the next
+    // run, if a response changed it since the last save. The CLI calls this
+    // once per command (and per watch poll), never from the request path.
+    pub fn save_window(&self) -> Result<()> {
+        if !self.window_unsaved.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        match self.quota_window() {
+            Some(w) => crate::quota::persist(&self.host(), &w),
+            None => Ok(()),
+        }
+    }
+
+    // `quota view` without a request: the window last persisted for this
+    // host, in the shape of the live probe. None when nothing is stored.
+    pub fn quota_cached(&self, st: &crate::state::State) -> Option<JsonValue> {
+        let (w, seen_at) = crate::quota::saved(st, &self.host())?;
+        let mut out = serde_json::Map::new();
+        if let Some(limit) = w.limit {
+            out.insert("x-ratelimit-limit".into(), limit.to_string().into());
+        }
+        out.insert(
+            "x-ratelimit-remaining".into(),
+            w.remaining.to_string().into(),
+        );
+        out.insert("x-ratelimit-reset".into(), w.reset.to_string().into());
+        out.insert("source".into(), "cache".into());
+        out.insert("seen_at".into(), seen_at.into());
+        // The window has refilled since; the counts above are from before
+        if w.reset <= chrono::Utc::now().timestamp() {
+            out.insert("expired".into(), true.into());
+        }
+        Some(JsonValue::Object(out))
+    }
+
+    // Called once per request sent; counts it, remembers the rate-limit
+    // window (in memory only, see save_window) and appends the audit record.
+    fn audit(&self, method: &str, url: &Url, qp: &[(String, String)], resp: Option<&Response>) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let header = |name: &str| {
+            resp.and_then(|r| r.headers().get(name))
+                .and_then(|v| v.to_str().ok())
+        };
+        if let Some(w) = crate::quota::Window::from_headers(header) {
+            *self.window.lock().unwrap_or_else(|e| e.into_inner()) = Some(w);
+            self.window_unsaved.store(true, Ordering::Relaxed);
+        }
+        if !self.cfg.audit.enabled {
+            return;
+        }
+        // Followed `next_page` URLs carry their whole query in the URL
+        let query: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .chain(qp.iter().cloned())
+            .collect();
+        let rec = crate::audit::record(
+            self.cfg.profile_name(),
+            &url.origin().ascii_serialization(),
+            method,
+            url.path(),
+            &query,
+            resp.map(|r| r.status().as_u16()),
+            header("x-request-id"),
+            header("x-ratelimit-remaining"),
+        );
+        if let Err(e) = crate::audit::append(&self.cfg.audit.log_path(), &rec) {
+            warn!("audit log: {}", e);
+        }
+    }
+
     pub fn pagination_qp(&self, cli: &crate::cli::Cli) -> Vec<(String, String)> {
         let mut qp = vec![];
         if let Some(p) = cli.page {
             qp.push(("page".into(), p.to_string()));
         }
-        if let Some(pp) = cli.per_page {
+        if let Some(pp) = effective_per_page(cli.per_page, cli.limit) {
             qp.push(("per_page".into(), pp.to_string()));
         }
         qp
     }
 
+    // --all / --limit / --max-pages: follow the pages and merge their items,
+    // leaving out repeats of items from earlier pages unless --no-dedupe.
     async fn req_paginated(
         &self,
         url: Url,
@@ -336,77 +1206,508 @@ impl PexelsClient {
             &str, // output key
         )],
     ) -> Result<JsonValue> {
-        let mut next = Some((url, qp));
-        let mut pages = 0u32;
-        let mut collected = 0u32;
-        let limit = cli.limit.unwrap_or(u32::MAX);
-        let max_pages = cli.max_pages.unwrap_or(u32::MAX);
-        let mut aggregate = serde_json::Map::new();
+        use futures::StreamExt;
+        let mut out = serde_json::Map::new();
         // seed arrays
         for (_, out_key) in item_keys.iter() {
-            aggregate.insert((*out_key).to_string(), JsonValue::Array(vec![]));
+            out.insert((*out_key).to_string(), JsonValue::Array(vec![]));
         }
-        while let Some((u, q)) = next.take() {
-            if pages >= max_pages || collected >= limit {
-                break;
+        let chunks = self.paginate(url, qp, cli, item_keys)?;
+        futures::pin_mut!(chunks);
+        while let Some(chunk) = chunks.next().await {
+            for (k, v) in chunk? {
+                match (out.get_mut(&k), v) {
+                    (Some(JsonValue::Array(dest)), JsonValue::Array(items)) => dest.extend(items),
+                    (_, v) => {
+                        out.insert(k, v);
+                    }
+                }
             }
-            let resp = self.req(u.clone(), q.clone()).await?;
-            // copy non-array metadata on first page
-            if pages == 0 {
-                if let Some(obj) = resp.as_object() {
-                    for (k, v) in obj.iter() {
-                        if !item_keys.iter().any(|(ik, _)| ik == k) && k != "next_page" {
-                            aggregate.insert(k.clone(), v.clone());
-                        }
+        }
+        Ok(JsonValue::Object(out))
+    }
+
+    // A paginated walk as it happens: one chunk per page holding the items
+    // it contributes (within --limit and the resume skip, without repeats)
+    // under the output keys. The first chunk also carries the response
+    // metadata; the last one carries `continue`, `partial` or `rate_limited`
+    // when the walk stopped early. Once a repeat was dropped, chunks carry
+    // the running count as `duplicates_dropped`. With --page-concurrency
+    // above 1 the pages after the first are fetched that many at a time by
+    // number (known from total_results) and yielded in order; every request
+    // still goes through the rate limiter. Past --soft-deadline no further
+    // pages are fetched. With --resume the walk starts where the file says
+    // and records its position after every page.
+    pub fn paginate<'a>(
+        &'a self,
+        url: Url,
+        mut qp: Vec<(String, String)>,
+        cli: &'a crate::cli::Cli,
+        item_keys: &'a [(&'a str, &'a str)],
+    ) -> Result<impl futures::Stream<Item = Result<serde_json::Map<String, JsonValue>>> + 'a> {
+        let mut agg = Aggregate::new(cli, item_keys);
+        let resume = match &cli.resume {
+            Some(path) => {
+                let base = match crate::resume::load(path)? {
+                    Some(cp) if !cp.matches(url.path(), &qp) => {
+                        return Err(crate::resume::mismatch_error(path, &cp))
+                    }
+                    Some(cp) if cp.done => return Err(crate::resume::done_error(path, &cp)),
+                    Some(cp) => {
+                        qp.retain(|(k, _)| k != "page");
+                        qp.push(("page".into(), cp.page.to_string()));
+                        agg.resume(&cp);
+                        cp
+                    }
+                    None => crate::resume::Checkpoint::start(url.path(), &qp),
+                };
+                Some((path.clone(), base))
+            }
+            None => None,
+        };
+        let walk = Walk {
+            agg,
+            next: Some((url.clone(), qp.clone())),
+            ahead: None,
+            ended: false,
+            resume,
+        };
+        Ok(futures::stream::try_unfold(walk, move |mut walk| {
+            let (url, qp) = (url.clone(), qp.clone());
+            async move {
+                while !walk.ended {
+                    self.walk_page(&mut walk, &url, &qp, cli).await?;
+                    walk.checkpoint()?;
+                    if let Some(chunk) = walk.agg.take_chunk() {
+                        return Ok(Some((chunk, walk)));
                     }
                 }
+                Ok(None)
             }
-            // merge arrays
-            for (in_key, out_key) in item_keys.iter() {
-                if let Some(arr) = resp.get(*in_key).and_then(|v| v.as_array()) {
-                    let cur = aggregate.get_mut(&out_key.to_string()).unwrap();
-                    let dest = cur.as_array_mut().unwrap();
-                    for item in arr {
-                        if collected < limit {
-                            dest.push(item.clone());
-                            collected += 1;
-                        }
+        }))
+    }
+
+    // Advance `walk` by one page (or to its end).
+    async fn walk_page<'a>(
+        &'a self,
+        walk: &mut Walk<'a>,
+        url: &Url,
+        qp: &[(String, String)],
+        cli: &'a crate::cli::Cli,
+    ) -> Result<()> {
+        use futures::stream::{self, StreamExt};
+        if let Some(fetches) = walk.ahead.as_mut() {
+            match fetches.next().await {
+                None => walk.ended = true,
+                Some(Err(e)) => return Err(e),
+                Some(Ok(Err(w))) => {
+                    walk.agg.rate_limited(&w);
+                    walk.ended = true;
+                }
+                Some(Ok(Ok(mut resp))) => {
+                    if cli.positions {
+                        crate::output::annotate_positions(&mut resp);
                     }
+                    walk.ended = !walk.agg.absorb(&resp)? || walk.agg.out_of_time();
                 }
             }
-            pages += 1;
-            if collected >= limit || pages >= max_pages {
-                break;
+            return Ok(());
+        }
+        let Some((u, q)) = walk.next.take().filter(|_| !walk.agg.done()) else {
+            walk.ended = true;
+            return Ok(());
+        };
+        if walk.agg.pages > 0 {
+            if let Some(w) = self.await_quota(cli.no_wait).await {
+                walk.agg.rate_limited(&w);
+                walk.ended = true;
+                return Ok(());
+            }
+        }
+        let mut resp = self.req_list(u, q, cli).await?;
+        if cli.positions {
+            crate::output::annotate_positions(&mut resp);
+        }
+        if !walk.agg.absorb(&resp)? || walk.agg.out_of_time() {
+            walk.ended = true;
+            return Ok(());
+        }
+        if cli.page_concurrency > 1 {
+            if let Some((pages, per_page)) = walk.agg.remaining_pages(&resp) {
+                let (url, qp) = (url.clone(), qp.to_vec());
+                let fetches = stream::iter(pages)
+                    .map(move |page| {
+                        let (url, mut q) = (url.clone(), qp.clone());
+                        q.retain(|(k, _)| k != "page" && k != "per_page");
+                        q.push(("page".into(), page.to_string()));
+                        q.push(("per_page".into(), per_page.to_string()));
+                        async move {
+                            if let Some(w) = self.await_quota(cli.no_wait).await {
+                                return Ok(Err(w));
+                            }
+                            self.req_list(url, q, cli).await.map(Ok)
+                        }
+                    })
+                    .buffered(cli.page_concurrency as usize);
+                #[cfg(not(target_arch = "wasm32"))]
+                let fetches = fetches.boxed();
+                // reqwest's wasm futures are not Send
+                #[cfg(target_arch = "wasm32")]
+                let fetches = fetches.boxed_local();
+                walk.ahead = Some(fetches);
+                return Ok(());
+            }
+        }
+        walk.next = resp
+            .get("next_page")
+            .and_then(|v| v.as_str())
+            .and_then(|n| Url::parse(n).ok())
+            .map(|parsed| (parsed, vec![]));
+        walk.ended = walk.next.is_none();
+        Ok(())
+    }
+}
+
+// Page fetched ahead by number, or the rate-limit window that stopped it.
+type PageFetch = Result<std::result::Result<JsonValue, crate::quota::Window>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type PageFetches<'a> = futures::stream::BoxStream<'a, PageFetch>;
+#[cfg(target_arch = "wasm32")]
+type PageFetches<'a> = futures::stream::LocalBoxStream<'a, PageFetch>;
+
+// A paginated walk between the chunks it yields.
+struct Walk<'a> {
+    agg: Aggregate<'a>,
+    // Next page to follow by its next_page link
+    next: Option<(Url, Vec<(String, String)>)>,
+    // --page-concurrency: the remaining pages, fetched by number
+    ahead: Option<PageFetches<'a>>,
+    ended: bool,
+    // --resume: the file and the position the walk started from
+    resume: Option<(std::path::PathBuf, crate::resume::Checkpoint)>,
+}
+
+impl Walk<'_> {
+    // --resume: store where the walk stands now. A walk stopped early (by
+    // --limit, --max-pages, --no-wait, --soft-deadline or the size ceiling)
+    // resumes at its continue point; one that ran out of pages is done.
+    fn checkpoint(&self) -> Result<()> {
+        let Some((path, base)) = &self.resume else {
+            return Ok(());
+        };
+        let agg = &self.agg;
+        if agg.pages == 0 && agg.chunk.is_empty() {
+            return Ok(());
+        }
+        let num = |k: &str| agg.chunk.get("continue").and_then(|c| c[k].as_u64());
+        let (page, skip) = match num("page") {
+            Some(page) => (page, num("skip").unwrap_or(0) as usize),
+            None => (agg.last_page + 1, 0),
+        };
+        let cp = crate::resume::Checkpoint {
+            page,
+            skip,
+            collected: base.collected + agg.collected,
+            pages: base.pages + agg.pages,
+            done: self.ended && num("page").is_none(),
+            ..base.clone()
+        };
+        crate::resume::save(path, &cp)
+    }
+}
+
+// Progress of a paginated walk within --limit, --max-pages and the response
+// size ceiling; `chunk` collects what the current page contributes.
+struct Aggregate<'a> {
+    item_keys: &'a [(&'a str, &'a str)],
+    chunk: serde_json::Map<String, JsonValue>,
+    pages: u32,
+    collected: u32,
+    limit: u32,
+    max_pages: u32,
+    bytes: u64,
+    max_bytes: u64,
+    // Items already returned by the run being continued
+    to_skip: usize,
+    // Page number of the last response, for a resume point
+    last_page: u64,
+    // --soft-deadline: no further pages once this has passed
+    deadline: Option<chrono::DateTime<chrono::Utc>>,
+    // Identities taken so far, unless --no-dedupe; repeats are dropped
+    seen: Option<std::collections::HashSet<String>>,
+    duplicates: u64,
+}
+
+impl<'a> Aggregate<'a> {
+    fn new(cli: &crate::cli::Cli, item_keys: &'a [(&'a str, &'a str)]) -> Self {
+        Self {
+            item_keys,
+            chunk: serde_json::Map::new(),
+            pages: 0,
+            collected: 0,
+            limit: cli.limit.unwrap_or(u32::MAX),
+            max_pages: cli.max_pages.unwrap_or(u32::MAX),
+            bytes: 0,
+            // Stop paging once the aggregate is already past the response size ceiling
+            max_bytes: cli.max_response_bytes().unwrap_or(u64::MAX),
+            to_skip: cli.resume_skip,
+            last_page: 0,
+            deadline: cli
+                .soft_deadline
+                .and_then(|d| chrono::Duration::from_std(d).ok())
+                .map(|d| chrono::Utc::now() + d),
+            seen: (!cli.no_dedupe).then(Default::default),
+            duplicates: 0,
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.pages >= self.max_pages || self.collected >= self.limit
+    }
+
+    // Continue a walk from `cp`: its items and pages count against the
+    // limits, and the first page drops what it already returned.
+    fn resume(&mut self, cp: &crate::resume::Checkpoint) {
+        self.limit = self.limit.saturating_sub(cp.collected);
+        self.max_pages = self.max_pages.saturating_sub(cp.pages);
+        self.to_skip = cp.skip;
+    }
+
+    // What was added since the last call, if anything.
+    fn take_chunk(&mut self) -> Option<serde_json::Map<String, JsonValue>> {
+        (!self.chunk.is_empty()).then(|| std::mem::take(&mut self.chunk))
+    }
+
+    // Take one page's items; false when the walk ends with it.
+    fn absorb(&mut self, resp: &JsonValue) -> Result<bool> {
+        // copy non-array metadata on first page
+        if self.pages == 0 {
+            if let Some(obj) = resp.as_object() {
+                for (k, v) in obj.iter() {
+                    if !self.item_keys.iter().any(|(ik, _)| ik == k) && k != "next_page" {
+                        self.chunk.insert(k.clone(), v.clone());
+                    }
+                }
             }
-            if let Some(next_url) = resp.get("next_page").and_then(|v| v.as_str()) {
-                if let Ok(parsed) = Url::parse(next_url) {
-                    next = Some((parsed, vec![]));
-                } else {
-                    break;
+        }
+        let mut taken = 0usize;
+        let mut available = 0usize;
+        for (in_key, out_key) in self.item_keys.iter() {
+            let mut dest = vec![];
+            if let Some(arr) = resp.get(*in_key).and_then(|v| v.as_array()) {
+                available += arr.len();
+                for item in arr {
+                    if self.to_skip > 0 {
+                        self.to_skip -= 1;
+                        taken += 1;
+                        continue;
+                    }
+                    if self.collected < self.limit && self.repeated(item) {
+                        self.duplicates += 1;
+                        taken += 1;
+                        continue;
+                    }
+                    if self.collected < self.limit {
+                        if self.max_bytes != u64::MAX {
+                            self.bytes += serde_json::to_vec(item)?.len() as u64;
+                        }
+                        dest.push(item.clone());
+                        self.collected += 1;
+                        taken += 1;
+                    }
                 }
+            }
+            self.chunk
+                .insert((*out_key).to_string(), JsonValue::Array(dest));
+        }
+        if self.duplicates > 0 {
+            self.chunk
+                .insert("duplicates_dropped".into(), self.duplicates.into());
+        }
+        self.pages += 1;
+        self.last_page = resp
+            .get("page")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(self.pages as u64);
+        let has_next = resp.get("next_page").map(|v| !v.is_null()).unwrap_or(false);
+        if self.done() || self.bytes > self.max_bytes {
+            // Record where a follow-up run should resume: the rest of this
+            // page (skipping what was taken) or the next page.
+            let page = self.last_page;
+            let resume = if taken < available {
+                Some((page, taken))
+            } else if has_next {
+                Some((page + 1, 0))
             } else {
-                break;
+                None
+            };
+            if let Some((page, skip)) = resume {
+                self.chunk.insert(
+                    "continue".into(),
+                    serde_json::json!({"page": page, "skip": skip}),
+                );
             }
+            return Ok(false);
         }
-        Ok(JsonValue::Object(aggregate))
+        Ok(has_next)
+    }
+
+    // Whether `item` was already taken from an earlier page (or this one).
+    fn repeated(&mut self, item: &JsonValue) -> bool {
+        match (&mut self.seen, crate::normalize::identity(item)) {
+            (Some(seen), Some(key)) => !seen.insert(key),
+            _ => false,
+        }
+    }
+
+    // Page numbers still needed after `first` and their page size, when the
+    // response says how many there are.
+    fn remaining_pages(&self, first: &JsonValue) -> Option<(std::ops::RangeInclusive<u64>, u64)> {
+        let num = |k: &str| first.get(k).and_then(|v| v.as_u64());
+        let (page, per_page, total) = (num("page")?, num("per_page")?, num("total_results")?);
+        if per_page == 0 {
+            return None;
+        }
+        let last = total.div_ceil(per_page);
+        let by_pages = page + (self.max_pages - self.pages) as u64;
+        let needed = (self.limit - self.collected) as u64 + self.to_skip as u64;
+        let by_limit = page + needed.div_ceil(per_page);
+        Some((page + 1..=last.min(by_pages).min(by_limit), per_page))
+    }
+
+    // Past --soft-deadline: stop with what was collected, marked partial and
+    // resumable from the next page.
+    fn out_of_time(&mut self) -> bool {
+        if self.deadline.is_none_or(|d| chrono::Utc::now() < d) {
+            return false;
+        }
+        self.chunk.insert(
+            "continue".into(),
+            serde_json::json!({"page": self.last_page + 1, "skip": 0}),
+        );
+        self.chunk.insert("partial".into(), JsonValue::Bool(true));
+        true
+    }
+
+    // --no-wait at an exhausted window: stop, resumable from the next page.
+    fn rate_limited(&mut self, w: &crate::quota::Window) {
+        self.chunk.insert(
+            "continue".into(),
+            serde_json::json!({"page": self.last_page + 1, "skip": 0}),
+        );
+        self.chunk.insert(
+            "rate_limited".into(),
+            serde_json::json!({"resume_at": w.resume_at()}),
+        );
+    }
+}
+
+// Whole-transfer timeout for media downloads unless --download-timeout is set.
+pub const DEFAULT_DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+
+// Largest page size the API accepts.
+pub const MAX_PER_PAGE: u32 = 80;
+
+// Explicit --per-page wins; otherwise size pages to --limit so small exports
+// take the fewest requests (min(80, limit)) instead of the API default of 15.
+pub fn effective_per_page(per_page: Option<u32>, limit: Option<u32>) -> Option<u32> {
+    per_page.or_else(|| limit.filter(|l| *l > 0).map(|l| l.min(MAX_PER_PAGE)))
+}
+
+// A success response whose body is not valid JSON.
+struct MalformedBody {
+    status: u16,
+    content_type: Option<String>,
+    request_id: Option<String>,
+    body: Vec<u8>,
+    reason: String,
+}
+
+impl MalformedBody {
+    fn into_error(self) -> anyhow::Error {
+        malformed_json_error(
+            self.status,
+            self.content_type.as_deref(),
+            self.request_id.as_deref(),
+            &self.body,
+            &self.reason,
+        )
     }
 }
 
-async fn parse_json(resp: Response) -> Result<JsonValue> {
+// Outer error: transport failure reading the body. Inner error: body read fine
+// but is not JSON. An empty body is treated as null.
+async fn read_json(resp: Response) -> Result<std::result::Result<JsonValue, MalformedBody>> {
+    let status = resp.status().as_u16();
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let content_type = header("content-type");
+    let request_id = header("x-request-id");
     let bytes = resp.bytes().await?;
-    let v: JsonValue = serde_json::from_slice(&bytes).unwrap_or(JsonValue::String(
-        String::from_utf8_lossy(&bytes).to_string(),
-    ));
-    Ok(v)
+    if bytes.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(Ok(JsonValue::Null));
+    }
+    Ok(serde_json::from_slice(&bytes).map_err(|e| MalformedBody {
+        status,
+        content_type,
+        request_id,
+        body: bytes.to_vec(),
+        reason: e.to_string(),
+    }))
+}
+
+// Structured error for a persistently malformed body. The raw bytes are saved
+// to a temp file (referenced as raw_path) instead of being inlined lossily.
+pub fn malformed_json_error(
+    status: u16,
+    content_type: Option<&str>,
+    request_id: Option<&str>,
+    body: &[u8],
+    reason: &str,
+) -> anyhow::Error {
+    let name = format!(
+        "pexels-raw-{}-{}.body",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+        std::process::id()
+    );
+    let path = std::env::temp_dir().join(name);
+    let raw_path = match std::fs::write(&path, body) {
+        Ok(()) => JsonValue::String(path.display().to_string()),
+        Err(e) => {
+            warn!("could not save raw body: {}", e);
+            JsonValue::Null
+        }
+    };
+    let err = serde_json::json!({
+        "error": "malformed JSON response",
+        "code": status,
+        "reason": reason,
+        "content_type": content_type,
+        "request_id": request_id,
+        "bytes": body.len(),
+        "raw_path": raw_path,
+    });
+    crate::support::record_failure(&err);
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "malformed JSON response".into()))
 }
 
 async fn http_error(resp: Response) -> anyhow::Error {
     let status = resp.status();
+    let url = resp.url().to_string();
     let rid = resp
         .headers()
         .get("x-request-id")
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
+    if status == StatusCode::UNAUTHORIZED {
+        crate::support::record_failure(&serde_json::json!({ "code": 401, "url": url }));
+        return anyhow::Error::new(crate::health::TokenRejected { request_id: rid });
+    }
     let text = resp.text().await.unwrap_or_default();
     let mut err = serde_json::Map::new();
     err.insert("code".into(), JsonValue::Number(status.as_u16().into()));
@@ -429,6 +1730,9 @@ async fn http_error(resp: Response) -> anyhow::Error {
     if !text.is_empty() {
         err.insert("body".into(), JsonValue::String(text));
     }
+    let mut trace = err.clone();
+    trace.insert("url".into(), JsonValue::String(url));
+    crate::support::record_failure(&JsonValue::Object(trace));
     anyhow::anyhow!(serde_yaml::to_string(&JsonValue::Object(err))
         .unwrap_or_else(|_| format!("http error {}", status)))
 }
@@ -448,6 +1752,38 @@ fn retry_after_delay(resp: &Response, attempt: u32, override_secs: Option<u64>)
     backoff_delay(attempt)
 }
 
+fn conditional_headers(prev: &Validators) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(v) = prev
+        .etag
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        headers.insert(IF_NONE_MATCH, v);
+    }
+    if let Some(v) = prev
+        .last_modified
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        headers.insert(IF_MODIFIED_SINCE, v);
+    }
+    headers
+}
+
+fn validators_of(resp: &Response) -> Validators {
+    let header = |name| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    }
+}
+
 fn redact(s: &str) -> String {
     s.replace(|c: char| c.is_ascii_graphic(), "*")
 }
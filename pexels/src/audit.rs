@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Default location of the append-only audit log (JSONL, one record per API call).
+pub fn default_path() -> PathBuf {
+    crate::util::data_dir().join("audit.jsonl")
+}
+
+// Build a single audit record. Values that are unknown (e.g. transport errors
+// without a status) are kept as null so every line has the same shape.
+#[allow(clippy::too_many_arguments)]
+pub fn record(
+    profile: &str,
+    host: &str,
+    method: &str,
+    endpoint: &str,
+    query: &[(String, String)],
+    status: Option<u16>,
+    request_id: Option<&str>,
+    quota_remaining: Option<&str>,
+) -> JsonValue {
+    let query: serde_json::Map<String, JsonValue> = query
+        .iter()
+        .map(|(k, v)| (k.clone(), JsonValue::String(v.clone())))
+        .collect();
+    serde_json::json!({
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "profile": profile,
+        "host": host,
+        "method": method,
+        "endpoint": endpoint,
+        "query": query,
+        "status": status,
+        "request_id": request_id,
+        "quota_remaining": quota_remaining.and_then(|s| s.parse::<u64>().ok()),
+    })
+}
+
+// Append one record as a single JSON line. The file is opened in append mode
+// so concurrent writers never truncate earlier entries.
+pub fn append(path: &Path, rec: &JsonValue) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("create audit dir")?;
+    }
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("open audit log")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = f.metadata()?.permissions();
+        perms.set_mode(0o600);
+        f.set_permissions(perms)?;
+    }
+    let mut line = serde_json::to_string(rec).context("serialize audit record")?;
+    line.push('\n');
+    f.write_all(line.as_bytes()).context("write audit log")?;
+    Ok(())
+}
+
+// Records in log order; unreadable lines are skipped. A missing log is empty.
+pub fn read(path: &Path) -> Result<Vec<JsonValue>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let raw = fs::read_to_string(path).context("read audit log")?;
+    Ok(raw
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+// Record picked by `util replay-request`: a 1-based position (negative
+// counts from the end, -1 is the latest) or a logged `request_id`.
+pub fn select<'a>(records: &'a [JsonValue], selector: &str) -> Option<(usize, &'a JsonValue)> {
+    if let Ok(n) = selector.parse::<i64>() {
+        let len = records.len() as i64;
+        let idx = if n < 0 { len + n } else { n - 1 };
+        return (0..len)
+            .contains(&idx)
+            .then(|| (idx as usize + 1, &records[idx as usize]));
+    }
+    records
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, r)| r.get("request_id").and_then(|v| v.as_str()) == Some(selector))
+        .map(|(i, r)| (i + 1, r))
+}
+
+// Leaf-level differences between two JSON documents as
+// `{path, recorded, current}`, at most `limit` of them. Paths use the
+// `--fields` notation (`photos[0].src.large`); missing sides are null.
+pub fn diff(recorded: &JsonValue, current: &JsonValue, limit: usize) -> Vec<JsonValue> {
+    let mut out = vec![];
+    diff_at("", recorded, current, limit, &mut out);
+    out
+}
+
+fn diff_at(path: &str, a: &JsonValue, b: &JsonValue, limit: usize, out: &mut Vec<JsonValue>) {
+    if out.len() >= limit || a == b {
+        return;
+    }
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (a, b) {
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            let mut keys: Vec<&String> = x.keys().chain(y.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for k in keys {
+                let null = JsonValue::Null;
+                diff_at(
+                    &child(k),
+                    x.get(k).unwrap_or(&null),
+                    y.get(k).unwrap_or(&null),
+                    limit,
+                    out,
+                );
+            }
+        }
+        (JsonValue::Array(x), JsonValue::Array(y)) => {
+            for i in 0..x.len().max(y.len()) {
+                let null = JsonValue::Null;
+                diff_at(
+                    &format!("{}[{}]", path, i),
+                    x.get(i).unwrap_or(&null),
+                    y.get(i).unwrap_or(&null),
+                    limit,
+                    out,
+                );
+            }
+        }
+        _ => out.push(serde_json::json!({
+            "path": if path.is_empty() { "." } else { path },
+            "recorded": a,
+            "current": b,
+        })),
+    }
+}
@@ -0,0 +1,208 @@
+// Blocking facade over the async client, in the spirit of `reqwest::blocking`.
+// Each client owns a private current-thread runtime, so callers (scripts,
+// build.rs, non-tokio apps) never set one up themselves. Do not use from
+// inside an async context: blocking on a runtime there panics.
+
+use crate::api::PexelsClient;
+use crate::config::Config;
+use crate::models::{Collection, Media, Page, Photo, Video};
+use anyhow::{Context, Result};
+use futures::stream::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::pin::Pin;
+use tokio::runtime::{Builder, Runtime};
+
+pub struct Client {
+    inner: PexelsClient,
+    rt: Runtime,
+}
+
+impl Client {
+    pub fn new(cfg: Config) -> Result<Self> {
+        let rt = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("build runtime")?;
+        let inner = {
+            let _guard = rt.enter();
+            PexelsClient::new(cfg)?
+        };
+        Ok(Self { inner, rt })
+    }
+
+    pub fn with_token(token: impl Into<String>) -> Result<Self> {
+        Self::new(Config {
+            token: Some(token.into()),
+            timeout_secs: 15,
+            max_retries: 3,
+            ..Default::default()
+        })
+    }
+
+    // The wrapped async client, for APIs not mirrored here.
+    pub fn async_client(&self) -> &PexelsClient {
+        &self.inner
+    }
+
+    pub fn photos(&self) -> Photos<'_> {
+        Photos { client: self }
+    }
+
+    pub fn videos(&self) -> Videos<'_> {
+        Videos { client: self }
+    }
+
+    pub fn collections(&self) -> Collections<'_> {
+        Collections { client: self }
+    }
+
+    pub fn photo(&self, id: &str) -> Result<Photo> {
+        self.rt.block_on(self.inner.photo(id))
+    }
+
+    pub fn video(&self, id: &str) -> Result<Video> {
+        self.rt.block_on(self.inner.video(id))
+    }
+
+    pub fn photos_get(&self, id: &str) -> Result<JsonValue> {
+        self.rt.block_on(self.inner.photos_get(id))
+    }
+
+    pub fn videos_get(&self, id: &str) -> Result<JsonValue> {
+        self.rt.block_on(self.inner.videos_get(id))
+    }
+
+    pub fn collections_get(&self, id: &str) -> Result<JsonValue> {
+        self.rt.block_on(self.inner.collections_get(id))
+    }
+
+    pub fn download_url_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        self.rt.block_on(self.inner.download_url_bytes(url))
+    }
+
+    fn wrap<'a, T>(&'a self, inner: crate::pages::PageRequest<'a, T>) -> PageRequest<'a, T> {
+        PageRequest {
+            rt: &self.rt,
+            inner,
+        }
+    }
+}
+
+pub struct Photos<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Photos<'a> {
+    pub fn search(&self, query: &str) -> PageRequest<'a, Photo> {
+        PageRequest {
+            rt: &self.client.rt,
+            inner: self.client.inner.photos().search(query),
+        }
+    }
+
+    pub fn curated(&self) -> PageRequest<'a, Photo> {
+        PageRequest {
+            rt: &self.client.rt,
+            inner: self.client.inner.photos().curated(),
+        }
+    }
+}
+
+pub struct Videos<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Videos<'a> {
+    pub fn search(&self, query: &str) -> PageRequest<'a, Video> {
+        self.client.wrap(self.client.inner.videos().search(query))
+    }
+
+    pub fn popular(&self) -> PageRequest<'a, Video> {
+        self.client.wrap(self.client.inner.videos().popular())
+    }
+}
+
+pub struct Collections<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Collections<'a> {
+    pub fn featured(&self) -> PageRequest<'a, Collection> {
+        self.client.wrap(self.client.inner.collections().featured())
+    }
+
+    pub fn mine(&self) -> PageRequest<'a, Collection> {
+        self.client.wrap(self.client.inner.collections().mine())
+    }
+
+    pub fn media(&self, id: &str) -> PageRequest<'a, Media> {
+        self.client.wrap(self.client.inner.collections().media(id))
+    }
+}
+
+// Blocking counterpart of `pages::PageRequest`; the same builder methods apply.
+pub struct PageRequest<'a, T> {
+    rt: &'a Runtime,
+    inner: crate::pages::PageRequest<'a, T>,
+}
+
+impl<'a, T: DeserializeOwned + 'a> PageRequest<'a, T> {
+    pub fn param(self, key: &str, value: impl ToString) -> Self {
+        self.map(|r| r.param(key, value))
+    }
+
+    pub fn page(self, page: u32) -> Self {
+        self.map(|r| r.page(page))
+    }
+
+    pub fn per_page(self, per_page: u32) -> Self {
+        self.map(|r| r.per_page(per_page))
+    }
+
+    pub fn limit(self, limit: u32) -> Self {
+        self.map(|r| r.limit(limit))
+    }
+
+    pub fn max_pages(self, max_pages: u32) -> Self {
+        self.map(|r| r.max_pages(max_pages))
+    }
+
+    fn map(
+        self,
+        f: impl FnOnce(crate::pages::PageRequest<'a, T>) -> crate::pages::PageRequest<'a, T>,
+    ) -> Self {
+        Self {
+            rt: self.rt,
+            inner: f(self.inner),
+        }
+    }
+
+    pub fn pages(self) -> BlockingIter<'a, Result<Page<T>>> {
+        BlockingIter {
+            rt: self.rt,
+            stream: Box::pin(self.inner.pages()),
+        }
+    }
+
+    pub fn items(self) -> BlockingIter<'a, Result<T>> {
+        BlockingIter {
+            rt: self.rt,
+            stream: Box::pin(self.inner.items()),
+        }
+    }
+}
+
+// Iterator that drives an async stream on the client's runtime.
+pub struct BlockingIter<'a, I> {
+    rt: &'a Runtime,
+    stream: Pin<Box<dyn Stream<Item = I> + 'a>>,
+}
+
+impl<I> Iterator for BlockingIter<'_, I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<I> {
+        self.rt.block_on(self.stream.next())
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Result cache for list pages. Entries are keyed by the logical request --
+// endpoint, sorted parameters, locale and a hash of the token -- rather than
+// the literal URL, so `-q cats --color red` and `--color red -q cats` (or a
+// `next_page` URL and the equivalent `--page`) share one entry. Each entry is
+// one JSON file named after the key's hash; expired entries are ignored and
+// overwritten by the next fetch.
+
+// Seconds an entry stays fresh unless `cache.ttl` is set (0 disables).
+pub const DEFAULT_TTL_SECS: u64 = 300;
+
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+pub fn default_dir() -> PathBuf {
+    let proj = directories::ProjectDirs::from("", "", "pexels").expect("config dirs");
+    proj.cache_dir().join("results")
+}
+
+// `GET <host><path>?<sorted params>|locale=..|token=<hash>`. Query pairs
+// embedded in `url` are merged with `qp`; `page=1` is dropped since it is
+// the default.
+pub fn logical_key(
+    url: &url::Url,
+    qp: &[(String, String)],
+    locale: Option<&str>,
+    token: Option<&str>,
+) -> String {
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .chain(qp.iter().cloned())
+        .filter(|(k, v)| !(k == "page" && v == "1"))
+        .collect();
+    params.sort();
+    params.dedup();
+    let query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let token = token
+        .map(|t| format!("{:016x}", crate::util::fnv1a64(t.as_bytes())))
+        .unwrap_or_default();
+    format!(
+        "GET {}{}?{}|locale={}|token={}",
+        url.origin().ascii_serialization(),
+        url.path(),
+        query.join("&"),
+        locale.unwrap_or_default(),
+        token
+    )
+}
+
+// Structured error for an `--offline` request with no cached response.
+pub fn offline_miss(url: &url::Url, qp: &[(String, String)]) -> anyhow::Error {
+    let query: serde_json::Map<String, JsonValue> = qp
+        .iter()
+        .map(|(k, v)| (k.clone(), JsonValue::String(v.clone())))
+        .collect();
+    let err = serde_json::json!({
+        "error": "not cached",
+        "endpoint": url.path(),
+        "query": query,
+        "hint": "run the same command online once to cache it, or drop --offline",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "not cached".into()))
+}
+
+// Structured error for a network request attempted under `--offline`
+// (downloads, pings, live quota probes, ...).
+pub fn offline_error(url: &url::Url) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "network access disabled by --offline",
+        "url": url.as_str(),
+        "hint": "only list and get results from the cache are available offline",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "offline".into()))
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{:016x}.json",
+            crate::util::fnv1a64(key.as_bytes())
+        ))
+    }
+
+    // Fresh value stored under `key`, if any.
+    pub fn get(&self, key: &str) -> Option<JsonValue> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        let entry = self.entry(key)?;
+        let stored = entry.get("stored_at").and_then(|t| t.as_i64())?;
+        let age = chrono::Utc::now().timestamp().saturating_sub(stored);
+        if age < 0 || age as u64 >= self.ttl.as_secs() {
+            return None;
+        }
+        entry.get("value").cloned()
+    }
+
+    // `--offline` lookup: the stored value for `key` whatever its age. A key
+    // made without a token (CI without credentials) matches the newest entry
+    // for the same request under any token.
+    pub fn get_offline(&self, key: &str) -> Option<JsonValue> {
+        if let Some(v) = self.entry(key).and_then(|e| e.get("value").cloned()) {
+            return Some(v);
+        }
+        let prefix = key.strip_suffix("|token=")?;
+        std::fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|e| std::fs::read(e.ok()?.path()).ok())
+            .filter_map(|raw| serde_json::from_slice::<JsonValue>(&raw).ok())
+            .filter(|e| {
+                e.get("key")
+                    .and_then(|k| k.as_str())
+                    .and_then(|k| k.strip_prefix(prefix))
+                    .is_some_and(|rest| rest.starts_with("|token="))
+            })
+            .max_by_key(|e| e.get("stored_at").and_then(|t| t.as_i64()))
+            .and_then(|e| e.get("value").cloned())
+    }
+
+    fn entry(&self, key: &str) -> Option<JsonValue> {
+        let raw = std::fs::read(self.path(key)).ok()?;
+        let entry: JsonValue = serde_json::from_slice(&raw).ok()?;
+        // Hash collisions: the stored key must match exactly
+        (entry.get("key").and_then(|k| k.as_str()) == Some(key)).then_some(entry)
+    }
+
+    // Store `value` under `key` (temp file + rename, so readers never see a
+    // partial entry).
+    pub fn put(&self, key: &str, value: &JsonValue) -> Result<()> {
+        if self.ttl.is_zero() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir).context("create cache dir")?;
+        let path = self.path(key);
+        let entry = serde_json::json!({
+            "key": key,
+            "stored_at": chrono::Utc::now().timestamp(),
+            "value": value,
+        });
+        let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec(&entry)?).context("write cache entry")?;
+        std::fs::rename(&tmp, &path).context("replace cache entry")?;
+        Ok(())
+    }
+}
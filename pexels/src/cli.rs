@@ -1,10 +1,14 @@
 use crate::api::PexelsClient;
-use crate::config::{Config, TokenSource};
+use crate::config::{Config, TokenSource, TokenStorage};
+use crate::download::IfBusy;
 use crate::output::emit_raw_bytes;
-use crate::output::{emit_data, wrap_ok, OutputFormat};
+use crate::output::{collapse_data, emit_data, wrap_ok, Collapse, Emit, OutputFormat};
+use crate::pick::PickAction;
+use crate::state::State;
 use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde_json::Value as JsonValue;
+use std::io::{self, Write};
 // std::time::Duration not used here
 
 #[derive(Parser, Debug)]
@@ -17,7 +21,9 @@ use serde_json::Value as JsonValue;
   pexels photos search -q cats
   pexels photos curated
   pexels videos popular
-  pexels collections featured"#
+  pexels collections featured
+
+Every subcommand's --help lists its own examples; see also `pexels examples`."#
 )]
 #[command(arg_required_else_help = true)]
 pub struct Cli {
@@ -27,13 +33,25 @@ pub struct Cli {
     /// Raw output (HTTP body)
     #[arg(long, global = true)]
     pub raw: bool,
+    /// One JSON object per line: list results emit each projected item, without the envelope
+    #[arg(long, global = true, conflicts_with_all = ["json", "raw"])]
+    pub ndjson: bool,
+    /// Output format; csv/tsv/parquet print one row per item with dot-path headers (src.original)
+    #[arg(long, global = true, value_enum, conflicts_with_all = ["json", "raw", "ndjson"])]
+    pub output: Option<OutputFormat>,
     /// Fields selection (dot paths or sets)
-    #[arg(long, global = true)]
+    #[arg(long, global = true, value_delimiter = ',')]
     pub fields: Vec<String>,
+    /// Drop fields after selection (same path syntax as --fields)
+    #[arg(long = "exclude-fields", global = true, value_delimiter = ',')]
+    pub exclude_fields: Vec<String>,
+    /// Computed field per item, e.g. 'megapixels = round(width*height/1e6, 1)' (repeatable)
+    #[arg(long, global = true, value_name = "NAME = EXPR", value_parser = crate::expr::parse_map)]
+    pub map: Vec<crate::expr::MapExpr>,
     /// Page number
     #[arg(long, global = true)]
     pub page: Option<u32>,
-    /// Per page
+    /// Per page (default: `default_per_page` from config, else the API's)
     #[arg(long = "per-page", global = true)]
     pub per_page: Option<u32>,
     /// Fetch all pages
@@ -45,27 +63,209 @@ pub struct Cli {
     /// Max pages when --all
     #[arg(long = "max-pages", global = true)]
     pub max_pages: Option<u32>,
+    /// Stop paging after this long (e.g. 30s, 2m) and return what was collected with meta.partial
+    /// and a continue point, instead of running unbounded
+    #[arg(long = "soft-deadline", global = true, value_name = "DURATION", value_parser = crate::util::parse_duration)]
+    pub soft_deadline: Option<std::time::Duration>,
+    /// Pages fetched at once when paging through results (--all/--limit/--max-pages)
+    #[arg(
+        long = "page-concurrency",
+        global = true,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=16)
+    )]
+    pub page_concurrency: u32,
+    /// Keep items that show up again on a later page (by default repeats are dropped and
+    /// counted in meta.duplicates_dropped)
+    #[arg(long = "no-dedupe", global = true)]
+    pub no_dedupe: bool,
+    /// With --raw: fetch the page stored in this file (the first page when it
+    /// doesn't exist) and store the response's next_page URL back
+    #[arg(
+        long = "cursor-file",
+        global = true,
+        value_name = "PATH",
+        requires = "raw",
+        conflicts_with_all = ["all", "limit", "max_pages"]
+    )]
+    pub cursor_file: Option<std::path::PathBuf>,
+    /// With --all: record the walk's position in this file after every page and, when it
+    /// already holds one, continue from there instead of the first page
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        requires = "all",
+        conflicts_with_all = ["cursor_file", "page"]
+    )]
+    pub resume: Option<std::path::PathBuf>,
     /// jq expression passthrough (not executed in CLI, forwarded intent)
     #[arg(long, global = true)]
     pub jq: Option<String>,
-    /// jmes expression passthrough
+    /// JMESPath expression applied to the output envelope (to each item with --ndjson)
     #[arg(long, global = true)]
     pub jmes: Option<String>,
-    /// Timeout seconds
-    #[arg(long, global = true, default_value_t = 15)]
-    pub timeout: u64,
-    /// Max retries
-    #[arg(long = "max-retries", global = true, default_value_t = 3)]
-    pub max_retries: u32,
+    /// Timeout seconds (default 15, or `timeout` from config)
+    #[arg(long, global = true)]
+    pub timeout: Option<u64>,
+    /// Max retries (default 3, or `max_retries` from config)
+    #[arg(long = "max-retries", global = true)]
+    pub max_retries: Option<u32>,
+    /// Timeout seconds for media downloads (default 300)
+    #[arg(long = "download-timeout", global = true)]
+    pub download_timeout: Option<u64>,
+    /// Max retries for media downloads (default: --max-retries)
+    #[arg(long = "download-retries", global = true)]
+    pub download_retries: Option<u32>,
+    /// Re-download files already on disk instead of revalidating them (If-None-Match/If-Modified-Since)
+    #[arg(long = "no-conditional", global = true)]
+    pub no_conditional: bool,
+    /// Name downloads from a template, e.g. "{id}_{photographer_slug}.{ext}" (PATH becomes a directory)
+    #[arg(long = "name-template", global = true, value_name = "TEMPLATE")]
+    pub name_template: Option<String>,
+    /// Never draw the download progress bar on stderr
+    #[arg(long = "no-progress", global = true)]
+    pub no_progress: bool,
+    /// Seconds to wait for a busy config/state lock (0 = fail immediately; default 10)
+    #[arg(long = "wait-lock", global = true, value_name = "SECS")]
+    pub wait_lock: Option<u64>,
+    /// Bypass the result cache for this run (the fresh result is still cached); `quota view` probes live
+    #[arg(long, global = true)]
+    pub refresh: bool,
+    /// Stop at an exhausted rate-limit window and print when to resume, instead of sleeping until it resets
+    #[arg(long = "no-wait", global = true)]
+    pub no_wait: bool,
+    /// Simulate failures by attempt number: KIND@N[,KIND@N] with KIND an HTTP status, timeout or connect
+    #[cfg(feature = "fault-injection")]
+    #[arg(long = "inject-fault", global = true, value_delimiter = ',', value_parser = crate::fault::parse)]
+    pub inject_fault: Vec<crate::fault::Fault>,
+    /// Answer list/get commands from the result cache only (any age); nothing is fetched
+    #[arg(long, global = true, conflicts_with = "refresh")]
+    pub offline: bool,
     /// Retry-After cap seconds (override)
     #[arg(long = "retry-after", global = true)]
     pub retry_after: Option<u64>,
     /// Host override for testing
     #[arg(long, global = true)]
     pub host: Option<String>,
-    /// Locale for Accept-Language
+    /// Config file to use instead of the default location; also PEXELS_CONFIG
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<std::path::PathBuf>,
+    /// Config profile to use (`profiles.<name>`: token, host, locale, defaults); also PEXELS_PROFILE
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// API token for this run only, over profiles and PEXELS_TOKEN; never saved. Less safe than
+    /// PEXELS_TOKEN or the config file: it shows up in process lists and shell history
+    #[arg(
+        long = "token",
+        global = true,
+        value_name = "TOKEN",
+        conflicts_with = "token_file"
+    )]
+    pub run_token: Option<String>,
+    /// Read this run's API token from a file (e.g. a mounted secret); never saved. Less safe than
+    /// the config file, which is kept private (0600)
+    #[arg(long = "token-file", global = true, value_name = "PATH")]
+    pub token_file: Option<std::path::PathBuf>,
+    /// Read this run's API token from stdin (`op read ... | pexels --token-stdin ...`; a hidden
+    /// prompt on a terminal), keeping it out of argv and the environment; never saved
+    #[arg(
+        long = "token-stdin",
+        global = true,
+        conflicts_with_all = ["run_token", "token_file"]
+    )]
+    pub token_stdin: bool,
+    /// Named host profile from config (`hosts.<name>`: base URL, headers, cacert)
+    #[arg(long = "host-profile", global = true)]
+    pub host_profile: Option<String>,
+    /// Locale for Accept-Language (default: `locale` from config)
     #[arg(long, global = true)]
     pub locale: Option<String>,
+    /// Also write the result to files: FORMAT=PATH[,FORMAT=PATH] (json, yaml, csv, tsv, md, parquet)
+    #[arg(long = "also-write", global = true, value_delimiter = ',')]
+    pub also_write: Vec<String>,
+    /// Emit canonical records (id, kind, title, creator, width, height, duration, urls)
+    #[arg(long, global = true)]
+    pub normalize: bool,
+    /// Stamp items with their original API position (_position: page/index/rank)
+    #[arg(long, global = true)]
+    pub positions: bool,
+    /// Collapse nested structures deeper than N levels in YAML output
+    #[arg(long = "max-depth", global = true)]
+    pub max_depth: Option<usize>,
+    /// Truncate long nested arrays in YAML output
+    #[arg(long, global = true)]
+    pub compact: bool,
+    /// Show full resources: no default projection, no YAML collapsing
+    #[arg(long, global = true)]
+    pub full: bool,
+    /// Abort (or truncate) when the response exceeds N megabytes
+    #[arg(long = "max-response-mb", global = true)]
+    pub max_response_mb: Option<u64>,
+    /// Policy when --max-response-mb is exceeded
+    #[arg(long = "on-oversize", global = true, value_enum, default_value_t = OversizePolicy::Abort)]
+    pub on_oversize: OversizePolicy,
+    /// Human-readable sizes, durations and counts in YAML output
+    #[arg(long, global = true, overrides_with = "no_human")]
+    pub human: bool,
+    /// Keep numbers raw even when config enables human output
+    #[arg(long = "no-human", global = true)]
+    pub no_human: bool,
+    /// Choose results interactively before output or download
+    #[arg(long, global = true)]
+    pub pick: bool,
+    /// What to do with picked items
+    #[arg(long = "then", global = true, value_enum, default_value_t = PickAction::Emit, requires = "pick")]
+    pub pick_then: PickAction,
+    /// Directory for --then download
+    #[arg(
+        long = "pick-dir",
+        global = true,
+        default_value = ".",
+        requires = "pick"
+    )]
+    pub pick_dir: std::path::PathBuf,
+    /// Run the named config post-processor on each downloaded file
+    #[arg(long, global = true)]
+    pub post: Option<String>,
+    /// Poll a list command and print new items as JSON lines
+    #[arg(long, global = true)]
+    pub watch: bool,
+    /// Seconds between watch polls (default: watch.interval from config, else 60)
+    #[arg(long, global = true, requires = "watch")]
+    pub interval: Option<u64>,
+    /// Stop watching after a duration (e.g. 90s, 30m, 2h) and print a summary
+    #[arg(long = "for", global = true, requires = "watch", value_parser = crate::util::parse_duration)]
+    pub watch_for: Option<std::time::Duration>,
+    /// Append watch items as NDJSON to this file instead of stdout
+    #[arg(
+        long = "ndjson-file",
+        global = true,
+        value_name = "PATH",
+        requires = "watch"
+    )]
+    pub ndjson_file: Option<std::path::PathBuf>,
+    /// Rotate --ndjson-file: size=100MB or daily (rotated files get a UTC timestamp)
+    #[arg(long, global = true, requires = "ndjson_file", value_parser = crate::sink::parse_rotate)]
+    pub rotate: Option<crate::sink::Rotate>,
+    /// Deliver results to a file path or POST them to an http(s) URL instead of stdout
+    #[arg(long, global = true, value_name = "TARGET", value_parser = crate::sink::parse_target)]
+    pub sink: Option<crate::sink::Target>,
+    /// Extra header for every --sink POST, e.g. "Authorization: Bearer <key>" (repeatable)
+    #[arg(
+        long = "sink-header",
+        global = true,
+        value_name = "HEADER",
+        requires = "sink"
+    )]
+    pub sink_headers: Vec<String>,
+    /// Keep at most N items per photographer (or video creator)
+    #[arg(long = "max-per-photographer", global = true)]
+    pub max_per_photographer: Option<usize>,
+    /// Print only the meta block, only the data block, or both
+    #[arg(long, global = true, value_enum, default_value_t = Emit::Both)]
+    pub emit: Emit,
     /// Verbose logging
     #[arg(long, global = true)]
     pub verbose: bool,
@@ -75,6 +275,15 @@ pub struct Cli {
     /// Color control
     #[arg(long, global = true, value_enum)]
     pub color: Option<ColorChoice>,
+    // Original arguments when re-run by `pexels continue`
+    #[arg(skip)]
+    pub replay_argv: Option<Vec<String>>,
+    // Set when the output format was chosen because stdout is not a terminal
+    #[arg(skip)]
+    pub format_autoselected: bool,
+    // Leading items of the first page to drop when continuing mid-page
+    #[arg(skip)]
+    pub resume_skip: usize,
 
     #[command(subcommand)]
     pub command: Commands,
@@ -87,6 +296,73 @@ pub enum ColorChoice {
     Never,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum OversizePolicy {
+    Abort,
+    Truncate,
+}
+
+impl Cli {
+    // Token given for this run with --token, --token-file or --token-stdin.
+    pub fn flag_token(&self) -> Result<Option<String>> {
+        if self.token_stdin {
+            let login_stdin = matches!(&self.command, Commands::Auth(a)
+                if matches!(a.sub, AuthSub::Login { stdin: true, .. }));
+            if login_stdin {
+                let err = serde_json::json!({
+                    "error": "--token-stdin and auth login --stdin both read stdin",
+                    "hint": "auth login --stdin alone reads and saves the token",
+                });
+                return Err(anyhow::anyhow!(serde_yaml::to_string(&err)?));
+            }
+            return token_from_stdin().map(Some);
+        }
+        let Some(path) = &self.token_file else {
+            return Ok(self.run_token.clone());
+        };
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("read token file {}", path.display()))?;
+        // Secret mounts usually end in a newline
+        let token = raw.trim();
+        if !token.is_empty() && !token.contains(char::is_whitespace) {
+            return Ok(Some(token.to_string()));
+        }
+        let err = serde_json::json!({
+            "error": "token file must hold exactly one token",
+            "path": path.display().to_string(),
+        });
+        Err(anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(
+            |_| "token file must hold exactly one token".into()
+        )))
+    }
+
+    // Format named by --output, --raw, --ndjson or --json, if any
+    pub fn explicit_format(&self) -> Option<OutputFormat> {
+        if let Some(fmt) = &self.output {
+            Some(fmt.clone())
+        } else if self.raw {
+            Some(OutputFormat::Raw)
+        } else if self.ndjson {
+            Some(OutputFormat::Ndjson)
+        } else if self.json {
+            Some(OutputFormat::Json)
+        } else {
+            None
+        }
+    }
+
+    // --all, --limit or --max-pages: list commands follow next_page.
+    pub fn walks_pages(&self) -> bool {
+        self.all || self.limit.is_some() || self.max_pages.is_some()
+    }
+
+    // Response size ceiling in bytes from --max-response-mb
+    pub fn max_response_bytes(&self) -> Option<u64> {
+        self.max_response_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     Auth(AuthCmd),
@@ -96,6 +372,63 @@ pub enum Commands {
     Videos(VideosCmd),
     Collections(CollectionsCmd),
     Util(UtilCmd),
+    /// Inspect persisted CLI state
+    State(StateCmd),
+    /// Queue downloads across sessions and fetch them in one run
+    Queue(QueueCmd),
+    /// Build datasets and other bundles from search results
+    Export(ExportCmd),
+    /// Page through curated or search photos in the terminal, mark some and download them
+    Browse {
+        #[arg(short = 'q', long = "query")]
+        query: Option<String>,
+        /// Where marked photos go, named photo-<id>.<ext> (default: `download_dir` from config)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+        /// Transfers in flight at once
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Print a shell completion script (e.g. `pexels completions bash > /etc/bash_completion.d/pexels`)
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Call any API endpoint with the configured token, retries and audit log
+    Api {
+        #[arg(value_enum, ignore_case = true)]
+        method: ApiMethod,
+        /// Path on the API host (e.g. /v1/search), or a full URL on that host
+        path: String,
+        /// Query parameter KEY=VALUE (repeatable)
+        #[arg(long = "param", short = 'p', value_name = "KEY=VALUE", value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Resume the last list command cut short by --limit/--max-pages
+    Continue,
+    /// Show runnable examples, optionally for one command (e.g. `photos search`)
+    Examples {
+        /// Command path, or a recipe category/name with --recipes
+        #[arg(value_name = "COMMAND")]
+        command: Vec<String>,
+        /// Multi-step recipes (export, wallpaper, pipelines) filled from your config
+        #[arg(long)]
+        recipes: bool,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum ApiMethod {
+    Get,
+    Head,
+}
+
+// `--param KEY=VALUE` for `pexels api`; the value may itself contain `=`.
+pub fn parse_param(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_string(), v.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got `{}`", s)),
+    }
 }
 
 #[derive(Args, Debug)]
@@ -108,8 +441,16 @@ pub enum AuthSub {
     Login {
         #[arg(value_name = "TOKEN")]
         token: Option<String>,
+        /// Read the token from stdin (a hidden prompt on a terminal), keeping it out of shell history and process lists
+        #[arg(long, conflicts_with = "token")]
+        stdin: bool,
+        /// Save without first checking the token against the API
+        #[arg(long = "no-verify")]
+        no_verify: bool,
     },
     Status,
+    /// Which token is active: fingerprint, source, profile and last-seen quota (no request)
+    Whoami,
     Logout,
 }
 
@@ -120,8 +461,19 @@ pub struct ConfigCmd {
 }
 #[derive(Subcommand, Debug)]
 pub enum ConfigSub {
-    Set { key: String, value: String },
-    Get { key: String },
+    Set {
+        key: String,
+        value: String,
+    },
+    Get {
+        key: String,
+    },
+    /// Remove a key from config.yaml so its default applies again
+    Unset {
+        key: String,
+    },
+    /// Every key with its effective value and source (default, file, env, cli, keyring)
+    List,
     Path,
 }
 
@@ -143,12 +495,61 @@ pub struct PhotosCmd {
 #[derive(Subcommand, Debug)]
 pub enum PhotosSub {
     Search {
-        #[arg(short = 'q', long = "query")]
-        query: String,
+        #[arg(short = 'q', long = "query", required_unless_present_any = ["any_of", "all_of"])]
+        query: Option<String>,
+        /// Run one search per term and merge the results (comma separated)
+        #[arg(long = "any-of", value_delimiter = ',')]
+        any_of: Vec<String>,
+        /// Terms every result must mention (added to each search, checked on alt text)
+        #[arg(long = "all-of", value_delimiter = ',')]
+        all_of: Vec<String>,
+        /// Drop results whose alt text mentions any of these terms
+        #[arg(long = "none-of", value_delimiter = ',')]
+        none_of: Vec<String>,
+        #[command(flatten)]
+        filters: PhotoFilters,
+        /// Add per-quadrant average color and busy-ness (fetches each result's small variant)
+        #[arg(long)]
+        analyze: bool,
+        /// Draw each result's small variant with its one-line summary instead of the usual output
+        #[arg(long)]
+        preview: bool,
+        #[command(flatten)]
+        preview_args: PreviewArgs,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    Curated {
+        #[command(flatten)]
+        count: CountArgs,
     },
-    Curated,
+    /// One photo, or several looked up at once (a list with meta.errors for misses)
     Get {
-        id: String,
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+        /// Lookups in flight at once with several IDs
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+    },
+    /// Exit 0 if the photo exists, 4 if not (3 for a rejected token); prints only with --json
+    Exists { id: String },
+    /// Photo of the day: one stable pick per calendar day from curated or a query
+    Potd {
+        /// Theme query to pick from instead of curated
+        #[arg(short = 'q', long = "query")]
+        query: Option<String>,
+    },
+    /// Estimate total download size per size tier (metadata + sampled HEADs)
+    Estimate {
+        /// Search query (curated when omitted)
+        #[arg(short = 'q', long = "query")]
+        query: Option<String>,
+        /// Size tier to highlight in the summary
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        /// Photos sampled per tier for Content-Length
+        #[arg(long, default_value_t = 5)]
+        sample: usize,
     },
     /// Return canonical photo URL (src.original)
     Url {
@@ -157,13 +558,231 @@ pub enum PhotosSub {
         #[arg(long, value_enum)]
         size: Option<PhotoSize>,
     },
+    /// Resolve many photo IDs to URLs at once (ID -> URL map, errors per ID)
+    Urls {
+        /// File with one photo ID per line, `-` for stdin (blank lines and # comments skipped)
+        #[arg(long)]
+        input: std::path::PathBuf,
+        /// Size variant from src.* (default: original)
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        /// Lookups in flight at once
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+    },
+    /// Draw a photo in the terminal (kitty, iTerm2 or sixel graphics, else colored blocks)
+    Preview {
+        id: String,
+        /// Size variant to draw (default: small)
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        #[command(flatten)]
+        preview_args: PreviewArgs,
+    },
+    /// Dominant colors of a photo (k-means over a small variant), with proportions
+    Palette {
+        id: String,
+        /// Number of colors
+        #[arg(
+            long,
+            default_value_t = crate::palette::DEFAULT_COUNT,
+            value_parser = clap::value_parser!(u8).range(1..=16),
+        )]
+        count: u8,
+    },
+    /// WCAG contrast of overlay text against a region of the photo
+    Contrast {
+        id: String,
+        /// Text color as #rgb or #rrggbb
+        #[arg(long = "text-color", value_parser = crate::contrast::parse_hex)]
+        text_color: [u8; 3],
+        /// Where the text sits
+        #[arg(long, value_enum, default_value_t = crate::contrast::Region::Full)]
+        region: crate::contrast::Region,
+    },
+    /// Search and download every result into a directory
+    DownloadAll {
+        #[arg(short = 'q', long = "query")]
+        query: String,
+        /// Target directory, files named photo-<id>.<ext> (default: `download_dir` from config)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+        /// Size variant from src.* (default: original)
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        /// Transfers in flight at once
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+        #[command(flatten)]
+        mirror: MirrorArgs,
+        #[command(flatten)]
+        web: WebOptimizeArgs,
+        /// Write per-quadrant average color and busy-ness to <file>.analysis.json
+        #[arg(long)]
+        analyze: bool,
+    },
     /// Download the original photo bytes to path
     Download {
         id: String,
         path: String,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+        #[command(flatten)]
+        web: WebOptimizeArgs,
+        /// Write per-quadrant average color and busy-ness to <path>.analysis.json
+        #[arg(long)]
+        analyze: bool,
     },
 }
 
+// Search filters passed through to the API.
+#[derive(Args, Clone, Debug, Default)]
+pub struct PhotoFilters {
+    /// Photo orientation
+    #[arg(long, value_enum)]
+    pub orientation: Option<Orientation>,
+    /// Minimum photo size: large (24MP), medium (12MP) or small (4MP)
+    #[arg(long = "size", value_enum)]
+    pub size: Option<SearchSize>,
+    /// Desired color: a name (red, orange, ...) or a hex code such as #ffffff
+    #[arg(long, value_parser = parse_color)]
+    pub color: Option<String>,
+}
+
+impl PhotoFilters {
+    pub fn query_params(&self) -> Vec<(String, String)> {
+        let mut qp = vec![];
+        if let Some(o) = self.orientation {
+            qp.push(("orientation".into(), o.key().into()));
+        }
+        if let Some(s) = self.size {
+            qp.push(("size".into(), s.key().into()));
+        }
+        if let Some(c) = &self.color {
+            qp.push(("color".into(), c.clone()));
+        }
+        qp
+    }
+}
+
+// Video search filters passed through to the API.
+#[derive(Args, Clone, Debug, Default)]
+pub struct VideoFilters {
+    /// Minimum video width in pixels
+    #[arg(long = "min-width", value_name = "PX")]
+    pub min_width: Option<u32>,
+    /// Minimum video height in pixels
+    #[arg(long = "min-height", value_name = "PX")]
+    pub min_height: Option<u32>,
+    /// Minimum duration in seconds
+    #[arg(long = "min-duration", value_name = "SECS")]
+    pub min_duration: Option<u32>,
+    /// Maximum duration in seconds
+    #[arg(long = "max-duration", value_name = "SECS")]
+    pub max_duration: Option<u32>,
+}
+
+impl VideoFilters {
+    pub fn query_params(&self) -> Vec<(String, String)> {
+        [
+            ("min_width", self.min_width),
+            ("min_height", self.min_height),
+            ("min_duration", self.min_duration),
+            ("max_duration", self.max_duration),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k.to_string(), v.to_string())))
+        .collect()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_duration, self.max_duration) {
+            if min > max {
+                let err = serde_json::json!({
+                    "error": "invalid duration range",
+                    "min_duration": min,
+                    "max_duration": max,
+                    "hint": "--min-duration must not exceed --max-duration",
+                });
+                return Err(anyhow::anyhow!(
+                    serde_yaml::to_string(&err).unwrap_or_else(|_| "invalid duration range".into())
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum Orientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl Orientation {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Orientation::Landscape => "landscape",
+            Orientation::Portrait => "portrait",
+            Orientation::Square => "square",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum SearchSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl SearchSize {
+    pub fn key(&self) -> &'static str {
+        match self {
+            SearchSize::Large => "large",
+            SearchSize::Medium => "medium",
+            SearchSize::Small => "small",
+        }
+    }
+}
+
+// Color names the search API accepts besides hex codes.
+pub const SEARCH_COLORS: [&str; 12] = [
+    "red",
+    "orange",
+    "yellow",
+    "green",
+    "turquoise",
+    "blue",
+    "violet",
+    "pink",
+    "brown",
+    "black",
+    "gray",
+    "white",
+];
+
+// Accept a supported color name or a hex code, normalized to `#rrggbb`.
+pub fn parse_color(s: &str) -> Result<String, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    if SEARCH_COLORS.contains(&lower.as_str()) {
+        return Ok(lower);
+    }
+    let hex = lower.strip_prefix('#').unwrap_or(&lower);
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(format!("#{}", hex));
+    }
+    Err(format!(
+        "expected one of {} or a hex code like #ffffff",
+        SEARCH_COLORS.join(", ")
+    ))
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 pub enum PhotoSize {
     #[value(name = "original")]
@@ -185,7 +804,18 @@ pub enum PhotoSize {
 }
 
 impl PhotoSize {
-    fn key(&self) -> &'static str {
+    pub const ALL: [PhotoSize; 8] = [
+        PhotoSize::Original,
+        PhotoSize::Large2x,
+        PhotoSize::Large,
+        PhotoSize::Medium,
+        PhotoSize::Small,
+        PhotoSize::Portrait,
+        PhotoSize::Landscape,
+        PhotoSize::Tiny,
+    ];
+
+    pub fn key(&self) -> &'static str {
         match self {
             PhotoSize::Original => "original",
             PhotoSize::Large2x => "large2x",
@@ -206,9 +836,48 @@ pub struct VideosCmd {
 }
 #[derive(Subcommand, Debug)]
 pub enum VideosSub {
-    Search { query: String },
-    Popular,
-    Get { id: String },
+    Search {
+        query: String,
+        #[command(flatten)]
+        filters: VideoFilters,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    Popular {
+        #[command(flatten)]
+        filters: VideoFilters,
+        /// Count results per duration range, e.g. duration:0-10,10-30,30+
+        #[arg(long, value_name = "FIELD:RANGES", value_parser = crate::video::parse_buckets)]
+        bucket: Option<crate::video::Buckets>,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    /// One video, or several looked up at once (a list with meta.errors for misses)
+    Get {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+        /// Lookups in flight at once with several IDs
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+    },
+    /// Exit 0 if the video exists, 4 if not (3 for a rejected token); prints only with --json
+    Exists { id: String },
+    /// Print the link of the best matching video file
+    Url {
+        id: String,
+        #[command(flatten)]
+        rendition: crate::video::Rendition,
+    },
+    /// Download a video file to path, picking the best matching rendition
+    Download {
+        id: String,
+        path: String,
+        #[command(flatten)]
+        rendition: crate::video::Rendition,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+    },
 }
 
 #[derive(Args, Debug)]
@@ -218,10 +887,240 @@ pub struct CollectionsCmd {
 }
 #[derive(Subcommand, Debug)]
 pub enum CollectionsSub {
+    List {
+        #[command(flatten)]
+        expand: ExpandArgs,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    Featured {
+        #[command(flatten)]
+        expand: ExpandArgs,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    Get {
+        id: String,
+    },
+    Items {
+        id: String,
+        #[command(flatten)]
+        count: CountArgs,
+    },
+    /// Mirror every photo and video of a collection into a directory
+    Download {
+        id: String,
+        /// Target directory, files named <kind>-<id>.<ext> plus manifest.json (default: `download_dir` from config)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+        /// Photo size variant from src.* (default: original)
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        #[command(flatten)]
+        rendition: crate::video::Rendition,
+        /// Transfers in flight at once
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+        #[command(flatten)]
+        mirror: MirrorArgs,
+    },
+}
+
+// `--dest` for batch downloads: extra places each fetched file is copied or
+// uploaded to (see mirror.rs).
+#[derive(Args, Debug, Clone)]
+pub struct MirrorArgs {
+    /// Also copy each file to DEST: a directory, s3://bucket/prefix or webdav://host/path (repeatable)
+    #[arg(long = "dest", value_name = "DEST", value_parser = crate::mirror::parse_dest)]
+    pub dest: Vec<crate::mirror::Dest>,
+}
+
+// `--web-optimize` for photo downloads: a WebP/AVIF copy of each file (see
+// optimize.rs).
+#[derive(Args, Debug, Clone)]
+pub struct WebOptimizeArgs {
+    /// Also write a WebP/AVIF copy of each photo, metadata stripped except attribution XMP
+    #[arg(long = "web-optimize")]
+    pub web_optimize: bool,
+    /// Format for --web-optimize (default: web.format from config, else webp)
+    #[arg(
+        long = "web-format",
+        value_enum,
+        value_name = "FORMAT",
+        requires = "web_optimize"
+    )]
+    pub web_format: Option<crate::optimize::WebFormat>,
+    /// Quality 0-100 for --web-optimize (default: web.quality from config, else 80)
+    #[arg(
+        long = "web-quality",
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        requires = "web_optimize"
+    )]
+    pub web_quality: Option<u8>,
+}
+
+impl WebOptimizeArgs {
+    // Flags over `web.*` config; None without --web-optimize.
+    pub fn settings(&self, cfg: &Config) -> Option<crate::optimize::Settings> {
+        self.web_optimize.then(|| crate::optimize::Settings {
+            format: self.web_format.or(cfg.web.format).unwrap_or_default(),
+            quality: self
+                .web_quality
+                .or(cfg.web.quality)
+                .unwrap_or(crate::optimize::DEFAULT_QUALITY),
+        })
+    }
+}
+
+// Inline thumbnails for `photos preview` and `photos search --preview` (see
+// preview.rs).
+#[derive(Args, Debug, Clone)]
+pub struct PreviewArgs {
+    /// Preview width in terminal columns (default: 40, capped at the terminal width)
+    #[arg(
+        long = "preview-width",
+        value_name = "COLS",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub preview_width: Option<u32>,
+    /// Graphics protocol (default: detected from the terminal; blocks, or ascii without color)
+    #[arg(long = "preview-protocol", value_enum, value_name = "PROTOCOL")]
+    pub preview_protocol: Option<crate::preview::Protocol>,
+}
+
+impl PreviewArgs {
+    // Protocol and width in columns: flags, else what the terminal supports.
+    pub fn settings(&self, cli: &Cli) -> (crate::preview::Protocol, u32) {
+        let color = match cli.color {
+            Some(ColorChoice::Always) => true,
+            Some(ColorChoice::Never) => false,
+            _ => std::env::var("NO_COLOR").is_err(),
+        };
+        let protocol = self
+            .preview_protocol
+            .unwrap_or_else(|| crate::preview::Protocol::detect(|k| std::env::var(k).ok(), color));
+        let cols = self.preview_width.unwrap_or_else(|| {
+            crate::preview::terminal_cols().map_or(crate::preview::DEFAULT_WIDTH, |w| {
+                w.min(crate::preview::DEFAULT_WIDTH)
+            })
+        });
+        (protocol, cols)
+    }
+}
+
+// `--expand` for collection listings: per-collection media type breakdown.
+#[derive(Args, Debug, Clone)]
+pub struct ExpandArgs {
+    /// Add media_breakdown {photos, videos, total} per collection (one or two extra requests each)
+    #[arg(long, alias = "expand-counts")]
+    pub expand: bool,
+    /// Collections expanded at once
+    #[arg(long = "expand-concurrency", default_value_t = 4, requires = "expand")]
+    pub expand_concurrency: usize,
+}
+
+// `--count` for list commands: total_results instead of the items.
+#[derive(Args, Debug, Clone)]
+pub struct CountArgs {
+    /// Print only the list's total_results, fetched with a one-item page
+    #[arg(long)]
+    pub count: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct StateCmd {
+    #[command(subcommand)]
+    sub: StateSub,
+}
+#[derive(Subcommand, Debug)]
+pub enum StateSub {
+    /// Database path, schema version and entry counts per namespace
+    Info,
+    /// List entries (optionally one namespace, e.g. potd or continue)
+    List {
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+    Get {
+        key: String,
+    },
+    Rm {
+        key: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ExportCmd {
+    #[command(subcommand)]
+    sub: ExportSub,
+}
+#[derive(Subcommand, Debug)]
+pub enum ExportSub {
+    /// Download search results into train/val/test directories with an index.csv
+    Dataset {
+        #[arg(short = 'q', long = "query")]
+        query: String,
+        /// Dataset root: <dest>/<split>/photo-<id>.<ext> and <dest>/index.csv
+        #[arg(long)]
+        dest: std::path::PathBuf,
+        /// Percentages for train/val/test; a photo's split follows from its id
+        #[arg(long, default_value = "80/10/10", value_parser = crate::dataset::parse_split)]
+        split: crate::dataset::Split,
+        /// Size variants from src.* (comma separated; several go to <split>/<size>/)
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "large")]
+        size: Vec<PhotoSize>,
+        /// Transfers in flight at once
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+        /// Skip photos whose id or content hash an earlier corpus lists (index.csv, .json, .ndjson or .parquet; repeatable)
+        #[arg(long = "exclude-manifest", value_name = "PATH")]
+        exclude_manifest: Vec<std::path::PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct QueueCmd {
+    #[command(subcommand)]
+    sub: QueueSub,
+}
+#[derive(Subcommand, Debug)]
+pub enum QueueSub {
+    /// Queue a photo or video for the next `queue run`
+    Add {
+        #[arg(value_enum, value_name = "TYPE")]
+        kind: crate::queue::MediaType,
+        id: String,
+        /// Photo size variant from src.* (default: original)
+        #[arg(long, value_enum)]
+        size: Option<PhotoSize>,
+        #[command(flatten)]
+        rendition: crate::video::Rendition,
+    },
+    /// Show queued items in the order they were added
     List,
-    Featured,
-    Get { id: String },
-    Items { id: String },
+    /// Download every queued item; finished items leave the queue
+    Run {
+        /// Target directory, files named <kind>-<id>.<ext> (default: `download_dir` from config, else .)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+        /// Transfers in flight at once
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// When another process is writing the same path: wait, skip, or error
+        #[arg(long = "if-busy", value_enum, default_value_t = IfBusy::Wait)]
+        if_busy: IfBusy,
+        #[command(flatten)]
+        mirror: MirrorArgs,
+    },
+    /// Remove every queued item
+    Clear,
 }
 
 #[derive(Args, Debug)]
@@ -233,56 +1132,246 @@ pub struct UtilCmd {
 pub enum UtilSub {
     Inspect,
     Ping,
+    /// Package redacted diagnostics into a .tar.gz for bug reports
+    SupportBundle {
+        #[arg(value_name = "OUT", default_value = "pexels-support.tar.gz")]
+        path: std::path::PathBuf,
+    },
+    /// Re-issue a request from the audit log and diff it against the recorded one
+    ReplayRequest {
+        /// Position in the audit log (1-based; -1 is the latest) or a logged request_id
+        #[arg(value_name = "AUDIT_ID|INDEX", allow_hyphen_values = true)]
+        selector: String,
+    },
 }
 
-pub async fn run(cli: Cli) -> Result<()> {
+pub async fn run(mut cli: Cli) -> Result<()> {
+    crate::config::set_path_override(cli.config.clone());
+    if let Some(secs) = cli.wait_lock {
+        crate::lock::set_default_wait(std::time::Duration::from_secs(secs));
+    }
+    if let Some(t) = &cli.name_template {
+        crate::naming::check_template(t)?;
+    }
+    if let Some(expr) = &cli.jmes {
+        crate::jmes::compile(expr)?;
+    }
+    if let Some(target) = &cli.sink {
+        crate::output::set_sink(crate::sink::open(target, &cli.sink_headers)?);
+    }
+    if let Commands::Continue = cli.command {
+        return run_continue().await;
+    }
+    if let Commands::Examples { command, recipes } = &cli.command {
+        return run_examples(command, *recipes, &cli);
+    }
+    if let Commands::Completions { shell } = cli.command {
+        return write_completions(shell, &mut io::stdout().lock());
+    }
     // Load config and build client
+    let cfg = resolve_config(&cli)?;
+    cli.human = (cli.human || cfg.human) && !cli.no_human;
+    cli.locale = cfg.locale.clone();
+    if cli.per_page.is_none() {
+        cli.per_page = cfg.default_per_page;
+    }
+    if cli.output.is_none() && !cli.json && !cli.raw && !cli.ndjson {
+        use std::io::IsTerminal;
+        // Sinks other than stdout get machine formats
+        let tty = io::stdout().is_terminal() && !crate::output::sink_redirected();
+        let (fmt, auto) = crate::output::default_format(cfg.output.as_ref(), tty, cli.all);
+        cli.output = Some(fmt);
+        cli.format_autoselected = auto;
+    }
+
+    let client = PexelsClient::new(cfg.clone())?;
+    // Rate-limit window carried over from earlier runs
+    if let Ok(st) = State::load() {
+        client.restore_window(&st);
+    }
+    let res = dispatch(client.clone(), cfg, &mut cli).await;
+    // Once per command, so `quota view` and the next run see the window
+    save_window(&client);
+    res
+}
+
+async fn dispatch(client: PexelsClient, cfg: Config, cli: &mut Cli) -> Result<()> {
+    if uses_api(&cli.command) && !cli.offline {
+        check_token(&client).await?;
+    }
+    if let Some(listing) = count_listing(cli)? {
+        return run_count(&client, cli, listing).await;
+    }
+    if cli.watch {
+        // Polls must see new items, never a cached page
+        cli.refresh = true;
+        return run_watch(client, cli).await;
+    }
+    run_command(client, cfg, cli).await
+}
+
+fn save_window(client: &PexelsClient) {
+    if let Err(e) = client.save_window() {
+        tracing::warn!("rate-limit window not saved: {}", e);
+    }
+}
+
+// Effective config for a run, lowest precedence first: config.yaml, the
+// `--profile`/PEXELS_PROFILE section, the token from the environment, the
+// `--host-profile`, then explicit flags (--host, --locale, --token, ...).
+fn resolve_config(cli: &Cli) -> Result<Config> {
     let mut cfg = Config::load().context("load config")?;
+    if let Some(name) = cli.profile.clone().or_else(crate::config::env_profile) {
+        // `auth login --profile new` creates the profile
+        let creating =
+            matches!(&cli.command, Commands::Auth(a) if matches!(a.sub, AuthSub::Login { .. }));
+        match cfg.select_profile(&name) {
+            Err(_) if creating => cfg.profile = Some(name),
+            res => res?,
+        }
+    }
     cfg.apply_env();
-    cfg.apply_cli(&cli);
+    if let Some(name) = &cli.host_profile {
+        cfg.select_host_profile(name)?;
+    }
+    cfg.apply_cli(cli);
+    if let Some(token) = cli.flag_token()? {
+        cfg.use_flag_token(token);
+    }
+    Ok(cfg)
+}
 
-    let client = PexelsClient::new(cfg.clone())?;
+// Commands whose work is API calls with the token.
+fn uses_api(cmd: &Commands) -> bool {
+    match cmd {
+        Commands::Photos(_)
+        | Commands::Videos(_)
+        | Commands::Collections(_)
+        | Commands::Export(_)
+        | Commands::Browse { .. }
+        | Commands::Api { .. } => true,
+        Commands::Queue(q) => matches!(q.sub, QueueSub::Run { .. }),
+        _ => false,
+    }
+}
+
+// Once-a-day validation of the token before API commands. A 401 fails the
+// run up front; network trouble is left for the command itself to report.
+async fn check_token(client: &PexelsClient) -> Result<()> {
+    let Some(token) = client.config().token.clone().filter(|t| !t.is_empty()) else {
+        return Ok(());
+    };
+    let Ok(mut st) = State::load() else {
+        return Ok(());
+    };
+    let now = chrono::Utc::now().timestamp();
+    if !crate::health::due(&st, &token, now) {
+        return Ok(());
+    }
+    let res = client.util_ping().await;
+    let rejected =
+        matches!(&res, Err(e) if e.downcast_ref::<crate::health::TokenRejected>().is_some());
+    match res {
+        Err(e) if !rejected => {
+            tracing::debug!("token check skipped: {}", e);
+            Ok(())
+        }
+        res => {
+            crate::health::record(&mut st, &token, !rejected, now);
+            st.save()?;
+            res
+        }
+    }
+}
 
+async fn run_command(client: PexelsClient, cfg: Config, cli: &Cli) -> Result<()> {
     match &cli.command {
-        Commands::Auth(auth) => run_auth(auth, cfg).await,
-        Commands::Config(cmd) => run_config(cmd, cfg).await,
-        Commands::Quota(cmd) => run_quota(cmd, client, &cli).await,
-        Commands::Photos(cmd) => run_photos(cmd, client, &cli).await,
-        Commands::Videos(cmd) => run_videos(cmd, client, &cli).await,
-        Commands::Collections(cmd) => run_collections(cmd, client, &cli).await,
-        Commands::Util(cmd) => run_util(cmd, client, &cli).await,
+        Commands::Auth(auth) => run_auth(auth, client, cfg).await,
+        Commands::Config(cmd) => run_config(cmd, cfg, cli).await,
+        Commands::Quota(cmd) => run_quota(cmd, client, cli).await,
+        Commands::Photos(cmd) => run_photos(cmd, client, cli).await,
+        Commands::Videos(cmd) => run_videos(cmd, client, cli).await,
+        Commands::Collections(cmd) => run_collections(cmd, client, cli).await,
+        Commands::Util(cmd) => run_util(cmd, client, cli).await,
+        Commands::State(cmd) => run_state(cmd, cli),
+        Commands::Queue(cmd) => run_queue(cmd, client, cli).await,
+        Commands::Export(cmd) => run_export(cmd, client, cli).await,
+        Commands::Browse {
+            query,
+            dir,
+            concurrency,
+        } => run_browse(&client, cli, query, dir, *concurrency).await,
+        Commands::Api {
+            method,
+            path,
+            params,
+        } => run_api(&client, cli, *method, path, params).await,
+        Commands::Continue | Commands::Examples { .. } | Commands::Completions { .. } => {
+            unreachable!("handled above")
+        }
     }
 }
 
-fn fmt_from_cli(cli: &Cli) -> OutputFormat {
-    if cli.raw {
-        OutputFormat::Raw
-    } else if cli.json {
-        OutputFormat::Json
-    } else {
-        OutputFormat::Yaml
+// Default nesting depth kept in YAML output; deeper levels are summarized.
+const DEFAULT_YAML_DEPTH: usize = 2;
+// Number of array elements kept with --compact.
+const COMPACT_ITEMS: usize = 3;
+
+// Collapse limits for YAML output; None when --full is set.
+fn collapse_from_cli(cli: &Cli) -> Option<Collapse> {
+    // --jmes queries need the real values, not collapsed summaries
+    if cli.full || cli.jmes.is_some() {
+        return None;
     }
+    Some(Collapse {
+        max_depth: Some(cli.max_depth.unwrap_or(DEFAULT_YAML_DEPTH)),
+        max_items: cli.compact.then_some(COMPACT_ITEMS),
+    })
+}
+
+fn fmt_from_cli(cli: &Cli) -> OutputFormat {
+    cli.explicit_format().unwrap_or(OutputFormat::Yaml)
 }
 
-async fn run_auth(cmd: &AuthCmd, mut cfg: Config) -> Result<()> {
+async fn run_auth(cmd: &AuthCmd, client: PexelsClient, cfg: Config) -> Result<()> {
     match &cmd.sub {
-        AuthSub::Login { token } => {
+        AuthSub::Login {
+            token,
+            stdin,
+            no_verify,
+        } => {
             // Decide env-vs-positional based on presence of positional arg (pre-resolution)
-            let env_var = if token.is_none() {
+            let env_var = if token.is_none() && !*stdin {
                 Config::env_token_var()
             } else {
                 None
             };
-            // Resolve token: positional -> PEXELS_TOKEN -> PEXELS_API_KEY
+            let token = if *stdin {
+                Some(token_from_stdin()?)
+            } else {
+                token.clone()
+            };
+            // Resolve token: --stdin / positional -> PEXELS_TOKEN -> PEXELS_API_KEY
             let token_val = token
-                .clone()
                 .or_else(|| std::env::var("PEXELS_TOKEN").ok())
                 .or_else(|| std::env::var("PEXELS_API_KEY").ok())
-                .context("token not provided; pass TOKEN or set env PEXELS_TOKEN\npexels auth login [TOKEN]")?;
-            cfg.token = Some(token_val);
-            cfg.token_source = Some(TokenSource::Config);
-            cfg.save()?;
-            let payload = if let Some(var) = env_var {
+                .context("token not provided; pass TOKEN, use --stdin or set env PEXELS_TOKEN\npexels auth login [TOKEN]")?;
+            let quota = if *no_verify {
+                None
+            } else {
+                Some(verify_login_token(&client, &token_val).await?)
+            };
+            Config::update(|c| {
+                match &cfg.profile {
+                    Some(name) => {
+                        c.profiles.entry(name.clone()).or_default().token = Some(token_val)
+                    }
+                    None => c.token = Some(token_val),
+                }
+                c.token_source = Some(TokenSource::Config);
+                Ok(())
+            })?;
+            let mut payload = if let Some(var) = env_var {
                 serde_json::json!({
                     "status": "ok",
                     "message": format!("token saved from env {}", var),
@@ -293,6 +1382,18 @@ async fn run_auth(cmd: &AuthCmd, mut cfg: Config) -> Result<()> {
                     "message": "token saved",
                 })
             };
+            if let Some(name) = &cfg.profile {
+                payload["profile"] = name.clone().into();
+            }
+            payload["storage"] = cfg.token_storage.as_str().into();
+            payload["verified"] = quota.is_some().into();
+            if let Some(w) = quota.flatten() {
+                payload["quota"] = serde_json::json!({
+                    "limit": w.limit,
+                    "remaining": w.remaining,
+                    "resets_at": w.resume_at(),
+                });
+            }
             let out = wrap_ok(&payload, None);
             emit_data(&OutputFormat::Yaml, &out)
         }
@@ -301,10 +1402,29 @@ async fn run_auth(cmd: &AuthCmd, mut cfg: Config) -> Result<()> {
             let out = wrap_ok(&payload, None);
             emit_data(&OutputFormat::Yaml, &out)
         }
+        AuthSub::Whoami => {
+            let payload = build_whoami(&cfg)?;
+            let out = wrap_ok(&payload, None);
+            emit_data(&OutputFormat::Yaml, &out)
+        }
         AuthSub::Logout => {
-            cfg.token = None;
-            cfg.token_source = Some(TokenSource::None);
-            cfg.save()?;
+            Config::update(|c| {
+                match &cfg.profile {
+                    Some(name) => {
+                        if let Some(p) = c.profiles.get_mut(name) {
+                            p.token = None;
+                        }
+                    }
+                    None => {
+                        c.token = None;
+                        c.token_source = Some(TokenSource::None);
+                    }
+                }
+                Ok(())
+            })?;
+            if cfg.token_storage == TokenStorage::Keyring {
+                crate::keychain::delete(crate::keychain::account(cfg.profile.as_deref()))?;
+            }
             let payload = serde_json::json!({"status":"logged out"});
             let out = wrap_ok(&payload, None);
             emit_data(&OutputFormat::Yaml, &out)
@@ -312,33 +1432,146 @@ async fn run_auth(cmd: &AuthCmd, mut cfg: Config) -> Result<()> {
     }
 }
 
-async fn run_config(cmd: &ConfigCmd, mut cfg: Config) -> Result<()> {
-    match &cmd.sub {
-        ConfigSub::Set { key, value } => {
-            match key.as_str() {
-                "token" | "api_key" => cfg.token = Some(value.clone()),
-                _ => anyhow::bail!("unsupported key"),
-            }
-            cfg.save()?;
-            let payload = serde_json::json!({"status":"ok"});
-            let out = wrap_ok(&payload, None);
-            emit_data(&OutputFormat::Yaml, &out)
-        }
-        ConfigSub::Get { key } => {
-            let v = match key.as_str() {
-                "token" | "api_key" => cfg.token.clone().unwrap_or_default(),
-                _ => String::new(),
-            };
-            emit_data(&OutputFormat::Raw, &JsonValue::String(v))
-        }
-        ConfigSub::Path => emit_data(
-            &OutputFormat::Raw,
+// `auth login --stdin` and `--token-stdin`: a hidden prompt on a terminal,
+// else whatever is piped in (`pass show pexels | pexels auth login --stdin`).
+fn token_from_stdin() -> Result<String> {
+    use std::io::{IsTerminal, Read};
+    if io::stdin().is_terminal() {
+        return parse_token_input(&prompt_token()?);
+    }
+    let mut raw = String::new();
+    io::stdin()
+        .read_to_string(&mut raw)
+        .context("read token from stdin")?;
+    parse_token_input(&raw)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn prompt_token() -> Result<String> {
+    Ok(dialoguer::Password::new()
+        .with_prompt("Pexels API token")
+        .interact()?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn prompt_token() -> Result<String> {
+    anyhow::bail!("no interactive prompt on this target; pipe the token to --stdin")
+}
+
+// Token from stdin input: surrounding whitespace (the trailing newline of
+// `echo` or a secret manager) is dropped; empty or multi-line input fails.
+pub fn parse_token_input(raw: &str) -> Result<String> {
+    let token = raw.trim();
+    let problem = if token.is_empty() {
+        "empty token on stdin"
+    } else if token.contains(char::is_whitespace) {
+        "token on stdin contains whitespace or several lines"
+    } else {
+        return Ok(token.to_string());
+    };
+    let err = serde_json::json!({
+        "error": problem,
+        "hint": "pipe exactly one token, e.g. pass show pexels | pexels auth login --stdin",
+    });
+    Err(anyhow::anyhow!(
+        serde_yaml::to_string(&err).unwrap_or_else(|_| problem.into())
+    ))
+}
+
+// Check a token before `auth login` stores it; a rejected one is not saved
+// (exit code 3, like any 401). Returns the rate-limit window it reported.
+async fn verify_login_token(
+    client: &PexelsClient,
+    token: &str,
+) -> Result<Option<crate::quota::Window>> {
+    let mut cfg = client.config().clone();
+    cfg.token = Some(token.to_string());
+    let check = client.with_config(cfg)?;
+    match check.verify_token().await {
+        Ok(w) => {
+            if let Ok(mut st) = State::load() {
+                let now = chrono::Utc::now().timestamp();
+                crate::health::record(&mut st, token, true, now);
+                let _ = st.save();
+            }
+            Ok(w)
+        }
+        Err(e) if e.downcast_ref::<crate::health::TokenRejected>().is_some() => {
+            let err = serde_json::json!({
+                "error": "token rejected by the API; not saved",
+                "code": 401,
+                "fingerprint": crate::health::fingerprint(token),
+                "hint": "check the token for typos, or pass --no-verify to save it anyway",
+            });
+            Err(e.context(serde_yaml::to_string(&err).unwrap_or_else(|_| "token rejected".into())))
+        }
+        Err(e) => {
+            let err = serde_json::json!({
+                "error": "could not verify the token; not saved",
+                "reason": crate::output::error_value(&e),
+                "hint": "retry, or pass --no-verify to save it without checking",
+            });
+            Err(anyhow::anyhow!(
+                serde_yaml::to_string(&err).unwrap_or_else(|_| "could not verify the token".into())
+            ))
+        }
+    }
+}
+
+async fn run_config(cmd: &ConfigCmd, cfg: Config, cli: &Cli) -> Result<()> {
+    match &cmd.sub {
+        ConfigSub::Set { key, value } => {
+            let entry = crate::settings::lookup(key)?;
+            let saved = Config::update(|cfg| entry.set(cfg, value))?;
+            // Tokens moved back into the file: drop the keychain copies
+            if cfg.token_storage == TokenStorage::Keyring
+                && saved.token_storage == TokenStorage::File
+            {
+                saved.forget_keychain_tokens()?;
+            }
+            let payload = serde_json::json!({"status":"ok"});
+            let out = wrap_ok(&payload, None);
+            emit_data(&OutputFormat::Yaml, &out)
+        }
+        ConfigSub::Get { key } => {
+            let v = crate::settings::lookup(key)?.get(&cfg);
+            emit_data(&OutputFormat::Raw, &JsonValue::String(v))
+        }
+        ConfigSub::Unset { key } => {
+            let entry = crate::settings::lookup(key)?;
+            let name = entry.name();
+            let saved = Config::update(|c| match name.as_str() {
+                // Pull the tokens back out of the keychain first
+                "token_storage" => c.set_token_storage(TokenStorage::File),
+                _ => entry.unset(c),
+            })?;
+            if cfg.token_storage == TokenStorage::Keyring {
+                match name.as_str() {
+                    "token_storage" => saved.forget_keychain_tokens()?,
+                    "token" => crate::keychain::delete(crate::keychain::account(None))?,
+                    _ => {}
+                }
+            }
+            let payload = serde_json::json!({"status":"ok", "unset": name});
+            let out = wrap_ok(&payload, None);
+            emit_data(&OutputFormat::Yaml, &out)
+        }
+        ConfigSub::List => emit_wrapped(cli, &JsonValue::Array(crate::settings::list(&cfg))),
+        ConfigSub::Path => emit_data(
+            &OutputFormat::Raw,
             &JsonValue::String(cfg.path().display().to_string()),
         ),
     }
 }
 
 async fn run_quota(_cmd: &QuotaCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
+    // The window persisted by earlier calls costs no request; --refresh (or
+    // an empty store) probes live
+    if !cli.refresh {
+        if let Some(data) = State::load().ok().and_then(|st| client.quota_cached(&st)) {
+            return emit_enveloped(cli, data, &[]);
+        }
+    }
     // Reachability check: HEAD curated
     let reachable = client.util_ping().await.is_ok();
     let mut data = client
@@ -348,31 +1581,99 @@ async fn run_quota(_cmd: &QuotaCmd, client: PexelsClient, cli: &Cli) -> Result<(
     if let Some(obj) = data.as_object_mut() {
         obj.insert("reachable".into(), serde_json::json!(reachable));
     }
-    emit_enveloped(cli, data, &DefaultFields::None)
+    emit_enveloped(cli, data, &[])
 }
 
 async fn run_photos(cmd: &PhotosCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
     match &cmd.sub {
-        PhotosSub::Search { query } => {
-            let data = client.photos_search(query, cli).await?;
-            emit_enveloped(cli, data, &DefaultFields::Photos)
+        PhotosSub::Search {
+            query,
+            any_of,
+            all_of,
+            none_of,
+            filters,
+            analyze,
+            preview,
+            preview_args,
+            ..
+        } => {
+            let plan = crate::query::BoolQuery {
+                base: query.clone(),
+                any_of: any_of.clone(),
+                all_of: all_of.clone(),
+                none_of: none_of.clone(),
+            };
+            if plan.is_plain() && !*analyze && !*preview && streams_pages(cli) {
+                let listing = crate::api::Listing::PhotosSearch {
+                    query: plan.base.as_deref().unwrap_or_default(),
+                    filters: filters.query_params(),
+                };
+                let defaults = client.config().projection("photos");
+                return stream_list(&client, cli, listing, &defaults, |_| {}).await;
+            }
+            let mut data = search_photos(&client, cli, &plan, filters).await?;
+            if *preview {
+                return preview_photos(&client, cli, &data, preview_args).await;
+            }
+            let mut defaults = client.config().projection("photos");
+            if *analyze {
+                analyze_photos(&client, &mut data).await;
+                defaults.push("analysis".into());
+            }
+            emit_list(&client, cli, data, &defaults).await
         }
-        PhotosSub::Curated => {
-            if cli.raw {
+        PhotosSub::Curated { .. } => {
+            if matches!(fmt_from_cli(cli), OutputFormat::Raw) {
                 let url = client
                     .base_photos()
                     .join("curated")
                     .map_err(|e| anyhow::anyhow!(e))?;
                 let bytes = client.req_bytes(url, client.pagination_qp(cli)).await?;
                 emit_raw_bytes(&bytes)
+            } else if streams_pages(cli) {
+                let defaults = client.config().projection("photos");
+                let listing = crate::api::Listing::PhotosCurated;
+                stream_list(&client, cli, listing, &defaults, |_| {}).await
             } else {
                 let data = client.photos_curated(cli).await?;
-                emit_enveloped(cli, data, &DefaultFields::Photos)
+                emit_list(&client, cli, data, &client.config().projection("photos")).await
             }
         }
-        PhotosSub::Get { id } => {
-            let data = client.photos_get(id).await?;
-            emit_enveloped(cli, data, &DefaultFields::Photos)
+        PhotosSub::Get { ids, concurrency } => {
+            let defaults = client.config().projection("photos");
+            if let [id] = ids.as_slice() {
+                let data = client.photos_get(id).await?;
+                return emit_enveloped(cli, data, &defaults);
+            }
+            let data = get_many(ids, *concurrency, "photos", |id| client.photos_get(id)).await?;
+            let failed = data["lookups"]["failed"].as_u64().unwrap_or(0);
+            emit_enveloped(cli, data, &defaults)?;
+            lookups_outcome("photo", failed)
+        }
+        PhotosSub::Exists { id } => {
+            let found = client.photos_lookup(id, false).await;
+            report_exists(cli, "photo", id, found)
+        }
+        PhotosSub::Potd { query } => {
+            let data = photo_of_the_day(&client, query.as_deref()).await?;
+            emit_enveloped(cli, data, &client.config().projection("photos"))
+        }
+        PhotosSub::Estimate {
+            query,
+            size,
+            sample,
+        } => {
+            let data = match query {
+                Some(q) => client.photos_search(q, &[], cli).await?,
+                None => client.photos_curated(cli).await?,
+            };
+            let photos = data
+                .get("photos")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            let report = estimate_sizes(&client, &photos, *size, *sample).await;
+            emit_wrapped(cli, &report)
         }
         PhotosSub::Url { id, size } => {
             let data = client.photos_get(id).await?;
@@ -384,130 +1685,2378 @@ async fn run_photos(cmd: &PhotosCmd, client: PexelsClient, cli: &Cli) -> Result<
                 .ok_or_else(|| anyhow::anyhow!(format!("src.{} not found", size.key())))?;
             let fmt = fmt_from_cli(cli);
             let out = serde_json::json!({ "data": url });
-            emit_data(&fmt, &out)
+            emit_final(cli, &fmt, &out)
+        }
+        PhotosSub::Urls {
+            input,
+            size,
+            concurrency,
+        } => {
+            let ids = read_ids(input)?;
+            let size = size.unwrap_or(PhotoSize::Original);
+            let (urls, errors) = resolve_urls(&client, cli, &ids, size, *concurrency).await;
+            let meta = serde_json::json!({
+                "requested": ids.len(),
+                "resolved": urls.len(),
+                "failed": errors.len(),
+            });
+            let failed = errors.len();
+            let payload = serde_json::json!({ "urls": urls, "errors": errors });
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&payload, Some(meta)))?;
+            if failed > 0 {
+                let err = serde_json::json!({
+                    "error": "some photo ids did not resolve",
+                    "failed": failed,
+                    "hint": "see data.errors",
+                });
+                return Err(anyhow::anyhow!(serde_yaml::to_string(&err)
+                    .unwrap_or_else(|_| "some photo ids did not resolve".into())));
+            }
+            Ok(())
         }
-        PhotosSub::Download { id, path } => {
+        PhotosSub::Preview {
+            id,
+            size,
+            preview_args,
+        } => {
+            let data = client.photos_get(id).await?;
+            let keys = size.map_or(SMALL_VARIANTS.to_vec(), |s| vec![s.key()]);
+            let (_, img) = variant_image(&client, &data, &keys).await?;
+            let (protocol, cols) = preview_args.settings(cli);
+            let mut out = io::stdout().lock();
+            out.write_all(&crate::preview::render(&img, protocol, cols)?)?;
+            writeln!(out, "{}", crate::pick::label(&data))?;
+            Ok(())
+        }
+        PhotosSub::Palette { id, count } => {
+            let data = client.photos_get(id).await?;
+            let (source, img) = small_variant(&client, &data).await?;
+            let colors: Vec<JsonValue> = crate::palette::extract(&img, *count as usize)
+                .iter()
+                .map(|s| s.to_json())
+                .collect();
+            let report = serde_json::json!({
+                "id": data.get("id").cloned().unwrap_or(JsonValue::Null),
+                "source": source,
+                "avg_color": data.get("avg_color").cloned().unwrap_or(JsonValue::Null),
+                "colors": colors,
+            });
+            emit_wrapped(cli, &report)
+        }
+        PhotosSub::Contrast {
+            id,
+            text_color,
+            region,
+        } => {
+            let data = client.photos_get(id).await?;
+            let (source, img) = small_variant(&client, &data).await?;
+            let mut report = crate::contrast::report(&img, *text_color, *region);
+            report["id"] = data.get("id").cloned().unwrap_or(JsonValue::Null);
+            report["source"] = JsonValue::String(source);
+            emit_wrapped(cli, &report)
+        }
+        PhotosSub::DownloadAll {
+            query,
+            dir,
+            size,
+            concurrency,
+            if_busy,
+            mirror,
+            web,
+            analyze,
+        } => {
+            let dir = &download_dir(client.config(), dir, false)?;
+            let data = client.photos_search(query, &[], cli).await?;
+            let size = size.unwrap_or(PhotoSize::Original);
+            let mut jobs = vec![];
+            let mut missing = vec![];
+            let mut credits = std::collections::HashMap::new();
+            for item in data
+                .get("photos")
+                .and_then(|p| p.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let id = item.get("id").cloned().unwrap_or(JsonValue::Null);
+                match item
+                    .pointer(&format!("/src/{}", size.key()))
+                    .and_then(|u| u.as_str())
+                {
+                    Some(url) => {
+                        let name = crate::pick::file_name_for(item, Some(url));
+                        let dest = media_dest(&client, cli, item, url, dir, Some(&name)).await?;
+                        credits
+                            .insert(dest.clone(), crate::optimize::Attribution::from_media(item));
+                        jobs.push(crate::download::Job {
+                            id,
+                            url: url.to_string(),
+                            dest,
+                        });
+                    }
+                    None => missing.push(id),
+                }
+            }
+            let web = web.settings(client.config()).map(|s| (s, &credits));
+            let (mut summary, _) = bulk_download_each(
+                &client,
+                cli,
+                jobs,
+                *if_busy,
+                *concurrency,
+                &mirror.dest,
+                web,
+                *analyze,
+            )
+            .await?;
+            if !missing.is_empty() {
+                summary["missing_size"] = serde_json::json!({ "size": size.key(), "ids": missing });
+            }
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&summary, None))?;
+            bulk_outcome(&summary)
+        }
+        PhotosSub::Download {
+            id,
+            path,
+            if_busy,
+            web,
+            analyze,
+        } => {
             let data = client.photos_get(id).await?;
             let url = data
                 .get("src")
                 .and_then(|v| v.get("original"))
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| anyhow::anyhow!("src.original not found"))?;
-            // download bytes
-            let bytes = client.download_url_bytes(url).await?;
-            // write file
-            use std::fs::{self, File};
-            use std::io::Write as _;
-            use std::path::Path;
-            let p = Path::new(path);
-            if let Some(dir) = p.parent() {
-                fs::create_dir_all(dir)?;
-            }
-            let mut f = File::create(p)?;
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perms = f.metadata()?.permissions();
-                perms.set_mode(0o600);
-                f.set_permissions(perms)?;
+            let p = media_dest(&client, cli, &data, url, std::path::Path::new(path), None).await?;
+            let post = post_steps(&client, cli)?;
+            // Fenced write: lock + .part file, bytes fetched only once the lock is held
+            let written = download_media(&client, cli, url, &p, *if_busy).await?;
+            let mut v = post_process(&written, &post)?;
+            if let Some(settings) = web.settings(client.config()) {
+                if needs_web_copy(&written, settings) {
+                    let credit = crate::optimize::Attribution::from_media(&data);
+                    v["web"] = crate::optimize::optimize(&written.path, settings, &credit)?;
+                }
+            }
+            if *analyze && needs_analysis(&written) {
+                v["analysis"] = crate::analyze::analyze_file(&written.path)?;
             }
-            f.write_all(&bytes)?;
-            let abs = std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
             let fmt = fmt_from_cli(cli);
-            let out = serde_json::json!({
-                "data": { "path": abs.display().to_string(), "bytes": bytes.len() }
+            let out = serde_json::json!({ "data": v });
+            emit_final(cli, &fmt, &out)
+        }
+    }
+}
+
+// `photos get`/`videos get` with several IDs: `fetch` runs for up to
+// `concurrency` IDs at a time and the items found come back in request
+// order under `key`, with each failed ID in `errors` (moved to meta by
+// `shape_output`). A rejected token fails the whole command.
+async fn get_many<'a, F, Fut>(
+    ids: &'a [String],
+    concurrency: usize,
+    key: &str,
+    fetch: F,
+) -> Result<JsonValue>
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: std::future::Future<Output = Result<JsonValue>>,
+{
+    use futures::stream::{self, StreamExt};
+    let results: Vec<Result<JsonValue>> = stream::iter(ids)
+        .map(|id| fetch(id))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    let (mut items, mut errors) = (vec![], vec![]);
+    for (id, res) in ids.iter().zip(results) {
+        match res {
+            Ok(item) => items.push(item),
+            Err(e) if crate::health::http_status(&e) == Some(401) => return Err(e),
+            Err(e) => {
+                let mut entry = match crate::output::error_value(&e) {
+                    v @ JsonValue::Object(_) => v,
+                    other => serde_json::json!({ "error": other }),
+                };
+                entry["id"] = JsonValue::String(id.clone());
+                errors.push(entry);
+            }
+        }
+    }
+    let lookups = serde_json::json!({
+        "requested": ids.len(),
+        "found": items.len(),
+        "failed": errors.len(),
+    });
+    let mut data = serde_json::json!({ key: items, "lookups": lookups });
+    if !errors.is_empty() {
+        data["errors"] = errors.into();
+    }
+    Ok(data)
+}
+
+// Error (after the list has been printed) when any ID failed.
+fn lookups_outcome(kind: &str, failed: u64) -> Result<()> {
+    if failed == 0 {
+        return Ok(());
+    }
+    let err = serde_json::json!({
+        "error": format!("some {} ids could not be fetched", kind),
+        "failed": failed,
+        "hint": "see meta.errors",
+    });
+    Err(anyhow::anyhow!(
+        serde_yaml::to_string(&err).unwrap_or_else(|_| "some ids could not be fetched".into())
+    ))
+}
+
+// `photos exists`/`videos exists`: success when the GET found `id`,
+// `NotFound` (exit code 4) on a 404, any other failure as is. Silent unless
+// --json asks for `{id, kind, exists}`.
+fn report_exists(cli: &Cli, kind: &'static str, id: &str, found: Result<JsonValue>) -> Result<()> {
+    let exists = match found {
+        Ok(_) => true,
+        Err(e) if crate::health::http_status(&e) == Some(404) => false,
+        Err(e) => return Err(e),
+    };
+    if cli.json {
+        let v = serde_json::json!({ "id": id, "kind": kind, "exists": exists });
+        emit_data(&OutputFormat::Json, &wrap_ok(&v, None))?;
+    }
+    if exists {
+        Ok(())
+    } else {
+        Err(anyhow::Error::new(crate::health::NotFound {
+            kind,
+            id: id.to_string(),
+        }))
+    }
+}
+
+// Target directory of a bulk download: --dir, else `download_dir` from
+// config, else the working directory when `cwd_ok`.
+pub fn download_dir(
+    cfg: &Config,
+    dir: &Option<std::path::PathBuf>,
+    cwd_ok: bool,
+) -> Result<std::path::PathBuf> {
+    match dir.clone().or_else(|| cfg.download_dir.clone()) {
+        Some(d) => Ok(d),
+        None if cwd_ok => Ok(".".into()),
+        None => {
+            let err = serde_json::json!({
+                "error": "no download directory",
+                "hint": "pass --dir, or set one: pexels config set download_dir ~/Pictures/pexels",
+            });
+            Err(anyhow::anyhow!(
+                serde_yaml::to_string(&err).unwrap_or_else(|_| "no download directory".into())
+            ))
+        }
+    }
+}
+
+// Run download jobs through the shared downloader, apply --post to each
+// written file, and summarize ({succeeded, failed, skipped, items}).
+async fn bulk_download(
+    client: &PexelsClient,
+    cli: &Cli,
+    jobs: Vec<crate::download::Job>,
+    if_busy: IfBusy,
+    concurrency: usize,
+    mirrors: &[crate::mirror::Dest],
+) -> Result<JsonValue> {
+    Ok(bulk_download_each(
+        client,
+        cli,
+        jobs,
+        if_busy,
+        concurrency,
+        mirrors,
+        None,
+        false,
+    )
+    .await?
+    .0)
+}
+
+// `bulk_download`, also reporting which jobs (in order) did not fail. Each
+// finished file gets its `--web-optimize` copy (`web` holds the settings and
+// each destination's credit) and `--analyze` sidecar, is post-processed and
+// then copied to every `--dest` while the other transfers continue (items
+// get `web`, `analysis` and `mirrors: [{dest, ok, location|error}]`, the
+// summary totals for web and mirrors); a failed conversion or copy counts
+// the job as not done.
+#[allow(clippy::too_many_arguments)]
+async fn bulk_download_each(
+    client: &PexelsClient,
+    cli: &Cli,
+    jobs: Vec<crate::download::Job>,
+    if_busy: IfBusy,
+    concurrency: usize,
+    mirrors: &[crate::mirror::Dest],
+    web: Option<(
+        crate::optimize::Settings,
+        &std::collections::HashMap<std::path::PathBuf, crate::optimize::Attribution>,
+    )>,
+    analyze: bool,
+) -> Result<(JsonValue, Vec<bool>)> {
+    let post = post_steps(client, cli)?;
+    let opts = download_opts(cli, if_busy);
+    let uploader = if mirrors.is_empty() {
+        None
+    } else {
+        Some(crate::mirror::Uploader::new(
+            client.config().remotes.clone(),
+        )?)
+    };
+    let (post, uploader) = (&post, &uploader);
+    let results = crate::download::fetch_all_then(client, jobs, opts, concurrency, |w| {
+        let (path, fresh, unchanged) = (w.path.clone(), !w.skipped, w.unchanged);
+        let optimized = match web {
+            Some((settings, credits)) if needs_web_copy(w, settings) => Some(
+                crate::optimize::optimize(
+                    &path,
+                    settings,
+                    &credits.get(&path).cloned().unwrap_or_default(),
+                )
+                .unwrap_or_else(|e| crate::output::error_value(&e)),
+            ),
+            _ => None,
+        };
+        let analysis = (analyze && needs_analysis(w)).then(|| {
+            crate::analyze::analyze_file(&path).unwrap_or_else(|e| crate::output::error_value(&e))
+        });
+        async move {
+            let post = match post {
+                Some((name, steps)) if fresh => Some(crate::post::run(name, steps, &path)),
+                _ => None,
+            };
+            // Unchanged skips are complete files; busy skips may not be
+            let copies = match uploader {
+                Some(up) if (fresh || unchanged) && !matches!(post, Some(Err(_))) => {
+                    Some(up.put_all(mirrors, &path).await)
+                }
+                _ => None,
+            };
+            (optimized, analysis, post, copies)
+        }
+    })
+    .await;
+    let (results, after): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .map(|(job, res, after)| ((job, res), after.unwrap_or((None, None, None, None))))
+        .unzip();
+    let mut summary = crate::download::summarize(&results);
+    let mut ok: Vec<bool> = results.iter().map(|(_, r)| r.is_ok()).collect();
+    if let Some(items) = summary["items"].as_array_mut() {
+        for ((i, v), (optimized, analysis, post, copies)) in items.iter_mut().enumerate().zip(after)
+        {
+            if let Some(optimized) = optimized {
+                ok[i] &= optimized.get("error").is_none();
+                v["web"] = optimized;
+            }
+            // Advisory: a photo that can't be analyzed still counts as done
+            if let Some(analysis) = analysis {
+                v["analysis"] = analysis;
+            }
+            if let Some(post) = post {
+                v["post"] = post?;
+            }
+            if let Some(copies) = copies {
+                ok[i] &= copies.iter().all(|c| c["ok"] == true);
+                v["mirrors"] = copies.into();
+            }
+        }
+    }
+    let items = summary["items"]
+        .as_array()
+        .map_or(&[][..], |v| v.as_slice());
+    let mirrored = (!mirrors.is_empty()).then(|| crate::mirror::tally(items));
+    let optimized = web.is_some().then(|| crate::optimize::tally(items));
+    if let Some(t) = mirrored {
+        summary["mirrors"] = t;
+    }
+    if let Some(t) = optimized {
+        summary["web"] = t;
+    }
+    Ok((summary, ok))
+}
+
+// Whether a download needs its --web-optimize copy written: fresh files
+// always, unchanged ones only when the copy is missing (busy skips may be
+// incomplete).
+fn needs_web_copy(w: &crate::download::Written, settings: crate::optimize::Settings) -> bool {
+    !w.skipped || (w.unchanged && !crate::optimize::output_path(&w.path, settings.format).exists())
+}
+
+// Whether a download needs its --analyze sidecar: fresh files always,
+// unchanged ones only when the sidecar is missing.
+fn needs_analysis(w: &crate::download::Written) -> bool {
+    !w.skipped || (w.unchanged && !crate::analyze::sidecar_path(&w.path).exists())
+}
+
+// Concurrent small-variant fetches for `photos search --analyze`/`--preview`.
+const ANALYZE_CONCURRENCY: usize = 8;
+
+// Attach `analysis` (see analyze.rs) to every photo of a result page; a
+// photo that can't be fetched or decoded gets `analysis: {error}`.
+async fn analyze_photos(client: &PexelsClient, data: &mut JsonValue) {
+    use futures::stream::{self, StreamExt};
+    let Some(photos) = data.get_mut("photos").and_then(|p| p.as_array_mut()) else {
+        return;
+    };
+    let reports: Vec<JsonValue> = stream::iter(photos.clone())
+        .map(|photo| async move {
+            match small_variant(client, &photo).await {
+                Ok((_, img)) => crate::analyze::analyze(&img),
+                Err(e) => serde_json::json!({ "error": crate::output::error_value(&e) }),
+            }
+        })
+        .buffered(ANALYZE_CONCURRENCY)
+        .collect()
+        .await;
+    for (p, r) in photos.iter_mut().zip(reports) {
+        p["analysis"] = r;
+    }
+}
+
+// `pexels browse`: pick photos in the terminal, then download the marked ones.
+async fn run_browse(
+    client: &PexelsClient,
+    cli: &Cli,
+    query: &Option<String>,
+    dir: &Option<std::path::PathBuf>,
+    concurrency: usize,
+) -> Result<()> {
+    // Resolved first so a missing directory fails before anything is marked
+    let dir = download_dir(client.config(), dir, false)?;
+    let per_page = cli.per_page.unwrap_or(crate::tui::DEFAULT_PER_PAGE);
+    let marked = crate::tui::browse(client, query.as_deref(), per_page).await?;
+    let mut jobs = vec![];
+    for item in &marked {
+        let Some(url) = crate::pick::media_url(item) else {
+            continue;
+        };
+        let name = crate::pick::file_name(item);
+        let dest = media_dest(client, cli, item, &url, &dir, Some(&name)).await?;
+        jobs.push(crate::download::Job {
+            id: item.get("id").cloned().unwrap_or(JsonValue::Null),
+            url,
+            dest,
+        });
+    }
+    let summary = bulk_download(client, cli, jobs, IfBusy::Wait, concurrency, &[]).await?;
+    emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&summary, None))?;
+    bulk_outcome(&summary)
+}
+
+// `photos search --preview`: each photo's small variant drawn inline with
+// its label; a photo that can't be fetched gets its label and the reason.
+async fn preview_photos(
+    client: &PexelsClient,
+    cli: &Cli,
+    data: &JsonValue,
+    args: &PreviewArgs,
+) -> Result<()> {
+    use futures::stream::{self, StreamExt};
+    let photos = data
+        .get("photos")
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let (protocol, cols) = args.settings(cli);
+    let mut images = stream::iter(photos.iter())
+        .map(|photo| small_variant(client, photo))
+        .buffered(ANALYZE_CONCURRENCY);
+    let mut out = io::stdout().lock();
+    for photo in &photos {
+        match images.next().await {
+            Some(Ok((_, img))) => out.write_all(&crate::preview::render(&img, protocol, cols)?)?,
+            Some(Err(e)) => {
+                let reason = e.to_string();
+                writeln!(out, "(no preview: {})", reason.lines().next().unwrap_or(""))?
+            }
+            None => {}
+        }
+        writeln!(out, "{}", crate::pick::label(photo))?;
+    }
+    Ok(())
+}
+
+// Photo IDs from `path` (`-` for stdin): one per line, blank lines and
+// `#` comments skipped, repeats dropped.
+pub fn read_ids(path: &std::path::Path) -> Result<Vec<String>> {
+    use std::io::Read;
+    let text = if path.as_os_str() == "-" {
+        let mut raw = String::new();
+        io::stdin()
+            .read_to_string(&mut raw)
+            .context("read ids from stdin")?;
+        raw
+    } else {
+        std::fs::read_to_string(path).map_err(|e| {
+            let err = serde_json::json!({
+                "error": "cannot read --input",
+                "path": path.display().to_string(),
+                "reason": e.to_string(),
             });
-            emit_data(&fmt, &out)
+            anyhow::anyhow!(
+                serde_yaml::to_string(&err).unwrap_or_else(|_| "cannot read --input".into())
+            )
+        })?
+    };
+    let mut seen = std::collections::HashSet::new();
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter(|l| seen.insert(l.to_string()))
+        .map(String::from)
+        .collect())
+}
+
+// `photos urls`: src.<size> of each photo, `concurrency` lookups at a time
+// through the result cache. Failures are recorded per ID.
+async fn resolve_urls(
+    client: &PexelsClient,
+    cli: &Cli,
+    ids: &[String],
+    size: PhotoSize,
+    concurrency: usize,
+) -> (
+    serde_json::Map<String, JsonValue>,
+    serde_json::Map<String, JsonValue>,
+) {
+    use futures::stream::{self, StreamExt};
+    let results: Vec<Result<JsonValue>> = stream::iter(ids)
+        .map(|id| async move {
+            if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(anyhow::anyhow!(serde_yaml::to_string(
+                    &serde_json::json!({ "error": "not a photo id" })
+                )?));
+            }
+            let data = client.photos_lookup(id, !cli.refresh).await?;
+            data.pointer(&format!("/src/{}", size.key()))
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!(format!("src.{} not found", size.key())))
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    let (mut urls, mut errors) = (serde_json::Map::new(), serde_json::Map::new());
+    for (id, res) in ids.iter().zip(results) {
+        match res {
+            Ok(url) => {
+                urls.insert(id.clone(), url);
+            }
+            Err(e) => {
+                errors.insert(id.clone(), crate::output::error_value(&e));
+            }
+        }
+    }
+    (urls, errors)
+}
+
+// Error (after the summary has been printed) when any job failed.
+fn bulk_outcome(summary: &JsonValue) -> Result<()> {
+    let failed = summary["failed"].as_u64().unwrap_or(0);
+    let mirror_failed: u64 = summary["mirrors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m["failed"].as_u64())
+        .sum();
+    let web_failed = summary["web"]["failed"].as_u64().unwrap_or(0);
+    if web_failed > 0 && failed == 0 {
+        let err = serde_json::json!({
+            "error": "some web optimizations failed",
+            "failed": web_failed,
+            "hint": "see data.items[].web",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "some web optimizations failed".into())
+        ));
+    }
+    if mirror_failed > 0 && failed == 0 {
+        let err = serde_json::json!({
+            "error": "some copies to --dest failed",
+            "failed": mirror_failed,
+            "hint": "see data.items[].mirrors; re-running skips unchanged downloads and copies again",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "some copies failed".into())
+        ));
+    }
+    if failed > 0 {
+        let err = serde_json::json!({
+            "error": "some downloads failed",
+            "failed": failed,
+            "hint": "see data.items[].error; re-running resumes and skips unchanged files",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "some downloads failed".into())
+        ));
+    }
+    Ok(())
+}
+
+// Concurrent HEAD requests while sampling sizes.
+const ESTIMATE_CONCURRENCY: usize = 8;
+
+// Estimate download volume per size tier: HEAD a spread-out sample of each
+// tier's URLs and extrapolate the mean Content-Length to every photo.
+async fn estimate_sizes(
+    client: &PexelsClient,
+    photos: &[JsonValue],
+    selected: Option<PhotoSize>,
+    sample: usize,
+) -> JsonValue {
+    use futures::stream::{self, StreamExt};
+    let picks = sample_indices(photos.len(), sample.max(1));
+    let mut tiers = vec![];
+    for size in PhotoSize::ALL {
+        let urls: Vec<String> = picks
+            .iter()
+            .filter_map(|i| photos[*i].get("src")?.get(size.key())?.as_str())
+            .map(|s| s.to_string())
+            .collect();
+        let lengths: Vec<u64> = stream::iter(urls)
+            .map(|u| async move { client.head_content_length(&u).await })
+            .buffer_unordered(ESTIMATE_CONCURRENCY)
+            .filter_map(|r| async move { r.ok().flatten() })
+            .collect()
+            .await;
+        let avg = if lengths.is_empty() {
+            None
+        } else {
+            Some(lengths.iter().sum::<u64>() / lengths.len() as u64)
+        };
+        tiers.push(serde_json::json!({
+            "size": size.key(),
+            "sampled": lengths.len(),
+            "avg_bytes": avg,
+            "est_total_bytes": avg.map(|a| a * photos.len() as u64),
+        }));
+    }
+    let mut report = serde_json::json!({ "count": photos.len(), "tiers": tiers });
+    if let Some(sel) = selected {
+        let tier = report["tiers"]
+            .as_array()
+            .and_then(|t| t.iter().find(|t| t["size"] == sel.key()))
+            .cloned();
+        report["selected"] = tier.unwrap_or(JsonValue::Null);
+    }
+    report
+}
+
+// Up to `n` indices spread evenly over `0..len`.
+pub fn sample_indices(len: usize, n: usize) -> Vec<usize> {
+    if len == 0 {
+        return vec![];
+    }
+    let n = n.min(len);
+    (0..n).map(|i| i * len / n).collect()
+}
+
+// Candidate pool size for the photo of the day (one full API page).
+const POTD_POOL: u32 = crate::api::MAX_PER_PAGE;
+
+// Pick today's photo deterministically and cache it so every call on the same
+// local calendar day returns the same item, even if the pool changes.
+async fn photo_of_the_day(client: &PexelsClient, query: Option<&str>) -> Result<JsonValue> {
+    let today = chrono::Local::now().date_naive().to_string();
+    let key = match query {
+        Some(q) => format!("potd:query:{}", q),
+        None => "potd:curated".to_string(),
+    };
+    let mut state = State::load()?;
+    if let Some(entry) = state.get(&key) {
+        if entry.get("date").and_then(|d| d.as_str()) == Some(today.as_str()) {
+            if let Some(photo) = entry.get("photo") {
+                return Ok(photo.clone());
+            }
         }
     }
+    let page = client.photos_pool(query, POTD_POOL).await?;
+    let photos = page
+        .get("photos")
+        .and_then(|v| v.as_array())
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("no photos to pick from"))?;
+    let photo = photos[potd_index(&today, &key, photos.len())].clone();
+    state.set(&key, serde_json::json!({ "date": today, "photo": photo }));
+    if let Err(e) = state.save() {
+        tracing::warn!("potd cache not saved: {}", e);
+    }
+    Ok(photo)
+}
+
+// Stable index for a given day and pick key.
+pub fn potd_index(date: &str, key: &str, len: usize) -> usize {
+    let seed = crate::util::fnv1a64(format!("{}|{}", date, key).as_bytes());
+    (seed % len as u64) as usize
+}
+
+// Fetch and decode the first of src.small / tiny / medium for local
+// analysis; returns the URL used with the image.
+async fn small_variant(
+    client: &PexelsClient,
+    photo: &JsonValue,
+) -> Result<(String, image::DynamicImage)> {
+    variant_image(client, photo, &SMALL_VARIANTS).await
+}
+
+// src.* keys tried, in order, for a small copy of a photo.
+const SMALL_VARIANTS: [&str; 3] = ["small", "tiny", "medium"];
+
+// First of `keys` present in the photo's src, fetched and decoded.
+async fn variant_image(
+    client: &PexelsClient,
+    photo: &JsonValue,
+    keys: &[&str],
+) -> Result<(String, image::DynamicImage)> {
+    let url = keys
+        .iter()
+        .find_map(|k| {
+            photo
+                .get("src")
+                .and_then(|s| s.get(*k))
+                .and_then(|v| v.as_str())
+        })
+        .ok_or_else(|| anyhow::anyhow!("src.{} not found", keys.first().unwrap_or(&"small")))?
+        .to_string();
+    let bytes = client.download_url_bytes(&url).await?;
+    let img = image::load_from_memory(&bytes).map_err(|e| {
+        let err = serde_json::json!({
+            "error": "could not decode photo",
+            "url": url,
+            "reason": e.to_string(),
+        });
+        anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "could not decode photo".into())
+        )
+    })?;
+    Ok((url, img))
+}
+
+// One fetch of the list command being watched, with its projection.
+async fn watch_fetch(client: &PexelsClient, cli: &Cli) -> Result<(JsonValue, Vec<String>)> {
+    let cfg = client.config();
+    Ok(match &cli.command {
+        Commands::Photos(cmd) => {
+            let data = match &cmd.sub {
+                PhotosSub::Search {
+                    query,
+                    any_of,
+                    all_of,
+                    none_of,
+                    filters,
+                    ..
+                } => {
+                    let plan = crate::query::BoolQuery {
+                        base: query.clone(),
+                        any_of: any_of.clone(),
+                        all_of: all_of.clone(),
+                        none_of: none_of.clone(),
+                    };
+                    search_photos(client, cli, &plan, filters).await?
+                }
+                PhotosSub::Curated { .. } => client.photos_curated(cli).await?,
+                _ => return Err(watch_unsupported()),
+            };
+            (data, cfg.projection("photos"))
+        }
+        Commands::Videos(cmd) => {
+            let data = match &cmd.sub {
+                VideosSub::Search { query, filters, .. } => {
+                    filters.validate()?;
+                    client
+                        .videos_search(query, &filters.query_params(), cli)
+                        .await?
+                }
+                VideosSub::Popular {
+                    filters, bucket, ..
+                } => popular_videos(client, cli, filters, bucket.as_ref()).await?,
+                _ => return Err(watch_unsupported()),
+            };
+            (data, cfg.projection("videos"))
+        }
+        Commands::Collections(cmd) => {
+            let data = match &cmd.sub {
+                CollectionsSub::List { .. } => client.collections_list(cli).await?,
+                CollectionsSub::Featured { .. } => client.collections_featured(cli).await?,
+                CollectionsSub::Items { id, .. } => client.collections_items(id, cli).await?,
+                _ => return Err(watch_unsupported()),
+            };
+            (data, cfg.projection("collections"))
+        }
+        _ => return Err(watch_unsupported()),
+    })
+}
+
+// The list behind a command run with --count.
+fn count_listing(cli: &Cli) -> Result<Option<crate::api::Listing<'_>>> {
+    use crate::api::Listing;
+    Ok(Some(match &cli.command {
+        Commands::Photos(cmd) => match &cmd.sub {
+            PhotosSub::Search {
+                query,
+                any_of,
+                all_of,
+                none_of,
+                filters,
+                count,
+                ..
+            } if count.count => {
+                if !(any_of.is_empty() && all_of.is_empty() && none_of.is_empty()) {
+                    return Err(count_unsupported());
+                }
+                Listing::PhotosSearch {
+                    query: query.as_deref().unwrap_or_default(),
+                    filters: filters.query_params(),
+                }
+            }
+            PhotosSub::Curated { count } if count.count => Listing::PhotosCurated,
+            _ => return Ok(None),
+        },
+        Commands::Videos(cmd) => match &cmd.sub {
+            VideosSub::Search {
+                query,
+                filters,
+                count,
+            } if count.count => {
+                filters.validate()?;
+                Listing::VideosSearch {
+                    query,
+                    filters: filters.query_params(),
+                }
+            }
+            VideosSub::Popular { filters, count, .. } if count.count => {
+                filters.validate()?;
+                Listing::VideosPopular {
+                    filters: filters.query_params(),
+                }
+            }
+            _ => return Ok(None),
+        },
+        Commands::Collections(cmd) => match &cmd.sub {
+            CollectionsSub::List { count, .. } if count.count => Listing::CollectionsList,
+            CollectionsSub::Featured { count, .. } if count.count => Listing::CollectionsFeatured,
+            CollectionsSub::Items { id, count } if count.count => Listing::CollectionsItems { id },
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    }))
+}
+
+async fn run_count(
+    client: &PexelsClient,
+    cli: &Cli,
+    listing: crate::api::Listing<'_>,
+) -> Result<()> {
+    // Flags that pick or page through items have nothing to act on
+    let flags = [
+        ("--all", cli.all),
+        ("--limit", cli.limit.is_some()),
+        ("--max-pages", cli.max_pages.is_some()),
+        ("--page", cli.page.is_some()),
+        ("--watch", cli.watch),
+        ("--cursor-file", cli.cursor_file.is_some()),
+        ("--pick", cli.pick),
+    ];
+    let clashing: Vec<&str> = flags
+        .iter()
+        .filter(|(_, on)| *on)
+        .map(|(f, _)| *f)
+        .collect();
+    if !clashing.is_empty() {
+        let err = serde_json::json!({
+            "error": "--count prints a single number",
+            "conflicts": clashing,
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "--count conflicts".into())
+        ));
+    }
+    let total = client.count(listing, cli).await?;
+    emit_data(
+        &OutputFormat::Raw,
+        &JsonValue::String(format!("{}\n", total)),
+    )
+}
+
+fn count_unsupported() -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "--count does not combine with --any-of/--all-of/--none-of",
+        "hint": "count each term with its own search, e.g. pexels photos search -q cats --count",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "count unsupported".into()))
+}
+
+fn watch_unsupported() -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "--watch needs a list command",
+        "hint": "e.g. pexels photos search -q cats --watch --interval 300",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "watch unsupported".into()))
+}
+
+// Seconds between watch polls: --interval, else watch.interval, else 60.
+fn watch_interval(cli: &Cli, cfg: &Config) -> std::time::Duration {
+    let secs = cli.interval.or(cfg.watch.interval).unwrap_or(60);
+    std::time::Duration::from_secs(secs.max(1))
+}
+
+// Re-read config.yaml when it changed since `seen`; the new client keeps the
+// session's API call count. Unreadable edits are logged and ignored.
+fn reload_config(
+    client: &PexelsClient,
+    cli: &Cli,
+    seen: &mut Option<std::time::SystemTime>,
+) -> Option<PexelsClient> {
+    let modified = Config::modified();
+    if modified == *seen {
+        return None;
+    }
+    *seen = modified;
+    let cfg = match resolve_config(cli) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            tracing::warn!("config reload skipped: {:#}", e);
+            return None;
+        }
+    };
+    let changed = client.config().changed_keys(&cfg);
+    if changed.is_empty() {
+        return None;
+    }
+    match client.with_config(cfg) {
+        Ok(next) => {
+            tracing::warn!("config reloaded: {} changed", changed.join(", "));
+            Some(next)
+        }
+        Err(e) => {
+            tracing::warn!("config reload skipped: {:#}", e);
+            None
+        }
+    }
+}
+
+// Poll until --for elapses (or Ctrl-C). Every item not seen before is
+// printed as one JSON line; the session ends with a summary envelope line.
+// Edits to config.yaml (token, projections, watch.interval, ...) apply from
+// the next poll on.
+async fn run_watch(mut client: PexelsClient, cli: &Cli) -> Result<()> {
+    let started = chrono::Utc::now();
+    let clock = std::time::Instant::now();
+    let mut cfg_mtime = Config::modified();
+    let mut reloads = 0u64;
+    let mut seen = std::collections::HashSet::new();
+    let (mut polls, mut new_items, mut baseline) = (0u64, 0u64, 0u64);
+    let mut sink = match &cli.ndjson_file {
+        Some(path) => Some(crate::sink::NdjsonSink::open(path, cli.rotate)?),
+        None => None,
+    };
+    let reason = loop {
+        if let Some(next) = reload_config(&client, cli, &mut cfg_mtime) {
+            client = next;
+            reloads += 1;
+        }
+        let (data, defaults) = watch_fetch(&client, cli).await?;
+        let projector = projector_from_cli(cli, &defaults);
+        let (items, _meta) = shape_output(&data);
+        let mut out = vec![];
+        for item in items.as_array().into_iter().flatten() {
+            let key = crate::normalize::identity(item).unwrap_or_else(|| item.to_string());
+            if !seen.insert(key) {
+                continue;
+            }
+            if polls == 0 {
+                baseline += 1;
+            } else {
+                new_items += 1;
+            }
+            let rec = projector.apply(item);
+            match sink.as_mut() {
+                Some(sink) => sink.write(&rec)?,
+                None => writeln!(out, "{}", serde_json::to_string(&rec)?)?,
+            }
+        }
+        // One delivery per poll
+        if !out.is_empty() {
+            crate::output::deliver(&out, OutputFormat::Ndjson.content_type())?;
+        }
+        polls += 1;
+        save_window(&client);
+        let remaining = cli.watch_for.map(|d| d.saturating_sub(clock.elapsed()));
+        if remaining == Some(std::time::Duration::ZERO) {
+            break "deadline";
+        }
+        let interval = watch_interval(cli, client.config());
+        let nap = remaining.map(|r| r.min(interval)).unwrap_or(interval);
+        if sleep_or_interrupt(nap).await {
+            break "interrupted";
+        }
+        if cli.watch_for.map(|d| clock.elapsed() >= d).unwrap_or(false) {
+            break "deadline";
+        }
+    };
+    let mut summary = serde_json::json!({
+        "items_seen": seen.len(),
+        "baseline_items": baseline,
+        "new_items": new_items,
+        "polls": polls,
+        "config_reloads": reloads,
+        "api_calls": client.api_calls(),
+        "started_at": started.to_rfc3339(),
+        "ended_at": chrono::Utc::now().to_rfc3339(),
+        "elapsed_secs": clock.elapsed().as_secs(),
+        "stopped": reason,
+    });
+    if let (Some(sink), Some(path)) = (sink.as_mut(), &cli.ndjson_file) {
+        sink.finish()?;
+        let rotated: Vec<String> = sink
+            .rotated()
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        summary["output"] = serde_json::json!({
+            "path": path.display().to_string(),
+            "rotated": rotated,
+        });
+    }
+    let env = wrap_ok(&summary, Some(serde_json::json!({ "summary": true })));
+    let line = serde_json::to_string(&env)? + "\n";
+    crate::output::deliver(line.as_bytes(), OutputFormat::Ndjson.content_type())
+}
+
+// Sleep for `d`; true when Ctrl-C arrived first.
+async fn sleep_or_interrupt(d: std::time::Duration) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::select! {
+            _ = tokio::time::sleep(d) => false,
+            _ = tokio::signal::ctrl_c() => true,
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        crate::util::sleep(d).await;
+        false
+    }
+}
+
+// Plain search, or the merged result of a boolean query plan.
+async fn search_photos(
+    client: &PexelsClient,
+    cli: &Cli,
+    plan: &crate::query::BoolQuery,
+    filters: &PhotoFilters,
+) -> Result<JsonValue> {
+    let qp = filters.query_params();
+    if plan.is_plain() {
+        return client
+            .photos_search(plan.base.as_deref().unwrap_or_default(), &qp, cli)
+            .await;
+    }
+    let mut responses = vec![];
+    for q in plan.searches() {
+        responses.push(client.photos_search(&q, &qp, cli).await?);
+    }
+    Ok(plan.merge(&responses, "photos"))
+}
+
+// Popular videos with duration bounds re-checked locally and optional
+// bucket counts (moved into meta by `shape_output`).
+async fn popular_videos(
+    client: &PexelsClient,
+    cli: &Cli,
+    filters: &VideoFilters,
+    bucket: Option<&crate::video::Buckets>,
+) -> Result<JsonValue> {
+    filters.validate()?;
+    let mut data = client.videos_popular(&filters.query_params(), cli).await?;
+    let dropped =
+        crate::video::retain_duration(&mut data, filters.min_duration, filters.max_duration);
+    if dropped > 0 {
+        data["duration_filtered"] = dropped.into();
+    }
+    if let Some(b) = bucket {
+        let items = data["videos"].as_array().cloned().unwrap_or_default();
+        data["buckets"] = b.counts(&items);
+    }
+    Ok(data)
 }
 
 async fn run_videos(cmd: &VideosCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
+    if streams_pages(cli) {
+        let defaults = client.config().projection("videos");
+        match &cmd.sub {
+            VideosSub::Search { query, filters, .. } => {
+                filters.validate()?;
+                let listing = crate::api::Listing::VideosSearch {
+                    query,
+                    filters: filters.query_params(),
+                };
+                return stream_list(&client, cli, listing, &defaults, |_| {}).await;
+            }
+            // Bucket counts need every video
+            VideosSub::Popular {
+                filters,
+                bucket: None,
+                ..
+            } => {
+                filters.validate()?;
+                let listing = crate::api::Listing::VideosPopular {
+                    filters: filters.query_params(),
+                };
+                let (min, max) = (filters.min_duration, filters.max_duration);
+                return stream_list(&client, cli, listing, &defaults, |chunk| {
+                    crate::video::retain_duration(chunk, min, max);
+                })
+                .await;
+            }
+            _ => {}
+        }
+    }
     let data = match &cmd.sub {
-        VideosSub::Search { query } => client.videos_search(query, cli).await?,
-        VideosSub::Popular => client.videos_popular(cli).await?,
-        VideosSub::Get { id } => client.videos_get(id).await?,
+        VideosSub::Exists { id } => {
+            let found = client.videos_get(id).await;
+            return report_exists(cli, "video", id, found);
+        }
+        VideosSub::Search { query, filters, .. } => {
+            filters.validate()?;
+            client
+                .videos_search(query, &filters.query_params(), cli)
+                .await?
+        }
+        VideosSub::Popular {
+            filters, bucket, ..
+        } => popular_videos(&client, cli, filters, bucket.as_ref()).await?,
+        VideosSub::Get { ids, concurrency } => match ids.as_slice() {
+            [id] => client.videos_get(id).await?,
+            _ => {
+                let data =
+                    get_many(ids, *concurrency, "videos", |id| client.videos_get(id)).await?;
+                let failed = data["lookups"]["failed"].as_u64().unwrap_or(0);
+                emit_enveloped(cli, data, &client.config().projection("videos"))?;
+                return lookups_outcome("video", failed);
+            }
+        },
+        VideosSub::Url { id, rendition } => {
+            let data = client.videos_get(id).await?;
+            let file = crate::video::select_file(&data, rendition)
+                .ok_or_else(|| crate::video::no_match_error(&data, rendition))?;
+            let out = serde_json::json!({ "data": file.get("link") });
+            return emit_final(cli, &fmt_from_cli(cli), &out);
+        }
+        VideosSub::Download {
+            id,
+            path,
+            rendition,
+            if_busy,
+        } => {
+            let data = client.videos_get(id).await?;
+            let file = crate::video::select_file(&data, rendition)
+                .ok_or_else(|| crate::video::no_match_error(&data, rendition))?;
+            let url = file
+                .get("link")
+                .and_then(|l| l.as_str())
+                .unwrap_or_default();
+            let p = media_dest(&client, cli, &data, url, std::path::Path::new(path), None).await?;
+            let post = post_steps(&client, cli)?;
+            let written = download_media(&client, cli, url, &p, *if_busy).await?;
+            let mut v = post_process(&written, &post)?;
+            for k in ["quality", "width", "height", "fps"] {
+                if let Some(x) = file.get(k) {
+                    v[k] = x.clone();
+                }
+            }
+            let out = serde_json::json!({ "data": v });
+            return emit_final(cli, &fmt_from_cli(cli), &out);
+        }
     };
-    emit_enveloped(cli, data, &DefaultFields::Videos)
+    emit_list(&client, cli, data, &client.config().projection("videos")).await
 }
 
 async fn run_collections(cmd: &CollectionsCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
+    if streams_pages(cli) {
+        let listing = match &cmd.sub {
+            CollectionsSub::List { expand, .. } if !expand.expand => {
+                Some(crate::api::Listing::CollectionsList)
+            }
+            CollectionsSub::Featured { expand, .. } if !expand.expand => {
+                Some(crate::api::Listing::CollectionsFeatured)
+            }
+            CollectionsSub::Items { id, .. } => Some(crate::api::Listing::CollectionsItems { id }),
+            _ => None,
+        };
+        if let Some(listing) = listing {
+            let defaults = client.config().projection("collections");
+            return stream_list(&client, cli, listing, &defaults, |_| {}).await;
+        }
+    }
     let data = match &cmd.sub {
-        CollectionsSub::List => client.collections_list(cli).await?,
-        CollectionsSub::Featured => client.collections_featured(cli).await?,
+        CollectionsSub::List { expand, .. } | CollectionsSub::Featured { expand, .. } => {
+            let mut data = match &cmd.sub {
+                CollectionsSub::List { .. } => client.collections_list(cli).await?,
+                _ => client.collections_featured(cli).await?,
+            };
+            if !expand.expand {
+                data
+            } else {
+                expand_collections(&client, &mut data, expand.expand_concurrency).await;
+                let mut defaults = client.config().projection("collections");
+                defaults.push("media_breakdown".into());
+                return emit_list(&client, cli, data, &defaults).await;
+            }
+        }
         CollectionsSub::Get { id } => client.collections_get(id).await?,
-        CollectionsSub::Items { id } => client.collections_items(id, cli).await?,
+        CollectionsSub::Items { id, .. } => client.collections_items(id, cli).await?,
+        CollectionsSub::Download {
+            id,
+            dir,
+            size,
+            rendition,
+            concurrency,
+            if_busy,
+            mirror,
+        } => {
+            let dir = &download_dir(client.config(), dir, false)?;
+            let size = size.unwrap_or(PhotoSize::Original);
+            let data = client.collections_items_all(id, cli).await?;
+            let mut jobs = vec![];
+            let mut kinds = vec![];
+            let mut missing = vec![];
+            for item in data
+                .get("media")
+                .and_then(|m| m.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let item_id = item.get("id").cloned().unwrap_or(JsonValue::Null);
+                let kind = crate::normalize::kind_of(item);
+                let url = match kind {
+                    crate::normalize::Kind::Video => crate::video::select_file(item, rendition)
+                        .and_then(|f| f.get("link"))
+                        .and_then(|l| l.as_str()),
+                    _ => item
+                        .pointer(&format!("/src/{}", size.key()))
+                        .and_then(|u| u.as_str()),
+                };
+                let Some(url) = url else {
+                    missing.push(serde_json::json!({ "id": item_id, "kind": kind.as_str() }));
+                    continue;
+                };
+                let name = crate::pick::file_name_for(item, Some(url));
+                let dest = media_dest(&client, cli, item, url, dir, Some(&name)).await?;
+                kinds.push(kind);
+                jobs.push(crate::download::Job {
+                    id: item_id,
+                    url: url.to_string(),
+                    dest,
+                });
+            }
+            let urls: Vec<String> = jobs.iter().map(|j| j.url.clone()).collect();
+            let mut summary =
+                bulk_download(&client, cli, jobs, *if_busy, *concurrency, &mirror.dest).await?;
+            if let Some(items) = summary["items"].as_array_mut() {
+                for ((v, kind), url) in items.iter_mut().zip(&kinds).zip(&urls) {
+                    v["kind"] = serde_json::json!(kind.as_str());
+                    v["url"] = serde_json::json!(url);
+                }
+            }
+            if !missing.is_empty() {
+                summary["missing"] = serde_json::json!(missing);
+            }
+            let manifest = write_manifest(dir, id, &summary)?;
+            summary["manifest"] = serde_json::json!(manifest.display().to_string());
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&summary, None))?;
+            return bulk_outcome(&summary);
+        }
     };
-    emit_enveloped(cli, data, &DefaultFields::Collections)
+    emit_list(
+        &client,
+        cli,
+        data,
+        &client.config().projection("collections"),
+    )
+    .await
+}
+
+// Attach `media_breakdown` to every collection in a listing, fetching up to
+// `concurrency` at once. Failures are recorded per collection.
+async fn expand_collections(client: &PexelsClient, data: &mut JsonValue, concurrency: usize) {
+    use futures::stream::{self, StreamExt};
+    let Some(cols) = data.get_mut("collections").and_then(|c| c.as_array_mut()) else {
+        return;
+    };
+    let ids: Vec<String> = cols
+        .iter()
+        .map(|c| match c.get("id") {
+            Some(JsonValue::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        })
+        .collect();
+    let breakdowns: Vec<JsonValue> = stream::iter(ids)
+        .map(|id| async move {
+            match media_breakdown(client, &id).await {
+                Ok(v) => v,
+                Err(e) => serde_json::json!({ "error": crate::output::error_value(&e) }),
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    for (c, b) in cols.iter_mut().zip(breakdowns) {
+        c["media_breakdown"] = b;
+    }
+}
+
+// Photo/video counts from the first media page; when the collection is larger
+// than one page, a `type=photos` query supplies the exact photo total.
+async fn media_breakdown(client: &PexelsClient, id: &str) -> Result<JsonValue> {
+    const PAGE: usize = 80;
+    let page = client
+        .collection_media_page(id, vec![("per_page".into(), PAGE.to_string())])
+        .await?;
+    let media = page
+        .get("media")
+        .and_then(|m| m.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let count = |kind: crate::normalize::Kind| {
+        media
+            .iter()
+            .filter(|m| crate::normalize::kind_of(m) == kind)
+            .count() as u64
+    };
+    let total = page
+        .get("total_results")
+        .and_then(|t| t.as_u64())
+        .unwrap_or(media.len() as u64);
+    let mut photos = count(crate::normalize::Kind::Photo);
+    let mut videos = count(crate::normalize::Kind::Video);
+    if total > media.len() as u64 {
+        let only_photos = client
+            .collection_media_page(
+                id,
+                vec![
+                    ("type".into(), "photos".into()),
+                    ("per_page".into(), "1".into()),
+                ],
+            )
+            .await?;
+        photos = only_photos
+            .get("total_results")
+            .and_then(|t| t.as_u64())
+            .unwrap_or(photos);
+        videos = total.saturating_sub(photos);
+    }
+    Ok(serde_json::json!({ "photos": photos, "videos": videos, "total": total }))
+}
+
+// `manifest.json` in the mirror directory: what was fetched, from where, and
+// what failed. Replaced atomically so readers never see a partial file.
+fn write_manifest(
+    dir: &std::path::Path,
+    collection: &str,
+    summary: &JsonValue,
+) -> Result<std::path::PathBuf> {
+    std::fs::create_dir_all(dir).context("create download dir")?;
+    let path = dir.join("manifest.json");
+    let manifest = serde_json::json!({
+        "collection": collection,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "succeeded": summary["succeeded"],
+        "failed": summary["failed"],
+        "skipped": summary["skipped"],
+        "items": summary["items"],
+        "missing": summary.get("missing").cloned().unwrap_or(serde_json::json!([])),
+    });
+    let tmp = dir.join("manifest.json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&manifest)?).context("write manifest")?;
+    std::fs::rename(&tmp, &path).context("replace manifest")?;
+    Ok(path)
+}
+
+fn run_state(cmd: &StateCmd, cli: &Cli) -> Result<()> {
+    let mut state = State::load()?;
+    let entry_json = |key: &str, e: &crate::state::Entry| {
+        serde_json::json!({
+            "key": key,
+            "namespace": crate::state::namespace(key),
+            "value": e.value,
+            "updated_at": e.updated_at,
+        })
+    };
+    match &cmd.sub {
+        StateSub::Info => {
+            let mut counts = serde_json::Map::new();
+            for (k, _) in state.entries(None) {
+                let n = counts
+                    .entry(crate::state::namespace(k).to_string())
+                    .or_insert(serde_json::json!(0));
+                *n = serde_json::json!(n.as_u64().unwrap_or(0) + 1);
+            }
+            emit_wrapped(
+                cli,
+                &serde_json::json!({
+                    "path": state.path().display().to_string(),
+                    "schema_version": state.schema_version()?,
+                    "namespaces": counts,
+                }),
+            )
+        }
+        StateSub::List { namespace } => {
+            let items: Vec<JsonValue> = state
+                .entries(namespace.as_deref())
+                .map(|(k, e)| entry_json(k, e))
+                .collect();
+            let meta = serde_json::json!({ "total_results": items.len() });
+            emit_final(
+                cli,
+                &fmt_from_cli(cli),
+                &wrap_ok(&JsonValue::Array(items), Some(meta)),
+            )
+        }
+        StateSub::Get { key } => match state.entry(key) {
+            Some(e) => emit_wrapped(cli, &entry_json(key, e)),
+            None => Err(state_missing(key)),
+        },
+        StateSub::Rm { key } => {
+            if state.remove(key).is_none() {
+                return Err(state_missing(key));
+            }
+            state.save()?;
+            emit_wrapped(cli, &serde_json::json!({ "removed": key }))
+        }
+    }
+}
+
+// `pexels api`: raw body with --raw, otherwise the usual envelope (list
+// responses split into data/meta) without a default projection.
+async fn run_api(
+    client: &PexelsClient,
+    cli: &Cli,
+    method: ApiMethod,
+    path: &str,
+    params: &[(String, String)],
+) -> Result<()> {
+    let url = client.api_url(path)?;
+    let qp = params.to_vec();
+    match method {
+        ApiMethod::Head => {
+            let data = client.api_head(url, qp).await?;
+            emit_wrapped(cli, &data)
+        }
+        ApiMethod::Get if matches!(fmt_from_cli(cli), OutputFormat::Raw) => {
+            emit_raw_bytes(&client.req_bytes(url, qp).await?)
+        }
+        ApiMethod::Get => {
+            let data = client.api_get(url, qp).await?;
+            emit_enveloped(cli, data, &[])
+        }
+    }
+}
+
+async fn run_export(cmd: &ExportCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
+    match &cmd.sub {
+        ExportSub::Dataset {
+            query,
+            dest,
+            split,
+            size,
+            concurrency,
+            if_busy,
+            exclude_manifest,
+        } => {
+            let excluded = crate::dataset::Exclusions::load(exclude_manifest)?;
+            let data = client.photos_search(query, &[], cli).await?;
+            let mut sizes: Vec<&str> = vec![];
+            for key in size.iter().map(|s| s.key()) {
+                if !sizes.contains(&key) {
+                    sizes.push(key);
+                }
+            }
+            // One entry per job, indexed once its file is in place
+            let (mut jobs, mut entries, mut missing) = (vec![], vec![], vec![]);
+            let mut photos = std::collections::BTreeMap::<&str, u64>::new();
+            let mut skipped_ids = 0u64;
+            for item in data
+                .get("photos")
+                .and_then(|p| p.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let id = match &item["id"] {
+                    JsonValue::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if excluded.ids.contains(&id) {
+                    skipped_ids += 1;
+                    continue;
+                }
+                let part = split.assign(&id);
+                *photos.entry(part).or_default() += 1;
+                for key in &sizes {
+                    let Some(url) = item
+                        .pointer(&format!("/src/{}", key))
+                        .and_then(|u| u.as_str())
+                    else {
+                        missing.push(serde_json::json!({ "id": item["id"], "size": key }));
+                        continue;
+                    };
+                    let mut rel = std::path::PathBuf::from(part);
+                    if sizes.len() > 1 {
+                        rel.push(key);
+                    }
+                    rel.push(crate::pick::file_name_for(item, Some(url)));
+                    std::fs::create_dir_all(dest.join(rel.parent().unwrap_or(&rel)))
+                        .context("create dataset dir")?;
+                    jobs.push(crate::download::Job {
+                        id: item["id"].clone(),
+                        url: url.to_string(),
+                        dest: dest.join(&rel),
+                    });
+                    let file = rel.to_string_lossy().replace('\\', "/");
+                    entries.push((item, part, file, *key));
+                }
+            }
+            let (mut summary, ok) =
+                bulk_download_each(&client, cli, jobs, *if_busy, *concurrency, &[], None, false)
+                    .await?;
+            let (mut rows, mut duplicates) = (vec![], vec![]);
+            for ((item, part, file, key), ok) in entries.into_iter().zip(ok) {
+                if !ok {
+                    continue;
+                }
+                let path = dest.join(&file);
+                let sha256 = crate::dataset::sha256_file(&path)?;
+                // Same bytes as a sample of an earlier corpus under another id
+                if excluded.hashes.contains(&sha256) {
+                    std::fs::remove_file(&path).context("remove duplicate sample")?;
+                    duplicates.push(path.display().to_string());
+                    continue;
+                }
+                rows.push(crate::dataset::row(item, part, &file, key, &sha256));
+            }
+            for item in summary["items"].as_array_mut().into_iter().flatten() {
+                if duplicates.iter().any(|d| item["path"] == d.as_str()) {
+                    item["excluded"] = "content hash".into();
+                }
+            }
+            // Nothing may have been downloaded when every photo is excluded
+            std::fs::create_dir_all(dest).context("create dataset dir")?;
+            let index = dest.join("index.csv");
+            crate::dataset::write_index(&index, &rows)?;
+            summary["dest"] = JsonValue::String(dest.display().to_string());
+            summary["index"] = JsonValue::String(index.display().to_string());
+            summary["rows"] = rows.len().into();
+            summary["photos"] = serde_json::json!(photos);
+            if !missing.is_empty() {
+                summary["missing_size"] = missing.into();
+            }
+            if !excluded.is_empty() {
+                summary["excluded"] = serde_json::json!({
+                    "ids": skipped_ids,
+                    "hashes": duplicates.len(),
+                });
+            }
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&summary, None))?;
+            bulk_outcome(&summary)
+        }
+    }
+}
+
+async fn run_queue(cmd: &QueueCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
+    let mut state = State::load()?;
+    match &cmd.sub {
+        QueueSub::Add {
+            kind,
+            id,
+            size,
+            rendition,
+        } => {
+            let mut item = crate::queue::QueueItem::new(*kind, id);
+            match kind {
+                crate::queue::MediaType::Photo => {
+                    item.size = Some(size.unwrap_or(PhotoSize::Original).key().to_string());
+                }
+                crate::queue::MediaType::Video => {
+                    item.quality = rendition.quality.map(|q| q.key().to_string());
+                    item.max_width = rendition.max_width;
+                    item.max_height = rendition.max_height;
+                }
+            }
+            let replaced = crate::queue::add(&mut state, &item);
+            state.save()?;
+            emit_wrapped(
+                cli,
+                &serde_json::json!({
+                    "queued": item,
+                    "replaced": replaced,
+                    "total": crate::queue::items(&state).len(),
+                }),
+            )
+        }
+        QueueSub::List => {
+            let items = serde_json::to_value(crate::queue::items(&state))?;
+            let total = items.as_array().map_or(0, |a| a.len());
+            let meta = serde_json::json!({ "total_results": total });
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&items, Some(meta)))
+        }
+        QueueSub::Clear => {
+            let removed = crate::queue::clear(&mut state);
+            state.save()?;
+            emit_wrapped(cli, &serde_json::json!({ "removed": removed }))
+        }
+        QueueSub::Run {
+            dir,
+            concurrency,
+            if_busy,
+            mirror,
+        } => {
+            let dir = &download_dir(client.config(), dir, true)?;
+            let queued = crate::queue::items(&state);
+            drop(state);
+            let mut jobs = vec![];
+            let mut runnable = vec![];
+            let mut unresolved = vec![];
+            let mut deferred = None;
+            for (i, item) in queued.iter().enumerate() {
+                // One lookup per item: spread them over rate-limit windows
+                if let Some(w) = client.await_quota(cli.no_wait).await {
+                    deferred = Some((queued.len() - i, w));
+                    break;
+                }
+                match queued_job(&client, cli, item, dir).await {
+                    Ok(job) => {
+                        jobs.push(job);
+                        runnable.push(item);
+                    }
+                    Err(e) => {
+                        let mut v = crate::output::error_value(&e);
+                        v["id"] = serde_json::json!(item.id);
+                        v["kind"] = serde_json::json!(item.kind.as_str());
+                        unresolved.push(v);
+                    }
+                }
+            }
+            let (mut summary, ok) = bulk_download_each(
+                &client,
+                cli,
+                jobs,
+                *if_busy,
+                *concurrency,
+                &mirror.dest,
+                None,
+                false,
+            )
+            .await?;
+            // Reload so items queued while downloading are kept
+            let mut state = State::load()?;
+            for (item, done) in runnable.iter().zip(ok) {
+                if done {
+                    state.remove(&item.key());
+                }
+            }
+            state.save()?;
+            if !unresolved.is_empty() {
+                let failed = summary["failed"].as_u64().unwrap_or(0) + unresolved.len() as u64;
+                summary["failed"] = failed.into();
+                summary["unresolved"] = JsonValue::Array(unresolved);
+            }
+            summary["remaining"] = crate::queue::items(&state).len().into();
+            if let Some((n, w)) = deferred {
+                // --no-wait: the rest stays queued for a run after the reset
+                summary["deferred"] = n.into();
+                summary["resume_at"] = w.resume_at().into();
+            }
+            emit_final(cli, &fmt_from_cli(cli), &wrap_ok(&summary, None))?;
+            bulk_outcome(&summary)
+        }
+    }
+}
+
+// Look up a queued item and turn it into a download job under `dir`.
+async fn queued_job(
+    client: &PexelsClient,
+    cli: &Cli,
+    item: &crate::queue::QueueItem,
+    dir: &std::path::Path,
+) -> Result<crate::download::Job> {
+    let (data, url) = match item.kind {
+        crate::queue::MediaType::Photo => {
+            let data = client.photos_get(&item.id).await?;
+            let size = item.size.as_deref().unwrap_or("original");
+            let url = data
+                .pointer(&format!("/src/{}", size))
+                .and_then(|u| u.as_str())
+                .ok_or_else(|| anyhow::anyhow!(format!("src.{} not found", size)))?
+                .to_string();
+            (data, url)
+        }
+        crate::queue::MediaType::Video => {
+            let data = client.videos_get(&item.id).await?;
+            let rendition = item.rendition();
+            let url = crate::video::select_file(&data, &rendition)
+                .and_then(|f| f.get("link"))
+                .and_then(|l| l.as_str())
+                .ok_or_else(|| crate::video::no_match_error(&data, &rendition))?
+                .to_string();
+            (data, url)
+        }
+    };
+    let name = crate::pick::file_name_for(&data, Some(&url));
+    let dest = media_dest(client, cli, &data, &url, dir, Some(&name)).await?;
+    Ok(crate::download::Job {
+        id: serde_json::json!(item.id),
+        url,
+        dest,
+    })
+}
+
+fn state_missing(key: &str) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "state key not found",
+        "key": key,
+        "hint": "pexels state list shows stored keys",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "state key not found".into()))
 }
 
 async fn run_util(cmd: &UtilCmd, client: PexelsClient, cli: &Cli) -> Result<()> {
     match &cmd.sub {
         UtilSub::Inspect => {
             let data = client.util_inspect().await?;
-            emit_wrapped(&fmt_from_cli(cli), &data)
+            emit_wrapped(cli, &data)
         }
         UtilSub::Ping => {
             client.util_ping().await?;
-            emit_wrapped(&fmt_from_cli(cli), &serde_json::json!({"ok":true}))
+            emit_wrapped(cli, &serde_json::json!({"ok":true}))
+        }
+        UtilSub::SupportBundle { path } => {
+            let data = support_bundle(&client, path).await?;
+            emit_wrapped(cli, &data)
+        }
+        UtilSub::ReplayRequest { selector } => {
+            let data = replay_request(&client, selector).await?;
+            emit_wrapped(cli, &data)
+        }
+    }
+}
+
+// Differences listed by `util replay-request` before truncating.
+const REPLAY_DIFF_LIMIT: usize = 50;
+
+// `util replay-request`: send a logged GET/HEAD again and compare status,
+// quota and (when the result cache still has the recorded body) the
+// response itself.
+pub async fn replay_request(client: &PexelsClient, selector: &str) -> Result<JsonValue> {
+    let path = client.config().audit.log_path();
+    let records = crate::audit::read(&path)?;
+    let Some((index, rec)) = crate::audit::select(&records, selector) else {
+        let err = serde_json::json!({
+            "error": "no such audit record",
+            "selector": selector,
+            "records": records.len(),
+            "path": path.display().to_string(),
+            "hint": "pass a 1-based position (-1 for the latest) or a request_id; logging needs audit.enabled: true",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "no such audit record".into())
+        ));
+    };
+    let field = |k: &str| rec.get(k).cloned().unwrap_or(JsonValue::Null);
+    let method = rec.get("method").and_then(|v| v.as_str()).unwrap_or("GET");
+    let url = client.api_url(&format!(
+        "{}{}",
+        rec.get("host").and_then(|v| v.as_str()).unwrap_or_default(),
+        rec.get("endpoint")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+    ))?;
+    let qp: Vec<(String, String)> = rec
+        .get("query")
+        .and_then(|q| q.as_object())
+        .map(|q| {
+            q.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let recorded_body = (method == "GET")
+        .then(|| client.cached_response(&url, &qp))
+        .flatten();
+    let res = match method {
+        "GET" => client.api_get(url, qp).await,
+        "HEAD" => client.api_head(url, qp).await,
+        other => anyhow::bail!("cannot replay {} requests", other),
+    };
+    // Failed replays keep the structured error as the current response
+    let (status, body) = match res {
+        Ok(v) if method == "HEAD" => (v.get("status").cloned().unwrap_or(JsonValue::Null), None),
+        Ok(v) => (JsonValue::from(200), Some(v)),
+        Err(e) => {
+            let v = crate::output::error_value(&e);
+            (v.get("code").cloned().unwrap_or(JsonValue::Null), Some(v))
+        }
+    };
+    let mut out = serde_json::json!({
+        "request": {
+            "index": index,
+            "ts": field("ts"),
+            "method": method,
+            "endpoint": field("endpoint"),
+            "query": field("query"),
+            "request_id": field("request_id"),
+        },
+        "recorded": { "status": field("status"), "quota_remaining": field("quota_remaining") },
+        "current": {
+            "status": status,
+            "quota_remaining": client.quota_window().map(|w| w.remaining),
+        },
+        "status_changed": field("status") != status,
+    });
+    match (recorded_body, body) {
+        (Some(before), Some(after)) => {
+            let diffs = crate::audit::diff(&before, &after, REPLAY_DIFF_LIMIT + 1);
+            out["identical"] = diffs.is_empty().into();
+            out["differences_truncated"] = (diffs.len() > REPLAY_DIFF_LIMIT).into();
+            out["differences"] = diffs.into_iter().take(REPLAY_DIFF_LIMIT).collect();
+        }
+        (None, Some(after)) => {
+            out["recorded"]["body"] = "not cached".into();
+            out["current"]["body"] = after;
         }
+        _ => {}
     }
+    Ok(out)
 }
 
-enum DefaultFields {
-    None,
-    Photos,
-    Videos,
-    Collections,
+// Collect diagnostics into a tarball. The previous failure trace is read
+// before the doctor ping so a failing ping cannot replace it.
+async fn support_bundle(client: &PexelsClient, out: &std::path::Path) -> Result<JsonValue> {
+    use crate::support;
+    let cfg = client.config();
+    let mut entries: Vec<(String, Vec<u8>)> = vec![];
+    let last_failure = std::fs::read(support::last_failure_path()).ok();
+    let ping = match client.util_ping().await {
+        Ok(()) => serde_json::json!({"ok": true}),
+        Err(e) => serde_json::json!({"ok": false, "error": crate::output::error_value(&e)}),
+    };
+    let doctor = serde_json::json!({
+        "auth": build_auth_status(cfg),
+        "settings": client.util_inspect().await?,
+        "ping": ping,
+        "config_path": cfg.path().display().to_string(),
+        "config_exists": cfg.path().exists(),
+        "data_dir": crate::util::data_dir().display().to_string(),
+        "audit_enabled": cfg.audit.enabled,
+    });
+    entries.push((
+        "version.json".into(),
+        serde_json::to_vec_pretty(&support::build_info())?,
+    ));
+    entries.push((
+        "config.yaml".into(),
+        support::redacted_config(cfg)?.into_bytes(),
+    ));
+    entries.push(("doctor.json".into(), serde_json::to_vec_pretty(&doctor)?));
+    if let Some(log) = support::tail_lines(&cfg.audit.log_path(), support::LOG_TAIL_LINES) {
+        entries.push(("audit.jsonl".into(), log.into_bytes()));
+    }
+    if let Some(trace) = last_failure {
+        entries.push(("last_failure.json".into(), trace));
+    }
+    let bytes = support::write_bundle(out, &entries)?;
+    Ok(serde_json::json!({
+        "path": out.display().to_string(),
+        "bytes": bytes,
+        "files": entries.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>(),
+    }))
 }
 
-fn emit_enveloped(cli: &Cli, data: JsonValue, defaults: &DefaultFields) -> Result<()> {
-    let fmt = fmt_from_cli(cli);
-    let fields = if cli.fields.is_empty() {
-        match defaults {
-            DefaultFields::None => vec![],
-            DefaultFields::Photos => vec![
-                "id".into(),
-                "photographer".into(),
-                "alt".into(),
-                "width".into(),
-                "height".into(),
-                "avg_color".into(),
-            ],
-            DefaultFields::Videos => vec!["duration".into(), "width".into(), "height".into()],
-            DefaultFields::Collections => {
-                vec!["title".into(), "description".into(), "media_count".into()]
-            }
+// Validated steps of the --post pipeline, if one was requested.
+fn post_steps(client: &PexelsClient, cli: &Cli) -> Result<Option<(String, Vec<String>)>> {
+    let Some(name) = cli.post.as_deref() else {
+        return Ok(None);
+    };
+    let steps = crate::post::resolve(&client.config().postprocess, name)?;
+    Ok(Some((name.to_string(), steps.to_vec())))
+}
+
+// Download settings from the global flags.
+fn download_opts(cli: &Cli, if_busy: IfBusy) -> crate::download::Options {
+    use std::io::IsTerminal;
+    crate::download::Options {
+        if_busy,
+        conditional: !cli.no_conditional,
+        progress: !cli.no_progress && io::stderr().is_terminal(),
+    }
+}
+
+// Where `item`'s media from `url` goes: `--name-template` rendered under
+// `base`, else `default_name` under `base`, else `base` itself (an explicit
+// file path). The template's {ext} comes from the URL, or from a HEAD
+// request's Content-Type when the URL has none.
+async fn media_dest(
+    client: &PexelsClient,
+    cli: &Cli,
+    item: &JsonValue,
+    url: &str,
+    base: &std::path::Path,
+    default_name: Option<&str>,
+) -> Result<std::path::PathBuf> {
+    let dest = match (&cli.name_template, default_name) {
+        (Some(t), _) => {
+            let ext = match crate::naming::ext_from_url(url) {
+                Some(ext) => ext,
+                None => client
+                    .head_content_type(url)
+                    .await?
+                    .as_deref()
+                    .and_then(crate::naming::ext_from_content_type)
+                    .unwrap_or(match crate::normalize::kind_of(item) {
+                        crate::normalize::Kind::Video => "mp4",
+                        _ => "jpg",
+                    })
+                    .to_string(),
+            };
+            crate::naming::join_rendered(base, &crate::naming::render_template(t, item, &ext))
         }
-    } else {
-        cli.fields.clone()
+        (None, Some(name)) => crate::naming::join_rendered(base, name),
+        (None, None) => base.to_path_buf(),
     };
+    Ok(crate::naming::long_path(&dest))
+}
+
+async fn download_media(
+    client: &PexelsClient,
+    cli: &Cli,
+    url: &str,
+    dest: &std::path::Path,
+    if_busy: IfBusy,
+) -> Result<crate::download::Written> {
+    crate::download::fetch(client, url, dest, download_opts(cli, if_busy)).await
+}
+
+// Download result, with the post-processor run on freshly written files.
+fn post_process(
+    written: &crate::download::Written,
+    post: &Option<(String, Vec<String>)>,
+) -> Result<JsonValue> {
+    let mut v = written.to_json();
+    if let (Some((name, steps)), false) = (post, written.skipped) {
+        v["post"] = crate::post::run(name, steps, &written.path)?;
+    }
+    Ok(v)
+}
+
+// --ndjson over --all/--limit/--max-pages writes each page's items as the
+// page arrives instead of after the whole walk. Options that need every item
+// at once (--pick, --max-per-photographer, --also-write, --max-response-mb)
+// keep the merged path.
+fn streams_pages(cli: &Cli) -> bool {
+    matches!(fmt_from_cli(cli), OutputFormat::Ndjson)
+        && cli.walks_pages()
+        && !cli.pick
+        && cli.max_per_photographer.is_none()
+        && cli.also_write.is_empty()
+        && cli.max_response_bytes().is_none()
+}
+
+// Print the walk over `listing` as NDJSON page by page; `filter` adjusts
+// each chunk first. A reader that stops early (`| head`) ends the walk.
+async fn stream_list(
+    client: &PexelsClient,
+    cli: &Cli,
+    listing: crate::api::Listing<'_>,
+    defaults: &[String],
+    filter: impl Fn(&mut JsonValue),
+) -> Result<()> {
+    use futures::StreamExt;
+    let projector = projector_from_cli(cli, defaults);
+    let expr = cli.jmes.as_deref().map(crate::jmes::compile).transpose()?;
+    let chunks = client.list_pages(listing, cli)?;
+    futures::pin_mut!(chunks);
+    let (mut checked, mut resume) = (false, None);
+    while let Some(chunk) = chunks.next().await {
+        let mut chunk = JsonValue::Object(chunk?);
+        filter(&mut chunk);
+        if let Some(c) = chunk.get("continue") {
+            resume = Some(c.clone());
+        }
+        let Some(items) = crate::output::ITEM_KEYS
+            .iter()
+            .find_map(|k| chunk.get(*k).and_then(|v| v.as_array()))
+        else {
+            continue;
+        };
+        if !checked && !items.is_empty() {
+            warn_unmatched_fields(cli, items);
+            checked = true;
+        }
+        let mut lines: Vec<JsonValue> = items.iter().map(|it| projector.apply(it)).collect();
+        if let Some(expr) = &expr {
+            lines = crate::jmes::search_items(expr, &lines)?;
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        let mut buf = vec![];
+        crate::output::write_ndjson(&mut buf, &JsonValue::Array(lines))?;
+        match crate::output::deliver(&buf, OutputFormat::Ndjson.content_type()) {
+            Err(e) if crate::output::is_broken_pipe(&e) => return Ok(()),
+            res => res?,
+        }
+    }
+    if let Some(page) = resume.as_ref().and_then(|c| c["page"].as_u64()) {
+        let skip = resume
+            .as_ref()
+            .and_then(|c| c["skip"].as_u64())
+            .unwrap_or(0);
+        if let Err(e) = record_continuation(cli, page, skip) {
+            tracing::warn!("continuation not saved: {}", e);
+        }
+    }
+    Ok(())
+}
+
+// List output, optionally narrowed with --pick and handed to --then.
+async fn emit_list(
+    client: &PexelsClient,
+    cli: &Cli,
+    data: JsonValue,
+    defaults: &[String],
+) -> Result<()> {
+    if !cli.pick {
+        return emit_enveloped(cli, data, defaults);
+    }
+    let Some(key) = crate::output::ITEM_KEYS
+        .iter()
+        .find(|k| data.get(**k).map(|v| v.is_array()).unwrap_or(false))
+    else {
+        // Single resources have nothing to pick from
+        return emit_enveloped(cli, data, defaults);
+    };
+    let items = data[*key].as_array().cloned().unwrap_or_default();
+    let chosen: Vec<JsonValue> = crate::pick::pick(&items)?
+        .into_iter()
+        .map(|i| items[i].clone())
+        .collect();
+    match cli.pick_then {
+        PickAction::Emit => {
+            let mut data = data;
+            data[*key] = JsonValue::Array(chosen);
+            emit_enveloped(cli, data, defaults)
+        }
+        PickAction::Urls => {
+            let mut out = io::stdout().lock();
+            for item in &chosen {
+                if let Some(u) = crate::pick::media_url(item) {
+                    writeln!(out, "{}", u)?;
+                }
+            }
+            Ok(())
+        }
+        PickAction::Download => {
+            let post = post_steps(client, cli)?;
+            let mut results = vec![];
+            for item in &chosen {
+                let Some(url) = crate::pick::media_url(item) else {
+                    continue;
+                };
+                let name = crate::pick::file_name(item);
+                let dest = media_dest(client, cli, item, &url, &cli.pick_dir, Some(&name)).await?;
+                let written = download_media(client, cli, &url, &dest, IfBusy::Wait).await?;
+                results.push(post_process(&written, &post)?);
+            }
+            emit_final(
+                cli,
+                &fmt_from_cli(cli),
+                &wrap_ok(&JsonValue::Array(results), None),
+            )
+        }
+    }
+}
+
+// `defaults` is the resource projection applied when --fields is not given.
+fn emit_enveloped(cli: &Cli, data: JsonValue, defaults: &[String]) -> Result<()> {
+    let fmt = fmt_from_cli(cli);
 
     if matches!(fmt, OutputFormat::Raw) {
         let s = serde_json::to_string(&data)?;
+        if let Some(max) = cli.max_response_bytes() {
+            if s.len() as u64 > max {
+                return Err(crate::output::oversize_error(s.len() as u64, max));
+            }
+        }
         return emit_raw_bytes(s.as_bytes());
     }
 
     // New pipeline: compute meta from full response, extract items, then project items and wrap.
     use serde_json::Value as V;
-    let (data_val, meta) = shape_output(&data);
-    let out = match (&data, &data_val) {
+    let mut data = data;
+    if cli.positions {
+        // Single pages are stamped here; --all pages were stamped while fetching
+        crate::output::annotate_positions(&mut data);
+    }
+    let (mut data_val, mut meta) = shape_output(&data);
+    if let (Some(max), V::Array(items)) = (cli.max_per_photographer, &mut data_val) {
+        let dropped = crate::query::cap_per_creator(items, max);
+        meta["per_photographer"] = serde_json::json!({ "max": max, "dropped": dropped });
+    }
+    if let Some(page) = meta.pointer("/continue/page").and_then(|p| p.as_u64()) {
+        let skip = meta
+            .pointer("/continue/skip")
+            .and_then(|s| s.as_u64())
+            .unwrap_or(0);
+        if let Err(e) = record_continuation(cli, page, skip) {
+            tracing::warn!("continuation not saved: {}", e);
+        }
+    }
+    let projector = projector_from_cli(cli, defaults);
+    match &data_val {
+        V::Array(items) => {
+            let missing = warn_unmatched_fields(cli, items);
+            if !missing.is_empty() {
+                meta["unmatched_fields"] = serde_json::json!(missing);
+            }
+        }
+        _ => {
+            warn_unmatched_fields(cli, std::slice::from_ref(&data));
+        }
+    }
+    if cli.format_autoselected {
+        meta["format_autoselected"] = V::Bool(true);
+    }
+    let envelope = |projector: &crate::proj::Projector, meta: JsonValue| match (&data, &data_val) {
         (V::Object(_obj), V::Array(items)) => {
-            let projected_items = crate::proj::project_items_with_fallback(items, &fields);
+            let projected_items = items.iter().map(|it| projector.apply(it)).collect();
             wrap_ok(&V::Array(projected_items), Some(meta))
         }
         _ => {
             // Single-resource path: project object as a whole with fallback to avoid empty {}
-            let projected = if let V::Object(_) = &data {
-                crate::proj::project_item_with_fallback(&data, &fields)
-            } else {
-                crate::proj::project(&data, &fields)
-            };
             // Omit meta for single-resource outputs
-            wrap_ok(&projected, None)
+            wrap_ok(&projector.apply(&data), None)
+        }
+    };
+    let mut out = envelope(&projector, meta.clone());
+    if !cli.also_write.is_empty() {
+        let table = envelope(&projector_for(cli, defaults, true), meta);
+        crate::output::write_exports(&cli.also_write, &out, &table)?;
+    }
+    if let Some(max) = cli.max_response_bytes() {
+        let truncate = cli.on_oversize == OversizePolicy::Truncate;
+        crate::output::enforce_size(&mut out, max, truncate)?;
+    }
+    let out = match (&fmt, collapse_from_cli(cli)) {
+        (OutputFormat::Yaml, Some(opts)) => {
+            let mut out = out;
+            out["data"] = collapse_data(&out["data"], &opts);
+            out
+        }
+        _ => out,
+    };
+    let out = humanize_for(cli, &fmt, out);
+    emit_final(cli, &fmt, &crate::output::select_emit(out, cli.emit))
+}
+
+// State key holding the last truncated list command.
+const CONTINUE_KEY: &str = "continue:last";
+
+// Remember the current command line (minus --page) and where to resume.
+fn record_continuation(cli: &Cli, page: u64, skip: u64) -> Result<()> {
+    let argv = cli
+        .replay_argv
+        .clone()
+        .unwrap_or_else(|| std::env::args().skip(1).collect());
+    let mut state = State::load()?;
+    state.set(
+        CONTINUE_KEY,
+        serde_json::json!({ "argv": strip_page_args(&argv), "page": page, "skip": skip }),
+    );
+    state.save()
+}
+
+// Drop `--page N` / `--page=N` so the resume page can be appended.
+pub fn strip_page_args(argv: &[String]) -> Vec<String> {
+    let mut out = vec![];
+    let mut it = argv.iter();
+    while let Some(a) = it.next() {
+        if a == "--page" {
+            it.next();
+        } else if !a.starts_with("--page=") {
+            out.push(a.clone());
         }
+    }
+    out
+}
+
+// Re-run the last truncated list command from its continuation point.
+fn run_examples(path: &[String], recipes: bool, cli: &Cli) -> Result<()> {
+    if recipes {
+        return run_recipes(path, cli);
+    }
+    let found = crate::examples::for_command(path);
+    if found.is_empty() {
+        let err = serde_json::json!({
+            "error": "no examples for command",
+            "command": path.join(" "),
+            "hint": "run `pexels examples` to list every command",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "no examples".into())
+        ));
+    }
+    let items: Vec<JsonValue> = found
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "command": e.command,
+                "example": e.line,
+                "about": e.about,
+            })
+        })
+        .collect();
+    emit_wrapped(cli, &serde_json::json!({ "items": items }))
+}
+
+fn run_recipes(filter: &[String], cli: &Cli) -> Result<()> {
+    let found = crate::examples::recipes(filter);
+    if found.is_empty() {
+        let err = serde_json::json!({
+            "error": "no such recipe",
+            "recipe": filter.join(" "),
+            "hint": "run `pexels examples --recipes` to list categories and names",
+        });
+        return Err(anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "no such recipe".into())
+        ));
+    }
+    let mut cfg = Config::load().context("load config")?;
+    cfg.apply_env();
+    let values = crate::examples::placeholders(&cfg);
+    let items: Vec<JsonValue> = found
+        .iter()
+        .map(|r| {
+            let steps: Vec<String> = r
+                .steps
+                .iter()
+                .map(|s| crate::examples::fill(s, &values))
+                .collect();
+            serde_json::json!({
+                "name": r.name,
+                "category": r.category,
+                "about": r.about,
+                "steps": steps,
+            })
+        })
+        .collect();
+    emit_wrapped(cli, &serde_json::json!({ "items": items }))
+}
+
+// Completion script for `shell` covering every subcommand, flag and enum
+// value (`--size`, `--orientation`, `--quality`, ...) of the clap definition.
+pub fn write_completions(shell: clap_complete::Shell, out: &mut dyn Write) -> Result<()> {
+    clap_complete::generate(shell, &mut command(), "pexels", out);
+    out.flush()?;
+    Ok(())
+}
+
+// Clap command with the per-subcommand examples attached to --help.
+pub fn command() -> clap::Command {
+    use clap::CommandFactory;
+    crate::examples::attach(Cli::command())
+}
+
+async fn run_continue() -> Result<()> {
+    let state = State::load()?;
+    let Some(entry) = state.get(CONTINUE_KEY) else {
+        let err = serde_json::json!({
+            "error": "nothing to continue",
+            "hint": "run a list command with --all and --limit or --max-pages first",
+        });
+        return Err(anyhow::anyhow!(serde_yaml::to_string(&err)?));
     };
-    emit_data(&fmt, &out)
+    let argv: Vec<String> = entry
+        .get("argv")
+        .and_then(|a| a.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    let page = entry.get("page").and_then(|p| p.as_u64()).unwrap_or(1);
+    let mut full = vec!["pexels".to_string()];
+    full.extend(argv.iter().cloned());
+    full.push("--page".into());
+    full.push(page.to_string());
+    let mut cli = Cli::try_parse_from(full)?;
+    cli.replay_argv = Some(argv);
+    cli.resume_skip = entry.get("skip").and_then(|s| s.as_u64()).unwrap_or(0) as usize;
+    Box::pin(run(cli)).await
+}
+
+// Explicit --fields that match nothing in any item: a structured warning on
+// stderr, with the nearest paths that do exist. Returns the missing paths.
+fn warn_unmatched_fields(cli: &Cli, items: &[JsonValue]) -> Vec<String> {
+    if cli.fields.is_empty() {
+        return vec![];
+    }
+    let shaped: Vec<JsonValue>;
+    let items = if cli.normalize {
+        shaped = items.iter().map(crate::normalize::normalize).collect();
+        &shaped[..]
+    } else {
+        items
+    };
+    // Names computed by --map are not looked up in the item
+    let fields: Vec<String> = cli
+        .fields
+        .iter()
+        .filter(|f| !cli.map.iter().any(|m| &m.name == *f))
+        .cloned()
+        .collect();
+    let missing = crate::suggest::unmatched_fields(items, &fields);
+    if let Some(w) = crate::suggest::fields_warning(&missing, items.len()) {
+        let _ = crate::output::emit_warning(&w);
+    }
+    missing.into_iter().map(|(path, _)| path).collect()
+}
+
+// Per-item projection for the current flags. `_position` is always carried
+// over so --fields never drops it.
+pub fn projector_from_cli(cli: &Cli, defaults: &[String]) -> crate::proj::Projector {
+    projector_for(cli, defaults, fmt_from_cli(cli).is_table())
+}
+
+// `table`: the output is one row per item (csv, tsv, md, parquet). Rows are
+// canonical records, the same columns for every kind, unless --fields or
+// --full pick from the API shape; `defaults` only shape YAML/JSON output.
+fn projector_for(cli: &Cli, defaults: &[String], table: bool) -> crate::proj::Projector {
+    let canonical = cli.normalize || (table && cli.fields.is_empty() && !cli.full);
+    let fields = if !cli.fields.is_empty() {
+        cli.fields.clone()
+    } else if cli.full || canonical {
+        // --full bypasses projection; canonical records are already compact
+        vec![]
+    } else {
+        defaults.to_vec()
+    };
+    let projector = crate::proj::Projector::new(fields)
+        .exclude(cli.exclude_fields.clone())
+        .normalize(canonical)
+        .maps(cli.map.clone());
+    if cli.positions {
+        projector.keep(crate::output::POSITION_KEY)
+    } else {
+        projector
+    }
 }
 
 // Convert API response into the new output shape
@@ -546,9 +4095,38 @@ pub fn shape_output(input: &JsonValue) -> (JsonValue, JsonValue) {
             None => Value::Null,
         },
     );
+    // Searches behind a merged --any-of/--all-of/--none-of result
+    if let Some(plan) = input.get("query_plan") {
+        meta.insert("query_plan".into(), plan.clone());
+    }
+    // Per-range counts and locally dropped items from `videos popular`;
+    // where --no-wait paging stopped at the rate-limit wall; paging cut short
+    // by --soft-deadline; repeats dropped while paging; multi-ID get counts
+    // and misses
+    for key in [
+        "buckets",
+        "duration_filtered",
+        "rate_limited",
+        "partial",
+        "duplicates_dropped",
+        "lookups",
+        "errors",
+    ] {
+        if let Some(v) = input.get(key) {
+            meta.insert(key.into(), v.clone());
+        }
+    }
+    // Continuation point recorded when --limit/--max-pages cut the result short
+    if let Some(Value::Object(c)) = input.get("continue") {
+        let mut c = c.clone();
+        if let Some(page) = c.get("page").and_then(|p| p.as_u64()) {
+            c.insert("args".into(), json!(format!("--page {}", page)));
+        }
+        meta.insert("continue".into(), Value::Object(c));
+    }
     // Data extraction: prefer items arrays
     if let Some(obj) = input.as_object() {
-        for key in ["photos", "videos", "collections", "media"] {
+        for key in crate::output::ITEM_KEYS {
             if let Some(Value::Array(items)) = obj.get(key) {
                 let data = Value::Array(items.clone());
                 return (data, Value::Object(meta));
@@ -558,12 +4136,60 @@ pub fn shape_output(input: &JsonValue) -> (JsonValue, JsonValue) {
     (input.clone(), Value::Object(meta))
 }
 
-fn emit_wrapped(fmt: &OutputFormat, payload: &JsonValue) -> Result<()> {
-    let out = wrap_ok(payload, None);
-    emit_data(fmt, &out)
+// Print a finished envelope, applying --jmes first.
+fn emit_final(cli: &Cli, fmt: &OutputFormat, out: &JsonValue) -> Result<()> {
+    let Some(src) = &cli.jmes else {
+        return emit_data(fmt, out);
+    };
+    let expr = crate::jmes::compile(src)?;
+    match (fmt, out.get("data")) {
+        (OutputFormat::Ndjson, Some(JsonValue::Array(items))) => {
+            let items = crate::jmes::search_items(&expr, items)?;
+            emit_data(fmt, &JsonValue::Array(items))
+        }
+        _ => emit_data(fmt, &crate::jmes::search(&expr, out)?),
+    }
+}
+
+fn emit_wrapped(cli: &Cli, payload: &JsonValue) -> Result<()> {
+    let fmt = fmt_from_cli(cli);
+    let out = humanize_for(cli, &fmt, wrap_ok(payload, None));
+    emit_final(cli, &fmt, &out)
+}
+
+// --human only affects YAML; machine formats keep numbers numeric.
+fn humanize_for(cli: &Cli, fmt: &OutputFormat, out: JsonValue) -> JsonValue {
+    if cli.human && matches!(fmt, OutputFormat::Yaml) {
+        let seps = crate::human::separators(cli.locale.as_deref());
+        crate::human::humanize(&out, seps)
+    } else {
+        out
+    }
 }
 
 // Build auth status payload (expanded details)
+// `auth whoami`: identifies the active token without revealing it. Quota
+// and token-check details come from the state store, so no request is sent.
+pub fn build_whoami(cfg: &Config) -> Result<JsonValue> {
+    let status = build_auth_status(cfg);
+    let token = cfg.token.as_deref().filter(|t| !t.is_empty());
+    let client = PexelsClient::new(cfg.clone())?;
+    let st = State::load().ok();
+    Ok(serde_json::json!({
+        "fingerprint": token.map(crate::health::fingerprint),
+        "source": status["source"],
+        "details": status["details"],
+        "profile": cfg.profile_name(),
+        "host_profile": cfg.host_profile_name,
+        "host": client.host(),
+        "quota": st.as_ref().and_then(|st| client.quota_cached(st)),
+        "token_check": st
+            .as_ref()
+            .zip(token)
+            .and_then(|(st, t)| crate::health::last_check(st, t)),
+    }))
+}
+
 pub fn build_auth_status(cfg: &Config) -> JsonValue {
     let (src, present) = cfg.token_source_with_presence();
     let details = match src.as_str() {
@@ -572,13 +4198,19 @@ pub fn build_auth_status(cfg: &Config) -> JsonValue {
             serde_json::json!({ "var": var, "set": var.is_some() })
         }
         "config" => {
-            serde_json::json!({ "path": cfg.path().canonicalize().unwrap_or_else(|_| cfg.path()).display().to_string(), "profile": null })
+            serde_json::json!({ "path": cfg.path().canonicalize().unwrap_or_else(|_| cfg.path()).display().to_string(), "profile": cfg.profile })
+        }
+        "keyring" => {
+            serde_json::json!({ "service": crate::keychain::SERVICE, "account": crate::keychain::account(cfg.profile.as_deref()) })
         }
+        // --token / --token-file / --token-stdin: used for this run, never saved
+        "flag" => serde_json::json!({ "ephemeral": true }),
         _ => serde_json::json!({ "reason": "no token found" }),
     };
     serde_json::json!({
         "present": present,
         "source": src,
+        "profile": cfg.profile_name(),
         "details": details,
     })
 }
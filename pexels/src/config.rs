@@ -1,25 +1,110 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Config file named by --config for this process (see `config_path`).
+static PATH_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub fn set_path_override(path: Option<PathBuf>) {
+    *PATH_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = path;
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Config {
     pub token: Option<String>,
     #[serde(default)]
     pub token_source: Option<TokenSource>,
+    // Where `auth login` keeps tokens: this file or the OS keychain
+    #[serde(default, skip_serializing_if = "TokenStorage::is_default")]
+    pub token_storage: TokenStorage,
+    #[serde(default, skip_serializing_if = "AuditConfig::is_default")]
+    pub audit: AuditConfig,
+    // Default `--fields` per resource (photos, videos, collections)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projections: BTreeMap<String, Vec<String>>,
+    // Named shell pipelines run on downloaded files (`--post <name>`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub postprocess: BTreeMap<String, Vec<String>>,
+    // Human-readable sizes/durations/counts in YAML output unless --no-human
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub human: bool,
+    #[serde(default, skip_serializing_if = "WatchConfig::is_default")]
+    pub watch: WatchConfig,
+    #[serde(default, skip_serializing_if = "CacheConfig::is_default")]
+    pub cache: CacheConfig,
+    #[serde(default, skip_serializing_if = "WebConfig::is_default")]
+    pub web: WebConfig,
+    // Output format when no flag is given; unset means auto (YAML on a
+    // terminal, JSON/NDJSON when piped)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<crate::output::OutputFormat>,
+    // Defaults for --per-page, --timeout (seconds) and --max-retries; the
+    // flags win
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_per_page: Option<u32>,
+    // Accept-Language and number formatting; --locale wins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, rename = "timeout", skip_serializing_if = "Option::is_none")]
+    pub default_timeout: Option<u64>,
+    #[serde(
+        default,
+        rename = "max_retries",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_max_retries: Option<u32>,
+    // Target of download-all, collections download and queue run without --dir
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+    // Named profiles (`--profile work`, PEXELS_PROFILE), each overriding the
+    // top-level token, host, locale and defaults
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    // Active profile for this run
+    #[serde(skip)]
+    pub profile: Option<String>,
+    // Named endpoints selected with `--host-profile` (API gateways needing
+    // their own base URL, headers and CA bundle)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, HostProfile>,
+    // Credentials for `--dest` uploads, keyed by WebDAV/SFTP host name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, RemoteAuth>,
     #[serde(skip)]
     pub host: Option<String>,
+    // Keys overridden for this run and by what ("env", "cli"), for `config list`
     #[serde(skip)]
-    pub timeout_secs: u64,
+    pub overrides: BTreeMap<String, &'static str>,
+    // Profile chosen for this run; its headers and CA bundle go on the client
     #[serde(skip)]
-    pub locale: Option<String>,
+    pub host_profile: Option<HostProfile>,
+    #[serde(skip)]
+    pub host_profile_name: Option<String>,
+    #[serde(skip)]
+    pub timeout_secs: u64,
     #[serde(skip)]
     pub max_retries: u32,
     #[serde(skip)]
     pub retry_after: Option<u64>,
+    // Media transfers; None falls back to the download defaults in api.rs
+    #[serde(skip)]
+    pub download_timeout_secs: Option<u64>,
+    #[serde(skip)]
+    pub download_retries: Option<u32>,
+    // Fail instead of waiting when the rate-limit window is exhausted
+    #[serde(skip)]
+    pub no_wait: bool,
+    // Answer from the result cache only; never touch the network
+    #[serde(skip)]
+    pub offline: bool,
+    #[cfg(feature = "fault-injection")]
+    #[serde(skip)]
+    pub faults: Vec<crate::fault::Fault>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -27,35 +112,344 @@ pub struct Config {
 pub enum TokenSource {
     Env,
     Config,
+    Keyring,
+    // --token / --token-file / --token-stdin, this run only
+    Flag,
     #[default]
     None,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStorage {
+    #[default]
+    File,
+    // Tokens live in the OS keychain (see keychain.rs); the file keeps none
+    Keyring,
+}
+
+impl TokenStorage {
+    fn is_default(&self) -> bool {
+        self == &TokenStorage::File
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "file" => Ok(TokenStorage::File),
+            "keyring" => Ok(TokenStorage::Keyring),
+            _ => anyhow::bail!("expected file or keyring"),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenStorage::File => "file",
+            TokenStorage::Keyring => "keyring",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct AuditConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // Override for the audit log location (defaults to the data dir)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl AuditConfig {
+    fn is_default(&self) -> bool {
+        self == &AuditConfig::default()
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(crate::audit::default_path)
+    }
+}
+
+// Settings for `--watch` sessions; re-read while a session runs.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct WatchConfig {
+    // Seconds between polls when --interval is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+}
+
+impl WatchConfig {
+    fn is_default(&self) -> bool {
+        self == &WatchConfig::default()
+    }
+}
+
+// Result cache for list pages (see cache.rs).
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct CacheConfig {
+    // Seconds a cached page stays fresh; 0 disables the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u64>,
+}
+
+impl CacheConfig {
+    fn is_default(&self) -> bool {
+        self == &CacheConfig::default()
+    }
+
+    pub fn ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ttl.unwrap_or(crate::cache::DEFAULT_TTL_SECS))
+    }
+}
+
+// Defaults for `--web-optimize` (see optimize.rs).
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct WebConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<crate::optimize::WebFormat>,
+    // 0-100
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+}
+
+impl WebConfig {
+    fn is_default(&self) -> bool {
+        self == &WebConfig::default()
+    }
+}
+
+// `profiles.<name>` entry; unset fields fall back to the top level.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<crate::output::OutputFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub human: Option<bool>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projections: BTreeMap<String, Vec<String>>,
+}
+
+// Token kept in the keychain for `profile`. An unreachable keychain is
+// logged and treated as no token, so commands that need none still run.
+fn keychain_token(profile: Option<&str>) -> Option<String> {
+    crate::keychain::get(crate::keychain::account(profile)).unwrap_or_else(|e| {
+        tracing::warn!("keychain: {}", e);
+        None
+    })
+}
+
+// Profile named by PEXELS_PROFILE, when set and non-empty.
+pub fn env_profile() -> Option<String> {
+    std::env::var("PEXELS_PROFILE")
+        .ok()
+        .filter(|v| !v.is_empty())
+}
+
+// `hosts.<name>` entry: where and how to reach the API through a gateway.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct HostProfile {
+    // Replaces https://api.pexels.com; may carry a path prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    // Extra headers sent with every request (they win over the defaults)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    // PEM file with extra root certificates for the gateway's TLS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cacert: Option<PathBuf>,
+}
+
+// Fields of a host profile addressable as `hosts.<name>.<field>`.
+pub const HOST_PROFILE_FIELDS: &[&str] = &["base", "cacert", "headers.<name>"];
+
+impl HostProfile {
+    // Set one field from a `config set hosts.<name>.<field>` value; an
+    // empty value removes it.
+    pub fn set(&mut self, field: &str, value: &str) -> Result<()> {
+        let value = (!value.is_empty()).then(|| value.to_string());
+        match field {
+            "base" => self.base = value.map(|v| v.trim_end_matches('/').to_string()),
+            "cacert" => self.cacert = value.map(PathBuf::from),
+            f => {
+                let name = f
+                    .strip_prefix("headers.")
+                    .filter(|n| !n.is_empty())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "unknown host profile field {}; expected one of {}",
+                            f,
+                            HOST_PROFILE_FIELDS.join(", ")
+                        )
+                    })?;
+                match value {
+                    Some(v) => self.headers.insert(name.to_string(), v),
+                    None => self.headers.remove(name),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, field: &str) -> Option<String> {
+        match field {
+            "base" => self.base.clone(),
+            "cacert" => self.cacert.as_ref().map(|p| p.display().to_string()),
+            f => f
+                .strip_prefix("headers.")
+                .and_then(|n| self.headers.get(n).cloned()),
+        }
+    }
+}
+
+// `hosts.<name>.<field>` config key -> (profile name, field).
+pub fn host_profile_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("hosts.")?
+        .split_once('.')
+        .filter(|(name, field)| !name.is_empty() && !field.is_empty())
+}
+
+// `remotes.<host>` entry: how `--dest` logs in to a WebDAV or SFTP host
+// when the URL carries no user.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct RemoteAuth {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    // WebDAV basic-auth password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    // SFTP private key (else the SSH agent and ~/.ssh/config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<PathBuf>,
+}
+
+// Fields of a remote addressable as `remotes.<host>.<field>`.
+pub const REMOTE_FIELDS: &[&str] = &["user", "password", "identity"];
+
+impl RemoteAuth {
+    // Set one field from a `config set remotes.<host>.<field>` value; an
+    // empty value removes it.
+    pub fn set(&mut self, field: &str, value: &str) -> Result<()> {
+        let value = (!value.is_empty()).then(|| value.to_string());
+        match field {
+            "user" => self.user = value,
+            "password" => self.password = value,
+            "identity" => self.identity = value.map(PathBuf::from),
+            f => anyhow::bail!(
+                "unknown remote field {}; expected one of {}",
+                f,
+                REMOTE_FIELDS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, field: &str) -> Option<String> {
+        match field {
+            "user" => self.user.clone(),
+            "password" => self.password.clone(),
+            "identity" => self.identity.as_ref().map(|p| p.display().to_string()),
+            _ => None,
+        }
+    }
+}
+
+// `remotes.<host>.<field>` config key -> (host, field). Host names contain
+// dots, so the field is the last segment.
+pub fn remote_key(key: &str) -> Option<(&str, &str)> {
+    key.strip_prefix("remotes.")?
+        .rsplit_once('.')
+        .filter(|(host, field)| !host.is_empty() && !field.is_empty())
+}
+
+// Request timeout and retries when neither flags nor config set them.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 15;
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+// Resources that have a default projection.
+pub const PROJECTION_RESOURCES: &[&str] = &["photos", "videos", "collections"];
+
+// Built-in projection used when config has no `projections.<resource>` entry.
+pub fn builtin_projection(resource: &str) -> Vec<String> {
+    let fields: &[&str] = match resource {
+        "photos" => &["id", "photographer", "alt", "width", "height", "avg_color"],
+        "videos" => &["duration", "width", "height"],
+        "collections" => &["title", "description", "media_count"],
+        _ => &[],
+    };
+    fields.iter().map(|f| f.to_string()).collect()
+}
+
 impl Config {
+    // Default fields for a resource; an empty configured list disables projection.
+    pub fn projection(&self, resource: &str) -> Vec<String> {
+        self.projections
+            .get(resource)
+            .cloned()
+            .unwrap_or_else(|| builtin_projection(resource))
+    }
+
     pub fn load() -> Result<Self> {
         let path = Self::config_path();
         if path.exists() {
             let data = fs::read_to_string(&path).context("read config file")?;
             let mut cfg: Config = serde_yaml::from_str(&data).context("parse config yaml")?;
-            cfg.timeout_secs = 15;
-            cfg.max_retries = 3;
+            cfg.timeout_secs = cfg.default_timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+            cfg.max_retries = cfg.default_max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+            if cfg.token_storage == TokenStorage::Keyring && cfg.token.is_none() {
+                cfg.token = keychain_token(None);
+                if cfg.token.is_some() {
+                    cfg.token_source = Some(TokenSource::Keyring);
+                }
+            }
             Ok(cfg)
         } else {
             Ok(Config {
-                timeout_secs: 15,
-                max_retries: 3,
+                timeout_secs: DEFAULT_TIMEOUT_SECS,
+                max_retries: DEFAULT_MAX_RETRIES,
                 ..Default::default()
             })
         }
     }
 
+    // Write under the config lock so concurrent invocations never interleave.
     pub fn save(&self) -> Result<()> {
+        let _lock = crate::lock::FileLock::acquire_blocking(
+            &crate::lock::lock_path_for(&self.path()),
+            crate::lock::default_wait(),
+        )?;
+        self.write()
+    }
+
+    // Read-modify-write under the lock: reload the file, apply `f`, save.
+    // Changes made by other processes since this one started are kept.
+    pub fn update<F>(f: F) -> Result<Config>
+    where
+        F: FnOnce(&mut Config) -> Result<()>,
+    {
+        let path = Self::config_path();
+        let _lock = crate::lock::FileLock::acquire_blocking(
+            &crate::lock::lock_path_for(&path),
+            crate::lock::default_wait(),
+        )?;
+        let mut cfg = Self::load()?;
+        f(&mut cfg)?;
+        cfg.write()?;
+        Ok(cfg)
+    }
+
+    // Temp file + rename so readers never see a half-written file.
+    fn write(&self) -> Result<()> {
         let path = self.path();
         if let Some(dir) = path.parent() {
             fs::create_dir_all(dir).context("create config dir")?;
         }
-        let data = serde_yaml::to_string(&self).context("serialize config")?;
-        let mut f = fs::File::create(&path).context("create config file")?;
+        let data = serde_yaml::to_string(&self.stored()?).context("serialize config")?;
+        let tmp = path.with_extension("yaml.tmp");
+        let mut f = fs::File::create(&tmp).context("create config file")?;
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
@@ -64,6 +458,9 @@ impl Config {
             f.set_permissions(perms)?;
         }
         f.write_all(data.as_bytes()).context("write config file")?;
+        f.sync_all().context("write config file")?;
+        drop(f);
+        fs::rename(&tmp, &path).context("replace config file")?;
         Ok(())
     }
 
@@ -71,7 +468,120 @@ impl Config {
         Self::config_path()
     }
 
+    // What goes into the file. With `token_storage: keyring`, tokens set
+    // since loading (login, `config set token`, a switch from file storage)
+    // move into the keychain and none is written out.
+    fn stored(&self) -> Result<Config> {
+        let mut out = self.clone();
+        if self.token_storage == TokenStorage::File {
+            if matches!(out.token_source, Some(TokenSource::Keyring)) {
+                out.token_source = Some(TokenSource::Config);
+            }
+            return Ok(out);
+        }
+        if let Some(t) = &self.token {
+            if !matches!(
+                self.token_source,
+                Some(TokenSource::Keyring | TokenSource::Env | TokenSource::Flag)
+            ) {
+                crate::keychain::set(crate::keychain::account(None), t)?;
+            }
+        }
+        for (name, p) in &self.profiles {
+            if let Some(t) = &p.token {
+                crate::keychain::set(crate::keychain::account(Some(name)), t)?;
+            }
+        }
+        out.token = None;
+        out.token_source = None;
+        for p in out.profiles.values_mut() {
+            p.token = None;
+        }
+        Ok(out)
+    }
+
+    // Switch `token_storage`. Going back to the file pulls every profile's
+    // token out of the keychain (the top-level one was read by `load`); the
+    // caller clears the keychain once the file is written.
+    pub fn set_token_storage(&mut self, storage: TokenStorage) -> Result<()> {
+        if storage == self.token_storage {
+            return Ok(());
+        }
+        if storage == TokenStorage::File {
+            for (name, p) in self.profiles.iter_mut() {
+                if p.token.is_none() {
+                    p.token = crate::keychain::get(crate::keychain::account(Some(name)))?;
+                }
+            }
+        }
+        self.token_storage = storage;
+        Ok(())
+    }
+
+    // Delete the keychain entries of the top-level token and every profile.
+    pub fn forget_keychain_tokens(&self) -> Result<()> {
+        crate::keychain::delete(crate::keychain::account(None))?;
+        for name in self.profiles.keys() {
+            crate::keychain::delete(crate::keychain::account(Some(name)))?;
+        }
+        Ok(())
+    }
+
+    // Modification time of the config file, used to notice edits.
+    pub fn modified() -> Option<std::time::SystemTime> {
+        fs::metadata(Self::config_path()).ok()?.modified().ok()
+    }
+
+    // Dotted keys whose configured value differs between `self` and `other`
+    // (`token`, `audit.enabled`, `projections.photos`, ...). Values are not
+    // returned so secrets never reach logs.
+    pub fn changed_keys(&self, other: &Config) -> Vec<String> {
+        let a = serde_json::to_value(self).unwrap_or_default();
+        let b = serde_json::to_value(other).unwrap_or_default();
+        let empty = serde_json::Map::new();
+        let (ma, mb) = (
+            a.as_object().unwrap_or(&empty),
+            b.as_object().unwrap_or(&empty),
+        );
+        let keys: std::collections::BTreeSet<&String> = ma.keys().chain(mb.keys()).collect();
+        let mut out = vec![];
+        for k in keys {
+            let (va, vb) = (ma.get(k), mb.get(k));
+            if va == vb {
+                continue;
+            }
+            // A section missing on one side compares as empty
+            let sa = va.map_or(Some(&empty), |v| v.as_object());
+            let sb = vb.map_or(Some(&empty), |v| v.as_object());
+            match (sa, sb) {
+                (Some(ia), Some(ib)) => {
+                    let inner: std::collections::BTreeSet<&String> =
+                        ia.keys().chain(ib.keys()).collect();
+                    for ik in inner {
+                        if ia.get(ik) != ib.get(ik) {
+                            out.push(format!("{}.{}", k, ik));
+                        }
+                    }
+                }
+                _ => out.push(k.clone()),
+            }
+        }
+        out
+    }
+
+    // --config, else PEXELS_CONFIG, else config.yaml in the OS config dir.
+    // Every load, save and lock goes through here.
     pub fn config_path() -> PathBuf {
+        if let Some(path) = PATH_OVERRIDE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        {
+            return path;
+        }
+        if let Some(path) = std::env::var_os("PEXELS_CONFIG").filter(|v| !v.is_empty()) {
+            return PathBuf::from(path);
+        }
         // Vendorless per spec
         let proj = ProjectDirs::from("", "", "pexels").expect("config dirs");
         let path = proj.config_dir().join("config.yaml");
@@ -79,36 +589,127 @@ impl Config {
     }
 
     pub fn apply_env(&mut self) {
-        if let Ok(v) = std::env::var("PEXELS_TOKEN") {
-            if !v.is_empty() {
-                self.token = Some(v);
-                self.token_source = Some(TokenSource::Env);
-            }
-        } else if let Ok(v) = std::env::var("PEXELS_API_KEY") {
-            if !v.is_empty() {
-                self.token = Some(v);
-                self.token_source = Some(TokenSource::Env);
-            }
+        let token = std::env::var("PEXELS_TOKEN")
+            .or_else(|_| std::env::var("PEXELS_API_KEY"))
+            .ok()
+            .filter(|v| !v.is_empty());
+        if let Some(v) = token {
+            self.token = Some(v);
+            self.token_source = Some(TokenSource::Env);
+            self.overrides.insert("token".into(), "env");
         }
     }
 
     pub fn apply_cli(&mut self, cli: &crate::cli::Cli) {
-        self.timeout_secs = cli.timeout;
-        self.max_retries = cli.max_retries;
+        if let Some(secs) = cli.timeout {
+            self.timeout_secs = secs;
+            self.overrides.insert("timeout".into(), "cli");
+        }
+        if let Some(n) = cli.max_retries {
+            self.max_retries = n;
+            self.overrides.insert("max_retries".into(), "cli");
+        }
+        if let Some(n) = cli.per_page {
+            self.default_per_page = Some(n);
+            self.overrides.insert("default_per_page".into(), "cli");
+        }
+        if let Some(fmt) = cli.explicit_format() {
+            self.output = Some(fmt);
+            self.overrides.insert("default_format".into(), "cli");
+        }
         self.retry_after = cli.retry_after;
+        self.download_timeout_secs = cli.download_timeout;
+        self.download_retries = cli.download_retries;
+        self.no_wait = cli.no_wait;
+        self.offline = cli.offline;
+        #[cfg(feature = "fault-injection")]
+        {
+            self.faults = cli.inject_fault.clone();
+        }
         if let Some(host) = cli.host.clone() {
             self.host = Some(host);
         }
         if let Some(locale) = cli.locale.clone() {
             self.locale = Some(locale);
+            self.overrides.insert("locale".into(), "cli");
         }
     }
 
+    // `--token`/`--token-file`/`--token-stdin`: an ephemeral token over profiles and the
+    // environment. Saves reload the file (`Config::update`), so it is never
+    // written back.
+    pub fn use_flag_token(&mut self, token: String) {
+        self.token = Some(token);
+        self.token_source = Some(TokenSource::Flag);
+        self.overrides.insert("token".into(), "cli");
+    }
+
+    // Apply `--profile <name>` over the top-level settings. Runs first, so
+    // PEXELS_TOKEN and explicit flags still win.
+    pub fn select_profile(&mut self, name: &str) -> Result<()> {
+        let Some(p) = self.profiles.get(name).cloned() else {
+            let err = serde_json::json!({
+                "error": "unknown profile",
+                "profile": name,
+                "did_you_mean": crate::suggest::nearest(name, self.profiles.keys().map(|k| k.as_str())),
+                "profiles": self.profiles.keys().collect::<Vec<_>>(),
+                "hint": "create it with: pexels auth login --profile <name>",
+            });
+            return Err(anyhow::anyhow!(
+                serde_yaml::to_string(&err).unwrap_or_else(|_| "unknown profile".into())
+            ));
+        };
+        if let Some(token) = p.token {
+            self.token = Some(token);
+            self.token_source = Some(TokenSource::Config);
+        } else if self.token_storage == TokenStorage::Keyring {
+            if let Some(token) = keychain_token(Some(name)) {
+                self.token = Some(token);
+                self.token_source = Some(TokenSource::Keyring);
+            }
+        }
+        self.host = p.host.or(self.host.take());
+        self.locale = p.locale.or(self.locale.take());
+        self.output = p.output.or(self.output.take());
+        self.human = p.human.unwrap_or(self.human);
+        self.projections.extend(p.projections);
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    // Apply `--host-profile <name>`: the profile's base URL unless --host
+    // was given, plus its headers and CA bundle.
+    pub fn select_host_profile(&mut self, name: &str) -> Result<()> {
+        let Some(profile) = self.hosts.get(name).cloned() else {
+            let err = serde_json::json!({
+                "error": "unknown host profile",
+                "profile": name,
+                "did_you_mean": crate::suggest::nearest(name, self.hosts.keys().map(|k| k.as_str())),
+                "profiles": self.hosts.keys().collect::<Vec<_>>(),
+                "hint": "define it with: pexels config set hosts.<name>.base <url>",
+            });
+            return Err(anyhow::anyhow!(
+                serde_yaml::to_string(&err).unwrap_or_else(|_| "unknown host profile".into())
+            ));
+        };
+        self.host = profile.base.clone().or(self.host.take());
+        self.host_profile = Some(profile);
+        self.host_profile_name = Some(name.to_string());
+        Ok(())
+    }
+
+    // Name reported for this run's profile (audit log, `auth status`).
+    pub fn profile_name(&self) -> &str {
+        self.profile.as_deref().unwrap_or("default")
+    }
+
     pub fn token_source_with_presence(&self) -> (String, bool) {
         let present = self.token.as_ref().map(|s| !s.is_empty()).unwrap_or(false);
         let src = match self.token_source.clone().unwrap_or(TokenSource::None) {
             TokenSource::Env => "env",
             TokenSource::Config => "config",
+            TokenSource::Keyring => "keyring",
+            TokenSource::Flag => "flag",
             TokenSource::None => "none",
         };
         (src.to_string(), present)
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use image::DynamicImage;
+use serde_json::Value as JsonValue;
+
+// WCAG 2.x contrast of overlay text against a photo (`photos contrast`).
+// Every pixel of the region is compared with the text color; because a photo
+// is not a flat background the report gives the ratio against the average
+// color and against the darkest/lightest 10% of pixels, and judges the
+// levels on that 10th percentile so a few stray pixels don't flip a verdict.
+
+// WCAG thresholds: AA body text, AA large text (18pt+ / 14pt bold), AAA
+pub const AA: f64 = 4.5;
+pub const AA_LARGE: f64 = 3.0;
+pub const AAA: f64 = 7.0;
+// Longest side the image is reduced to before sampling.
+const SAMPLE_SIDE: u32 = 256;
+
+// Part of the image the text sits on: a third for the edges, the middle
+// third band for `middle`, the central ninth for `center`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Region {
+    #[default]
+    Full,
+    Top,
+    Middle,
+    Bottom,
+    Left,
+    Right,
+    Center,
+}
+
+impl Region {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Region::Full => "full",
+            Region::Top => "top",
+            Region::Middle => "middle",
+            Region::Bottom => "bottom",
+            Region::Left => "left",
+            Region::Right => "right",
+            Region::Center => "center",
+        }
+    }
+
+    // (x, y, width, height) of the region in a `w` x `h` image.
+    pub fn rect(&self, w: u32, h: u32) -> (u32, u32, u32, u32) {
+        let (tw, th) = ((w / 3).max(1), (h / 3).max(1));
+        match self {
+            Region::Full => (0, 0, w, h),
+            Region::Top => (0, 0, w, th),
+            Region::Middle => (0, th, w, th),
+            Region::Bottom => (0, h - th, w, th),
+            Region::Left => (0, 0, tw, h),
+            Region::Right => (w - tw, 0, tw, h),
+            Region::Center => (tw, th, tw, th),
+        }
+    }
+}
+
+// `#fff`, `#ffffff` or the same without `#`.
+pub fn parse_hex(s: &str) -> Result<[u8; 3], String> {
+    let hex = s.trim().trim_start_matches('#');
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return Err(format!("expected #rgb or #rrggbb, got {:?}", s)),
+    };
+    let byte = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16);
+    match (byte(0), byte(2), byte(4)) {
+        (Ok(r), Ok(g), Ok(b)) => Ok([r, g, b]),
+        _ => Err(format!("expected #rgb or #rrggbb, got {:?}", s)),
+    }
+}
+
+pub fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// WCAG relative luminance of an sRGB color.
+pub fn luminance(rgb: [u8; 3]) -> f64 {
+    let lin = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * lin(rgb[0]) + 0.7152 * lin(rgb[1]) + 0.0722 * lin(rgb[2])
+}
+
+// Contrast ratio between two luminances, 1 to 21.
+pub fn ratio(a: f64, b: f64) -> f64 {
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    (hi + 0.05) / (lo + 0.05)
+}
+
+// Report for `text` over `region` of `img`: `{region, text_color,
+// background, ratio: {average, p10, min}, share: {aa, aa_large, aaa},
+// passes: {aa, aa_large, aaa}}`.
+pub fn report(img: &DynamicImage, text: [u8; 3], region: Region) -> JsonValue {
+    let small = if img.width().max(img.height()) > SAMPLE_SIDE {
+        img.thumbnail(SAMPLE_SIDE, SAMPLE_SIDE).to_rgb8()
+    } else {
+        img.to_rgb8()
+    };
+    let (x, y, w, h) = region.rect(small.width(), small.height());
+    let text_lum = luminance(text);
+    let mut sum = [0u64; 3];
+    let mut ratios = vec![];
+    for py in y..y + h {
+        for px in x..x + w {
+            let p = small.get_pixel(px, py).0;
+            for (s, c) in sum.iter_mut().zip(p) {
+                *s += c as u64;
+            }
+            ratios.push(ratio(text_lum, luminance(p)));
+        }
+    }
+    let n = ratios.len().max(1);
+    let avg = sum.map(|s| (s as f64 / n as f64).round() as u8);
+    ratios.sort_by(|a, b| a.total_cmp(b));
+    let min = ratios.first().copied().unwrap_or(1.0);
+    let p10 = ratios.get(ratios.len() / 10).copied().unwrap_or(1.0);
+    let share = |t: f64| {
+        round(
+            ratios.iter().filter(|r| **r >= t).count() as f64 / n as f64,
+            3,
+        )
+    };
+    serde_json::json!({
+        "region": region.as_str(),
+        "text_color": hex(text),
+        "background": hex(avg),
+        "ratio": {
+            "average": round(ratio(text_lum, luminance(avg)), 2),
+            "p10": round(p10, 2),
+            "min": round(min, 2),
+        },
+        "share": {
+            "aa": share(AA),
+            "aa_large": share(AA_LARGE),
+            "aaa": share(AAA),
+        },
+        "passes": {
+            "aa": p10 >= AA,
+            "aa_large": p10 >= AA_LARGE,
+            "aaa": p10 >= AAA,
+        },
+    })
+}
+
+fn round(v: f64, places: i32) -> f64 {
+    let f = 10f64.powi(places);
+    (v * f).round() / f
+}
@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+// `--cursor-file` for raw list output: the `next_page` URL of the last
+// response, so a shell loop can walk the pages without parsing JSON. A
+// missing file starts at the first page; an empty file means the last page
+// was already fetched.
+
+#[derive(Debug, PartialEq)]
+pub enum Cursor {
+    Start,
+    Next(String),
+    Done,
+}
+
+pub fn read(path: &Path) -> Result<Cursor> {
+    match std::fs::read_to_string(path) {
+        Ok(s) if s.trim().is_empty() => Ok(Cursor::Done),
+        Ok(s) => Ok(Cursor::Next(s.trim().to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Cursor::Start),
+        Err(e) => Err(e).context("read cursor file"),
+    }
+}
+
+// Store `next` (nothing after the last page), replacing the file atomically.
+pub fn write(path: &Path, next: Option<&str>) -> Result<()> {
+    let tmp = crate::download::part_path(path);
+    let body = next.map(|u| format!("{}\n", u)).unwrap_or_default();
+    std::fs::write(&tmp, body).context("write cursor file")?;
+    std::fs::rename(&tmp, path).context("write cursor file")
+}
+
+pub fn done_error(path: &Path) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "no more pages",
+        "cursor_file": path.display().to_string(),
+        "hint": "delete the cursor file to start over from the first page",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "no more pages".into()))
+}
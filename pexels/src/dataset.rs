@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+// `pexels export dataset`: search results downloaded into train/val/test
+// directories with an `index.csv` describing every file. A photo's split is
+// derived from a hash of its id, so it lands in the same split on every run
+// and a dataset grown later never leaks samples across splits.
+
+pub const SPLITS: [&str; 3] = ["train", "val", "test"];
+
+// Terms every Pexels photo is distributed under.
+pub const LICENSE: &str = "Pexels License (https://www.pexels.com/license/)";
+
+// Columns of `index.csv`, one row per downloaded file.
+pub const COLUMNS: [&str; 13] = [
+    "id",
+    "split",
+    "file",
+    "size",
+    "width",
+    "height",
+    "alt",
+    "photographer",
+    "photographer_url",
+    "url",
+    "avg_color",
+    "license",
+    "sha256",
+];
+
+// Percentages for train, val and test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Split([u32; 3]);
+
+// `--split 80/10/10`; a missing test share is 0 (`90/10`).
+pub fn parse_split(s: &str) -> Result<Split, String> {
+    let parts: Vec<&str> = s.split('/').map(str::trim).collect();
+    if !(2..=3).contains(&parts.len()) {
+        return Err(format!(
+            "invalid split '{}' (use TRAIN/VAL/TEST, e.g. 80/10/10)",
+            s
+        ));
+    }
+    let mut shares = [0u32; 3];
+    for (share, part) in shares.iter_mut().zip(&parts) {
+        *share = part
+            .parse()
+            .map_err(|_| format!("invalid split share '{}' in '{}'", part, s))?;
+    }
+    if shares.iter().sum::<u32>() != 100 {
+        return Err(format!("split '{}' must add up to 100", s));
+    }
+    Ok(Split(shares))
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split([80, 10, 10])
+    }
+}
+
+impl Split {
+    // The split of the photo with `id`.
+    pub fn assign(&self, id: &str) -> &'static str {
+        let digest = Sha256::digest(id.as_bytes());
+        let n = u64::from_be_bytes(digest[..8].try_into().expect("8 bytes")) % 100;
+        let mut bound = 0;
+        for (name, share) in SPLITS.iter().zip(self.0) {
+            bound += share as u64;
+            if n < bound {
+                return name;
+            }
+        }
+        // Only reached when every share is 0, which parse_split refuses
+        SPLITS[0]
+    }
+}
+
+// The index row of `file` (relative to the dataset root) holding `item` at
+// `size`, with the file's content hash.
+pub fn row(item: &JsonValue, split: &str, file: &str, size: &str, sha256: &str) -> Vec<String> {
+    let s = |k: &str| match item.get(k) {
+        Some(JsonValue::String(v)) => v.clone(),
+        Some(JsonValue::Null) | None => String::new(),
+        Some(v) => v.to_string(),
+    };
+    vec![
+        s("id"),
+        split.to_string(),
+        file.to_string(),
+        size.to_string(),
+        s("width"),
+        s("height"),
+        s("alt"),
+        s("photographer"),
+        s("photographer_url"),
+        s("url"),
+        s("avg_color"),
+        LICENSE.to_string(),
+        sha256.to_string(),
+    ]
+}
+
+// Hex SHA-256 of a downloaded file.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path).context("hash dataset file")?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).context("hash dataset file")?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// Write `index.csv` atomically.
+pub fn write_index(path: &Path, rows: &[Vec<String>]) -> Result<()> {
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .map(|c| crate::output::quote_field(c, ','))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut out = COLUMNS.join(",") + "\n";
+    for r in rows {
+        out.push_str(&line(r));
+        out.push('\n');
+    }
+    let tmp = crate::download::part_path(path);
+    std::fs::write(&tmp, out).context("write dataset index")?;
+    std::fs::rename(&tmp, path).context("write dataset index")
+}
+
+// Photo ids and content hashes of earlier corpora (`--exclude-manifest`),
+// so an incremental build never repeats a sample. Accepted manifests:
+//
+// - `.csv`: a previous `index.csv` (any CSV with an `id` and/or `sha256`
+//   column)
+// - `.json`: an array of ids, hashes or objects with `id`/`sha256`, or an
+//   object holding such an array under `items`, `photos`, `data`, `rows` or
+//   `files` (`collections download` manifests, `--json` output)
+// - `.ndjson`/`.jsonl`: one id, hash or object per line
+// - `.parquet`: `id` and `sha256` columns (cargo feature `parquet`)
+#[derive(Debug, Default)]
+pub struct Exclusions {
+    pub ids: HashSet<String>,
+    pub hashes: HashSet<String>,
+}
+
+// Object keys holding a content hash.
+const HASH_KEYS: [&str; 4] = ["sha256", "hash", "content_hash", "checksum"];
+
+// Object keys holding the entries of a JSON manifest.
+const ENTRY_KEYS: [&str; 5] = ["items", "photos", "data", "rows", "files"];
+
+impl Exclusions {
+    pub fn load(paths: &[PathBuf]) -> Result<Exclusions> {
+        let mut ex = Exclusions::default();
+        for path in paths {
+            ex.add_file(path).map_err(|e| {
+                let err = serde_json::json!({
+                    "error": "cannot read exclude manifest",
+                    "path": path.display().to_string(),
+                    "reason": e.to_string(),
+                });
+                anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| e.to_string()))
+            })?;
+        }
+        Ok(ex)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.hashes.is_empty()
+    }
+
+    fn add_file(&mut self, path: &Path) -> Result<()> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let bytes = std::fs::read(path)?;
+        match ext.as_str() {
+            "csv" => self.add_csv(&String::from_utf8_lossy(&bytes)),
+            "json" => self.add_json(&serde_json::from_slice(&bytes)?),
+            "ndjson" | "jsonl" => {
+                for line in String::from_utf8_lossy(&bytes).lines() {
+                    if !line.trim().is_empty() {
+                        self.add_entry(&serde_json::from_str(line)?);
+                    }
+                }
+            }
+            "parquet" => self.add_parquet(&bytes)?,
+            other => anyhow::bail!(
+                "unknown manifest type '{}' (csv, json, ndjson, parquet)",
+                other
+            ),
+        }
+        Ok(())
+    }
+
+    fn add_csv(&mut self, text: &str) {
+        let mut records = parse_csv(text).into_iter();
+        let Some(header) = records.next() else {
+            return;
+        };
+        let col = |names: &[&str]| header.iter().position(|h| names.contains(&h.trim()));
+        let (id, hash) = (col(&["id"]), col(&HASH_KEYS));
+        for rec in records {
+            let cell = |i: Option<usize>| i.and_then(|i| rec.get(i)).map(|c| c.trim());
+            if let Some(v) = cell(id).filter(|v| !v.is_empty()) {
+                self.ids.insert(v.to_string());
+            }
+            if let Some(v) = cell(hash).filter(|v| !v.is_empty()) {
+                self.hashes.insert(normalize_hash(v));
+            }
+        }
+    }
+
+    fn add_json(&mut self, v: &JsonValue) {
+        if let Some(entries) = ENTRY_KEYS.iter().find_map(|k| v.get(*k)) {
+            return self.add_json(entries);
+        }
+        match v {
+            JsonValue::Array(arr) => arr.iter().for_each(|e| self.add_entry(e)),
+            other => self.add_entry(other),
+        }
+    }
+
+    fn add_entry(&mut self, v: &JsonValue) {
+        match v {
+            JsonValue::String(s) if is_sha256(s) => {
+                self.hashes.insert(normalize_hash(s));
+            }
+            JsonValue::String(s) => {
+                self.ids.insert(s.clone());
+            }
+            JsonValue::Number(n) => {
+                self.ids.insert(n.to_string());
+            }
+            JsonValue::Object(o) => {
+                match o.get("id") {
+                    Some(JsonValue::String(s)) => {
+                        self.ids.insert(s.clone());
+                    }
+                    Some(JsonValue::Number(n)) => {
+                        self.ids.insert(n.to_string());
+                    }
+                    _ => {}
+                }
+                if let Some(h) = HASH_KEYS
+                    .iter()
+                    .find_map(|k| o.get(*k).and_then(|h| h.as_str()))
+                {
+                    self.hashes.insert(normalize_hash(h));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    fn add_parquet(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(ids) = crate::parquet::read_strings(bytes, "id")? {
+            self.ids.extend(ids);
+        }
+        for key in HASH_KEYS {
+            if let Some(hashes) = crate::parquet::read_strings(bytes, key)? {
+                self.hashes.extend(hashes.iter().map(|h| normalize_hash(h)));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn add_parquet(&mut self, _bytes: &[u8]) -> Result<()> {
+        anyhow::bail!(
+            "Parquet manifests are not available in this build; rebuild with `cargo install --path pexels --features parquet`"
+        )
+    }
+}
+
+fn is_sha256(s: &str) -> bool {
+    let s = s.strip_prefix("sha256:").unwrap_or(s);
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// `sha256:ABC...` and `abc...` name the same content.
+fn normalize_hash(s: &str) -> String {
+    s.trim()
+        .strip_prefix("sha256:")
+        .unwrap_or(s.trim())
+        .to_ascii_lowercase()
+}
+
+// Records of an RFC 4180 CSV document: quoted fields may hold commas, quotes
+// (doubled) and line breaks.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let (mut records, mut record, mut field) = (vec![], vec![], String::new());
+    let (mut quoted, mut chars) = (false, text.chars().peekable());
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
@@ -0,0 +1,412 @@
+use crate::api::PexelsClient;
+use crate::lock::{lock_path_for, FileLock};
+use crate::progress::Progress;
+use crate::state::State;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// What to do when another process is already writing the same destination.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum IfBusy {
+    Wait,
+    Skip,
+    Error,
+}
+
+// Result of a fenced write. `unchanged` marks a skip because the server
+// answered 304 for the file already on disk; `resumed_from` is the length of
+// the partial file a Range request continued from.
+#[derive(Clone, Debug)]
+pub struct Written {
+    pub path: PathBuf,
+    pub bytes: usize,
+    pub skipped: bool,
+    pub unchanged: bool,
+    pub resumed_from: u64,
+}
+
+// HTTP cache validators remembered for a downloaded file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    // Value for If-Range: the strong ETag when known, else Last-Modified.
+    pub fn if_range(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|e| !e.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+// Body of a (possibly conditional) fetch; None means 304 Not Modified.
+#[derive(Clone, Debug, Default)]
+pub struct Fetched {
+    pub body: Option<Vec<u8>>,
+    pub validators: Validators,
+}
+
+// Body streamed into a `.part` file: its final length (None on 304) and the
+// offset the transfer resumed from.
+#[derive(Clone, Debug, Default)]
+pub struct Transfer {
+    pub bytes: Option<u64>,
+    pub resumed_from: u64,
+    pub validators: Validators,
+}
+
+impl Written {
+    // Output payload shared by download commands.
+    pub fn to_json(&self) -> JsonValue {
+        let mut v = serde_json::json!({
+            "path": self.path.display().to_string(),
+            "bytes": self.bytes,
+        });
+        if self.skipped {
+            v["skipped"] = JsonValue::Bool(true);
+            let reason = if self.unchanged { "unchanged" } else { "busy" };
+            v["reason"] = JsonValue::String(reason.into());
+        }
+        if self.resumed_from > 0 {
+            v["resumed_from"] = self.resumed_from.into();
+        }
+        v
+    }
+}
+
+// Write the bytes produced by `fetch` to `dest` so concurrent invocations never
+// interleave: an exclusive lock fences the destination, data goes to a
+// `.part` sibling and is renamed into place only once complete. `fetch` runs
+// after the lock is held so a skipped download costs no request.
+pub async fn write_fenced<F, Fut>(dest: &Path, if_busy: IfBusy, fetch: F) -> Result<Written>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    write_fenced_if(dest, if_busy, || async move { fetch().await.map(Some) }).await
+}
+
+// Like `write_fenced`, but `fetch` may return None (e.g. on 304) to keep the
+// existing file untouched.
+pub async fn write_fenced_if<F, Fut>(dest: &Path, if_busy: IfBusy, fetch: F) -> Result<Written>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Option<Vec<u8>>>>,
+{
+    write_fenced_stream(dest, if_busy, |part| async move {
+        let Some(bytes) = fetch().await? else {
+            return Ok(Transfer::default());
+        };
+        let mut f = create_part(&part)?;
+        f.write_all(&bytes)?;
+        f.sync_all()?;
+        Ok(Transfer {
+            bytes: Some(bytes.len() as u64),
+            ..Default::default()
+        })
+    })
+    .await
+}
+
+// Streaming form: `fetch` gets the `.part` path and writes the body into it
+// itself (appending when it resumes a partial file left by an interrupted
+// run). The partial file survives a failed `fetch` so the next run can
+// resume it.
+pub async fn write_fenced_stream<F, Fut>(dest: &Path, if_busy: IfBusy, fetch: F) -> Result<Written>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: Future<Output = Result<Transfer>>,
+{
+    let lock_path = lock_path_for(dest);
+    let lock = match if_busy {
+        IfBusy::Wait => FileLock::acquire(&lock_path, None).await?,
+        IfBusy::Skip | IfBusy::Error => FileLock::try_acquire(&lock_path)?,
+    };
+    if lock.is_none() {
+        if if_busy == IfBusy::Skip {
+            return Ok(Written {
+                path: dest.to_path_buf(),
+                bytes: 0,
+                skipped: true,
+                unchanged: false,
+                resumed_from: 0,
+            });
+        }
+        return Err(busy_error(dest));
+    }
+
+    if let Some(dir) = dest.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let part = part_path(dest);
+    let transfer = fetch(part.clone()).await?;
+    let Some(bytes) = transfer.bytes else {
+        let bytes = fs::metadata(dest).map(|m| m.len() as usize).unwrap_or(0);
+        return Ok(Written {
+            path: fs::canonicalize(dest).unwrap_or_else(|_| dest.to_path_buf()),
+            bytes,
+            skipped: true,
+            unchanged: true,
+            resumed_from: 0,
+        });
+    };
+    fs::rename(&part, dest).context("move download into place")?;
+    let abs = fs::canonicalize(dest).unwrap_or_else(|_| dest.to_path_buf());
+    Ok(Written {
+        path: abs,
+        bytes: bytes as usize,
+        skipped: false,
+        unchanged: false,
+        resumed_from: transfer.resumed_from,
+    })
+}
+
+// Per-run settings shared by single and bulk downloads.
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    pub if_busy: IfBusy,
+    // Revalidate files already on disk with their recorded validators
+    pub conditional: bool,
+    // Draw a progress bar on stderr
+    pub progress: bool,
+}
+
+// One file of a bulk download.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub id: JsonValue,
+    pub url: String,
+    pub dest: PathBuf,
+}
+
+// State keys for downloads: `validators:<path>` holds the ETag /
+// Last-Modified of a finished file, `partial:<path>` those of an
+// interrupted transfer still sitting in `<path>.part`.
+fn download_key(ns: &str, path: &Path) -> String {
+    let abs = match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            fs::canonicalize(dir)
+                .map(|d| d.join(name))
+                .unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    };
+    format!("{}:{}", ns, abs.display())
+}
+
+// Recorded validators for `key`, if they were taken for the same URL.
+fn recorded_validators(st: &State, key: &str, url: &str) -> Validators {
+    st.get(key)
+        .filter(|rec| rec.get("url").and_then(|u| u.as_str()) == Some(url))
+        .and_then(|rec| serde_json::from_value(rec.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn set_validators(st: &mut State, key: &str, url: &str, v: &Validators) -> Result<()> {
+    if v.is_empty() {
+        st.remove(key);
+    } else {
+        let mut rec = serde_json::to_value(v)?;
+        rec["url"] = JsonValue::String(url.to_string());
+        st.set(key, rec);
+    }
+    Ok(())
+}
+
+// Fenced, streamed media download. Files already on disk are revalidated
+// with the ETag / Last-Modified recorded after their last transfer (when the
+// URL is the same); a 304 leaves them untouched and reports
+// `reason: unchanged`. A `.part` file left by an interrupted run is resumed
+// with an HTTP Range request.
+pub async fn fetch(
+    client: &PexelsClient,
+    url: &str,
+    dest: &Path,
+    opts: Options,
+) -> Result<Written> {
+    let done_key = download_key("validators", dest);
+    let part_key = download_key("partial", dest);
+    let label = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut progress = Progress::new(&label, opts.progress);
+    let mut fresh = Validators::default();
+    let (fresh_out, bar) = (&mut fresh, &mut progress);
+    let (done, pending) = (&done_key, &part_key);
+    let written = write_fenced_stream(dest, opts.if_busy, |part| async move {
+        let st = State::load()?;
+        let prev = match (opts.conditional, dest.exists()) {
+            (true, true) => recorded_validators(&st, done, url),
+            _ => Validators::default(),
+        };
+        let partial = recorded_validators(&st, pending, url);
+        drop(st);
+        let transfer = client
+            .download_to_file(url, &prev, &partial, &part, bar, |v| {
+                let mut st = State::load()?;
+                set_validators(&mut st, pending, url, v)?;
+                st.save()
+            })
+            .await?;
+        *fresh_out = transfer.validators.clone();
+        Ok(transfer)
+    })
+    .await?;
+    if !written.skipped {
+        let mut st = State::load()?;
+        st.remove(&part_key);
+        set_validators(&mut st, &done_key, url, &fresh)?;
+        st.save()?;
+    }
+    Ok(written)
+}
+
+// Run `jobs` with up to `concurrency` transfers in flight; results keep the
+// job order. Progress bars are only drawn for sequential runs.
+pub async fn fetch_all(
+    client: &PexelsClient,
+    jobs: Vec<Job>,
+    opts: Options,
+    concurrency: usize,
+) -> Vec<(Job, Result<Written>)> {
+    fetch_all_then(client, jobs, opts, concurrency, |_| async {})
+        .await
+        .into_iter()
+        .map(|(job, res, _)| (job, res))
+        .collect()
+}
+
+// `fetch_all`, running `then` on each written file while the other
+// transfers are still in flight (post-processing, mirror uploads).
+pub async fn fetch_all_then<T, F, Fut>(
+    client: &PexelsClient,
+    jobs: Vec<Job>,
+    opts: Options,
+    concurrency: usize,
+    then: F,
+) -> Vec<(Job, Result<Written>, Option<T>)>
+where
+    F: Fn(&Written) -> Fut,
+    Fut: std::future::Future<Output = T>,
+{
+    use futures::stream::{self, StreamExt};
+    let concurrency = concurrency.max(1);
+    let opts = Options {
+        progress: opts.progress && concurrency == 1,
+        ..opts
+    };
+    let then = &then;
+    stream::iter(jobs)
+        .map(|job| async move {
+            let res = fetch(client, &job.url, &job.dest, opts).await;
+            let after = match &res {
+                Ok(w) => Some(then(w).await),
+                Err(_) => None,
+            };
+            (job, res, after)
+        })
+        .buffered(concurrency)
+        .collect()
+        .await
+}
+
+// `{ succeeded, failed, skipped, items[] }` for a bulk run. Items carry the
+// job id plus the write result, or the error fields for failures.
+pub fn summarize(results: &[(Job, Result<Written>)]) -> JsonValue {
+    let (mut ok, mut failed, mut skipped) = (0, 0, 0);
+    let items: Vec<JsonValue> = results
+        .iter()
+        .map(|(job, res)| {
+            let mut v = match res {
+                Ok(w) => {
+                    if w.skipped {
+                        skipped += 1;
+                    } else {
+                        ok += 1;
+                    }
+                    w.to_json()
+                }
+                Err(e) => {
+                    failed += 1;
+                    let mut v = match crate::output::error_value(e) {
+                        v @ JsonValue::Object(_) => v,
+                        other => serde_json::json!({ "error": other }),
+                    };
+                    v["path"] = JsonValue::String(job.dest.display().to_string());
+                    v
+                }
+            };
+            v["id"] = job.id.clone();
+            v
+        })
+        .collect();
+    serde_json::json!({
+        "succeeded": ok,
+        "failed": failed,
+        "skipped": skipped,
+        "items": items,
+    })
+}
+
+// Open the `.part` file (owner-only) for a fresh body, or for appending.
+pub fn open_part(part: &Path, append: bool) -> Result<File> {
+    if !append {
+        return create_part(part);
+    }
+    OpenOptions::new()
+        .append(true)
+        .open(part)
+        .context("open partial file")
+}
+
+fn create_part(part: &Path) -> Result<File> {
+    let f = File::create(part).context("create partial file")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = f.metadata()?.permissions();
+        perms.set_mode(0o600);
+        f.set_permissions(perms)?;
+    }
+    Ok(f)
+}
+
+// `<dest>.part` next to the destination (same filesystem, so rename is atomic).
+pub fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+fn busy_error(dest: &Path) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "destination busy",
+        "path": dest.display().to_string(),
+        "hint": "another pexels process is writing this file; retry or pass --if-busy wait",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "destination busy".into()))
+}
@@ -0,0 +1,757 @@
+// Central registry of runnable examples. Every subcommand's `--help` lists
+// its entries and `pexels examples [COMMAND...]` prints them, so help text
+// and the examples command never drift apart; tests parse every line.
+
+pub struct Example {
+    // Subcommand path, e.g. "photos search"
+    pub command: &'static str,
+    pub line: &'static str,
+    pub about: &'static str,
+}
+
+const fn ex(command: &'static str, line: &'static str, about: &'static str) -> Example {
+    Example {
+        command,
+        line,
+        about,
+    }
+}
+
+pub const EXAMPLES: &[Example] = &[
+    ex(
+        "auth login",
+        "pexels auth login YOUR_TOKEN",
+        "check a token against the API and store it",
+    ),
+    ex(
+        "auth login",
+        "pexels --wait-lock 30 auth login YOUR_TOKEN --no-verify",
+        "skip the API check; wait up to 30s for the config lock",
+    ),
+    ex(
+        "auth login",
+        "pexels auth login --profile work --stdin",
+        "store a token piped from a secret manager in the `work` profile",
+    ),
+    ex(
+        "auth status",
+        "pexels auth status",
+        "show where the token comes from",
+    ),
+    ex("auth status", "pexels auth status --json", "same, as JSON"),
+    ex(
+        "auth status",
+        "pexels auth status --profile work",
+        "token source of the `work` profile",
+    ),
+    ex(
+        "auth whoami",
+        "pexels auth whoami",
+        "fingerprint of the active token",
+    ),
+    ex(
+        "auth whoami",
+        "pexels auth whoami --host-profile internal",
+        "token, host and quota as seen through a host profile",
+    ),
+    ex(
+        "auth whoami",
+        "pexels auth whoami --token-file /run/secrets/pexels_token",
+        "check a token mounted as a file, without saving it",
+    ),
+    ex(
+        "auth logout",
+        "pexels auth logout",
+        "remove the stored token",
+    ),
+    ex(
+        "auth logout",
+        "pexels --wait-lock 0 auth logout",
+        "fail at once if the config is locked",
+    ),
+    ex(
+        "config set",
+        "pexels config set projections.photos id,photographer,src.medium",
+        "default columns for photo lists",
+    ),
+    ex(
+        "config set",
+        "pexels config set audit.enabled true",
+        "log every API call to audit.jsonl",
+    ),
+    ex(
+        "config set",
+        "pexels config set default_per_page 40",
+        "page size when --per-page is not given",
+    ),
+    ex(
+        "config get",
+        "pexels config get projections.photos",
+        "effective default columns",
+    ),
+    ex(
+        "config get",
+        "pexels config get audit.path",
+        "where the audit log is written",
+    ),
+    ex(
+        "config get",
+        "pexels config get hosts.internal.base",
+        "base URL of the `internal` host profile",
+    ),
+    ex(
+        "config unset",
+        "pexels config unset download_dir",
+        "write bulk downloads to --dir or . again",
+    ),
+    ex(
+        "config unset",
+        "pexels config unset projections.photos",
+        "back to the built-in photo columns",
+    ),
+    ex(
+        "config list",
+        "pexels config list",
+        "every key with its value and where it comes from",
+    ),
+    ex(
+        "config list",
+        "pexels --profile work --timeout 60 config list --json",
+        "effective settings for the `work` profile with a flag applied",
+    ),
+    ex(
+        "config path",
+        "pexels config path",
+        "location of config.yaml",
+    ),
+    ex(
+        "config path",
+        "pexels config path --json",
+        "same, for scripts",
+    ),
+    ex(
+        "config path",
+        "pexels --config /etc/pexels/tenant-a.yaml config path",
+        "where --config (or PEXELS_CONFIG) points",
+    ),
+    ex("quota view", "pexels quota view", "remaining API quota"),
+    ex("quota view", "pexels quota view --json", "quota as JSON"),
+    ex(
+        "quota view",
+        "pexels quota view --refresh",
+        "probe the API instead of the last-seen headers",
+    ),
+    ex(
+        "photos search",
+        "pexels photos search -q cats --all --limit 200 --fields id,src.original --ndjson",
+        "one JSON line per photo, for jq -c or xargs",
+    ),
+    ex(
+        "photos search",
+        "pexels photos search -q beach --orientation landscape --analyze --fields id,analysis.quietest",
+        "landscape beach shots with their quietest quadrant for text",
+    ),
+    ex(
+        "photos search",
+        "pexels photos search --any-of cat,kitten --none-of studio --fields id,url",
+        "boolean query with selected fields",
+    ),
+    ex(
+        "photos curated",
+        "pexels photos curated --fields id,photographer,src.original --output csv",
+        "curated photos as a spreadsheet",
+    ),
+    ex(
+        "photos curated",
+        "pexels photos curated --watch --interval 600 --sink nats://localhost:4222/pexels.curated",
+        "publish each new curated photo to a NATS subject (build with --features nats)",
+    ),
+    ex(
+        "photos curated",
+        "pexels photos curated --fields id,width,height --map \"megapixels = round(width*height/1e6, 1)\"",
+        "add a computed column",
+    ),
+    ex("photos get", "pexels photos get 2014422", "one photo"),
+    ex(
+        "photos get",
+        "pexels photos get 2014422 1181244 3408744 --fields id,src.original",
+        "several photos at once; misses are listed in meta.errors",
+    ),
+    ex(
+        "photos get",
+        "pexels photos get 2014422 --fields id,src --jmes data.src.large2x",
+        "one URL via a JMESPath query",
+    ),
+    ex(
+        "photos exists",
+        "pexels photos exists 2014422",
+        "exit 0 if the photo is still there, 4 if it's gone, for shell guards",
+    ),
+    ex(
+        "photos exists",
+        "pexels photos exists 2014422 --json",
+        "{id, kind, exists} instead of a silent exit code",
+    ),
+    ex(
+        "photos potd",
+        "pexels photos potd",
+        "photo of the day from curated",
+    ),
+    ex(
+        "photos potd",
+        "pexels photos potd -q mountains",
+        "photo of the day for a theme",
+    ),
+    ex(
+        "photos estimate",
+        "pexels photos estimate -q cats --limit 500",
+        "download volume per size tier",
+    ),
+    ex(
+        "photos estimate",
+        "pexels photos estimate --size large2x --sample 10",
+        "curated, sampling 10 per tier",
+    ),
+    ex("photos url", "pexels photos url 2014422", "original URL"),
+    ex(
+        "photos url",
+        "pexels photos url 2014422 --size medium",
+        "a smaller variant",
+    ),
+    ex(
+        "photos urls",
+        "pexels photos urls --input ids.txt --size large",
+        "ID to URL map for a list of photos",
+    ),
+    ex(
+        "photos urls",
+        "pexels photos urls --input - --concurrency 16 --jmes \"data.urls\"",
+        "IDs piped on stdin, just the map",
+    ),
+    ex(
+        "photos urls",
+        "pexels photos urls --input ids.txt --sink https://ingest.example/api/photos --sink-header \"Authorization: Bearer KEY\"",
+        "POST the map to an ingest endpoint instead of printing it",
+    ),
+    ex(
+        "photos preview",
+        "pexels photos preview 2014422",
+        "draw the photo in the terminal",
+    ),
+    ex(
+        "photos preview",
+        "pexels photos preview 2014422 --size medium --preview-width 80 --preview-protocol blocks",
+        "wider, from the medium variant, as colored blocks",
+    ),
+    ex(
+        "photos palette",
+        "pexels photos palette 2014422",
+        "six dominant colors with proportions",
+    ),
+    ex(
+        "photos palette",
+        "pexels photos palette 2014422 --count 3 --json",
+        "top three colors as JSON",
+    ),
+    ex(
+        "photos contrast",
+        "pexels photos contrast 2014422 --text-color \"#fff\" --region top",
+        "is white text over the top third legible?",
+    ),
+    ex(
+        "photos contrast",
+        "pexels photos contrast 2014422 --text-color 1a1a1a --region center --json",
+        "dark text in the middle, as JSON",
+    ),
+    ex(
+        "photos download",
+        "pexels photos download 2014422 ./photo.jpg",
+        "save the original",
+    ),
+    ex(
+        "photos download",
+        "pexels photos download 2014422 ./photos --name-template {id}_{photographer_slug}.{ext}",
+        "name the file from its metadata",
+    ),
+    ex(
+        "photos download",
+        "pexels photos download 2014422 ./photo.jpg --web-optimize --web-format avif --web-quality 60",
+        "also write photo.avif for the web and report the savings",
+    ),
+    ex(
+        "photos download-all",
+        "pexels photos download-all -q cats --limit 50 --dir ./cats --size large",
+        "bulk download search results",
+    ),
+    ex(
+        "photos download-all",
+        "pexels photos download-all -q fog --dir ./fog --concurrency 8 --if-busy skip --analyze",
+        "faster, skipping busy files; write .analysis.json sidecars",
+    ),
+    ex(
+        "photos download-all",
+        "pexels photos download-all -q fog --dir ./fog --dest s3://media-bucket/fog --dest sftp://deploy@nas.local/srv/fog",
+        "also upload every file to an S3 bucket and a NAS over SFTP",
+    ),
+    ex(
+        "videos search",
+        "pexels videos search ocean",
+        "first page of videos",
+    ),
+    ex(
+        "videos search",
+        "pexels videos search ocean --min-width 1920 --max-duration 30",
+        "HD clips up to 30 seconds",
+    ),
+    ex(
+        "videos search",
+        "pexels videos search ocean --raw --cursor-file ocean.cursor",
+        "next page on each run; the file is emptied after the last one",
+    ),
+    ex("videos popular", "pexels videos popular", "popular videos"),
+    ex(
+        "videos popular",
+        "pexels videos popular --all --limit 40 --fields id,duration --ndjson",
+        "two pages, one line per video as each page arrives",
+    ),
+    ex(
+        "videos popular",
+        "pexels videos popular --all --limit 200 --max-duration 60 --bucket duration:0-10,10-30,30+",
+        "clips under a minute, counted per length slot",
+    ),
+    ex(
+        "videos exists",
+        "pexels videos exists 857195",
+        "exit 0 if the video is still there, 4 if it's gone",
+    ),
+    ex(
+        "videos exists",
+        "pexels videos exists 857195 --json",
+        "{id, kind, exists} instead of a silent exit code",
+    ),
+    ex("videos get", "pexels videos get 857195", "one video"),
+    ex(
+        "videos get",
+        "pexels videos get 857195 3571264 --concurrency 2 --fields id,video_files",
+        "renditions of two videos, fetched side by side",
+    ),
+    ex(
+        "videos get",
+        "pexels videos get 857195 --token-stdin",
+        "with a token piped in from a secret manager",
+    ),
+    ex(
+        "videos url",
+        "pexels videos url 857195 --quality hd",
+        "link of the best HD rendition",
+    ),
+    ex(
+        "videos url",
+        "pexels videos url 857195 --max-height 720 --json",
+        "best rendition up to 720p, as JSON",
+    ),
+    ex(
+        "videos download",
+        "pexels videos download 857195 ./clip.mp4 --quality hd",
+        "save the best HD rendition",
+    ),
+    ex(
+        "videos download",
+        "pexels videos download 857195 ./clip.mp4 --max-width 1280 --no-progress",
+        "small rendition, quiet",
+    ),
+    ex(
+        "collections list",
+        "pexels collections list",
+        "your collections",
+    ),
+    ex(
+        "collections list",
+        "pexels collections list --all --fields id,title --resume collections.resume",
+        "all of them, two columns; rerun after an interruption to fetch only the rest",
+    ),
+    ex(
+        "collections list",
+        "pexels collections list --expand",
+        "with photo/video counts per collection",
+    ),
+    ex(
+        "collections featured",
+        "pexels collections featured",
+        "featured collections",
+    ),
+    ex(
+        "collections featured",
+        "pexels collections featured --count",
+        "just the number of featured collections",
+    ),
+    ex(
+        "collections featured",
+        "pexels collections featured --all --soft-deadline 20s",
+        "as many pages as fit in 20 seconds; pexels continue picks up the rest",
+    ),
+    ex(
+        "collections get",
+        "pexels collections get abc123",
+        "one collection",
+    ),
+    ex(
+        "collections get",
+        "pexels collections get abc123 --json",
+        "same, as JSON",
+    ),
+    ex(
+        "collections get",
+        "pexels collections get abc123 --offline",
+        "from the result cache, without network or token",
+    ),
+    ex(
+        "collections items",
+        "pexels collections items abc123",
+        "media in a collection",
+    ),
+    ex(
+        "collections items",
+        "pexels collections items abc123 --all --normalize --no-dedupe",
+        "every item as canonical records, repeats across pages kept",
+    ),
+    ex(
+        "collections items",
+        "pexels collections items abc123 --all --per-page 80 --page-concurrency 4",
+        "a large collection, four pages at a time",
+    ),
+    ex("collections download", "pexels collections download abc123 --dir ./mirror", "originals and best video renditions, plus manifest.json"),
+    ex("collections download", "pexels collections download abc123 --dir ./mirror --size large --quality hd --concurrency 8", "smaller photos, HD videos, more transfers"),
+    ex("collections download", "pexels collections download abc123 --dir ./mirror --dest webdav://dav.example.com/pexels", "fetch once, upload to a WebDAV share too"),
+    ex("util inspect", "pexels util inspect", "effective settings"),
+    ex(
+        "util inspect",
+        "pexels util inspect --json",
+        "same, as JSON",
+    ),
+    ex("util ping", "pexels util ping", "check API reachability"),
+    ex(
+        "util ping",
+        "pexels util ping --timeout 5",
+        "with a short timeout",
+    ),
+    ex(
+        "util ping",
+        "pexels util ping --host-profile internal",
+        "through the gateway defined in hosts.internal",
+    ),
+    ex(
+        "util replay-request",
+        "pexels util replay-request -1",
+        "send the latest logged request again and diff it",
+    ),
+    ex(
+        "util replay-request",
+        "pexels util replay-request 6f1c2a9e-request-id --json",
+        "replay by the request_id Pexels returned",
+    ),
+    ex(
+        "util support-bundle",
+        "pexels util support-bundle",
+        "redacted diagnostics for a bug report",
+    ),
+    ex(
+        "util support-bundle",
+        "pexels util support-bundle /tmp/report.tar.gz",
+        "written to a chosen path",
+    ),
+    ex(
+        "queue add",
+        "pexels queue add photo 2014422 --size large2x",
+        "queue a photo at a chosen size",
+    ),
+    ex(
+        "queue add",
+        "pexels queue add video 857195 --quality hd --max-width 1920",
+        "queue a video rendition",
+    ),
+    ex("queue list", "pexels queue list", "what the next run will fetch"),
+    ex(
+        "queue list",
+        "pexels queue list --json",
+        "same, as JSON",
+    ),
+    ex(
+        "queue run",
+        "pexels queue run --dir ./downloads",
+        "download everything queued",
+    ),
+    ex(
+        "queue run",
+        "pexels queue run --dir ./downloads --concurrency 8 --if-busy skip",
+        "more transfers, skip files being written elsewhere",
+    ),
+    ex(
+        "queue run",
+        "pexels queue run --dir ./downloads --no-wait",
+        "stop at the rate-limit wall and print when to resume",
+    ),
+    ex("queue clear", "pexels queue clear", "empty the queue"),
+    ex(
+        "export dataset",
+        "pexels export dataset -q street --limit 5000 --dest ./ds --split 80/10/10",
+        "5000 street photos in train/val/test with an index.csv",
+    ),
+    ex(
+        "export dataset",
+        "pexels export dataset -q \"red car\" --all --dest ./cars --size medium,small --concurrency 8",
+        "two sizes per photo, each in its own directory",
+    ),
+    ex(
+        "export dataset",
+        "pexels export dataset -q street --all --dest ./ds2 --exclude-manifest ./ds/index.csv",
+        "a follow-up dataset without any photo or file already in ./ds",
+    ),
+    ex(
+        "queue clear",
+        "pexels queue clear --json",
+        "same, reporting the count as JSON",
+    ),
+    ex(
+        "browse",
+        "pexels browse --query cats --dir ./cats",
+        "page through cat photos, mark some, download them",
+    ),
+    ex(
+        "browse",
+        "pexels browse --per-page 40 --concurrency 8",
+        "curated photos, 40 a page, into download_dir from config",
+    ),
+    ex(
+        "completions",
+        "pexels completions bash",
+        "bash completion script on stdout",
+    ),
+    ex(
+        "completions",
+        "pexels completions zsh",
+        "zsh completion script, e.g. saved as _pexels on $fpath",
+    ),
+    ex(
+        "completions",
+        "pexels completions fish",
+        "fish completion script",
+    ),
+    ex(
+        "api",
+        "pexels api GET /v1/search --param query=cat --param per_page=5",
+        "search through the raw endpoint",
+    ),
+    ex(
+        "api",
+        "pexels api GET /videos/popular -p min_duration=30 --raw",
+        "unwrapped response body",
+    ),
+    ex(
+        "api",
+        "pexels api HEAD /v1/curated",
+        "status and rate-limit headers only",
+    ),
+    ex("state info", "pexels state info", "state database summary"),
+    ex("state info", "pexels state info --json", "same, as JSON"),
+    ex("state list", "pexels state list", "every stored entry"),
+    ex(
+        "state list",
+        "pexels state list --namespace potd",
+        "one namespace",
+    ),
+    ex(
+        "state get",
+        "pexels state get continue:last",
+        "the saved continuation point",
+    ),
+    ex(
+        "state get",
+        "pexels state get potd:curated --json",
+        "today's pick, as JSON",
+    ),
+    ex(
+        "state rm",
+        "pexels state rm continue:last",
+        "forget the continuation point",
+    ),
+    ex(
+        "state rm",
+        "pexels state rm potd:curated",
+        "re-pick the photo of the day",
+    ),
+    ex(
+        "continue",
+        "pexels continue",
+        "resume the last listing cut short by --limit",
+    ),
+    ex("continue", "pexels continue --json", "same, as JSON"),
+    ex(
+        "examples",
+        "pexels examples photos search",
+        "examples for one command",
+    ),
+    ex(
+        "examples",
+        "pexels examples videos",
+        "examples for a command group",
+    ),
+];
+
+// Multi-step recipes for `pexels examples --recipes`. `{{name}}` markers are
+// filled from the user's config (see `fill`); UPPERCASE words are left for
+// the user. Tests parse every `pexels` invocation in every step.
+pub struct Recipe {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub about: &'static str,
+    pub steps: &'static [&'static str],
+}
+
+pub const RECIPES: &[Recipe] = &[
+    Recipe {
+        name: "export-collection",
+        category: "export",
+        about: "export every item of a collection to CSV",
+        steps: &["pexels collections items COLLECTION_ID --all --normalize --also-write csv=collection.csv"],
+    },
+    Recipe {
+        name: "export-search",
+        category: "export",
+        about: "save a search with your default photo columns as CSV and JSON",
+        steps: &["pexels photos search -q QUERY --all --limit 200 --fields {{photo_fields}} --also-write csv=photos.csv --json > photos.json"],
+    },
+    Recipe {
+        name: "wallpaper-rotation",
+        category: "wallpaper",
+        about: "keep a folder of landscape wallpapers fresh",
+        steps: &[
+            "pexels photos download-all -q wallpaper --limit 30 --dir ./wallpapers --size original --if-busy skip",
+            "pexels photos curated --watch --interval {{interval}} --ndjson-file ./wallpapers/new.ndjson --rotate daily",
+        ],
+    },
+    Recipe {
+        name: "photo-of-the-day",
+        category: "wallpaper",
+        about: "fetch today's pick for a theme, the same one all day",
+        steps: &["pexels photos potd -q QUERY --fields id,src.original --json > potd.json"],
+    },
+    Recipe {
+        name: "pipe-ids-to-download",
+        category: "pipelines",
+        about: "download search results one id at a time",
+        steps: &["pexels photos search -q QUERY --limit 20 --fields id --json | jq -r '.data[].id' | xargs -I% pexels photos download % ./downloads --name-template {id}.{ext}"],
+    },
+    Recipe {
+        name: "audit-review",
+        category: "pipelines",
+        about: "record API calls and follow them live",
+        steps: &["pexels config set audit.enabled true", "tail -f {{audit_path}}"],
+    },
+];
+
+// Values for the `{{name}}` markers in recipes, from the user's config.
+pub fn placeholders(cfg: &crate::config::Config) -> Vec<(&'static str, String)> {
+    let photo_fields = cfg
+        .projections
+        .get("photos")
+        .filter(|f| !f.is_empty())
+        .map(|f| f.join(","))
+        .unwrap_or_else(|| "id,photographer,src.original".into());
+    let audit_path = cfg
+        .audit
+        .path
+        .clone()
+        .unwrap_or_else(crate::audit::default_path);
+    vec![
+        ("photo_fields", photo_fields),
+        ("interval", cfg.watch.interval.unwrap_or(60).to_string()),
+        ("audit_path", audit_path.display().to_string()),
+    ]
+}
+
+pub fn fill(step: &str, values: &[(&'static str, String)]) -> String {
+    values.iter().fold(step.to_string(), |acc, (k, v)| {
+        acc.replace(&format!("{{{{{}}}}}", k), v)
+    })
+}
+
+// Recipes whose category or name matches `filter` (all when empty).
+pub fn recipes(filter: &[String]) -> Vec<&'static Recipe> {
+    let want = filter.join(" ");
+    RECIPES
+        .iter()
+        .filter(|r| want.is_empty() || r.category == want || r.name == want)
+        .collect()
+}
+
+// Examples for a command path; a group prefix ("photos") matches all of
+// its subcommands.
+pub fn for_command(path: &[String]) -> Vec<&'static Example> {
+    let want = path.join(" ");
+    EXAMPLES
+        .iter()
+        .filter(|e| {
+            want.is_empty() || e.command == want || e.command.starts_with(&format!("{} ", want))
+        })
+        .collect()
+}
+
+// `Examples:` block for a subcommand's --help.
+pub fn help_block(command: &str) -> Option<String> {
+    let lines: Vec<String> = EXAMPLES
+        .iter()
+        .filter(|e| e.command == command)
+        .map(|e| format!("  {}\n      {}", e.line, e.about))
+        .collect();
+    (!lines.is_empty()).then(|| format!("Examples:\n{}", lines.join("\n")))
+}
+
+// Attach every subcommand's examples as its after_help.
+pub fn attach(cmd: clap::Command) -> clap::Command {
+    attach_at(cmd, "")
+}
+
+fn attach_at(mut cmd: clap::Command, prefix: &str) -> clap::Command {
+    let names: Vec<String> = cmd
+        .get_subcommands()
+        .map(|s| s.get_name().to_string())
+        .collect();
+    for name in names {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{} {}", prefix, name)
+        };
+        cmd = cmd.mut_subcommand(&name, |sub| {
+            let sub = attach_at(sub, &path);
+            match help_block(&path) {
+                Some(block) => sub.after_help(block),
+                None => sub,
+            }
+        });
+    }
+    cmd
+}
+
+// Split an example line into argv (double quotes group words).
+pub fn argv(line: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut cur = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if !cur.is_empty() {
+                    out.push(std::mem::take(&mut cur));
+                }
+            }
+            _ => cur.push(c),
+        }
+    }
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+    out
+}
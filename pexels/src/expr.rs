@@ -0,0 +1,331 @@
+use serde_json::Value as JsonValue;
+
+// `--map NAME = EXPR` computed fields. Expressions cover numbers, quoted
+// strings, dot paths into the item (`src.original`, `video_files.0.width`),
+// `+ - * / %`, parentheses and a few functions:
+// round(x[, digits]), floor, ceil, abs, min, max, lower, upper, len.
+// A missing or null operand makes the result null; so does division by zero.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapExpr {
+    pub name: String,
+    pub expr: Expr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Str(String),
+    Path(Vec<String>),
+    Neg(Box<Expr>),
+    Bin(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+const FUNCTIONS: &[&str] = &[
+    "round", "floor", "ceil", "abs", "min", "max", "lower", "upper", "len",
+];
+
+// clap value parser for `--map`.
+pub fn parse_map(s: &str) -> Result<MapExpr, String> {
+    let (name, body) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME = EXPR, got `{}`", s))?;
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    if !valid {
+        return Err(format!("invalid field name `{}`", name));
+    }
+    Ok(MapExpr {
+        name: name.to_string(),
+        expr: parse(body)?,
+    })
+}
+
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let tokens = tokenize(s)?;
+    let mut p = Parser { tokens, pos: 0 };
+    let expr = p.expr()?;
+    match p.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("unexpected `{}`", t.text())),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Ident(String),
+    Op(char),
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Num(n) => n.to_string(),
+            Token::Str(s) => format!("'{}'", s),
+            Token::Ident(s) => s.clone(),
+            Token::Op(c) => c.to_string(),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse()
+                .map_err(|_| format!("invalid number `{}`", text))?;
+            out.push(Token::Num(n));
+        } else if c == '\'' || c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&q| q == c)
+                .ok_or("unterminated string")?;
+            out.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            out.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/%(),".contains(c) {
+            out.push(Token::Op(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character `{}`", c));
+        }
+    }
+    Ok(out)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek().cloned() {
+            self.pos += 1;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Str(s)) => Ok(Expr::Str(s)),
+            Some(Token::Op('(')) => {
+                let e = self.expr()?;
+                if !self.eat(')') {
+                    return Err("missing `)`".into());
+                }
+                Ok(e)
+            }
+            Some(Token::Ident(name)) if self.eat('(') => {
+                if !FUNCTIONS.contains(&name.as_str()) {
+                    let near = crate::suggest::nearest(&name, FUNCTIONS.iter().copied());
+                    return Err(match near.as_slice() {
+                        [] => format!("unknown function `{}`", name),
+                        _ => format!(
+                            "unknown function `{}`; did you mean {}?",
+                            name,
+                            near.join(", ")
+                        ),
+                    });
+                }
+                let mut args = vec![];
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expr()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(format!("expected `,` or `)` in {}(...)", name));
+                        }
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Some(Token::Ident(path)) => {
+                Ok(Expr::Path(path.split('.').map(|p| p.to_string()).collect()))
+            }
+            Some(t) => Err(format!("unexpected `{}`", t.text())),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
+
+pub fn eval(expr: &Expr, item: &JsonValue) -> JsonValue {
+    match expr {
+        Expr::Num(n) => number(*n),
+        Expr::Str(s) => JsonValue::String(s.clone()),
+        Expr::Path(parts) => lookup(item, parts).cloned().unwrap_or(JsonValue::Null),
+        Expr::Neg(e) => num_of(&eval(e, item)).map_or(JsonValue::Null, |n| number(-n)),
+        Expr::Bin(op, a, b) => binary(*op, eval(a, item), eval(b, item)),
+        Expr::Call(name, args) => {
+            let args: Vec<JsonValue> = args.iter().map(|a| eval(a, item)).collect();
+            call(name, &args)
+        }
+    }
+}
+
+// Add every computed field to `out`, evaluated against `item`.
+pub fn apply(maps: &[MapExpr], item: &JsonValue, out: &mut JsonValue) {
+    if let Some(obj) = out.as_object_mut() {
+        for m in maps {
+            obj.insert(m.name.clone(), eval(&m.expr, item));
+        }
+    }
+}
+
+fn lookup<'a>(v: &'a JsonValue, parts: &[String]) -> Option<&'a JsonValue> {
+    parts.iter().try_fold(v, |cur, p| match cur {
+        JsonValue::Object(map) => map.get(p),
+        JsonValue::Array(arr) => p.parse::<usize>().ok().and_then(|i| arr.get(i)),
+        _ => None,
+    })
+}
+
+fn num_of(v: &JsonValue) -> Option<f64> {
+    match v {
+        JsonValue::Number(n) => n.as_f64(),
+        JsonValue::String(s) => s.parse().ok(),
+        JsonValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+// Whole numbers print as integers (`24000000`, not `24000000.0`).
+fn number(n: f64) -> JsonValue {
+    if !n.is_finite() {
+        return JsonValue::Null;
+    }
+    if n.fract() == 0.0 && n.abs() < 9.0e15 {
+        return JsonValue::from(n as i64);
+    }
+    serde_json::Number::from_f64(n).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+fn text_of(v: &JsonValue) -> String {
+    match v {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn binary(op: char, a: JsonValue, b: JsonValue) -> JsonValue {
+    if a.is_null() || b.is_null() {
+        return JsonValue::Null;
+    }
+    // `+` concatenates when either side is a non-numeric string
+    if op == '+' && (num_of(&a).is_none() || num_of(&b).is_none()) {
+        return JsonValue::String(text_of(&a) + &text_of(&b));
+    }
+    let (Some(x), Some(y)) = (num_of(&a), num_of(&b)) else {
+        return JsonValue::Null;
+    };
+    match op {
+        '+' => number(x + y),
+        '-' => number(x - y),
+        '*' => number(x * y),
+        '/' if y != 0.0 => number(x / y),
+        '%' if y != 0.0 => number(x % y),
+        _ => JsonValue::Null,
+    }
+}
+
+fn call(name: &str, args: &[JsonValue]) -> JsonValue {
+    if args.is_empty() || args.iter().any(|a| a.is_null()) {
+        return JsonValue::Null;
+    }
+    let nums: Option<Vec<f64>> = args.iter().map(num_of).collect();
+    match (name, nums.as_deref()) {
+        ("round", Some([x])) => number(x.round()),
+        ("round", Some([x, d])) => {
+            let f = 10f64.powi(*d as i32);
+            number((x * f).round() / f)
+        }
+        ("floor", Some([x])) => number(x.floor()),
+        ("ceil", Some([x])) => number(x.ceil()),
+        ("abs", Some([x])) => number(x.abs()),
+        ("min", Some(xs)) => number(xs.iter().copied().fold(f64::INFINITY, f64::min)),
+        ("max", Some(xs)) => number(xs.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        ("lower", _) if args.len() == 1 => JsonValue::String(text_of(&args[0]).to_lowercase()),
+        ("upper", _) if args.len() == 1 => JsonValue::String(text_of(&args[0]).to_uppercase()),
+        ("len", _) if args.len() == 1 => match &args[0] {
+            JsonValue::Array(a) => JsonValue::from(a.len()),
+            JsonValue::Object(o) => JsonValue::from(o.len()),
+            other => JsonValue::from(text_of(other).chars().count()),
+        },
+        _ => JsonValue::Null,
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+// Fault injection for `--inject-fault` (cargo feature `fault-injection`):
+// `429@2,timeout@4` makes the client's 2nd request attempt come back as a
+// 429 and its 4th time out, so retry/backoff, budgets and alerting can be
+// exercised without a real outage. Attempts are numbered from 1 across the
+// whole run, retries and downloads included.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultKind {
+    // A synthesized response with this status and an empty body
+    Status(u16),
+    // The request times out (a real reqwest timeout error)
+    Timeout,
+    // The connection is refused
+    Connect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fault {
+    pub kind: FaultKind,
+    pub attempt: u64,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            FaultKind::Status(code) => write!(f, "{}@{}", code, self.attempt),
+            FaultKind::Timeout => write!(f, "timeout@{}", self.attempt),
+            FaultKind::Connect => write!(f, "connect@{}", self.attempt),
+        }
+    }
+}
+
+// `KIND@ATTEMPT`, KIND being an HTTP status (400-599), `timeout` or `connect`.
+pub fn parse(s: &str) -> Result<Fault, String> {
+    let (kind, attempt) = s
+        .split_once('@')
+        .ok_or_else(|| format!("expected KIND@ATTEMPT (e.g. 429@2), got {}", s))?;
+    let attempt = attempt
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("attempt must be a positive number, got {}", attempt))?;
+    let kind = match kind.trim().to_ascii_lowercase().as_str() {
+        "timeout" => FaultKind::Timeout,
+        "connect" => FaultKind::Connect,
+        code => match code.parse::<u16>() {
+            Ok(c) if (400..600).contains(&c) => FaultKind::Status(c),
+            _ => {
+                return Err(format!(
+                    "unknown fault {}; expected an HTTP status (400-599), timeout or connect",
+                    kind
+                ))
+            }
+        },
+    };
+    Ok(Fault { kind, attempt })
+}
+
+// Fault scheduled for `attempt`, if any.
+pub fn at(faults: &[Fault], attempt: u64) -> Option<FaultKind> {
+    faults.iter().find(|f| f.attempt == attempt).map(|f| f.kind)
+}
@@ -0,0 +1,162 @@
+// Minimal C ABI over the blocking client. Every call returns a heap-allocated
+// JSON string in the CLI envelope shape — `{"data": ...}` on success or
+// `{"error": ...}` on failure — which the caller releases with
+// `pexels_string_free`. See `include/pexels.h` for the C declarations.
+
+use crate::blocking::Client;
+use crate::config::Config;
+use crate::output::error_value;
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+
+pub struct PexelsClient {
+    inner: Client,
+}
+
+/// Create a client. `host` may be NULL for the public API.
+/// Returns NULL when `token` is NULL/invalid UTF-8 or the client cannot be built.
+///
+/// # Safety
+/// `token` and `host` must be NULL or valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn pexels_client_new(
+    token: *const c_char,
+    host: *const c_char,
+) -> *mut PexelsClient {
+    let Some(token) = opt_str(token) else {
+        return std::ptr::null_mut();
+    };
+    let cfg = Config {
+        token: Some(token.to_string()),
+        host: opt_str(host).map(|s| s.to_string()),
+        timeout_secs: 15,
+        max_retries: 3,
+        ..Default::default()
+    };
+    match Client::new(cfg) {
+        Ok(inner) => Box::into_raw(Box::new(PexelsClient { inner })),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free a client created by `pexels_client_new`. NULL is ignored.
+///
+/// # Safety
+/// `client` must be NULL or a pointer returned by `pexels_client_new`, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn pexels_client_free(client: *mut PexelsClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Search photos; `per_page`/`limit` of 0 use API defaults / first page only.
+///
+/// # Safety
+/// `client` must come from `pexels_client_new`; `query` must be a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn pexels_search_photos(
+    client: *const PexelsClient,
+    query: *const c_char,
+    per_page: u32,
+    limit: u32,
+) -> *mut c_char {
+    respond(|| {
+        let client = client_ref(client)?;
+        let query = opt_str(query).context("query is required")?;
+        let mut req = client.inner.photos().search(query);
+        if per_page > 0 {
+            req = req.per_page(per_page);
+        }
+        // Without a limit only the first page is fetched, matching the CLI
+        req = if limit > 0 {
+            req.limit(limit)
+        } else {
+            req.max_pages(1)
+        };
+        let items = req.items().collect::<Result<Vec<_>>>()?;
+        Ok(serde_json::to_value(items)?)
+    })
+}
+
+/// Download a photo variant (`size` e.g. "original", "large"; NULL = original) to `path`.
+///
+/// # Safety
+/// `client` must come from `pexels_client_new`; string arguments must be valid or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pexels_download(
+    client: *const PexelsClient,
+    photo_id: *const c_char,
+    size: *const c_char,
+    path: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let client = client_ref(client)?;
+        let id = opt_str(photo_id).context("photo_id is required")?;
+        let path = opt_str(path).context("path is required")?;
+        let size = opt_str(size).unwrap_or("original");
+        let photo = client.inner.photos_get(id)?;
+        let url = photo
+            .get("src")
+            .and_then(|v| v.get(size))
+            .and_then(|v| v.as_str())
+            .with_context(|| {
+                let sizes = photo
+                    .get("src")
+                    .and_then(|v| v.as_object())
+                    .map(|m| m.keys().map(|k| k.as_str()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                match crate::suggest::nearest(size, sizes).as_slice() {
+                    [] => format!("src.{} not found", size),
+                    near => format!("src.{} not found; did you mean {}?", size, near.join(", ")),
+                }
+            })?;
+        let bytes = client.inner.download_url_bytes(url)?;
+        let p = Path::new(path);
+        if let Some(dir) = p.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(p, &bytes)?;
+        Ok(serde_json::json!({ "path": path, "bytes": bytes.len() }))
+    })
+}
+
+/// Free a string returned by any `pexels_*` call. NULL is ignored.
+///
+/// # Safety
+/// `s` must be NULL or a pointer returned by this library, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn pexels_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+unsafe fn opt_str<'a>(p: *const c_char) -> Option<&'a str> {
+    if p.is_null() {
+        None
+    } else {
+        CStr::from_ptr(p).to_str().ok()
+    }
+}
+
+unsafe fn client_ref<'a>(p: *const PexelsClient) -> Result<&'a PexelsClient> {
+    p.as_ref().context("client is NULL")
+}
+
+// Run `f` and render its outcome as an envelope JSON string.
+fn respond(f: impl FnOnce() -> Result<JsonValue>) -> *mut c_char {
+    let out = match f() {
+        Ok(data) => serde_json::json!({ "data": data }),
+        Err(err) => match error_value(&err) {
+            obj @ JsonValue::Object(_) => obj,
+            other => serde_json::json!({ "error": other }),
+        },
+    };
+    let s = serde_json::to_string(&out).unwrap_or_else(|_| r#"{"error":"encode"}"#.into());
+    CString::new(s)
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
@@ -0,0 +1,125 @@
+use crate::state::State;
+use std::fmt;
+
+// Token health: API commands re-validate the stored token at most once per
+// CHECK_EVERY_SECS (the last result lives in the state store as
+// `health:token:<hash>`), and any 401 surfaces as `TokenRejected`, which the
+// binary maps to exit code 3. `photos exists`/`videos exists` report a 404 as
+// `NotFound` (exit code 4).
+
+pub const NAMESPACE: &str = "health";
+pub const CHECK_EVERY_SECS: i64 = 86_400;
+// Process exit code for a rejected token.
+pub const EXIT_TOKEN_REJECTED: i32 = 3;
+// Process exit code for `exists` on a missing photo or video.
+pub const EXIT_NOT_FOUND: i32 = 4;
+
+// Key for the token's last check; only a hash of the token is stored.
+pub fn key(token: &str) -> String {
+    format!(
+        "{}:token:{:016x}",
+        NAMESPACE,
+        crate::util::fnv1a64(token.as_bytes())
+    )
+}
+
+// True unless the token passed a check within the last CHECK_EVERY_SECS.
+pub fn due(st: &State, token: &str, now: i64) -> bool {
+    let last = st.get(&key(token));
+    let ok = last.and_then(|v| v.get("ok")).and_then(|v| v.as_bool());
+    let checked = last
+        .and_then(|v| v.get("checked_at"))
+        .and_then(|v| v.as_i64());
+    match (ok, checked) {
+        (Some(true), Some(at)) => now - at >= CHECK_EVERY_SECS || now < at,
+        _ => true,
+    }
+}
+
+// Stage the outcome of a check at `now`.
+pub fn record(st: &mut State, token: &str, ok: bool, now: i64) {
+    st.set(
+        &key(token),
+        serde_json::json!({ "ok": ok, "checked_at": now }),
+    );
+}
+
+// Non-reversible identifier for a token: the first 16 hex digits of its
+// SHA-256, so two shells can be compared without printing either key.
+pub fn fingerprint(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(token.as_bytes());
+    let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
+
+// Last recorded check of `token`, if any (`ok`, `checked_at`).
+pub fn last_check(st: &State, token: &str) -> Option<serde_json::Value> {
+    st.get(&key(token)).cloned()
+}
+
+// The API answered 401: the token is wrong, revoked or expired.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenRejected {
+    pub request_id: Option<String>,
+}
+
+impl fmt::Display for TokenRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut err = serde_json::json!({
+            "error": "token invalid or revoked — run pexels auth login",
+            "code": 401,
+            "hint": "check PEXELS_TOKEN / PEXELS_API_KEY, or store a new token with pexels auth login",
+        });
+        if let Some(id) = &self.request_id {
+            err["request_id"] = id.clone().into();
+        }
+        let s = serde_yaml::to_string(&err).map_err(|_| fmt::Error)?;
+        f.write_str(s.trim_end())
+    }
+}
+
+impl std::error::Error for TokenRejected {}
+
+// The API answered 404 for `exists`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFound {
+    pub kind: &'static str,
+    pub id: String,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err = serde_json::json!({
+            "error": format!("{} not found", self.kind),
+            "code": 404,
+            "id": self.id,
+        });
+        let s = serde_yaml::to_string(&err).map_err(|_| fmt::Error)?;
+        f.write_str(s.trim_end())
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+// Exit code for a command that failed with `err`.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if err.downcast_ref::<TokenRejected>().is_some() {
+        EXIT_TOKEN_REJECTED
+    } else if err.downcast_ref::<NotFound>().is_some() {
+        EXIT_NOT_FOUND
+    } else {
+        1
+    }
+}
+
+// HTTP status behind an API error, when it came from a response.
+pub fn http_status(err: &anyhow::Error) -> Option<u16> {
+    if err.downcast_ref::<TokenRejected>().is_some() {
+        return Some(401);
+    }
+    crate::output::error_value(err)
+        .get("code")
+        .and_then(|c| c.as_u64())
+        .and_then(|c| u16::try_from(c).ok())
+}
@@ -0,0 +1,101 @@
+use serde_json::Value as JsonValue;
+
+// Human-readable rendering for YAML output (--human). Only known quantity
+// fields are rewritten; JSON and raw output always stay numeric.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Separators {
+    pub thousands: &'static str,
+    pub decimal: char,
+}
+
+// Digit grouping for a BCP 47 locale such as `de-DE`; English rules otherwise.
+pub fn separators(locale: Option<&str>) -> Separators {
+    let lang = locale
+        .and_then(|l| l.split(['-', '_']).next())
+        .unwrap_or("en")
+        .to_ascii_lowercase();
+    match lang.as_str() {
+        "de" | "es" | "it" | "pt" | "nl" | "id" | "tr" | "da" | "el" => Separators {
+            thousands: ".",
+            decimal: ',',
+        },
+        "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "fi" | "uk" | "hu" => Separators {
+            thousands: "\u{a0}",
+            decimal: ',',
+        },
+        _ => Separators {
+            thousands: ",",
+            decimal: '.',
+        },
+    }
+}
+
+pub fn format_count(n: u64, seps: Separators) -> String {
+    let digits = n.to_string();
+    let head = match digits.len() % 3 {
+        0 => 3,
+        r => r,
+    };
+    let mut out = digits[..head].to_string();
+    for group in digits.as_bytes()[head..].chunks(3) {
+        out.push_str(seps.thousands);
+        out.push_str(std::str::from_utf8(group).unwrap_or_default());
+    }
+    out
+}
+
+// Decimal units (1 MB = 1000^2 bytes), one fractional digit above bytes.
+pub fn format_bytes(n: u64, seps: Separators) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+    if n < 1000 {
+        return format!("{} B", n);
+    }
+    let mut v = n as f64 / 1000.0;
+    let mut unit = 0;
+    while v >= 999.95 && unit < UNITS.len() - 1 {
+        v /= 1000.0;
+        unit += 1;
+    }
+    let s = format!("{:.1}", v).replace('.', &seps.decimal.to_string());
+    format!("{} {}", s, UNITS[unit])
+}
+
+// `m:ss`, or `h:mm:ss` from one hour.
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+fn is_bytes_key(k: &str) -> bool {
+    k == "bytes" || k.ends_with("_bytes") || k == "content_length" || k == "size"
+}
+
+fn is_count_key(k: &str) -> bool {
+    k == "total_results" || k == "count" || k.ends_with("_count")
+}
+
+// Rewrite byte, duration and count fields anywhere in `v`.
+pub fn humanize(v: &JsonValue, seps: Separators) -> JsonValue {
+    match v {
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, val)| {
+                    let out = match val.as_u64() {
+                        Some(n) if is_bytes_key(k) => format_bytes(n, seps).into(),
+                        Some(n) if k == "duration" => format_duration(n).into(),
+                        Some(n) if is_count_key(k) => format_count(n, seps).into(),
+                        _ => humanize(val, seps),
+                    };
+                    (k.clone(), out)
+                })
+                .collect(),
+        ),
+        JsonValue::Array(arr) => JsonValue::Array(arr.iter().map(|x| humanize(x, seps)).collect()),
+        other => other.clone(),
+    }
+}
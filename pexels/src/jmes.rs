@@ -0,0 +1,46 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+
+// --jmes: JMESPath over the final output envelope, or over each item when
+// printing NDJSON.
+
+pub type Compiled = jmespath::Expression<'static>;
+
+// Compile up front so a bad expression fails before any request is made.
+pub fn compile(expr: &str) -> Result<Compiled> {
+    jmespath::compile(expr).map_err(|e| jmes_error("invalid --jmes expression", &e))
+}
+
+pub fn search(expr: &Compiled, data: &JsonValue) -> Result<JsonValue> {
+    let found = expr
+        .search(data)
+        .map_err(|e| jmes_error("--jmes evaluation failed", &e))?;
+    Ok(serde_json::to_value(&*found)?)
+}
+
+// Apply to every item of a list, dropping null results (so `[?...]`-style
+// filters and missing fields thin the stream rather than printing `null`).
+pub fn search_items(expr: &Compiled, items: &[JsonValue]) -> Result<Vec<JsonValue>> {
+    let mut out = vec![];
+    for item in items {
+        let v = search(expr, item)?;
+        if !v.is_null() {
+            out.push(v);
+        }
+    }
+    Ok(out)
+}
+
+fn jmes_error(what: &str, e: &jmespath::JmespathError) -> anyhow::Error {
+    let line = e.expression.lines().nth(e.line).unwrap_or(&e.expression);
+    let err = serde_json::json!({
+        "error": what,
+        "expression": e.expression,
+        "position": e.offset,
+        "line": e.line + 1,
+        "column": e.column + 1,
+        "reason": e.reason.to_string(),
+        "at": format!("{}\n{}^", line, " ".repeat(e.column)),
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| what.to_string()))
+}
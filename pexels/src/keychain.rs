@@ -0,0 +1,68 @@
+use anyhow::Result;
+
+// API tokens kept in the OS keychain (`token_storage: keyring`): Keychain on
+// macOS, Credential Manager on Windows, the Secret Service elsewhere. Each
+// token is one entry under service "pexels"; the account is the config
+// profile name, "default" for the top-level token.
+
+pub const SERVICE: &str = "pexels";
+
+// Keychain account for the token of `profile` (None: top level).
+pub fn account(profile: Option<&str>) -> &str {
+    profile.unwrap_or("default")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn entry(account: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, account).map_err(|e| unavailable(&e.to_string()))
+}
+
+// Stored token for `account`; None when there is no entry.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get(account: &str) -> Result<Option<String>> {
+    match entry(account)?.get_password() {
+        Ok(t) => Ok(Some(t)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(unavailable(&e.to_string())),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set(account: &str, token: &str) -> Result<()> {
+    entry(account)?
+        .set_password(token)
+        .map_err(|e| unavailable(&e.to_string()))
+}
+
+// Remove the entry for `account`; a missing entry is not an error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn delete(account: &str) -> Result<()> {
+    match entry(account)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(unavailable(&e.to_string())),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn get(_account: &str) -> Result<Option<String>> {
+    Err(unavailable("no keychain on this target"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn set(_account: &str, _token: &str) -> Result<()> {
+    Err(unavailable("no keychain on this target"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn delete(_account: &str) -> Result<()> {
+    Err(unavailable("no keychain on this target"))
+}
+
+fn unavailable(reason: &str) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "keychain unavailable",
+        "reason": reason,
+        "hint": "unlock the keychain, or store the token in the config file: pexels config set token_storage file",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "keychain unavailable".into()))
+}
@@ -1,6 +1,50 @@
+pub mod analyze;
 pub mod api;
+pub mod audit;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+pub mod cache;
 pub mod cli;
 pub mod config;
+pub mod contrast;
+pub mod cursor;
+pub mod dataset;
+pub mod download;
+pub mod examples;
+pub mod expr;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
+pub mod health;
+pub mod human;
+pub mod jmes;
+pub mod keychain;
+pub mod lock;
+pub mod mirror;
+pub mod models;
+pub mod naming;
+pub mod normalize;
+pub mod optimize;
 pub mod output;
+pub mod pages;
+pub mod palette;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod pick;
+pub mod post;
+pub mod preview;
+pub mod progress;
 pub mod proj;
+pub mod query;
+pub mod queue;
+pub mod quota;
+pub mod resume;
+pub mod settings;
+pub mod sink;
+pub mod state;
+pub mod suggest;
+pub mod support;
+pub mod tui;
 pub mod util;
+pub mod video;
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(not(target_arch = "wasm32"))]
+use fs2::FileExt;
+
+// How often a waiting process re-checks a contended lock.
+const POLL: Duration = Duration::from_millis(200);
+
+// How long config/state writers wait for a contended lock (--wait-lock).
+static DEFAULT_WAIT_MS: AtomicU64 = AtomicU64::new(10_000);
+
+pub fn set_default_wait(d: Duration) {
+    DEFAULT_WAIT_MS.store(d.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn default_wait() -> Duration {
+    Duration::from_millis(DEFAULT_WAIT_MS.load(Ordering::Relaxed))
+}
+
+// Exclusive advisory lock held for the lifetime of the value. Lock files are
+// never deleted: removing them while another process waits on the same inode
+// would let two holders coexist.
+pub struct FileLock {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    file: File,
+}
+
+impl FileLock {
+    // Try once; Ok(None) when another process holds the lock.
+    pub fn try_acquire(path: &Path) -> Result<Option<FileLock>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("create lock dir")?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("open lock {}", path.display()))?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if file.try_lock_exclusive().is_err() {
+            return Ok(None);
+        }
+        Ok(Some(FileLock { file }))
+    }
+
+    // Synchronous variant of `acquire` for short critical sections
+    // (config and state writes); errors when `wait` elapses.
+    pub fn acquire_blocking(path: &Path, wait: Duration) -> Result<FileLock> {
+        let start = Instant::now();
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(lock);
+            }
+            if start.elapsed() >= wait {
+                return Err(locked_error(path, wait));
+            }
+            std::thread::sleep(POLL.min(wait));
+        }
+    }
+
+    // Poll until acquired or `wait` elapses (None waits forever).
+    // Ok(None) means the wait timed out.
+    pub async fn acquire(path: &Path, wait: Option<Duration>) -> Result<Option<FileLock>> {
+        let start = Instant::now();
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(Some(lock));
+            }
+            if wait.map(|w| start.elapsed() >= w).unwrap_or(false) {
+                return Ok(None);
+            }
+            crate::util::sleep(POLL).await;
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+// Lock file fencing writes to `dest`. Lives under the data dir (keyed by the
+// absolute destination) so download directories stay free of lock files.
+pub fn lock_path_for(dest: &Path) -> PathBuf {
+    let abs = absolute(dest);
+    let key = crate::util::fnv1a64(abs.to_string_lossy().as_bytes());
+    crate::util::data_dir()
+        .join("locks")
+        .join(format!("{:016x}.lock", key))
+}
+
+// Absolute form of a path that may not exist yet (parent canonicalized when possible).
+fn absolute(p: &Path) -> PathBuf {
+    let joined = if p.is_absolute() {
+        p.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|d| d.join(p))
+            .unwrap_or_else(|_| p.to_path_buf())
+    };
+    match (joined.parent(), joined.file_name()) {
+        (Some(dir), Some(name)) => fs::canonicalize(dir)
+            .map(|d| d.join(name))
+            .unwrap_or(joined),
+        _ => joined,
+    }
+}
+
+fn locked_error(path: &Path, wait: Duration) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "lock busy",
+        "lock": path.display().to_string(),
+        "waited_secs": wait.as_secs_f64(),
+        "hint": "another pexels process is updating this file; retry or raise --wait-lock",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "lock busy".into()))
+}
@@ -1,17 +1,12 @@
-mod api;
-mod cli;
-mod config;
-mod output;
-mod proj;
-mod util;
-
 use anyhow::Result;
-use clap::Parser;
+use clap::FromArgMatches;
+use pexels::{cli, output};
 use tracing_subscriber::{fmt, EnvFilter};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = cli::Cli::parse();
+    let cli =
+        cli::Cli::from_arg_matches(&cli::command().get_matches()).unwrap_or_else(|e| e.exit());
 
     // Init logging to stderr only
     let filter = if cli.debug {
@@ -34,7 +29,12 @@ async fn main() -> Result<()> {
 
     if let Err(err) = cli::run(cli).await {
         // Render structured error to stderr
-        crate::output::emit_error(&err)?;
+        output::emit_error(&err)?;
+        // Scripts can tell a rejected token (3) from other failures
+        let code = pexels::health::exit_code(&err);
+        if code != 1 {
+            std::process::exit(code);
+        }
         // Ensure non-zero exit via anyhow error
         return Err(err);
     }
@@ -0,0 +1,596 @@
+use crate::config::RemoteAuth;
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// Extra destinations for batch downloads (`--dest`, repeatable). Each file
+// is fetched once into the primary directory and then copied or uploaded to
+// every destination as soon as it lands, so a second location costs no quota
+// or CDN bandwidth. Uploads stream from disk.
+//
+// - a plain path: copied into that directory
+// - `s3://bucket/prefix`: PUT with AWS SigV4 from AWS_ACCESS_KEY_ID,
+//   AWS_SECRET_ACCESS_KEY (+ AWS_SESSION_TOKEN), AWS_REGION (default
+//   us-east-1); AWS_ENDPOINT_URL switches to path-style requests against an
+//   S3-compatible store
+// - `webdav://[user:pass@]host/path`: HTTPS PUT (`webdav+http://` for plain
+//   HTTP) with basic auth
+// - `sftp://[user@]host[:port]/path`: the system `sftp` client in batch
+//   mode, so keys, the SSH agent, ~/.ssh/config and known_hosts all apply
+//
+// WebDAV and SFTP credentials come from the URL, else the environment
+// (PEXELS_WEBDAV_USER/PEXELS_WEBDAV_PASSWORD, PEXELS_SFTP_USER/
+// PEXELS_SFTP_IDENTITY), else `remotes.<host>` in the config.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dest {
+    Local(PathBuf),
+    S3 { bucket: String, prefix: String },
+    WebDav(url::Url),
+    Sftp(SftpTarget),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SftpTarget {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    // Remote directory; empty means the login directory
+    pub path: String,
+}
+
+impl fmt::Display for Dest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dest::Local(p) => write!(f, "{}", p.display()),
+            Dest::S3 { bucket, prefix } if prefix.is_empty() => write!(f, "s3://{}", bucket),
+            Dest::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+            Dest::WebDav(u) => {
+                // Never echo credentials
+                let mut u = u.clone();
+                let _ = u.set_password(None);
+                let _ = u.set_username("");
+                write!(f, "{}", u)
+            }
+            Dest::Sftp(t) => {
+                write!(f, "sftp://")?;
+                if let Some(user) = &t.user {
+                    write!(f, "{}@", user)?;
+                }
+                write!(f, "{}", t.host)?;
+                if let Some(port) = t.port {
+                    write!(f, ":{}", port)?;
+                }
+                write!(f, "/{}", t.path.trim_start_matches('/'))
+            }
+        }
+    }
+}
+
+pub fn parse_dest(s: &str) -> Result<Dest, String> {
+    if let Some(rest) = s.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(format!("missing bucket in {}", s));
+        }
+        return Ok(Dest::S3 {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        });
+    }
+    for (scheme, http) in [("webdav+http://", "http://"), ("webdav://", "https://")] {
+        if let Some(rest) = s.strip_prefix(scheme) {
+            let url = url::Url::parse(&format!("{}{}", http, rest))
+                .map_err(|e| format!("invalid WebDAV URL {}: {}", s, e))?;
+            return Ok(Dest::WebDav(url));
+        }
+    }
+    if s.starts_with("sftp://") {
+        let url = url::Url::parse(s).map_err(|e| format!("invalid SFTP URL {}: {}", s, e))?;
+        let host = url
+            .host_str()
+            .filter(|h| !h.is_empty())
+            .ok_or_else(|| format!("missing host in {}", s))?;
+        if url.password().is_some() {
+            return Err(format!(
+                "passwords are not supported in {}; use an SSH key or agent",
+                s
+            ));
+        }
+        let path = percent_decode(url.path());
+        return Ok(Dest::Sftp(SftpTarget {
+            user: Some(url.username().to_string()).filter(|u| !u.is_empty()),
+            host: host.to_string(),
+            port: url.port(),
+            path: if path == "/" { String::new() } else { path },
+        }));
+    }
+    if s.contains("://") {
+        return Err(format!(
+            "unsupported destination {} (use a directory, s3://bucket/prefix, webdav://host/path or sftp://host/path)",
+            s
+        ));
+    }
+    Ok(Dest::Local(PathBuf::from(s)))
+}
+
+fn percent_decode(s: &str) -> String {
+    url::form_urlencoded::parse(format!("p={}", s.replace('+', "%2B")).as_bytes())
+        .next()
+        .map(|(_, v)| v.into_owned())
+        .unwrap_or_else(|| s.to_string())
+}
+
+// Copies files to destinations: an HTTP client for uploads (no API token or
+// Pexels headers) plus the configured `remotes` credentials.
+pub struct Uploader {
+    http: reqwest::Client,
+    remotes: BTreeMap<String, RemoteAuth>,
+}
+
+impl Uploader {
+    pub fn new(remotes: BTreeMap<String, RemoteAuth>) -> Result<Self> {
+        let builder = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(std::time::Duration::from_secs(300));
+        Ok(Self {
+            http: builder.build()?,
+            remotes,
+        })
+    }
+
+    // Copy or upload `file` to `dest` under its file name; returns where it went.
+    pub async fn put(&self, dest: &Dest, file: &Path) -> Result<String> {
+        let name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .context("file has no name")?;
+        match dest {
+            Dest::Local(dir) => {
+                std::fs::create_dir_all(dir).context("create mirror dir")?;
+                let target = dir.join(&name);
+                let tmp = crate::download::part_path(&target);
+                std::fs::copy(file, &tmp).context("copy to mirror")?;
+                std::fs::rename(&tmp, &target).context("finish mirror copy")?;
+                Ok(target.display().to_string())
+            }
+            Dest::S3 { bucket, prefix } => {
+                let key = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let creds = S3Credentials::from_env()?;
+                let hash = sha256_file(file)?;
+                let (url, headers) =
+                    s3_put_request(&creds, bucket, &key, &hash, chrono::Utc::now());
+                let (body, len) = file_body(file)?;
+                let mut req = self
+                    .http
+                    .put(&url)
+                    .header(reqwest::header::CONTENT_LENGTH, len)
+                    .body(body);
+                for (k, v) in headers {
+                    req = req.header(k, v);
+                }
+                check(req.send().await?).await?;
+                Ok(format!("s3://{}/{}", bucket, key))
+            }
+            Dest::WebDav(base) => {
+                let mut url = base.clone();
+                url.path_segments_mut()
+                    .map_err(|_| anyhow::anyhow!("invalid WebDAV URL"))?
+                    .pop_if_empty()
+                    .push(&name);
+                let auth = webdav_credentials(&url, &self.remotes);
+                let _ = url.set_password(None);
+                let _ = url.set_username("");
+                let (body, len) = file_body(file)?;
+                let mut req = self
+                    .http
+                    .put(url.clone())
+                    .header(reqwest::header::CONTENT_LENGTH, len)
+                    .body(body);
+                if let Some((user, pass)) = auth {
+                    req = req.basic_auth(user, pass);
+                }
+                check(req.send().await?).await?;
+                Ok(Dest::WebDav(url).to_string())
+            }
+            Dest::Sftp(target) => {
+                let target = sftp_credentials(target, &self.remotes);
+                let identity = sftp_identity(&target, &self.remotes);
+                let (args, script) = sftp_batch(&target, identity.as_deref(), file, &name);
+                run_sftp(args, script).await?;
+                let dir = target.path.trim_end_matches('/');
+                let path = if dir.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", dir, name)
+                };
+                Ok(Dest::Sftp(SftpTarget { path, ..target }).to_string())
+            }
+        }
+    }
+
+    // Upload `file` to every destination, in order: `[{dest, ok, location|error}]`.
+    pub async fn put_all(&self, dests: &[Dest], file: &Path) -> Vec<JsonValue> {
+        let mut out = vec![];
+        for dest in dests {
+            out.push(match self.put(dest, file).await {
+                Ok(location) => serde_json::json!({
+                    "dest": dest.to_string(),
+                    "ok": true,
+                    "location": location,
+                }),
+                Err(e) => {
+                    let mut v = serde_json::json!({ "dest": dest.to_string(), "ok": false });
+                    v["error"] = crate::output::error_value(&e);
+                    v
+                }
+            });
+        }
+        out
+    }
+}
+
+fn env(k: &str) -> Option<String> {
+    std::env::var(k).ok().filter(|v| !v.is_empty())
+}
+
+// Basic-auth pair for a WebDAV URL: its userinfo, else
+// PEXELS_WEBDAV_USER/PEXELS_WEBDAV_PASSWORD, else `remotes.<host>`.
+pub fn webdav_credentials(
+    url: &url::Url,
+    remotes: &BTreeMap<String, RemoteAuth>,
+) -> Option<(String, Option<String>)> {
+    if !url.username().is_empty() {
+        let pass = url.password().map(percent_decode);
+        return Some((percent_decode(url.username()), pass));
+    }
+    if let Some(user) = env("PEXELS_WEBDAV_USER") {
+        return Some((user, env("PEXELS_WEBDAV_PASSWORD")));
+    }
+    let remote = remotes.get(url.host_str()?)?;
+    Some((remote.user.clone()?, remote.password.clone()))
+}
+
+// `target` with its user filled in from PEXELS_SFTP_USER or
+// `remotes.<host>.user` when the URL has none (else ssh's own default).
+pub fn sftp_credentials(target: &SftpTarget, remotes: &BTreeMap<String, RemoteAuth>) -> SftpTarget {
+    let user = target
+        .user
+        .clone()
+        .or_else(|| env("PEXELS_SFTP_USER"))
+        .or_else(|| remotes.get(&target.host).and_then(|r| r.user.clone()));
+    SftpTarget {
+        user,
+        ..target.clone()
+    }
+}
+
+// Private key for an SFTP host: PEXELS_SFTP_IDENTITY, else
+// `remotes.<host>.identity`; None leaves it to the agent and ~/.ssh/config.
+fn sftp_identity(target: &SftpTarget, remotes: &BTreeMap<String, RemoteAuth>) -> Option<PathBuf> {
+    env("PEXELS_SFTP_IDENTITY")
+        .map(PathBuf::from)
+        .or_else(|| remotes.get(&target.host).and_then(|r| r.identity.clone()))
+}
+
+// `sftp` arguments and batch script that upload `file` as `<path>/<name>`:
+// missing directories are created, and the file lands under a `.part` name
+// first and then replaces any previous copy.
+pub fn sftp_batch(
+    target: &SftpTarget,
+    identity: Option<&Path>,
+    file: &Path,
+    name: &str,
+) -> (Vec<String>, String) {
+    let mut args: Vec<String> = ["-q", "-b", "-", "-o", "BatchMode=yes"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    if let Some(port) = target.port {
+        args.extend(["-P".to_string(), port.to_string()]);
+    }
+    if let Some(identity) = identity {
+        args.extend(["-i".to_string(), identity.display().to_string()]);
+    }
+    args.push(match &target.user {
+        Some(user) => format!("{}@{}", user, target.host),
+        None => target.host.clone(),
+    });
+    let dir = target.path.trim_end_matches('/');
+    let mut script = String::new();
+    let mut prefix = if dir.starts_with('/') { "/" } else { "" }.to_string();
+    for part in dir.split('/').filter(|p| !p.is_empty()) {
+        prefix.push_str(part);
+        script.push_str(&format!("-mkdir {}\n", quote(&prefix)));
+        prefix.push('/');
+    }
+    let remote = if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    };
+    let part = format!("{}.part", remote);
+    script.push_str(&format!(
+        "put {} {}\n-rm {}\nrename {} {}\n",
+        quote(&file.display().to_string()),
+        quote(&part),
+        quote(&remote),
+        quote(&part),
+        quote(&remote)
+    ));
+    (args, script)
+}
+
+// Double-quoted sftp batch argument.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn run_sftp(args: Vec<String>, script: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+        let mut child = Command::new("sftp")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                sftp_error(serde_json::json!({
+                    "error": "sftp failed to start",
+                    "reason": e.to_string(),
+                    "hint": "install the OpenSSH sftp client",
+                }))
+            })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(script.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(sftp_error(serde_json::json!({
+                "error": "upload failed",
+                "status": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr).trim(),
+            })));
+        }
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn run_sftp(_args: Vec<String>, _script: String) -> Result<()> {
+    anyhow::bail!("sftp destinations are not supported on this target")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn sftp_error(err: JsonValue) -> anyhow::Error {
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "sftp failed".into()))
+}
+
+// Request body streaming `path` from disk, and its length.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_body(path: &Path) -> Result<(reqwest::Body, u64)> {
+    use std::io::Read;
+    let file = std::fs::File::open(path).context("read file for upload")?;
+    let len = file.metadata()?.len();
+    let chunks = futures::stream::unfold(Some(file), |file| async move {
+        let mut file = file?;
+        let mut buf = vec![0u8; 64 * 1024];
+        match file.read(&mut buf) {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(buf), Some(file)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    Ok((reqwest::Body::wrap_stream(chunks), len))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn file_body(path: &Path) -> Result<(reqwest::Body, u64)> {
+    let body = std::fs::read(path).context("read file for upload")?;
+    let len = body.len() as u64;
+    Ok((body.into(), len))
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path).context("read file for upload")?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).context("read file for upload")?;
+    Ok(hex(&hasher.finalize()))
+}
+
+async fn check(resp: reqwest::Response) -> Result<()> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = resp.text().await.unwrap_or_default();
+    let err = serde_json::json!({
+        "error": "upload failed",
+        "code": status.as_u16(),
+        "reason": status.canonical_reason().unwrap_or("error"),
+        "body": body.chars().take(500).collect::<String>(),
+    });
+    Err(anyhow::anyhow!(
+        serde_yaml::to_string(&err).unwrap_or_else(|_| format!("upload failed: {}", status))
+    ))
+}
+
+// Per-destination totals over every item's `mirrors` list.
+pub fn tally(items: &[JsonValue]) -> JsonValue {
+    let mut totals: BTreeMap<String, (u64, u64)> = Default::default();
+    for m in items
+        .iter()
+        .filter_map(|i| i.get("mirrors").and_then(|m| m.as_array()))
+        .flatten()
+    {
+        let dest = m.get("dest").and_then(|d| d.as_str()).unwrap_or_default();
+        let entry = totals.entry(dest.to_string()).or_default();
+        if m.get("ok").and_then(|o| o.as_bool()) == Some(true) {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(dest, (ok, failed))| {
+            serde_json::json!({ "dest": dest, "succeeded": ok, "failed": failed })
+        })
+        .collect()
+}
+
+pub struct S3Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+    // Path-style endpoint for S3-compatible stores (MinIO, R2, ...)
+    pub endpoint: Option<String>,
+}
+
+impl S3Credentials {
+    pub fn from_env() -> Result<Self> {
+        let var = |k: &str| std::env::var(k).ok().filter(|v| !v.is_empty());
+        let (Some(access_key), Some(secret_key)) =
+            (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY"))
+        else {
+            anyhow::bail!("s3 destination needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY");
+        };
+        Ok(Self {
+            access_key,
+            secret_key,
+            session_token: var("AWS_SESSION_TOKEN"),
+            region: var("AWS_REGION")
+                .or_else(|| var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|| "us-east-1".into()),
+            endpoint: var("AWS_ENDPOINT_URL").map(|e| e.trim_end_matches('/').to_string()),
+        })
+    }
+}
+
+// URL and signed headers (SigV4) for a single-part S3 PutObject whose body
+// has the hex SHA-256 `payload`.
+pub fn s3_put_request(
+    creds: &S3Credentials,
+    bucket: &str,
+    key: &str,
+    payload: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> (String, Vec<(String, String)>) {
+    let (base, host, path) = match &creds.endpoint {
+        Some(ep) => {
+            let host = ep
+                .split_once("://")
+                .map_or(ep.as_str(), |(_, h)| h)
+                .to_string();
+            (ep.clone(), host, format!("/{}/{}", bucket, uri_encode(key)))
+        }
+        None => {
+            let host = format!("{}.s3.{}.amazonaws.com", bucket, creds.region);
+            (
+                format!("https://{}", host),
+                host,
+                format!("/{}", uri_encode(key)),
+            )
+        }
+    };
+    let mut headers = vec![
+        ("host".to_string(), host),
+        ("x-amz-content-sha256".to_string(), payload.to_string()),
+        (
+            "x-amz-date".to_string(),
+            now.format("%Y%m%dT%H%M%SZ").to_string(),
+        ),
+    ];
+    if let Some(t) = &creds.session_token {
+        headers.push(("x-amz-security-token".to_string(), t.clone()));
+    }
+    let auth = sigv4_authorization(creds, "s3", "PUT", &path, &headers, payload, now);
+    headers.push(("authorization".to_string(), auth));
+    // reqwest sets Host itself
+    headers.retain(|(k, _)| k != "host");
+    (format!("{}{}", base, path), headers)
+}
+
+// SigV4 `Authorization` value for a request with an empty query string.
+// `path` is already URI-encoded, `headers` are the signed ones (lowercase
+// names, sorted) and `payload` is the hex SHA-256 of the body.
+pub fn sigv4_authorization(
+    creds: &S3Credentials,
+    service: &str,
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    payload: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let signed: Vec<&str> = headers.iter().map(|(k, _)| k.as_str()).collect();
+    let signed = signed.join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+        .collect();
+    let canonical = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method, path, canonical_headers, signed, payload
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, creds.region, service);
+    let to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical.as_bytes()))
+    );
+    let k_date = hmac_sha256(
+        format!("AWS4{}", creds.secret_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let k_region = hmac_sha256(&k_date, creds.region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex(&hmac_sha256(&k_signing, to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        creds.access_key, scope, signed, signature
+    )
+}
+
+// RFC 2104 HMAC over SHA-256.
+pub fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(msg);
+    mac.finalize().into_bytes().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// SigV4 URI encoding of an object key; `/` separates segments and is kept.
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+// Typed views over Pexels API resources. Fields default when absent so that
+// partial responses (and future API additions) deserialize without errors.
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Photo {
+    pub id: u64,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub photographer: String,
+    #[serde(default)]
+    pub photographer_url: String,
+    #[serde(default)]
+    pub photographer_id: u64,
+    #[serde(default)]
+    pub avg_color: Option<String>,
+    #[serde(default)]
+    pub src: PhotoSrc,
+    #[serde(default)]
+    pub liked: bool,
+    #[serde(default)]
+    pub alt: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PhotoSrc {
+    #[serde(default)]
+    pub original: String,
+    #[serde(default)]
+    pub large2x: String,
+    #[serde(default)]
+    pub large: String,
+    #[serde(default)]
+    pub medium: String,
+    #[serde(default)]
+    pub small: String,
+    #[serde(default)]
+    pub portrait: String,
+    #[serde(default)]
+    pub landscape: String,
+    #[serde(default)]
+    pub tiny: String,
+}
+
+// One page of a list endpoint.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Page<T> {
+    pub page: u32,
+    pub per_page: u32,
+    pub total_results: Option<u64>,
+    pub next_page: Option<String>,
+    pub prev_page: Option<String>,
+    pub items: Vec<T>,
+}
+
+// Search and list responses; an alias so list endpoints share one shape.
+pub type SearchResult<T> = Page<T>;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Video {
+    pub id: u64,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub image: String,
+    // seconds
+    #[serde(default)]
+    pub duration: u32,
+    #[serde(default)]
+    pub user: VideoUser,
+    #[serde(default)]
+    pub video_files: Vec<VideoFile>,
+    #[serde(default)]
+    pub video_pictures: Vec<VideoPicture>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VideoUser {
+    #[serde(default)]
+    pub id: u64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+// One rendition of a video. HLS entries come back without quality or size.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VideoFile {
+    #[serde(default)]
+    pub id: u64,
+    #[serde(default)]
+    pub quality: Option<String>,
+    #[serde(default)]
+    pub file_type: String,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub fps: Option<f64>,
+    #[serde(default)]
+    pub link: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct VideoPicture {
+    #[serde(default)]
+    pub id: u64,
+    #[serde(default)]
+    pub picture: String,
+    #[serde(default)]
+    pub nr: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Collection {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub media_count: u64,
+    #[serde(default)]
+    pub photos_count: u64,
+    #[serde(default)]
+    pub videos_count: u64,
+}
+
+// An entry of a collection's media list, tagged by its `type` field.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum Media {
+    Photo(Photo),
+    Video(Video),
+}
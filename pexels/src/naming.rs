@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+// Characters that are invalid in file names on Windows (and `/` everywhere).
+const RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+// Device names Windows refuses as file stems regardless of extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// Most filesystems cap a single component at 255 bytes.
+const MAX_COMPONENT_BYTES: usize = 255;
+
+// Paths at or beyond MAX_PATH need the `\\?\` prefix on Windows.
+const WINDOWS_MAX_PATH: usize = 260;
+
+// Sanitize a single path component so it is valid on every supported OS:
+// reserved/control characters become `_`, trailing dots/spaces are trimmed,
+// device names are prefixed, and the result is capped at 255 bytes while
+// keeping the extension.
+pub fn sanitize_component(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if RESERVED_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let trimmed = out.trim_end_matches(['.', ' ']).len();
+    out.truncate(trimmed);
+    if out.is_empty() {
+        return "_".into();
+    }
+    if is_reserved_name(&out) {
+        out.insert(0, '_');
+    }
+    truncate_component(&out, MAX_COMPONENT_BYTES)
+}
+
+// True when the stem (text before the first dot) is a Windows device name.
+pub fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem))
+}
+
+fn truncate_component(name: &str, max: usize) -> String {
+    if name.len() <= max {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(i) if i > 0 && name.len() - i <= 16 => (&name[..i], &name[i..]),
+        _ => (name, ""),
+    };
+    let mut budget = max.saturating_sub(ext.len());
+    while !stem.is_char_boundary(budget) {
+        budget -= 1;
+    }
+    format!("{}{}", &stem[..budget], ext)
+}
+
+// Join a rendered relative path (which may use `/` or `\` as separators) onto
+// `base`, sanitizing every component. Empty, `.` and `..` components are
+// dropped so a template can never escape the base directory.
+pub fn join_rendered(base: &Path, rendered: &str) -> PathBuf {
+    let mut out = base.to_path_buf();
+    for part in rendered.split(['/', '\\']) {
+        let part = part.trim();
+        if part.is_empty() || part == "." || part == ".." {
+            continue;
+        }
+        out.push(sanitize_component(part));
+    }
+    out
+}
+
+// Lowercase ASCII slug: alphanumerics kept, runs of anything else become `-`.
+pub fn slug(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') && !out.is_empty() {
+            out.push('-');
+        }
+    }
+    while out.ends_with('-') {
+        out.pop();
+    }
+    out
+}
+
+// Variables a `--name-template` may use.
+pub const TEMPLATE_VARS: &[&str] = &[
+    "id",
+    "kind",
+    "photographer",
+    "photographer_slug",
+    "photographer_id",
+    "width",
+    "height",
+    "alt",
+    "alt_slug",
+    "ext",
+];
+
+// Reject unknown `{var}`s before any request is made.
+pub fn check_template(template: &str) -> anyhow::Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let tail = &rest[start + 1..];
+        let Some(end) = tail.find('}') else {
+            return Err(template_error(template, "unclosed `{`"));
+        };
+        let var = &tail[..end];
+        if !TEMPLATE_VARS.contains(&var) {
+            return Err(template_error(
+                template,
+                &format!("unknown variable {{{}}}", var),
+            ));
+        }
+        rest = &tail[end + 1..];
+    }
+    Ok(())
+}
+
+fn template_error(template: &str, problem: &str) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "invalid name template",
+        "template": template,
+        "problem": problem,
+        "allowed": TEMPLATE_VARS.iter().map(|v| format!("{{{}}}", v)).collect::<Vec<_>>(),
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "invalid name template".into()))
+}
+
+// Fill a checked template from a photo/video record. Creator fields come from
+// the normalized record (photographer for photos, user for videos); missing
+// values render empty. The result is a relative path for `join_rendered`.
+pub fn render_template(template: &str, item: &serde_json::Value, ext: &str) -> String {
+    let n = crate::normalize::normalize(item);
+    let text = |v: &serde_json::Value| match v {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let photographer = text(&n["creator"]["name"]);
+    let alt = text(&n["title"]);
+    let vars = [
+        ("id", text(&n["id"])),
+        ("kind", text(&n["kind"])),
+        ("photographer_slug", slug(&photographer)),
+        ("photographer_id", text(&n["creator"]["id"])),
+        ("photographer", photographer),
+        ("width", text(&n["width"])),
+        ("height", text(&n["height"])),
+        ("alt_slug", slug(&alt)),
+        ("alt", alt),
+        ("ext", ext.to_string()),
+    ];
+    let mut out = template.to_string();
+    for (k, v) in vars {
+        out = out.replace(&format!("{{{}}}", k), &v);
+    }
+    out
+}
+
+// Lowercase file extension of a URL path (`.../x.JPEG?w=1` -> `jpeg`).
+pub fn ext_from_url(url: &str) -> Option<String> {
+    let u = url::Url::parse(url).ok()?;
+    let last = u.path().rsplit('/').next()?.to_string();
+    last.rsplit_once('.')
+        .map(|(_, e)| e.to_ascii_lowercase())
+        .filter(|e| !e.is_empty() && e.len() <= 5 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+// Extension for a media Content-Type (`image/jpeg; q=1` -> `jpg`).
+pub fn ext_from_content_type(ct: &str) -> Option<&'static str> {
+    let mime = ct.split(';').next()?.trim().to_ascii_lowercase();
+    Some(match mime.as_str() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        "image/avif" => "avif",
+        "image/heic" => "heic",
+        "video/mp4" => "mp4",
+        "video/quicktime" => "mov",
+        "video/webm" => "webm",
+        _ => return None,
+    })
+}
+
+// On Windows, prefix long absolute paths with `\\?\` so file APIs accept them.
+// Elsewhere the path is returned unchanged.
+pub fn long_path(p: &Path) -> PathBuf {
+    if cfg!(windows) {
+        windows_long_path(p)
+    } else {
+        p.to_path_buf()
+    }
+}
+
+fn windows_long_path(p: &Path) -> PathBuf {
+    let s = p.to_string_lossy();
+    if s.len() < WINDOWS_MAX_PATH || s.starts_with(r"\\?\") {
+        return p.to_path_buf();
+    }
+    let normalized = s.replace('/', "\\");
+    if let Some(unc) = normalized.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", unc))
+    } else if normalized.as_bytes().get(1) == Some(&b':') {
+        PathBuf::from(format!(r"\\?\{}", normalized))
+    } else {
+        // Relative paths cannot carry the prefix
+        p.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_long_path_prefix() {
+        let long = format!(r"C:\{}\file.jpg", "a".repeat(300));
+        let out = windows_long_path(Path::new(&long));
+        assert!(out.to_string_lossy().starts_with(r"\\?\C:\"));
+        let unc = format!(r"\\server\share\{}", "b".repeat(300));
+        let out = windows_long_path(Path::new(&unc));
+        assert!(out.to_string_lossy().starts_with(r"\\?\UNC\server\share"));
+        let short = r"C:\x\file.jpg";
+        assert_eq!(windows_long_path(Path::new(short)), PathBuf::from(short));
+    }
+}
@@ -0,0 +1,135 @@
+use serde_json::{json, Value};
+
+// Canonical record shared by photos, videos, collections and collection media:
+// { id, kind, title, creator{name,url,id}, width, height, duration,
+//   urls{page,file,thumb} }. Missing values are null so every record has the
+// same columns in tabular output.
+//
+// Table formats (csv, tsv, md, parquet) print these records unless --fields
+// or --full pick from the API shape, so a row means the same for every kind.
+
+// Flattened columns of a canonical record, in table order.
+pub const COLUMNS: [&str; 12] = [
+    "id",
+    "kind",
+    "title",
+    "creator.name",
+    "creator.url",
+    "creator.id",
+    "width",
+    "height",
+    "duration",
+    "urls.page",
+    "urls.file",
+    "urls.thumb",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Photo,
+    Video,
+    Collection,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Photo => "photo",
+            Kind::Video => "video",
+            Kind::Collection => "collection",
+        }
+    }
+}
+
+// Detect the resource kind: collection media carry an explicit `type`,
+// other shapes are recognized by their distinguishing fields.
+pub fn kind_of(item: &Value) -> Kind {
+    match item.get("type").and_then(|t| t.as_str()) {
+        Some(t) if t.eq_ignore_ascii_case("video") => return Kind::Video,
+        Some(t) if t.eq_ignore_ascii_case("photo") => return Kind::Photo,
+        _ => {}
+    }
+    if item.get("video_files").is_some() || item.get("duration").is_some() {
+        Kind::Video
+    } else if item.get("media_count").is_some() || item.get("photos_count").is_some() {
+        Kind::Collection
+    } else {
+        Kind::Photo
+    }
+}
+
+pub fn normalize(item: &Value) -> Value {
+    let kind = kind_of(item);
+    let get = |k: &str| item.get(k).cloned().unwrap_or(Value::Null);
+    match kind {
+        Kind::Photo => json!({
+            "id": get("id"),
+            "kind": kind.as_str(),
+            "title": get("alt"),
+            "creator": {
+                "name": get("photographer"),
+                "url": get("photographer_url"),
+                "id": get("photographer_id"),
+            },
+            "width": get("width"),
+            "height": get("height"),
+            "duration": Value::Null,
+            "urls": {
+                "page": get("url"),
+                "file": item.pointer("/src/original").cloned().unwrap_or(Value::Null),
+                "thumb": item.pointer("/src/tiny").cloned().unwrap_or(Value::Null),
+            },
+        }),
+        Kind::Video => {
+            let user = item.get("user");
+            let user_field = |k: &str| user.and_then(|u| u.get(k)).cloned().unwrap_or(Value::Null);
+            json!({
+                "id": get("id"),
+                "kind": kind.as_str(),
+                "title": Value::Null,
+                "creator": {
+                    "name": user_field("name"),
+                    "url": user_field("url"),
+                    "id": user_field("id"),
+                },
+                "width": get("width"),
+                "height": get("height"),
+                "duration": get("duration"),
+                "urls": {
+                    "page": get("url"),
+                    "file": best_video_file(item).unwrap_or(Value::Null),
+                    "thumb": get("image"),
+                },
+            })
+        }
+        Kind::Collection => json!({
+            "id": get("id"),
+            "kind": kind.as_str(),
+            "title": get("title"),
+            "creator": { "name": Value::Null, "url": Value::Null, "id": Value::Null },
+            "width": Value::Null,
+            "height": Value::Null,
+            "duration": Value::Null,
+            "urls": { "page": Value::Null, "file": Value::Null, "thumb": Value::Null },
+        }),
+    }
+}
+
+// Link of the widest entry in `video_files`.
+fn best_video_file(item: &Value) -> Option<Value> {
+    item.get("video_files")?
+        .as_array()?
+        .iter()
+        .max_by_key(|f| f.get("width").and_then(|w| w.as_u64()).unwrap_or(0))
+        .and_then(|f| f.get("link").cloned())
+}
+
+// Stable identity across kinds (photo and video ids may collide).
+pub fn identity(item: &Value) -> Option<String> {
+    let id = item.get("id")?;
+    let id = id
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| id.to_string());
+    Some(format!("{}:{}", kind_of(item).as_str(), id))
+}
@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+
+// `--web-optimize`: re-encode a downloaded photo as WebP or AVIF next to the
+// original (`photo-1.jpeg` -> `photo-1.webp`). Decoding drops EXIF, ICC
+// profiles and every other metadata block; only an attribution XMP packet
+// (photographer, Pexels page, license) is written back -- inside the WebP
+// container, or as a `photo-1.xmp` sidecar for AVIF.
+
+// Quality when neither --web-quality nor `web.quality` is set.
+pub const DEFAULT_QUALITY: u8 = 80;
+// rav1e speed for AVIF (1 slowest/smallest .. 10 fastest)
+const AVIF_SPEED: u8 = 6;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WebFormat {
+    #[default]
+    Webp,
+    Avif,
+}
+
+impl WebFormat {
+    pub fn ext(&self) -> &'static str {
+        match self {
+            WebFormat::Webp => "webp",
+            WebFormat::Avif => "avif",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub format: WebFormat,
+    // 0-100
+    pub quality: u8,
+}
+
+// Credit written into the optimized file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attribution {
+    pub photographer: Option<String>,
+    pub photographer_url: Option<String>,
+    // Photo page on pexels.com
+    pub source: Option<String>,
+}
+
+impl Attribution {
+    pub fn from_media(item: &JsonValue) -> Self {
+        let s = |k: &str| item.get(k).and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            photographer: s("photographer"),
+            photographer_url: s("photographer_url"),
+            source: s("url"),
+        }
+    }
+
+    // XMP packet: dc:creator, dc:source, dc:rights ("Photo by X on Pexels")
+    // and the license as xmpRights:WebStatement.
+    pub fn xmp(&self) -> String {
+        let mut props = String::new();
+        if let Some(name) = &self.photographer {
+            props.push_str(&format!(
+                "   <dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>\n",
+                escape(name)
+            ));
+            props.push_str(&format!(
+                "   <dc:rights><rdf:Alt><rdf:li xml:lang=\"x-default\">Photo by {} on Pexels</rdf:li></rdf:Alt></dc:rights>\n",
+                escape(name)
+            ));
+        }
+        if let Some(url) = &self.photographer_url {
+            props.push_str(&format!(
+                "   <xmpRights:Owner><rdf:Bag><rdf:li>{}</rdf:li></rdf:Bag></xmpRights:Owner>\n",
+                escape(url)
+            ));
+        }
+        if let Some(url) = &self.source {
+            props.push_str(&format!("   <dc:source>{}</dc:source>\n", escape(url)));
+        }
+        format!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+             <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+             <rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:xmpRights=\"http://ns.adobe.com/xap/1.0/rights/\">\n\
+             {}   <xmpRights:WebStatement>https://www.pexels.com/license/</xmpRights:WebStatement>\n  \
+             </rdf:Description>\n \
+             </rdf:RDF>\n\
+             </x:xmpmeta>\n\
+             <?xpacket end=\"r\"?>",
+            props
+        )
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Where the optimized copy of `original` goes: same stem, new extension
+// (`-web` is added when the original already has it).
+pub fn output_path(original: &Path, format: WebFormat) -> PathBuf {
+    let same = original
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(format.ext()));
+    if !same {
+        return original.with_extension(format.ext());
+    }
+    let stem = original
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    original.with_file_name(format!("{}-web.{}", stem, format.ext()))
+}
+
+// Encode `original` per `settings` and report the savings:
+// `{path, format, quality, original_bytes, bytes, saved_bytes, saved_pct, xmp}`.
+pub fn optimize(original: &Path, settings: Settings, credit: &Attribution) -> Result<JsonValue> {
+    let img = image::ImageReader::open(original)
+        .context("open downloaded photo")?
+        .with_guessed_format()
+        .context("read downloaded photo")?
+        .decode()
+        .map_err(|e| optimize_error(original, &e.to_string()))?;
+    let xmp = credit.xmp();
+    let out = output_path(original, settings.format);
+    let (bytes, sidecar) = match settings.format {
+        WebFormat::Webp => (
+            with_xmp(&encode_webp(&img, settings.quality)?, xmp.as_bytes())?,
+            None,
+        ),
+        WebFormat::Avif => {
+            let mut buf = vec![];
+            let enc = image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                &mut buf,
+                AVIF_SPEED,
+                settings.quality.clamp(1, 100),
+            );
+            img.write_with_encoder(enc)
+                .map_err(|e| optimize_error(original, &e.to_string()))?;
+            (buf, Some(out.with_extension("xmp")))
+        }
+    };
+    write_atomic(&out, &bytes)?;
+    if let Some(path) = &sidecar {
+        write_atomic(path, xmp.as_bytes())?;
+    }
+    let original_bytes = std::fs::metadata(original)?.len();
+    let saved = original_bytes as i64 - bytes.len() as i64;
+    Ok(serde_json::json!({
+        "path": out.display().to_string(),
+        "format": settings.format.ext(),
+        "quality": settings.quality,
+        "original_bytes": original_bytes,
+        "bytes": bytes.len(),
+        "saved_bytes": saved,
+        "saved_pct": percent(saved, original_bytes),
+        "xmp": sidecar.map_or("embedded".to_string(), |p| p.display().to_string()),
+    }))
+}
+
+// Totals over every item's `web` report: `{optimized, failed,
+// original_bytes, bytes, saved_bytes, saved_pct}`.
+pub fn tally(items: &[JsonValue]) -> JsonValue {
+    let (mut optimized, mut failed, mut before, mut after) = (0u64, 0u64, 0u64, 0u64);
+    for web in items.iter().filter_map(|i| i.get("web")) {
+        match (web["original_bytes"].as_u64(), web["bytes"].as_u64()) {
+            (Some(a), Some(b)) => {
+                optimized += 1;
+                before += a;
+                after += b;
+            }
+            _ => failed += 1,
+        }
+    }
+    let saved = before as i64 - after as i64;
+    serde_json::json!({
+        "optimized": optimized,
+        "failed": failed,
+        "original_bytes": before,
+        "bytes": after,
+        "saved_bytes": saved,
+        "saved_pct": percent(saved, before),
+    })
+}
+
+// `part` of `whole` in percent, one decimal.
+fn percent(part: i64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn encode_webp(img: &image::DynamicImage, quality: u8) -> Result<Vec<u8>> {
+    let (w, h) = (img.width(), img.height());
+    let encoded = if img.color().has_alpha() {
+        let rgba = img.to_rgba8();
+        webp::Encoder::from_rgba(&rgba, w, h)
+            .encode_simple(false, quality as f32)
+            .map(|m| m.to_vec())
+    } else {
+        let rgb = img.to_rgb8();
+        webp::Encoder::from_rgb(&rgb, w, h)
+            .encode_simple(false, quality as f32)
+            .map(|m| m.to_vec())
+    };
+    encoded.map_err(|e| anyhow::anyhow!("webp encoding failed: {:?}", e))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn encode_webp(_img: &image::DynamicImage, _quality: u8) -> Result<Vec<u8>> {
+    anyhow::bail!("webp encoding is not supported on this target")
+}
+
+// Add an XMP chunk to a WebP file, converting a simple (VP8/VP8L) file to
+// the extended format the metadata chunk requires.
+pub fn with_xmp(webp: &[u8], xmp: &[u8]) -> Result<Vec<u8>> {
+    if webp.len() < 20 || &webp[0..4] != b"RIFF" || &webp[8..12] != b"WEBP" {
+        anyhow::bail!("not a WebP file");
+    }
+    let chunks = &webp[12..];
+    let mut body = vec![];
+    if &chunks[0..4] == b"VP8X" {
+        body.extend_from_slice(chunks);
+        // XMP flag
+        body[8] |= 0x04;
+    } else {
+        let (w, h) = webp_size(chunks).context("unsupported WebP bitstream")?;
+        let mut vp8x = vec![0u8; 10];
+        vp8x[0] = 0x04;
+        vp8x[4..7].copy_from_slice(&(w - 1).to_le_bytes()[..3]);
+        vp8x[7..10].copy_from_slice(&(h - 1).to_le_bytes()[..3]);
+        push_chunk(&mut body, b"VP8X", &vp8x);
+        body.extend_from_slice(chunks);
+    }
+    push_chunk(&mut body, b"XMP ", xmp);
+    let mut out = Vec::with_capacity(body.len() + 12);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+fn push_chunk(out: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(fourcc);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+// Canvas size from the first chunk of a simple WebP file.
+fn webp_size(chunks: &[u8]) -> Option<(u32, u32)> {
+    let data = chunks.get(8..)?;
+    match chunks.get(0..4)? {
+        // Lossy: frame tag (3 bytes), start code (3), 14-bit width/height
+        b"VP8 " => {
+            let w = u16::from_le_bytes([*data.get(6)?, *data.get(7)?]) & 0x3fff;
+            let h = u16::from_le_bytes([*data.get(8)?, *data.get(9)?]) & 0x3fff;
+            Some((w as u32, h as u32))
+        }
+        // Lossless: signature byte, then 14-bit width-1 and height-1
+        b"VP8L" => {
+            let bits =
+                u32::from_le_bytes([*data.get(1)?, *data.get(2)?, *data.get(3)?, *data.get(4)?]);
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        _ => None,
+    }
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = crate::download::part_path(path);
+    std::fs::write(&tmp, bytes).context("write optimized photo")?;
+    std::fs::rename(&tmp, path).context("write optimized photo")?;
+    Ok(())
+}
+
+fn optimize_error(path: &Path, reason: &str) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "web optimization failed",
+        "path": path.display().to_string(),
+        "reason": reason,
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "web optimization failed".into()))
+}
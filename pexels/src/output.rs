@@ -1,56 +1,311 @@
 use anyhow::Result;
 use serde_json::Value as JsonValue;
 use std::io::{self, Write};
+use std::sync::Mutex;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Yaml,
     Json,
     Raw,
+    Ndjson,
+    Csv,
+    Tsv,
+    // Binary; needs the `parquet` cargo feature
+    Parquet,
+}
+
+impl OutputFormat {
+    // One row per item: csv, tsv and parquet. Rows are canonical records
+    // unless --fields or --full pick API fields (see cli::projector_from_cli).
+    pub fn is_table(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Parquet
+        )
+    }
+
+    // Media type of rendered output, for sinks that label what they deliver.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Yaml => "application/yaml",
+            OutputFormat::Json | OutputFormat::Raw => "application/json",
+            OutputFormat::Ndjson => "application/x-ndjson",
+            OutputFormat::Csv => "text/csv",
+            OutputFormat::Tsv => "text/tab-separated-values",
+            OutputFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+// Destination of command results: stdout unless --sink names a file or an
+// HTTP endpoint (see `crate::sink`). Each sink applies its own retry policy.
+pub trait Sink: Send {
+    // Deliver one rendered result (a whole envelope, or one page of lines
+    // when a walk is streamed).
+    fn write(&mut self, bytes: &[u8], content_type: &str) -> Result<()>;
+}
+
+// Standard output; nothing to retry. A closed pipe surfaces as an
+// `io::Error` so streamed walks can stop early.
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn write(&mut self, bytes: &[u8], _content_type: &str) -> Result<()> {
+        let mut out = io::stdout().lock();
+        out.write_all(bytes)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+// Sink chosen by --sink for this process; stdout when unset.
+static SINK: Mutex<Option<Box<dyn Sink>>> = Mutex::new(None);
+
+pub fn set_sink(sink: Box<dyn Sink>) {
+    *SINK.lock().unwrap_or_else(|e| e.into_inner()) = Some(sink);
+}
+
+// Whether results go somewhere other than stdout.
+pub fn sink_redirected() -> bool {
+    SINK.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+// Hand rendered output to the active sink.
+pub fn deliver(bytes: &[u8], content_type: &str) -> Result<()> {
+    match SINK.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        Some(sink) => sink.write(bytes, content_type),
+        None => StdoutSink.write(bytes, content_type),
+    }
+}
+
+// Whether `err` is stdout closed by the reader (`| head`).
+pub fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>().map(|e| e.kind()) == Some(io::ErrorKind::BrokenPipe)
+}
+
+// Format when no output flag is given: `output` from config.yaml if pinned,
+// else YAML on a terminal and JSON (NDJSON for --all) when piped. The flag
+// is true when the format was picked for a non-terminal stdout.
+pub fn default_format(
+    pinned: Option<&OutputFormat>,
+    stdout_tty: bool,
+    all: bool,
+) -> (OutputFormat, bool) {
+    match pinned {
+        Some(fmt) => (fmt.clone(), false),
+        None if stdout_tty => (OutputFormat::Yaml, false),
+        None if all => (OutputFormat::Ndjson, true),
+        None => (OutputFormat::Json, true),
+    }
 }
 
 pub fn emit_data(fmt: &OutputFormat, data: &JsonValue) -> Result<()> {
-    match fmt {
-        OutputFormat::Yaml => {
-            let s = serde_yaml::to_string(data)?;
-            println!("{}", s.trim_end());
-        }
-        OutputFormat::Json => {
-            let s = serde_json::to_string_pretty(data)?;
-            println!("{}", s);
-        }
-        OutputFormat::Raw => {
-            if let Some(s) = data.as_str() {
-                print!("{}", s);
-            } else {
-                print!("{}", serde_json::to_string(data)?);
+    if *fmt == OutputFormat::Parquet {
+        let bytes = parquet_output(data)?;
+        return ignore_broken_pipe(deliver(&bytes, fmt.content_type()));
+    }
+    let text = match fmt {
+        OutputFormat::Yaml => serde_yaml::to_string(data)?.trim_end().to_string() + "\n",
+        OutputFormat::Json => serde_json::to_string_pretty(data)? + "\n",
+        OutputFormat::Raw => match data.as_str() {
+            Some(s) => s.to_string(),
+            None => serde_json::to_string(data)?,
+        },
+        OutputFormat::Ndjson => {
+            let mut buf = vec![];
+            write_ndjson(&mut buf, data)?;
+            String::from_utf8(buf)?
+        }
+        OutputFormat::Csv => render_delimited(data.get("data").unwrap_or(data), ','),
+        OutputFormat::Tsv => render_delimited(data.get("data").unwrap_or(data), '\t'),
+        OutputFormat::Parquet => unreachable!("rendered above"),
+    };
+    ignore_broken_pipe(deliver(text.as_bytes(), fmt.content_type()))
+}
+
+// `--output parquet`: the file, never written to a terminal.
+fn parquet_output(data: &JsonValue) -> Result<Vec<u8>> {
+    use std::io::IsTerminal;
+    if io::stdout().is_terminal() && !sink_redirected() {
+        let err = serde_json::json!({
+            "error": "Parquet output is binary",
+            "hint": "redirect stdout (> results.parquet) or use --sink results.parquet",
+        });
+        return Err(anyhow::anyhow!(serde_yaml::to_string(&err)?));
+    }
+    export_bytes(ExportFormat::Parquet, data)
+}
+
+// A reader that stops early (`| head`) ends the output rather than failing
+// the command.
+fn ignore_broken_pipe(res: Result<()>) -> Result<()> {
+    match res {
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        other => other,
+    }
+}
+
+// NDJSON rendering without the envelope: a list becomes one compact line per
+// item, a single resource one line.
+pub fn write_ndjson<W: std::io::Write>(out: &mut W, data: &JsonValue) -> Result<()> {
+    let body = data.get("data").unwrap_or(data);
+    match body {
+        JsonValue::Array(items) => {
+            for item in items {
+                writeln!(out, "{}", serde_json::to_string(item)?)?;
             }
         }
+        other => writeln!(out, "{}", serde_json::to_string(other)?)?,
     }
     Ok(())
 }
 
+// Rendering limits for nested structures in human (YAML) output.
+// Depth is counted from each item: its top-level values are at depth 1.
+#[derive(Clone, Debug, Default)]
+pub struct Collapse {
+    pub max_depth: Option<usize>,
+    pub max_items: Option<usize>,
+}
+
+// Collapse one item according to the limits: containers beyond max_depth are
+// replaced by a short summary, arrays longer than max_items are cut with a marker.
+pub fn collapse(v: &JsonValue, opts: &Collapse) -> JsonValue {
+    collapse_inner(v, opts, 0)
+}
+
+// Apply collapse to envelope data: per item for lists, whole object otherwise.
+pub fn collapse_data(data: &JsonValue, opts: &Collapse) -> JsonValue {
+    match data {
+        JsonValue::Array(items) => {
+            JsonValue::Array(items.iter().map(|it| collapse(it, opts)).collect())
+        }
+        other => collapse(other, opts),
+    }
+}
+
+fn collapse_inner(v: &JsonValue, opts: &Collapse, depth: usize) -> JsonValue {
+    let too_deep = opts.max_depth.map(|d| depth > d).unwrap_or(false);
+    match v {
+        JsonValue::Object(map) if too_deep => {
+            JsonValue::String(format!("{{…}} ({} keys)", map.len()))
+        }
+        JsonValue::Array(arr) if too_deep => {
+            JsonValue::String(format!("[…] ({} items)", arr.len()))
+        }
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), collapse_inner(v, opts, depth + 1)))
+                .collect(),
+        ),
+        JsonValue::Array(arr) => {
+            let keep = opts.max_items.unwrap_or(usize::MAX).min(arr.len());
+            let mut out: Vec<JsonValue> = arr[..keep]
+                .iter()
+                .map(|v| collapse_inner(v, opts, depth + 1))
+                .collect();
+            if keep < arr.len() {
+                out.push(JsonValue::String(format!("… ({} more)", arr.len() - keep)));
+            }
+            JsonValue::Array(out)
+        }
+        other => other.clone(),
+    }
+}
+
+// Enforce a size ceiling on an envelope before rendering. List data is
+// truncated from the end when `truncate` is set (recorded in meta.truncated),
+// otherwise, or for single resources, a structured error is returned.
+pub fn enforce_size(out: &mut JsonValue, max_bytes: u64, truncate: bool) -> Result<()> {
+    let size = serde_json::to_vec(out)?.len() as u64;
+    if size <= max_bytes {
+        return Ok(());
+    }
+    let can_truncate = truncate && out.get("data").map(|d| d.is_array()).unwrap_or(false);
+    if !can_truncate {
+        return Err(oversize_error(size, max_bytes));
+    }
+    let items = out["data"].as_array().cloned().unwrap_or_default();
+    let total = items.len();
+    // Keep the largest prefix whose serialized size fits the budget
+    let mut used = serde_json::to_vec(&JsonValue::Array(vec![]))?.len() as u64;
+    let overhead = size - serde_json::to_vec(&out["data"])?.len() as u64;
+    let mut keep = 0;
+    for item in &items {
+        let n = serde_json::to_vec(item)?.len() as u64 + 1;
+        if overhead + used + n > max_bytes {
+            break;
+        }
+        used += n;
+        keep += 1;
+    }
+    // The truncation marker itself takes space; drop further items until it fits
+    loop {
+        out["data"] = JsonValue::Array(items[..keep].to_vec());
+        if let Some(meta) = out.get_mut("meta").and_then(|m| m.as_object_mut()) {
+            meta.insert(
+                "truncated".into(),
+                serde_json::json!({ "dropped": total - keep, "limit_bytes": max_bytes }),
+            );
+        }
+        if keep == 0 || serde_json::to_vec(out)?.len() as u64 <= max_bytes {
+            break;
+        }
+        keep -= 1;
+    }
+    tracing::warn!(
+        "response of {} bytes exceeds limit of {} bytes; dropped {} of {} items",
+        size,
+        max_bytes,
+        total - keep,
+        total
+    );
+    Ok(())
+}
+
+pub fn oversize_error(size: u64, max_bytes: u64) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "response too large",
+        "size_bytes": size,
+        "limit_bytes": max_bytes,
+        "hint": "narrow with --limit/--fields or pass --on-oversize truncate",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "response too large".into()))
+}
+
+// Structured form of an error: try to parse the error string as a YAML map
+// (HTTP errors, guardrails); else wrap into { error: <message> }.
+pub fn error_value(err: &anyhow::Error) -> JsonValue {
+    match serde_yaml::from_str::<JsonValue>(&err.to_string()) {
+        Ok(val) => val,
+        Err(_) => {
+            let mut map = serde_json::Map::new();
+            map.insert("error".into(), JsonValue::String(err.to_string()));
+            JsonValue::Object(map)
+        }
+    }
+}
+
 pub fn emit_error(err: &anyhow::Error) -> Result<()> {
-    // Try to parse the error string as YAML map; else wrap into structured map
-    let obj = if let Ok(val) = serde_yaml::from_str::<JsonValue>(&err.to_string()) {
-        val
-    } else {
-        let mut map = serde_json::Map::new();
-        map.insert("error".into(), JsonValue::String(err.to_string()));
-        JsonValue::Object(map)
-    };
+    let obj = error_value(err);
     let s = serde_yaml::to_string(&obj)?;
     let _ = writeln!(io::stderr(), "{}", s.trim_end());
     Ok(())
 }
 
-pub fn emit_raw_bytes(bytes: &[u8]) -> Result<()> {
-    let mut out = io::stdout().lock();
-    out.write_all(bytes)?;
-    out.flush()?;
+// Non-fatal diagnostics go to stderr in the same YAML shape as errors.
+pub fn emit_warning(warning: &JsonValue) -> Result<()> {
+    let s = serde_yaml::to_string(warning)?;
+    let _ = writeln!(io::stderr(), "{}", s.trim_end());
     Ok(())
 }
 
+pub fn emit_raw_bytes(bytes: &[u8]) -> Result<()> {
+    deliver(bytes, OutputFormat::Raw.content_type())
+}
+
 // Wrap successful payload into the standard envelope.
 // When meta is provided (list endpoints), include it; otherwise omit meta.
 pub fn wrap_ok(data: &JsonValue, meta: Option<JsonValue>) -> JsonValue {
@@ -69,6 +324,295 @@ pub fn wrap_ok(data: &JsonValue, meta: Option<JsonValue>) -> JsonValue {
     JsonValue::Object(root)
 }
 
+// Which part of the envelope to print (--emit).
+#[derive(Copy, Clone, Debug, PartialEq, clap::ValueEnum)]
+pub enum Emit {
+    Both,
+    Meta,
+    Data,
+}
+
+// Reduce an envelope to the block selected by --emit. Single resources have
+// no meta block, so `meta` yields an empty object for them.
+pub fn select_emit(out: JsonValue, emit: Emit) -> JsonValue {
+    let block = match emit {
+        Emit::Both => return out,
+        Emit::Meta => "meta",
+        Emit::Data => "data",
+    };
+    match out {
+        JsonValue::Object(mut root) => root
+            .remove(block)
+            .unwrap_or_else(|| JsonValue::Object(serde_json::Map::new())),
+        other => other,
+    }
+}
+
+// Formats available to --also-write.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+    Csv,
+    Tsv,
+    Md,
+    Parquet,
+}
+
+impl ExportFormat {
+    // One row per item, canonical records like `--output csv` (the rest
+    // write the whole envelope).
+    pub fn is_table(self) -> bool {
+        !matches!(self, ExportFormat::Json | ExportFormat::Yaml)
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "yaml" | "yml" => Ok(ExportFormat::Yaml),
+            "csv" => Ok(ExportFormat::Csv),
+            "tsv" => Ok(ExportFormat::Tsv),
+            "md" | "markdown" => Ok(ExportFormat::Md),
+            "parquet" => Ok(ExportFormat::Parquet),
+            other => anyhow::bail!(
+                "unknown export format: {} (json, yaml, csv, tsv, md, parquet)",
+                other
+            ),
+        }
+    }
+}
+
+// Parse one `format=path` export spec.
+pub fn parse_export_spec(spec: &str) -> Result<(ExportFormat, std::path::PathBuf)> {
+    let (fmt, path) = spec
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected FORMAT=PATH, got {}", spec))?;
+    if path.is_empty() {
+        anyhow::bail!("empty path in export spec {}", spec);
+    }
+    Ok((fmt.parse()?, path.into()))
+}
+
+// Render an envelope in an export format. Tabular formats use `data` only:
+// one row per item (or a single row for single resources).
+pub fn render_export(fmt: ExportFormat, envelope: &JsonValue) -> Result<String> {
+    let data = envelope.get("data").unwrap_or(envelope);
+    Ok(match fmt {
+        ExportFormat::Json => serde_json::to_string_pretty(envelope)? + "\n",
+        ExportFormat::Yaml => serde_yaml::to_string(envelope)?,
+        ExportFormat::Csv => render_delimited(data, ','),
+        ExportFormat::Tsv => render_delimited(data, '\t'),
+        ExportFormat::Md => render_markdown(data),
+        ExportFormat::Parquet => anyhow::bail!("parquet is a binary format; use export_bytes"),
+    })
+}
+
+// The file contents of an export, binary formats included.
+pub fn export_bytes(fmt: ExportFormat, envelope: &JsonValue) -> Result<Vec<u8>> {
+    if fmt != ExportFormat::Parquet {
+        return Ok(render_export(fmt, envelope)?.into_bytes());
+    }
+    #[cfg(feature = "parquet")]
+    {
+        crate::parquet::render(envelope.get("data").unwrap_or(envelope))
+    }
+    #[cfg(not(feature = "parquet"))]
+    {
+        let err = serde_json::json!({
+            "error": "Parquet output is not available in this build",
+            "hint": "rebuild with `cargo install --path pexels --features parquet`",
+        });
+        Err(anyhow::anyhow!(serde_yaml::to_string(&err)?))
+    }
+}
+
+// Flatten nested objects into dot-path columns; arrays stay JSON-encoded.
+pub fn flatten(v: &JsonValue) -> Vec<(String, JsonValue)> {
+    let mut out = vec![];
+    flatten_into("", v, &mut out);
+    out
+}
+
+fn flatten_into(prefix: &str, v: &JsonValue, out: &mut Vec<(String, JsonValue)>) {
+    match v {
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (k, val) in map {
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", prefix, k)
+                };
+                flatten_into(&key, val, out);
+            }
+        }
+        other => out.push((prefix.to_string(), other.clone())),
+    }
+}
+
+// Flattened rows of `data` (an item array, or a single resource as one row)
+// and their columns: the union in first-seen order, except that canonical
+// records keep the `normalize::COLUMNS` order.
+pub fn table_rows(data: &JsonValue) -> (Vec<String>, Vec<Vec<(String, JsonValue)>>) {
+    let items: Vec<&JsonValue> = match data {
+        JsonValue::Array(arr) => arr.iter().collect(),
+        other => vec![other],
+    };
+    let flat: Vec<Vec<(String, JsonValue)>> = items.iter().map(|it| flatten(it)).collect();
+    let mut headers: Vec<String> = vec![];
+    for row in &flat {
+        for (k, _) in row {
+            if !headers.contains(k) {
+                headers.push(k.clone());
+            }
+        }
+    }
+    let canonical = crate::normalize::COLUMNS;
+    if canonical.iter().all(|c| headers.iter().any(|h| h == c)) {
+        let rest = headers
+            .into_iter()
+            .filter(|h| !canonical.contains(&h.as_str()));
+        headers = canonical
+            .iter()
+            .map(|c| c.to_string())
+            .chain(rest)
+            .collect();
+    }
+    (headers, flat)
+}
+
+// Header row and string cells.
+pub fn to_table(data: &JsonValue) -> (Vec<String>, Vec<Vec<String>>) {
+    let (headers, flat) = table_rows(data);
+    let rows = flat
+        .iter()
+        .map(|row| {
+            headers
+                .iter()
+                .map(|h| {
+                    row.iter()
+                        .find(|(k, _)| k == h)
+                        .map(|(_, v)| cell(v))
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+    (headers, rows)
+}
+
+fn cell(v: &JsonValue) -> String {
+    match v {
+        JsonValue::Null => String::new(),
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn render_delimited(data: &JsonValue, delim: char) -> String {
+    let (headers, rows) = to_table(data);
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .map(|c| quote_field(c, delim))
+            .collect::<Vec<_>>()
+            .join(&delim.to_string())
+    };
+    let mut out = line(&headers);
+    out.push('\n');
+    for row in rows {
+        out.push_str(&line(&row));
+        out.push('\n');
+    }
+    out
+}
+
+// RFC 4180 quoting: wrap in quotes when the field holds the delimiter, a quote or a newline.
+pub fn quote_field(s: &str, delim: char) -> String {
+    if s.contains(delim) || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn render_markdown(data: &JsonValue) -> String {
+    let (headers, rows) = to_table(data);
+    let esc = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!(
+        "| {} |\n|{}\n",
+        headers
+            .iter()
+            .map(|h| esc(h))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        " --- |".repeat(headers.len())
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "| {} |\n",
+            row.iter().map(|c| esc(c)).collect::<Vec<_>>().join(" | ")
+        ));
+    }
+    out
+}
+
+// Materialize the envelope into every requested `format=path` target; table
+// formats write `table` (the canonical records by default) instead.
+pub fn write_exports(specs: &[String], envelope: &JsonValue, table: &JsonValue) -> Result<()> {
+    for spec in specs {
+        let (fmt, path) = parse_export_spec(spec)?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let source = if fmt.is_table() { table } else { envelope };
+        let body = export_bytes(fmt, source)?;
+        std::fs::write(&path, body)
+            .map_err(|e| anyhow::anyhow!("write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// Key holding an item's original API position.
+pub const POSITION_KEY: &str = "_position";
+
+// Item arrays of list responses, in lookup order.
+pub const ITEM_KEYS: [&str; 4] = ["photos", "videos", "collections", "media"];
+
+// Stamp every item of one API page with its original position:
+// `_position: { page, index, rank }` where index is 0-based within the page
+// and rank is the 1-based overall position. Items already stamped are kept.
+pub fn annotate_positions(page: &mut JsonValue) {
+    let num = |k: &str| page.get(k).and_then(|v| v.as_u64());
+    let page_no = num("page").unwrap_or(1).max(1);
+    let per_page = num("per_page");
+    let Some(key) = ITEM_KEYS
+        .iter()
+        .find(|k| page.get(**k).map(|v| v.is_array()).unwrap_or(false))
+    else {
+        return;
+    };
+    let Some(items) = page.get_mut(*key).and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+    let per_page = per_page.unwrap_or(items.len() as u64).max(1);
+    for (i, item) in items.iter_mut().enumerate() {
+        if let Some(map) = item.as_object_mut() {
+            if map.contains_key(POSITION_KEY) {
+                continue;
+            }
+            let rank = (page_no - 1) * per_page + i as u64 + 1;
+            map.insert(
+                POSITION_KEY.into(),
+                serde_json::json!({ "page": page_no, "index": i, "rank": rank }),
+            );
+        }
+    }
+}
+
 // Extract the `page` query param from a URL string.
 pub fn parse_page_number(url: &str) -> Option<u32> {
     // Accept absolute or relative URLs
@@ -0,0 +1,224 @@
+use crate::api::PexelsClient;
+use crate::models::{Collection, Media, Page, Photo, Video};
+use anyhow::{Context, Result};
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::marker::PhantomData;
+
+impl<T: DeserializeOwned> Page<T> {
+    // Parse one raw list response; `items_key` names the array (photos, videos, ...).
+    pub fn from_value(v: &JsonValue, items_key: &str) -> Result<Self> {
+        let items = match v.get(items_key) {
+            Some(arr) => serde_json::from_value(arr.clone())
+                .with_context(|| format!("parse {} items", items_key))?,
+            None => vec![],
+        };
+        let num = |k: &str| v.get(k).and_then(|x| x.as_u64());
+        let url = |k: &str| v.get(k).and_then(|x| x.as_str()).map(|s| s.to_string());
+        Ok(Page {
+            page: num("page").unwrap_or(1) as u32,
+            per_page: num("per_page").unwrap_or(0) as u32,
+            total_results: num("total_results"),
+            next_page: url("next_page"),
+            prev_page: url("prev_page"),
+            items,
+        })
+    }
+}
+
+// Typed photo endpoints: `client.photos().search("cats").pages()`.
+pub struct Photos<'a> {
+    client: &'a PexelsClient,
+}
+
+impl<'a> Photos<'a> {
+    pub(crate) fn new(client: &'a PexelsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn search(&self, query: &str) -> PageRequest<'a, Photo> {
+        PageRequest::new(self.client, self.client.base_photos(), "search", "photos")
+            .param("query", query)
+    }
+
+    pub fn curated(&self) -> PageRequest<'a, Photo> {
+        PageRequest::new(self.client, self.client.base_photos(), "curated", "photos")
+    }
+}
+
+// Typed video endpoints: `client.videos().search("waves").items()`.
+pub struct Videos<'a> {
+    client: &'a PexelsClient,
+}
+
+impl<'a> Videos<'a> {
+    pub(crate) fn new(client: &'a PexelsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn search(&self, query: &str) -> PageRequest<'a, Video> {
+        PageRequest::new(self.client, self.client.base_videos(), "search", "videos")
+            .param("query", query)
+    }
+
+    pub fn popular(&self) -> PageRequest<'a, Video> {
+        PageRequest::new(self.client, self.client.base_videos(), "popular", "videos")
+    }
+}
+
+// Typed collection endpoints; `media` yields photos and videos as `Media`.
+pub struct Collections<'a> {
+    client: &'a PexelsClient,
+}
+
+impl<'a> Collections<'a> {
+    pub(crate) fn new(client: &'a PexelsClient) -> Self {
+        Self { client }
+    }
+
+    pub fn featured(&self) -> PageRequest<'a, Collection> {
+        PageRequest::new(
+            self.client,
+            self.client.base_photos(),
+            "collections/featured",
+            "collections",
+        )
+    }
+
+    // The authenticated user's collections.
+    pub fn mine(&self) -> PageRequest<'a, Collection> {
+        PageRequest::new(
+            self.client,
+            self.client.base_photos(),
+            "collections",
+            "collections",
+        )
+    }
+
+    pub fn media(&self, id: &str) -> PageRequest<'a, Media> {
+        PageRequest::new(
+            self.client,
+            self.client.base_photos(),
+            &format!("collections/{}/media", id),
+            "media",
+        )
+    }
+}
+
+// Builder for a paginated list request. Paging follows `next_page` links and
+// stops at `limit` items or `max_pages` pages; 429/5xx responses are retried
+// with Retry-After/backoff by the underlying client.
+pub struct PageRequest<'a, T> {
+    client: &'a PexelsClient,
+    url: Result<Url, url::ParseError>,
+    qp: Vec<(String, String)>,
+    items_key: &'static str,
+    limit: Option<u32>,
+    max_pages: Option<u32>,
+    _item: PhantomData<T>,
+}
+
+struct PageState<'a> {
+    client: &'a PexelsClient,
+    next: Option<(Url, Vec<(String, String)>)>,
+    pages: u32,
+    collected: u32,
+    limit: u32,
+    max_pages: u32,
+}
+
+impl<'a, T: DeserializeOwned + 'a> PageRequest<'a, T> {
+    pub(crate) fn new(
+        client: &'a PexelsClient,
+        base: Url,
+        path: &str,
+        items_key: &'static str,
+    ) -> Self {
+        Self {
+            client,
+            url: base.join(path),
+            qp: vec![],
+            items_key,
+            limit: None,
+            max_pages: None,
+            _item: PhantomData,
+        }
+    }
+
+    pub fn param(mut self, key: &str, value: impl ToString) -> Self {
+        self.qp.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn page(self, page: u32) -> Self {
+        self.param("page", page)
+    }
+
+    pub fn per_page(self, per_page: u32) -> Self {
+        self.param("per_page", per_page)
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    // Stream of pages; the last page is cut so no more than `limit` items are yielded.
+    pub fn pages(self) -> impl Stream<Item = Result<Page<T>>> + 'a {
+        let items_key = self.items_key;
+        let start = self.url.map(|u| (u, self.qp));
+        let (next, err) = match start {
+            Ok(n) => (Some(n), None),
+            Err(e) => (None, Some(anyhow::anyhow!(e))),
+        };
+        let state = PageState {
+            client: self.client,
+            next,
+            pages: 0,
+            collected: 0,
+            limit: self.limit.unwrap_or(u32::MAX),
+            max_pages: self.max_pages.unwrap_or(u32::MAX),
+        };
+        let head = stream::iter(err.map(Err));
+        let body = stream::unfold(state, move |mut st| async move {
+            if st.pages >= st.max_pages || st.collected >= st.limit {
+                return None;
+            }
+            let (url, qp) = st.next.take()?;
+            let page = match st.client.req(url, qp).await {
+                Ok(v) => v,
+                Err(e) => return Some((Err(e), st)),
+            };
+            let mut parsed = match Page::<T>::from_value(&page, items_key) {
+                Ok(p) => p,
+                Err(e) => return Some((Err(e), st)),
+            };
+            let remaining = (st.limit - st.collected) as usize;
+            parsed.items.truncate(remaining);
+            st.collected += parsed.items.len() as u32;
+            st.pages += 1;
+            st.next = parsed
+                .next_page
+                .as_deref()
+                .and_then(|u| Url::parse(u).ok())
+                .map(|u| (u, vec![]));
+            Some((Ok(parsed), st))
+        });
+        head.chain(body)
+    }
+
+    // Flattened stream of items across pages.
+    pub fn items(self) -> impl Stream<Item = Result<T>> + 'a {
+        self.pages().flat_map(|page| match page {
+            Ok(p) => stream::iter(p.items.into_iter().map(Ok).collect::<Vec<_>>()),
+            Err(e) => stream::iter(vec![Err(e)]),
+        })
+    }
+}
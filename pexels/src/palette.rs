@@ -0,0 +1,117 @@
+use image::DynamicImage;
+use serde_json::Value as JsonValue;
+
+// Dominant colors of a photo (`photos palette`): k-means over the pixels of
+// a small variant, downscaled further so the cost stays flat. Centroids
+// start at evenly spaced luminance quantiles, so the same image always
+// yields the same palette.
+
+pub const DEFAULT_COUNT: u8 = 6;
+// Longest side the image is reduced to before clustering.
+const SAMPLE_SIDE: u32 = 96;
+const MAX_ITERATIONS: usize = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Swatch {
+    pub rgb: [u8; 3],
+    // Share of sampled pixels, 0-1
+    pub proportion: f64,
+}
+
+impl Swatch {
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.rgb[0], self.rgb[1], self.rgb[2])
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "hex": self.hex(),
+            "rgb": self.rgb,
+            "proportion": (self.proportion * 1000.0).round() / 1000.0,
+        })
+    }
+}
+
+// Up to `count` colors, most common first. Transparent pixels are ignored.
+pub fn extract(img: &DynamicImage, count: usize) -> Vec<Swatch> {
+    let small = if img.width().max(img.height()) > SAMPLE_SIDE {
+        img.thumbnail(SAMPLE_SIDE, SAMPLE_SIDE).to_rgba8()
+    } else {
+        img.to_rgba8()
+    };
+    let pixels: Vec<[f64; 3]> = small
+        .pixels()
+        .filter(|p| p[3] >= 128)
+        .map(|p| [p[0] as f64, p[1] as f64, p[2] as f64])
+        .collect();
+    kmeans(&pixels, count)
+}
+
+// k-means over RGB points; empty clusters are dropped.
+pub fn kmeans(pixels: &[[f64; 3]], count: usize) -> Vec<Swatch> {
+    let k = count.min(pixels.len());
+    if k == 0 {
+        return vec![];
+    }
+    let mut by_luma = pixels.to_vec();
+    by_luma.sort_by(|a, b| luma(a).total_cmp(&luma(b)));
+    let mut centroids: Vec<[f64; 3]> = (0..k)
+        .map(|j| by_luma[(2 * j + 1) * by_luma.len() / (2 * k)])
+        .collect();
+    let mut assignment = vec![usize::MAX; pixels.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (p, slot) in pixels.iter().zip(assignment.iter_mut()) {
+            let nearest = nearest(&centroids, p);
+            if *slot != nearest {
+                *slot = nearest;
+                changed = true;
+            }
+        }
+        let mut sums = vec![([0.0; 3], 0usize); k];
+        for (p, &c) in pixels.iter().zip(&assignment) {
+            for (sum, v) in sums[c].0.iter_mut().zip(p) {
+                *sum += v;
+            }
+            sums[c].1 += 1;
+        }
+        for (centroid, (sum, n)) in centroids.iter_mut().zip(&sums) {
+            if *n > 0 {
+                *centroid = sum.map(|s| s / *n as f64);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut sizes = vec![0usize; k];
+    for &c in &assignment {
+        sizes[c] += 1;
+    }
+    let mut out: Vec<Swatch> = centroids
+        .iter()
+        .zip(&sizes)
+        .filter(|(_, n)| **n > 0)
+        .map(|(c, n)| Swatch {
+            rgb: c.map(|v| v.round().clamp(0.0, 255.0) as u8),
+            proportion: *n as f64 / pixels.len() as f64,
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.proportion
+            .total_cmp(&a.proportion)
+            .then_with(|| a.rgb.cmp(&b.rgb))
+    });
+    out
+}
+
+fn luma(p: &[f64; 3]) -> f64 {
+    0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]
+}
+
+fn nearest(centroids: &[[f64; 3]], p: &[f64; 3]) -> usize {
+    let dist = |c: &[f64; 3]| (0..3).map(|i| (c[i] - p[i]).powi(2)).sum::<f64>();
+    (0..centroids.len())
+        .min_by(|a, b| dist(&centroids[*a]).total_cmp(&dist(&centroids[*b])))
+        .unwrap_or(0)
+}
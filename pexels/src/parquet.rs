@@ -0,0 +1,824 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+// `--output parquet` / `--also-write parquet=PATH` (cargo feature `parquet`):
+// the projected items as one Parquet row group, columns flattened like the
+// CSV export (`src.original`). Each column gets the narrowest type that holds
+// all of its values -- BOOLEAN, INT64, DOUBLE, else a UTF8 string, with
+// nested arrays and objects JSON-encoded -- and every column is nullable.
+// Pages are PLAIN encoded and uncompressed, the baseline every reader
+// supports; tests/unit_export.rs pins the layout against parquet.thrift.
+//
+// `read_strings` goes the other way for `export dataset --exclude-manifest`:
+// one flat column of a file written by us or by common tools (PLAIN or
+// dictionary encoded, uncompressed or Snappy, data pages v1 and v2). Every
+// length, count and nesting level read from the file is checked against what
+// is left of it, so damaged or hostile input fails with an error
+// (tests/unit_parquet.rs).
+
+const MAGIC: &[u8] = b"PAR1";
+
+// parquet.thrift enum values
+const BOOLEAN: i32 = 0;
+const INT64: i32 = 2;
+const DOUBLE: i32 = 5;
+const BYTE_ARRAY: i32 = 6;
+const OPTIONAL: i32 = 1;
+const UTF8: i32 = 0;
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+const UNCOMPRESSED: i32 = 0;
+const DATA_PAGE: i32 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Bool,
+    Int,
+    Double,
+    Text,
+}
+
+impl Kind {
+    fn of(v: &JsonValue) -> Option<Kind> {
+        match v {
+            JsonValue::Null => None,
+            JsonValue::Bool(_) => Some(Kind::Bool),
+            JsonValue::Number(n) if n.is_i64() => Some(Kind::Int),
+            JsonValue::Number(_) => Some(Kind::Double),
+            _ => Some(Kind::Text),
+        }
+    }
+
+    fn merge(self, other: Kind) -> Kind {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Kind::Int, Kind::Double) | (Kind::Double, Kind::Int) => Kind::Double,
+            _ => Kind::Text,
+        }
+    }
+
+    fn physical(self) -> i32 {
+        match self {
+            Kind::Bool => BOOLEAN,
+            Kind::Int => INT64,
+            Kind::Double => DOUBLE,
+            Kind::Text => BYTE_ARRAY,
+        }
+    }
+}
+
+// The Parquet file for `data` (an item array, or a single resource as one row).
+pub fn render(data: &JsonValue) -> Result<Vec<u8>> {
+    let (names, rows) = crate::output::table_rows(data);
+    let columns: Vec<Vec<&JsonValue>> = names
+        .iter()
+        .map(|name| {
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .find(|(k, _)| k == name)
+                        .map_or(&JsonValue::Null, |(_, v)| v)
+                })
+                .collect()
+        })
+        .collect();
+    let kinds: Vec<Kind> = columns
+        .iter()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| Kind::of(v))
+                .reduce(Kind::merge)
+                .unwrap_or(Kind::Text)
+        })
+        .collect();
+
+    let mut out = MAGIC.to_vec();
+    let mut chunks = vec![];
+    for ((name, values), kind) in names.iter().zip(&columns).zip(&kinds) {
+        let offset = out.len() as i64;
+        let page = data_page(values, *kind);
+        let mut header = Compact::default();
+        header.begin();
+        header.i32(1, DATA_PAGE);
+        header.i32(2, page.len() as i32);
+        header.i32(3, page.len() as i32);
+        header.struct_field(5);
+        header.i32(1, values.len() as i32);
+        header.i32(2, PLAIN);
+        header.i32(3, RLE);
+        header.i32(4, RLE);
+        header.end();
+        header.end();
+        let size = (header.buf.len() + page.len()) as i64;
+        out.extend(&header.buf);
+        out.extend(&page);
+        chunks.push((name, *kind, offset, size));
+    }
+
+    let mut meta = Compact::default();
+    meta.begin();
+    meta.i32(1, 1);
+    meta.list_field(2, STRUCT, names.len() + 1);
+    meta.begin();
+    meta.binary(4, b"schema");
+    meta.i32(5, names.len() as i32);
+    meta.end();
+    for (name, kind) in names.iter().zip(&kinds) {
+        meta.begin();
+        meta.i32(1, kind.physical());
+        meta.i32(3, OPTIONAL);
+        meta.binary(4, name.as_bytes());
+        if *kind == Kind::Text {
+            meta.i32(6, UTF8);
+        }
+        meta.end();
+    }
+    meta.i64(3, rows.len() as i64);
+    let groups = if rows.is_empty() { 0 } else { 1 };
+    meta.list_field(4, STRUCT, groups);
+    if groups == 1 {
+        meta.begin();
+        meta.list_field(1, STRUCT, chunks.len());
+        for (name, kind, offset, size) in &chunks {
+            meta.begin();
+            meta.i64(2, *offset);
+            meta.struct_field(3);
+            meta.i32(1, kind.physical());
+            meta.list_field(2, I32, 2);
+            meta.varint(zigzag(PLAIN as i64));
+            meta.varint(zigzag(RLE as i64));
+            meta.list_field(3, BINARY, 1);
+            meta.bytes(name.as_bytes());
+            meta.i32(4, UNCOMPRESSED);
+            meta.i64(5, rows.len() as i64);
+            meta.i64(6, *size);
+            meta.i64(7, *size);
+            meta.i64(9, *offset);
+            meta.end();
+            meta.end();
+        }
+        let total: i64 = chunks.iter().map(|c| c.3).sum();
+        meta.i64(2, total);
+        meta.i64(3, rows.len() as i64);
+        meta.end();
+    }
+    let created_by = format!("pexels-cli version {}", env!("CARGO_PKG_VERSION"));
+    meta.binary(6, created_by.as_bytes());
+    meta.end();
+
+    out.extend(&meta.buf);
+    out.extend((meta.buf.len() as u32).to_le_bytes());
+    out.extend(MAGIC);
+    Ok(out)
+}
+
+// Definition levels (RLE, length-prefixed) followed by the PLAIN values of
+// the non-null entries.
+fn data_page(values: &[&JsonValue], kind: Kind) -> Vec<u8> {
+    let mut levels = vec![];
+    let mut i = 0;
+    while i < values.len() {
+        let present = !values[i].is_null();
+        let run = values[i..]
+            .iter()
+            .take_while(|v| v.is_null() != present)
+            .count();
+        push_varint(&mut levels, (run as u64) << 1);
+        levels.push(present as u8);
+        i += run;
+    }
+    let mut page = (levels.len() as u32).to_le_bytes().to_vec();
+    page.extend(levels);
+    let present = values.iter().filter(|v| !v.is_null());
+    match kind {
+        Kind::Bool => {
+            let bits: Vec<bool> = present.map(|v| v.as_bool() == Some(true)).collect();
+            for byte in bits.chunks(8) {
+                page.push(
+                    byte.iter()
+                        .enumerate()
+                        .fold(0u8, |acc, (i, b)| acc | ((*b as u8) << i)),
+                );
+            }
+        }
+        Kind::Int => {
+            for v in present {
+                page.extend(v.as_i64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Kind::Double => {
+            for v in present {
+                page.extend(v.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Kind::Text => {
+            for v in present {
+                let s = match v {
+                    JsonValue::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                page.extend((s.len() as u32).to_le_bytes());
+                page.extend(s.as_bytes());
+            }
+        }
+    }
+    page
+}
+
+// Thrift compact protocol types
+const I32: u8 = 5;
+const I64: u8 = 6;
+const BINARY: u8 = 8;
+const LIST: u8 = 9;
+const STRUCT: u8 = 12;
+
+// Just enough of the Thrift compact protocol for the Parquet footer and
+// page headers: structs, i32/i64/binary fields and lists.
+#[derive(Default)]
+struct Compact {
+    buf: Vec<u8>,
+    // Last field id of each open struct
+    last: Vec<i16>,
+}
+
+impl Compact {
+    fn begin(&mut self) {
+        self.last.push(0);
+    }
+
+    fn end(&mut self) {
+        self.buf.push(0);
+        self.last.pop();
+    }
+
+    fn field(&mut self, id: i16, ty: u8) {
+        let last = self.last.last_mut().expect("open struct");
+        let delta = id - *last;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | ty);
+        } else {
+            self.buf.push(ty);
+            push_varint(&mut self.buf, zigzag(id as i64));
+        }
+        *last = id;
+    }
+
+    fn i32(&mut self, id: i16, v: i32) {
+        self.field(id, I32);
+        self.varint(zigzag(v as i64));
+    }
+
+    fn i64(&mut self, id: i16, v: i64) {
+        self.field(id, I64);
+        self.varint(zigzag(v));
+    }
+
+    fn binary(&mut self, id: i16, v: &[u8]) {
+        self.field(id, BINARY);
+        self.bytes(v);
+    }
+
+    // A nested struct field; close it with `end`.
+    fn struct_field(&mut self, id: i16) {
+        self.field(id, STRUCT);
+        self.begin();
+    }
+
+    // A list header; the elements follow.
+    fn list_field(&mut self, id: i16, elem: u8, len: usize) {
+        self.field(id, LIST);
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | elem);
+        } else {
+            self.buf.push(0xf0 | elem);
+            push_varint(&mut self.buf, len as u64);
+        }
+    }
+
+    fn bytes(&mut self, v: &[u8]) {
+        push_varint(&mut self.buf, v.len() as u64);
+        self.buf.extend(v);
+    }
+
+    fn varint(&mut self, v: u64) {
+        push_varint(&mut self.buf, v);
+    }
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn push_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+// Further parquet.thrift values the reader understands
+const INT32: i32 = 1;
+const FIXED_LEN_BYTE_ARRAY: i32 = 7;
+const REQUIRED: i32 = 0;
+const PLAIN_DICTIONARY: i32 = 2;
+const RLE_DICTIONARY: i32 = 8;
+const SNAPPY: i32 = 1;
+const DICTIONARY_PAGE: i32 = 2;
+const DATA_PAGE_V2: i32 = 3;
+
+// Files come from other tools (or anyone), so counts and lengths in them are
+// checked before anything is allocated for them: a column yields at most
+// MAX_VALUES values, and Thrift metadata nests at most MAX_DEPTH deep.
+const MAX_VALUES: usize = 1 << 24;
+const MAX_DEPTH: usize = 64;
+
+// The non-null values of top-level column `name` as strings (integers in
+// decimal), or None when the file has no such column.
+pub fn read_strings(bytes: &[u8], name: &str) -> Result<Option<Vec<String>>> {
+    let n = bytes.len();
+    if n < 12 || &bytes[..4] != MAGIC || &bytes[n - 4..] != MAGIC {
+        anyhow::bail!("not a Parquet file");
+    }
+    let len = u32::from_le_bytes(bytes[n - 8..n - 4].try_into().expect("4 bytes")) as usize;
+    let start = (n - 8)
+        .checked_sub(len)
+        .filter(|s| *s >= 4)
+        .context("corrupt Parquet footer")?;
+    let meta = Thrift::parse(&bytes[start..n - 8])?;
+    let Some(element) = meta
+        .list(2)
+        .iter()
+        .find(|e| e.bin(4) == Some(name.as_bytes()))
+    else {
+        return Ok(None);
+    };
+    let max_def = match element.int(3) {
+        Some(r) if r == REQUIRED as i64 => 0,
+        Some(r) if r == OPTIONAL as i64 => 1,
+        _ => anyhow::bail!("column {} is repeated or nested", name),
+    };
+    let kind = element.int(1).unwrap_or(-1) as i32;
+    let mut values = vec![];
+    for group in meta.list(4) {
+        for chunk in group.list(1) {
+            let md = chunk.get(3).context("column chunk without metadata")?;
+            let path = md.list(3);
+            if path.len() == 1 && path[0].as_bin() == Some(name.as_bytes()) {
+                read_chunk(bytes, md, kind, max_def, &mut values)?;
+            }
+        }
+    }
+    Ok(Some(values))
+}
+
+fn read_chunk(
+    bytes: &[u8],
+    md: &Thrift,
+    kind: i32,
+    max_def: u32,
+    out: &mut Vec<String>,
+) -> Result<()> {
+    let codec = md.int(4).unwrap_or(0) as i32;
+    if codec != UNCOMPRESSED && codec != SNAPPY {
+        anyhow::bail!("unsupported Parquet compression (codec {}); rewrite the file uncompressed or with Snappy", codec);
+    }
+    let mut remaining = md.int(5).unwrap_or(0);
+    let data_page = md.int(9).unwrap_or(0);
+    let mut pos = match md.int(11) {
+        Some(dict) if dict >= 4 && dict < data_page => dict,
+        _ => data_page,
+    } as usize;
+    let mut dictionary: Vec<String> = vec![];
+    while remaining > 0 {
+        let mut r = ThriftReader::new(bytes.get(pos..).context("page offset past end of file")?);
+        let header = r.parse_struct()?;
+        let body_len = size(header.int(3))?;
+        let body = r
+            .buf
+            .get(r.pos..)
+            .and_then(|b| b.get(..body_len))
+            .context("truncated Parquet page")?;
+        pos += r.pos + body_len;
+        let raw_len = size(header.int(2))?;
+        let page_type = header.int(1).unwrap_or(-1) as i32;
+        match page_type {
+            DICTIONARY_PAGE => {
+                let count = values(header.get(7).and_then(|h| h.int(1)))?;
+                let page = decompress(codec, body, raw_len)?;
+                dictionary = plain(&page, kind, count)?;
+            }
+            DATA_PAGE => {
+                let h = header.get(5).context("data page without header")?;
+                let count = values(h.int(1))?;
+                let page = decompress(codec, body, raw_len)?;
+                let (levels, rest) = if max_def == 0 {
+                    (vec![1; count], &page[..])
+                } else {
+                    let n =
+                        u32::from_le_bytes(page.get(..4).context("truncated levels")?.try_into()?)
+                            as usize;
+                    let levels = page.get(4..).and_then(|l| l.get(..n));
+                    let levels = hybrid(levels.context("truncated levels")?, 1, count)?;
+                    (levels, &page[4 + n..])
+                };
+                let present = levels.iter().filter(|l| **l == max_def).count();
+                let encoding = h.int(2).unwrap_or(0) as i32;
+                room(out, present)?;
+                out.extend(page_values(rest, encoding, kind, present, &dictionary)?);
+                remaining -= count as i64;
+            }
+            DATA_PAGE_V2 => {
+                let h = header.get(8).context("data page without header")?;
+                let count = values(h.int(1))?;
+                let nulls = values(h.int(2))?;
+                let levels_len = size(h.int(5))?
+                    .checked_add(size(h.int(6))?)
+                    .context("corrupt Parquet page header")?;
+                let values = body.get(levels_len..).context("truncated levels")?;
+                let values = if h.bool(7).unwrap_or(true) {
+                    decompress(codec, values, raw_len.saturating_sub(levels_len))?
+                } else {
+                    values.to_vec()
+                };
+                let encoding = h.int(4).unwrap_or(0) as i32;
+                let present = count.saturating_sub(nulls);
+                room(out, present)?;
+                out.extend(page_values(&values, encoding, kind, present, &dictionary)?);
+                remaining -= count as i64;
+            }
+            // Index pages and anything newer carry no values
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// A byte length from page metadata (absent is 0).
+fn size(v: Option<i64>) -> Result<usize> {
+    usize::try_from(v.unwrap_or(0)).context("negative length in Parquet page header")
+}
+
+// Whether `n` more values still fit in MAX_VALUES.
+fn room(out: &[String], n: usize) -> Result<()> {
+    if out.len() + n > MAX_VALUES {
+        anyhow::bail!("Parquet column has more than {} values", MAX_VALUES);
+    }
+    Ok(())
+}
+
+// A value count from page metadata (absent is 0), within MAX_VALUES.
+fn values(v: Option<i64>) -> Result<usize> {
+    let n = size(v)?;
+    if n > MAX_VALUES {
+        anyhow::bail!("Parquet page claims {} values", n);
+    }
+    Ok(n)
+}
+
+fn page_values(
+    buf: &[u8],
+    encoding: i32,
+    kind: i32,
+    count: usize,
+    dictionary: &[String],
+) -> Result<Vec<String>> {
+    match encoding {
+        PLAIN => plain(buf, kind, count),
+        PLAIN_DICTIONARY | RLE_DICTIONARY => {
+            let width = *buf.first().context("truncated dictionary indices")? as u32;
+            hybrid(&buf[1..], width, count)?
+                .into_iter()
+                .map(|i| {
+                    dictionary
+                        .get(i as usize)
+                        .cloned()
+                        .context("dictionary index out of range")
+                })
+                .collect()
+        }
+        other => anyhow::bail!("unsupported Parquet encoding {}", other),
+    }
+}
+
+fn plain(buf: &[u8], kind: i32, count: usize) -> Result<Vec<String>> {
+    let mut out = Vec::with_capacity(count.min(buf.len()));
+    let mut pos = 0;
+    let mut take = |n: usize| -> Result<&[u8]> {
+        let v = buf
+            .get(pos..)
+            .and_then(|b| b.get(..n))
+            .context("truncated Parquet values")?;
+        pos += n;
+        Ok(v)
+    };
+    for _ in 0..count {
+        out.push(match kind {
+            INT32 => i32::from_le_bytes(take(4)?.try_into()?).to_string(),
+            INT64 => i64::from_le_bytes(take(8)?.try_into()?).to_string(),
+            BYTE_ARRAY => {
+                let n = u32::from_le_bytes(take(4)?.try_into()?) as usize;
+                String::from_utf8_lossy(take(n)?).into_owned()
+            }
+            FIXED_LEN_BYTE_ARRAY => anyhow::bail!("fixed-length byte columns are not supported"),
+            other => anyhow::bail!("column type {} cannot hold ids or hashes", other),
+        });
+    }
+    Ok(out)
+}
+
+// `count` values of the RLE/bit-packing hybrid encoding.
+fn hybrid(buf: &[u8], width: u32, count: usize) -> Result<Vec<u32>> {
+    if width > 32 {
+        anyhow::bail!("invalid bit width {}", width);
+    }
+    let mut r = ThriftReader::new(buf);
+    let mut out = Vec::with_capacity(count.min(MAX_VALUES));
+    while out.len() < count && r.pos < buf.len() {
+        let header = r.varint()?;
+        let left = count - out.len();
+        if header & 1 == 1 {
+            // Groups of 8 values, `width` bytes per group
+            let groups = usize::try_from(header >> 1).unwrap_or(usize::MAX);
+            let len = groups
+                .checked_mul(width as usize)
+                .context("truncated RLE data")?;
+            let bytes = r.take(len)?;
+            for i in 0..groups.saturating_mul(8).min(left) {
+                let mut v = 0u32;
+                for b in 0..width as usize {
+                    let bit = i * width as usize + b;
+                    v |= (((bytes[bit / 8] >> (bit % 8)) & 1) as u32) << b;
+                }
+                out.push(v);
+            }
+        } else {
+            let mut v = 0u32;
+            for (i, b) in r.take(width.div_ceil(8) as usize)?.iter().enumerate() {
+                v |= (*b as u32) << (8 * i);
+            }
+            let run = usize::try_from(header >> 1).unwrap_or(usize::MAX);
+            out.extend(std::iter::repeat_n(v, run.min(left)));
+        }
+    }
+    if out.len() < count {
+        anyhow::bail!("truncated RLE data");
+    }
+    out.truncate(count);
+    Ok(out)
+}
+
+fn decompress(codec: i32, buf: &[u8], len: usize) -> Result<Vec<u8>> {
+    if codec != SNAPPY {
+        return Ok(buf.to_vec());
+    }
+    let out = snappy(buf)?;
+    if out.len() != len {
+        anyhow::bail!("corrupt Snappy page");
+    }
+    Ok(out)
+}
+
+// A raw Snappy block: the decoded length, then literals and back-references.
+pub fn snappy(buf: &[u8]) -> Result<Vec<u8>> {
+    let mut r = ThriftReader::new(buf);
+    let len = usize::try_from(r.varint()?).context("corrupt Snappy data")?;
+    // A tag expands to at most 64 bytes, so a longer claim is corrupt
+    if len > buf.len().saturating_mul(64) {
+        anyhow::bail!("corrupt Snappy data");
+    }
+    let mut out = Vec::with_capacity(len);
+    while r.pos < buf.len() {
+        let tag = r.take(1)?[0];
+        let (copy_len, offset) = match tag & 3 {
+            0 => {
+                let mut n = (tag >> 2) as usize;
+                if n >= 60 {
+                    let extra = r.take(n - 59)?;
+                    n = extra
+                        .iter()
+                        .enumerate()
+                        .fold(0, |acc, (i, b)| acc | (*b as usize) << (8 * i));
+                }
+                out.extend(r.take(n.checked_add(1).context("corrupt Snappy data")?)?);
+                continue;
+            }
+            1 => {
+                let low = r.take(1)?[0] as usize;
+                (
+                    ((tag >> 2) & 7) as usize + 4,
+                    ((tag as usize >> 5) << 8) | low,
+                )
+            }
+            2 => {
+                let b = r.take(2)?;
+                (
+                    (tag >> 2) as usize + 1,
+                    u16::from_le_bytes([b[0], b[1]]) as usize,
+                )
+            }
+            _ => {
+                let b = r.take(4)?;
+                (
+                    (tag >> 2) as usize + 1,
+                    u32::from_le_bytes(b.try_into()?) as usize,
+                )
+            }
+        };
+        if offset == 0 || offset > out.len() || out.len() + copy_len > len {
+            anyhow::bail!("corrupt Snappy data");
+        }
+        // Copies may overlap their own output
+        for _ in 0..copy_len {
+            out.push(out[out.len() - offset]);
+        }
+    }
+    if out.len() != len {
+        anyhow::bail!("corrupt Snappy data");
+    }
+    Ok(out)
+}
+
+// A decoded Thrift compact value, just detailed enough for Parquet metadata.
+#[derive(Debug)]
+enum Thrift {
+    Bool(bool),
+    Int(i64),
+    Double,
+    Bin(Vec<u8>),
+    List(Vec<Thrift>),
+    Struct(BTreeMap<i16, Thrift>),
+}
+
+impl Thrift {
+    fn parse(buf: &[u8]) -> Result<Thrift> {
+        ThriftReader::new(buf).parse_struct()
+    }
+
+    fn get(&self, id: i16) -> Option<&Thrift> {
+        match self {
+            Thrift::Struct(fields) => fields.get(&id),
+            _ => None,
+        }
+    }
+
+    fn int(&self, id: i16) -> Option<i64> {
+        match self.get(id)? {
+            Thrift::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn bool(&self, id: i16) -> Option<bool> {
+        match self.get(id)? {
+            Thrift::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    fn bin(&self, id: i16) -> Option<&[u8]> {
+        self.get(id)?.as_bin()
+    }
+
+    fn as_bin(&self) -> Option<&[u8]> {
+        match self {
+            Thrift::Bin(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn list(&self, id: i16) -> &[Thrift] {
+        match self.get(id) {
+            Some(Thrift::List(items)) => items,
+            _ => &[],
+        }
+    }
+}
+
+struct ThriftReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    // Structs and lists currently open
+    depth: usize,
+}
+
+impl<'a> ThriftReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let v = self
+            .buf
+            .get(self.pos..)
+            .and_then(|b| b.get(..n))
+            .context("truncated Parquet data")?;
+        self.pos += n;
+        Ok(v)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let (mut v, mut shift) = (0u64, 0);
+        loop {
+            let b = self.take(1)?[0];
+            if shift < 64 {
+                v |= ((b & 0x7f) as u64) << shift;
+            }
+            if b < 0x80 {
+                return Ok(v);
+            }
+            shift += 7;
+        }
+    }
+
+    fn zigzag(&mut self) -> Result<i64> {
+        let v = self.varint()?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    fn parse_struct(&mut self) -> Result<Thrift> {
+        self.enter()?;
+        let mut fields = BTreeMap::new();
+        let mut last = 0i16;
+        loop {
+            let header = self.take(1)?[0];
+            if header == 0 {
+                self.depth -= 1;
+                return Ok(Thrift::Struct(fields));
+            }
+            let id = match header >> 4 {
+                0 => self.zigzag()? as i16,
+                delta => last.wrapping_add(delta as i16),
+            };
+            last = id;
+            let value = match header & 0x0f {
+                1 => Thrift::Bool(true),
+                2 => Thrift::Bool(false),
+                ty => self.value(ty)?,
+            };
+            fields.insert(id, value);
+        }
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            anyhow::bail!("Parquet metadata nested too deeply");
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, ty: u8) -> Result<Thrift> {
+        Ok(match ty {
+            1 | 2 => Thrift::Bool(self.take(1)?[0] == 1),
+            3 => Thrift::Int(self.take(1)?[0] as i8 as i64),
+            4..=6 => Thrift::Int(self.zigzag()?),
+            7 => {
+                self.take(8)?;
+                Thrift::Double
+            }
+            8 => {
+                let n = self.varint()? as usize;
+                Thrift::Bin(self.take(n)?.to_vec())
+            }
+            9 | 10 => {
+                self.enter()?;
+                let header = self.take(1)?[0];
+                let n = match header >> 4 {
+                    15 => self.varint()?,
+                    n => n as u64,
+                };
+                let elem = header & 0x0f;
+                // Every element takes at least one byte
+                let mut items = vec![];
+                for _ in 0..n {
+                    items.push(self.value(elem)?);
+                }
+                self.depth -= 1;
+                Thrift::List(items)
+            }
+            11 => {
+                self.enter()?;
+                let n = self.varint()?;
+                let mut items = vec![];
+                if n > 0 {
+                    let types = self.take(1)?[0];
+                    for _ in 0..n {
+                        items.push(self.value(types >> 4)?);
+                        items.push(self.value(types & 0x0f)?);
+                    }
+                }
+                self.depth -= 1;
+                Thrift::List(items)
+            }
+            12 => self.parse_struct()?,
+            other => anyhow::bail!("unknown Thrift type {}", other),
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::Value as JsonValue;
+
+// What happens to the items chosen with --pick.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum PickAction {
+    // Normal output, limited to the picked items
+    Emit,
+    // One media URL per line
+    Urls,
+    // Download each picked item's media file
+    Download,
+}
+
+// One-line description shown in the picker.
+pub fn label(item: &JsonValue) -> String {
+    let n = crate::normalize::normalize(item);
+    let text = |v: &JsonValue| v.as_str().unwrap_or("").to_string();
+    let mut parts = vec![format!("{} {}", n["kind"].as_str().unwrap_or(""), n["id"])];
+    let title = text(&n["title"]);
+    if !title.is_empty() {
+        parts.push(title);
+    }
+    let creator = text(&n["creator"]["name"]);
+    if !creator.is_empty() {
+        parts.push(format!("by {}", creator));
+    }
+    if let (Some(w), Some(h)) = (n["width"].as_u64(), n["height"].as_u64()) {
+        parts.push(format!("{}x{}", w, h));
+    }
+    if let Some(d) = n["duration"].as_u64() {
+        parts.push(crate::human::format_duration(d));
+    }
+    parts.join(" — ")
+}
+
+// Media file URL: original photo or widest video file.
+pub fn media_url(item: &JsonValue) -> Option<String> {
+    crate::normalize::normalize(item)["urls"]["file"]
+        .as_str()
+        .map(String::from)
+}
+
+// `<kind>-<id>.<ext>`, extension taken from the media URL.
+pub fn file_name(item: &JsonValue) -> String {
+    file_name_for(item, media_url(item).as_deref())
+}
+
+// `<kind>-<id>.<ext>` with the extension taken from `url` (e.g. a size variant).
+pub fn file_name_for(item: &JsonValue, url: Option<&str>) -> String {
+    let n = crate::normalize::normalize(item);
+    let id = n["id"]
+        .as_str()
+        .map(String::from)
+        .unwrap_or_else(|| n["id"].to_string());
+    let kind = n["kind"].as_str().unwrap_or("item");
+    let ext = url
+        .and_then(crate::naming::ext_from_url)
+        .unwrap_or_else(|| if kind == "video" { "mp4" } else { "jpg" }.into());
+    crate::naming::sanitize_component(&format!("{}-{}.{}", kind, id, ext))
+}
+
+// Interactive multi-pick on the terminal: fuzzy-search one item at a time,
+// choose "[done]" (or press Esc) to finish. Returns indices in pick order.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick(items: &[JsonValue]) -> Result<Vec<usize>> {
+    use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        let err = serde_json::json!({
+            "error": "--pick needs an interactive terminal",
+            "hint": "drop --pick when piping, or filter with --fields/--limit",
+        });
+        return Err(anyhow::anyhow!(serde_yaml::to_string(&err)?));
+    }
+    let labels: Vec<String> = items.iter().map(label).collect();
+    let mut chosen: Vec<usize> = vec![];
+    loop {
+        let remaining: Vec<usize> = (0..items.len()).filter(|i| !chosen.contains(i)).collect();
+        if remaining.is_empty() {
+            break;
+        }
+        let mut choices = vec![format!("[done] ({} picked)", chosen.len())];
+        choices.extend(remaining.iter().map(|i| labels[*i].clone()));
+        let sel = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Pick items (type to filter)")
+            .items(&choices)
+            .default(0)
+            .interact_opt()?;
+        match sel {
+            Some(0) | None => break,
+            Some(n) => chosen.push(remaining[n - 1]),
+        }
+    }
+    Ok(chosen)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn pick(_items: &[JsonValue]) -> Result<Vec<usize>> {
+    anyhow::bail!("--pick is not available on this target")
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use serde_json::{json, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+// Placeholders a post-processing step may use.
+pub const PLACEHOLDERS: &[&str] = &["path", "dir", "name", "stem"];
+
+// Resolve a named pipeline from config and check every step before any runs,
+// so a typo fails before downloads start instead of after the first file.
+pub fn resolve<'a>(
+    pipelines: &'a BTreeMap<String, Vec<String>>,
+    name: &str,
+) -> Result<&'a [String]> {
+    let Some(steps) = pipelines.get(name) else {
+        let available: Vec<&String> = pipelines.keys().collect();
+        return Err(post_error(json!({
+            "error": "unknown post-processor",
+            "name": name,
+            "available": available,
+            "hint": "define it under postprocess.<name> in config.yaml",
+        })));
+    };
+    if steps.is_empty() {
+        return Err(post_error(json!({
+            "error": "empty post-processor",
+            "name": name,
+        })));
+    }
+    for step in steps {
+        for ph in placeholders(step) {
+            if !PLACEHOLDERS.contains(&ph.as_str()) {
+                return Err(post_error(json!({
+                    "error": "unknown placeholder in post-processor",
+                    "name": name,
+                    "step": step,
+                    "placeholder": format!("{{{}}}", ph),
+                    "allowed": PLACEHOLDERS,
+                })));
+            }
+        }
+    }
+    Ok(steps)
+}
+
+// `{name}` placeholders used in a step.
+fn placeholders(step: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut rest = step;
+    while let Some(start) = rest.find('{') {
+        let tail = &rest[start + 1..];
+        match tail.find('}') {
+            Some(end) => {
+                out.push(tail[..end].to_string());
+                rest = &tail[end + 1..];
+            }
+            None => break,
+        }
+    }
+    out
+}
+
+// Substitute placeholders with shell-quoted values for `file`.
+pub fn render(step: &str, file: &Path) -> String {
+    let dir = file
+        .parent()
+        .map(|d| d.display().to_string())
+        .unwrap_or_default();
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = file
+        .file_stem()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    step.replace("{path}", &quote(&file.display().to_string()))
+        .replace("{dir}", &quote(&dir))
+        .replace("{name}", &quote(&name))
+        .replace("{stem}", &quote(&stem))
+}
+
+#[cfg(not(windows))]
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(windows)]
+pub fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+// Run each step through the shell in order; the first failing step aborts
+// the pipeline with its exit status and stderr.
+pub fn run(name: &str, steps: &[String], file: &Path) -> Result<JsonValue> {
+    let mut done = vec![];
+    for step in steps {
+        let cmd = render(step, file);
+        #[cfg(not(windows))]
+        let output = Command::new("sh").arg("-c").arg(&cmd).output();
+        #[cfg(windows)]
+        let output = Command::new("cmd").arg("/C").arg(&cmd).output();
+        let output = output.map_err(|e| {
+            post_error(json!({
+                "error": "post-processor failed to start",
+                "name": name,
+                "command": cmd,
+                "reason": e.to_string(),
+            }))
+        })?;
+        if !output.status.success() {
+            return Err(post_error(json!({
+                "error": "post-processor step failed",
+                "name": name,
+                "command": cmd,
+                "status": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr).trim(),
+                "completed": done,
+            })));
+        }
+        done.push(JsonValue::String(cmd));
+    }
+    Ok(json!({ "name": name, "steps": done }))
+}
+
+fn post_error(v: JsonValue) -> anyhow::Error {
+    anyhow::anyhow!(serde_yaml::to_string(&v).unwrap_or_else(|_| "post-processor error".into()))
+}
@@ -0,0 +1,216 @@
+use anyhow::Result;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+// Inline photo previews (`photos preview`, `photos search --preview`). The
+// image goes out through the terminal's graphics protocol when one is known
+// (kitty, iTerm2 inline images, sixel); anything else gets truecolor half
+// blocks, or an ASCII ramp when color is off.
+
+// Preview width in terminal columns when --preview-width is not given.
+pub const DEFAULT_WIDTH: u32 = 40;
+// Pixels per column assumed when sizing images for sixel.
+const SIXEL_CELL_WIDTH: u32 = 8;
+// Largest base64 payload per kitty escape sequence.
+const KITTY_CHUNK: usize = 4096;
+// Dark to light.
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+    Blocks,
+    Ascii,
+}
+
+impl Protocol {
+    // Best protocol for the terminal described by `env` (e.g. `std::env::var`
+    // mapped to an Option); `color` false falls back to ASCII.
+    pub fn detect(env: impl Fn(&str) -> Option<String>, color: bool) -> Protocol {
+        let term = env("TERM").unwrap_or_default();
+        let program = env("TERM_PROGRAM").unwrap_or_default();
+        if env("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" || program == "ghostty" {
+            Protocol::Kitty
+        } else if matches!(program.as_str(), "iTerm.app" | "WezTerm")
+            || env("LC_TERMINAL").as_deref() == Some("iTerm2")
+        {
+            Protocol::Iterm
+        } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+            Protocol::Sixel
+        } else if !color || term == "dumb" {
+            Protocol::Ascii
+        } else {
+            Protocol::Blocks
+        }
+    }
+}
+
+// Terminal escape sequences (or text) drawing `img` `cols` columns wide,
+// ending with a newline.
+pub fn render(img: &DynamicImage, protocol: Protocol, cols: u32) -> Result<Vec<u8>> {
+    let cols = cols.max(1);
+    Ok(match protocol {
+        Protocol::Kitty => kitty(&png(img, cols * SIXEL_CELL_WIDTH)?, cols),
+        Protocol::Iterm => iterm(&png(img, cols * SIXEL_CELL_WIDTH)?, cols),
+        Protocol::Sixel => sixel(&fit(img, cols * SIXEL_CELL_WIDTH, 1.0)),
+        Protocol::Blocks => blocks(&fit(img, cols, 1.0)),
+        // Character cells are about twice as tall as wide
+        Protocol::Ascii => ascii(&fit(img, cols, 0.5)),
+    })
+}
+
+// Terminal width in columns, when stdout is a terminal.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn terminal_cols() -> Option<u32> {
+    use std::io::IsTerminal;
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    ratatui::crossterm::terminal::size()
+        .ok()
+        .map(|(w, _)| w as u32)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn terminal_cols() -> Option<u32> {
+    None
+}
+
+// `width` pixels wide, height scaled by `aspect` (rounded up to even so half
+// blocks pair up).
+fn fit(img: &DynamicImage, width: u32, aspect: f64) -> image::RgbImage {
+    let (w, h) = (img.width().max(1), img.height().max(1));
+    let height = ((width as f64 * h as f64 / w as f64) * aspect)
+        .round()
+        .max(1.0) as u32;
+    let height = height + height % 2;
+    img.resize_exact(width, height, FilterType::Triangle)
+        .to_rgb8()
+}
+
+// PNG of `img`, shrunk to at most `max_width` pixels wide.
+fn png(img: &DynamicImage, max_width: u32) -> Result<Vec<u8>> {
+    let img = if img.width() > max_width {
+        img.resize(max_width, u32::MAX, FilterType::Triangle)
+    } else {
+        img.clone()
+    };
+    let mut out = std::io::Cursor::new(vec![]);
+    img.write_to(&mut out, image::ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
+
+fn kitty(png: &[u8], cols: u32) -> Vec<u8> {
+    let data = base64::engine::general_purpose::STANDARD.encode(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = vec![];
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        let keys = if i == 0 {
+            format!("a=T,f=100,c={},m={}", cols, more)
+        } else {
+            format!("m={}", more)
+        };
+        out.extend_from_slice(format!("\x1b_G{};", keys).as_bytes());
+        out.extend_from_slice(chunk);
+        out.extend_from_slice(b"\x1b\\");
+    }
+    out.push(b'\n');
+    out
+}
+
+fn iterm(png: &[u8], cols: u32) -> Vec<u8> {
+    let data = base64::engine::general_purpose::STANDARD.encode(png);
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};preserveAspectRatio=1:{}\x07\n",
+        png.len(),
+        cols,
+        data
+    )
+    .into_bytes()
+}
+
+// Upper half block: foreground is the top pixel, background the bottom one.
+fn blocks(img: &image::RgbImage) -> Vec<u8> {
+    let mut out = String::new();
+    for y in (0..img.height()).step_by(2) {
+        for x in 0..img.width() {
+            let [r, g, b] = img.get_pixel(x, y).0;
+            let [r2, g2, b2] = img.get_pixel(x, (y + 1).min(img.height() - 1)).0;
+            out.push_str(&format!(
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                r, g, b, r2, g2, b2
+            ));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out.into_bytes()
+}
+
+fn ascii(img: &image::RgbImage) -> Vec<u8> {
+    let mut out = vec![];
+    for row in img.rows() {
+        for p in row {
+            let luma = (0.2126 * p[0] as f64 + 0.7152 * p[1] as f64 + 0.0722 * p[2] as f64) / 255.0;
+            let i = (luma * (ASCII_RAMP.len() - 1) as f64).round() as usize;
+            out.push(ASCII_RAMP[i]);
+        }
+        out.push(b'\n');
+    }
+    out
+}
+
+// Sixel with a fixed 6x6x6 color cube: each band of six pixel rows is
+// written once per color present in it.
+fn sixel(img: &image::RgbImage) -> Vec<u8> {
+    let level = |c: u8| (c as u32 * 5 + 127) / 255;
+    let index = |p: &image::Rgb<u8>| (level(p[0]) * 36 + level(p[1]) * 6 + level(p[2])) as usize;
+    let (w, h) = img.dimensions();
+    let mut out = format!("\x1bPq\"1;1;{};{}", w, h);
+    for i in 0..216u32 {
+        let pct = |l: u32| l * 100 / 5;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            i,
+            pct(i / 36),
+            pct(i / 6 % 6),
+            pct(i % 6)
+        ));
+    }
+    for band in (0..h).step_by(6) {
+        let rows = (h - band).min(6);
+        let mut bits = vec![vec![0u8; w as usize]; 216];
+        let mut used = [false; 216];
+        for dy in 0..rows {
+            for x in 0..w {
+                let i = index(img.get_pixel(x, band + dy));
+                bits[i][x as usize] |= 1 << dy;
+                used[i] = true;
+            }
+        }
+        for (i, cols) in bits.iter().enumerate().filter(|(i, _)| used[*i]) {
+            out.push_str(&format!("#{}", i));
+            let mut run = cols.iter().peekable();
+            while let Some(&b) = run.next() {
+                let mut n = 1;
+                while run.peek() == Some(&&b) {
+                    run.next();
+                    n += 1;
+                }
+                let ch = (63 + b) as char;
+                if n > 3 {
+                    out.push_str(&format!("!{}{}", n, ch));
+                } else {
+                    (0..n).for_each(|_| out.push(ch));
+                }
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\\n");
+    out.into_bytes()
+}
@@ -0,0 +1,82 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+// Redraw at most this often.
+const TICK: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 24;
+
+// Single-line transfer progress on stderr. Disabled bars cost nothing, so
+// callers create one unconditionally.
+pub struct Progress {
+    label: String,
+    total: Option<u64>,
+    done: u64,
+    enabled: bool,
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    pub fn new(label: &str, enabled: bool) -> Self {
+        Self {
+            label: label.to_string(),
+            total: None,
+            done: 0,
+            enabled,
+            drawn: None,
+        }
+    }
+
+    // Restart the count, e.g. when a retry resumes at `done` of `total`.
+    pub fn reset(&mut self, done: u64, total: Option<u64>) {
+        self.done = done;
+        self.total = total;
+        self.draw(true);
+    }
+
+    pub fn inc(&mut self, n: u64) {
+        self.done += n;
+        self.draw(false);
+    }
+
+    pub fn finish(&mut self) {
+        if self.enabled && self.drawn.is_some() {
+            self.draw(true);
+            eprintln!();
+            self.drawn = None;
+        }
+    }
+
+    pub fn line(&self) -> String {
+        let seps = crate::human::separators(None);
+        let done = crate::human::format_bytes(self.done, seps);
+        match self.total.filter(|t| *t > 0) {
+            Some(total) => {
+                let frac = (self.done as f64 / total as f64).clamp(0.0, 1.0);
+                let filled = (frac * BAR_WIDTH as f64).round() as usize;
+                format!(
+                    "{} [{}{}] {:>3}% {}/{}",
+                    self.label,
+                    "#".repeat(filled),
+                    "-".repeat(BAR_WIDTH - filled),
+                    (frac * 100.0).floor() as u64,
+                    done,
+                    crate::human::format_bytes(total, seps)
+                )
+            }
+            None => format!("{} {}", self.label, done),
+        }
+    }
+
+    fn draw(&mut self, force: bool) {
+        if !self.enabled {
+            return;
+        }
+        if !force && self.drawn.map(|t| t.elapsed() < TICK).unwrap_or(false) {
+            return;
+        }
+        let mut err = std::io::stderr().lock();
+        let _ = write!(err, "\r\x1b[2K{}", self.line());
+        let _ = err.flush();
+        self.drawn = Some(Instant::now());
+    }
+}
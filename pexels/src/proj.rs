@@ -3,6 +3,8 @@ use serde_json::{Map, Value};
 // Simple projection supporting:
 // - dot paths: a.b.c
 // - wildcard for arrays: items[*].id
+// - wildcard for object keys: src.*
+// - recursive descent: **.link (every `link` at any depth)
 // - named sets: @ids, @urls, @files, @thumbnails, @all
 pub fn project(input: &Value, fields: &[String]) -> Value {
     if fields.is_empty() || fields.iter().any(|f| f == "@all") {
@@ -27,6 +29,12 @@ pub fn project(input: &Value, fields: &[String]) -> Value {
                 let v = extract_by_keys(input, &["image", "thumbnail", "thumb", "tiny"]);
                 merge(&mut out, &v);
             }
+            path if is_structural(path) => {
+                let parts: Vec<&str> = path.split('.').collect();
+                if let Some(v) = prune(input, &parts) {
+                    merge(&mut out, &v);
+                }
+            }
             path => {
                 let val = select_path(input, path);
                 if !val.is_null() {
@@ -95,21 +103,204 @@ pub fn project_items_with_fallback(items: &[Value], fields: &[String]) -> Vec<Va
         .collect()
 }
 
+// Per-item projection pipeline: optional normalization, --fields selection
+// (with the non-empty fallback), --exclude-fields removal, then --map. Holds no
+// state between items so it can run on each item as pages arrive.
+#[derive(Clone, Debug, Default)]
+pub struct Projector {
+    fields: Vec<String>,
+    exclude: Vec<String>,
+    normalize: bool,
+    keep: Vec<String>,
+    maps: Vec<crate::expr::MapExpr>,
+}
+
+impl Projector {
+    pub fn new(fields: Vec<String>) -> Self {
+        Self {
+            fields,
+            ..Default::default()
+        }
+    }
+
+    pub fn exclude(mut self, paths: Vec<String>) -> Self {
+        self.exclude = paths;
+        self
+    }
+
+    pub fn normalize(mut self, on: bool) -> Self {
+        self.normalize = on;
+        self
+    }
+
+    // Computed fields (--map), evaluated on the item as --fields sees it.
+    pub fn maps(mut self, maps: Vec<crate::expr::MapExpr>) -> Self {
+        self.maps = maps;
+        self
+    }
+
+    // Top-level keys copied from the original item even when not selected.
+    pub fn keep(mut self, key: &str) -> Self {
+        self.keep.push(key.to_string());
+        self
+    }
+
+    pub fn apply(&self, item: &Value) -> Value {
+        let shaped = if self.normalize && item.is_object() {
+            crate::normalize::normalize(item)
+        } else {
+            item.clone()
+        };
+        let mut out = if shaped.is_object() {
+            project_item_with_fallback(&shaped, &self.fields)
+        } else {
+            project(&shaped, &self.fields)
+        };
+        for path in &self.exclude {
+            let parts: Vec<&str> = path.split('.').collect();
+            remove_path(&mut out, &parts);
+        }
+        crate::expr::apply(&self.maps, &shaped, &mut out);
+        if let (Some(orig), Some(map)) = (item.as_object(), out.as_object_mut()) {
+            for k in &self.keep {
+                if let Some(v) = orig.get(k) {
+                    map.insert(k.clone(), v.clone());
+                }
+            }
+        }
+        out
+    }
+}
+
+// Remove every value matched by `parts` (same path syntax as --fields).
+fn remove_path(v: &mut Value, parts: &[&str]) {
+    let Some((head, tail)) = parts.split_first() else {
+        return;
+    };
+    match (*head, v) {
+        ("**", v) => {
+            remove_path(v, tail);
+            match v {
+                Value::Object(map) => map.values_mut().for_each(|c| remove_path(c, parts)),
+                Value::Array(arr) => arr.iter_mut().for_each(|c| remove_path(c, parts)),
+                _ => {}
+            }
+        }
+        ("*", Value::Object(map)) if tail.is_empty() => map.clear(),
+        ("*", Value::Object(map)) => map.values_mut().for_each(|c| remove_path(c, tail)),
+        ("*" | "[*]", Value::Array(arr)) => arr.iter_mut().for_each(|c| remove_path(c, tail)),
+        (key, Value::Object(map)) => match key.strip_suffix("[*]") {
+            Some(base) => {
+                if let Some(Value::Array(arr)) = map.get_mut(base) {
+                    arr.iter_mut().for_each(|c| remove_path(c, tail));
+                }
+            }
+            None if tail.is_empty() => {
+                map.remove(key);
+            }
+            None => {
+                if let Some(child) = map.get_mut(key) {
+                    remove_path(child, tail);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+// Deep merge so overlapping paths (a.b, a.c) and wildcard results combine.
 fn merge(dst: &mut Value, src: &Value) {
-    match (dst.clone(), src) {
+    match (dst, src) {
         (_, Value::Null) => {}
-        (Value::Null, _) => *dst = src.clone(),
+        (d @ Value::Null, _) => *d = src.clone(),
         (Value::Object(a), Value::Object(b)) => {
-            let mut merged = a;
             for (k, v) in b {
-                merged.insert(k.clone(), v.clone());
+                match a.get_mut(k) {
+                    Some(existing) => merge(existing, v),
+                    None => {
+                        a.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (x, y) in a.iter_mut().zip(b) {
+                merge(x, y);
             }
-            *dst = Value::Object(merged);
         }
         _ => {}
     }
 }
 
+// Paths using key wildcards or recursive descent keep the input's shape.
+fn is_structural(path: &str) -> bool {
+    path.split('.').any(|p| p == "*" || p == "**")
+}
+
+// Copy of `input` reduced to the parts matched by `parts`; None when nothing matches.
+fn prune(input: &Value, parts: &[&str]) -> Option<Value> {
+    let Some((head, tail)) = parts.split_first() else {
+        return Some(input.clone());
+    };
+    let out = match (*head, input) {
+        ("**", _) => {
+            // Match here, then keep descending with `**` still in front
+            let mut here = prune(input, tail).unwrap_or(Value::Null);
+            let below = match input {
+                Value::Object(map) => {
+                    let m: Map<String, Value> = map
+                        .iter()
+                        .filter_map(|(k, v)| prune(v, parts).map(|p| (k.clone(), p)))
+                        .collect();
+                    Value::Object(m)
+                }
+                Value::Array(arr) => {
+                    Value::Array(arr.iter().filter_map(|v| prune(v, parts)).collect())
+                }
+                _ => Value::Null,
+            };
+            merge(&mut here, &below);
+            here
+        }
+        ("*", Value::Object(map)) => Value::Object(
+            map.iter()
+                .filter_map(|(k, v)| prune(v, tail).map(|p| (k.clone(), p)))
+                .collect(),
+        ),
+        ("*" | "[*]", Value::Array(arr)) => {
+            Value::Array(arr.iter().filter_map(|v| prune(v, tail)).collect())
+        }
+        (key, Value::Object(map)) => {
+            let (base, each) = match key.strip_suffix("[*]") {
+                Some(base) => (base, true),
+                None => (key, false),
+            };
+            let sub = match (map.get(base), each) {
+                (Some(Value::Array(arr)), true) => Some(Value::Array(
+                    arr.iter().filter_map(|v| prune(v, tail)).collect(),
+                )),
+                (Some(_), true) | (None, _) => None,
+                (Some(v), false) => prune(v, tail),
+            };
+            match sub {
+                Some(v) => {
+                    let mut m = Map::new();
+                    m.insert(base.to_string(), v);
+                    Value::Object(m)
+                }
+                None => Value::Null,
+            }
+        }
+        _ => Value::Null,
+    };
+    match &out {
+        Value::Null => None,
+        Value::Object(m) if m.is_empty() => None,
+        Value::Array(a) if a.is_empty() => None,
+        _ => Some(out),
+    }
+}
+
 fn select_path(input: &Value, path: &str) -> Value {
     let parts: Vec<&str> = path.split('.').collect();
     select_inner(input, &parts)
@@ -244,6 +435,47 @@ mod tests {
         assert!(out.is_object());
     }
 
+    #[test]
+    fn test_key_wildcard_and_recursive_descent() {
+        let v = json!({
+            "id": 1,
+            "src": {"original": "o", "tiny": "t"},
+            "video_files": [{"link": "a", "width": 1}, {"link": "b"}],
+            "user": {"name": "n"}
+        });
+        let out = project(&v, &["src.*".into()]);
+        assert_eq!(out, json!({"src": {"original": "o", "tiny": "t"}}));
+        let out = project(&v, &["**.link".into()]);
+        assert_eq!(out, json!({"video_files": [{"link": "a"}, {"link": "b"}]}));
+        let out = project(&v, &["user.name".into(), "src.*".into(), "id".into()]);
+        assert_eq!(out["user"]["name"], "n");
+        assert_eq!(out["src"]["tiny"], "t");
+    }
+
+    #[test]
+    fn test_projector_excludes_after_selection() {
+        let v = json!({"id": 1, "alt": "a", "src": {"original": "o", "tiny": "t"},
+            "video_files": [{"link": "l", "quality": "hd"}]});
+        let p = Projector::new(vec![]).exclude(vec![
+            "src.tiny".into(),
+            "**.quality".into(),
+            "alt".into(),
+        ]);
+        let out = p.apply(&v);
+        assert_eq!(out["src"], json!({"original": "o"}));
+        assert_eq!(out["video_files"], json!([{"link": "l"}]));
+        assert!(out.get("alt").is_none());
+        let p = Projector::new(vec!["alt".into()]).keep("id");
+        assert_eq!(p.apply(&v), json!({"alt": "a", "id": 1}));
+    }
+
+    #[test]
+    fn test_overlapping_paths_merge() {
+        let v = json!({"a": {"b": 1, "c": 2, "d": 3}});
+        let out = project(&v, &["a.b".into(), "a.c".into()]);
+        assert_eq!(out, json!({"a": {"b": 1, "c": 2}}));
+    }
+
     #[test]
     fn test_project_array_items_direct() {
         let items = json!([{"id":1,"width":100,"height":200,"src":{"original":"u"}}]);
@@ -0,0 +1,121 @@
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashSet;
+
+// Boolean search on top of the single-query API: one search per `any_of`
+// term (each including the base query and `all_of` terms), then client-side
+// filtering on the item's text (alt text, page URL slug) and dedupe by id.
+#[derive(Clone, Debug, Default)]
+pub struct BoolQuery {
+    pub base: Option<String>,
+    pub any_of: Vec<String>,
+    pub all_of: Vec<String>,
+    pub none_of: Vec<String>,
+}
+
+impl BoolQuery {
+    pub fn is_plain(&self) -> bool {
+        self.any_of.is_empty() && self.all_of.is_empty() && self.none_of.is_empty()
+    }
+
+    // Queries to send, in order.
+    pub fn searches(&self) -> Vec<String> {
+        let mut common: Vec<&str> = vec![];
+        if let Some(b) = self.base.as_deref().filter(|b| !b.trim().is_empty()) {
+            common.push(b.trim());
+        }
+        common.extend(self.all_of.iter().map(|s| s.as_str()));
+        if self.any_of.is_empty() {
+            return vec![common.join(" ")];
+        }
+        self.any_of
+            .iter()
+            .map(|t| {
+                let mut q = common.clone();
+                q.push(t);
+                q.join(" ")
+            })
+            .collect()
+    }
+
+    // True when the item mentions every `all_of` term and no `none_of` term.
+    pub fn matches(&self, item: &JsonValue) -> bool {
+        let text = item_text(item);
+        self.all_of.iter().all(|t| text.contains(&t.to_lowercase()))
+            && !self
+                .none_of
+                .iter()
+                .any(|t| text.contains(&t.to_lowercase()))
+    }
+
+    // Merge the responses of `searches()` into one response: items filtered,
+    // deduped by id (first occurrence wins), with a `query_plan` summary.
+    pub fn merge(&self, responses: &[JsonValue], items_key: &str) -> JsonValue {
+        let mut seen = HashSet::new();
+        let mut items = vec![];
+        let (mut duplicates, mut filtered) = (0usize, 0usize);
+        for resp in responses {
+            let page = resp.get(items_key).and_then(|v| v.as_array());
+            for item in page.into_iter().flatten() {
+                if !self.matches(item) {
+                    filtered += 1;
+                    continue;
+                }
+                let id = item.get("id").map(|v| v.to_string()).unwrap_or_default();
+                if !seen.insert(id) {
+                    duplicates += 1;
+                    continue;
+                }
+                items.push(item.clone());
+            }
+        }
+        let mut out = serde_json::Map::new();
+        out.insert("total_results".into(), json!(items.len()));
+        out.insert(items_key.into(), JsonValue::Array(items));
+        out.insert(
+            "query_plan".into(),
+            json!({
+                "searches": self.searches(),
+                "filtered_out": filtered,
+                "duplicates": duplicates,
+            }),
+        );
+        JsonValue::Object(out)
+    }
+}
+
+// Lowercased searchable text: alt text plus the words of the page URL slug.
+fn item_text(item: &JsonValue) -> String {
+    let alt = item.get("alt").and_then(|v| v.as_str()).unwrap_or("");
+    let slug = item
+        .get("url")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .replace(['-', '/'], " ");
+    format!("{} {}", alt, slug).to_lowercase()
+}
+
+// Contributor identity: photographer id/name for photos, user id/name for videos.
+pub fn creator_key(item: &JsonValue) -> Option<String> {
+    let creator = &crate::normalize::normalize(item)["creator"];
+    ["id", "name"]
+        .iter()
+        .map(|k| &creator[*k])
+        .find(|v| !v.is_null())
+        .map(|v| v.to_string())
+}
+
+// Keep at most `max` items per contributor, preserving order. Items without a
+// known contributor are never capped. Returns the number of items dropped.
+pub fn cap_per_creator(items: &mut Vec<JsonValue>, max: usize) -> usize {
+    let mut counts: std::collections::HashMap<String, usize> = Default::default();
+    let before = items.len();
+    items.retain(|item| match creator_key(item) {
+        Some(key) => {
+            let n = counts.entry(key).or_insert(0);
+            *n += 1;
+            *n <= max
+        }
+        None => true,
+    });
+    before - items.len()
+}
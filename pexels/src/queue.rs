@@ -0,0 +1,107 @@
+use crate::state::State;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+// Persistent download queue for `pexels queue`. Picks accumulate in the state
+// store as `queue:<kind>:<id>` across sessions and are fetched in one burst
+// by `queue run`; queuing an item again replaces its size/rendition.
+
+pub const NAMESPACE: &str = "queue";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaType {
+    #[value(alias = "photos")]
+    Photo,
+    #[value(alias = "videos")]
+    Video,
+}
+
+impl MediaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaType::Photo => "photo",
+            MediaType::Video => "video",
+        }
+    }
+}
+
+// One queued download. Photos keep their `src.*` size; videos the rendition
+// constraints used by `videos download`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub kind: MediaType,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<u64>,
+    pub added_at: String,
+}
+
+impl QueueItem {
+    pub fn new(kind: MediaType, id: &str) -> Self {
+        Self {
+            kind,
+            id: id.to_string(),
+            size: None,
+            quality: None,
+            max_width: None,
+            max_height: None,
+            added_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn key(&self) -> String {
+        key(self.kind, &self.id)
+    }
+
+    pub fn rendition(&self) -> crate::video::Rendition {
+        crate::video::Rendition {
+            quality: self
+                .quality
+                .as_deref()
+                .and_then(|q| crate::video::VideoQuality::from_str(q, true).ok()),
+            max_width: self.max_width,
+            max_height: self.max_height,
+        }
+    }
+}
+
+pub fn key(kind: MediaType, id: &str) -> String {
+    format!("{}:{}:{}", NAMESPACE, kind.as_str(), id)
+}
+
+// Stage `item`; true when it replaced an entry already queued.
+pub fn add(st: &mut State, item: &QueueItem) -> bool {
+    let key = item.key();
+    let replaced = st.get(&key).is_some();
+    st.set(&key, serde_json::to_value(item).unwrap_or_default());
+    replaced
+}
+
+// Queued items in the order they were added. Unreadable entries are skipped.
+pub fn items(st: &State) -> Vec<QueueItem> {
+    let mut out: Vec<QueueItem> = st
+        .entries(Some(NAMESPACE))
+        .filter_map(|(_, e)| serde_json::from_value(e.value.clone()).ok())
+        .collect();
+    out.sort_by(|a, b| a.added_at.cmp(&b.added_at));
+    out
+}
+
+// Stage removal of every queued entry; returns how many there were.
+pub fn clear(st: &mut State) -> usize {
+    let keys: Vec<String> = st
+        .entries(Some(NAMESPACE))
+        .map(|(k, _)| k.clone())
+        .collect();
+    for k in &keys {
+        st.remove(k);
+    }
+    keys.len()
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Below this many remaining requests, calls are spread over the rest of the
+// window instead of being sent back to back.
+pub const PACE_BELOW: u64 = 50;
+// Longest pause inserted between paced requests.
+pub const MAX_PACE: Duration = Duration::from_secs(2);
+
+// Rate-limit window reported by the API on every response:
+// `X-Ratelimit-Limit` (requests per window), `X-Ratelimit-Remaining` and
+// `X-Ratelimit-Reset` (UNIX seconds when the window refills).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Window {
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    pub reset: i64,
+}
+
+impl Window {
+    // Window from response headers; None when the response carries no
+    // remaining/reset pair (CDN media, errors from proxies, ...).
+    pub fn from_headers<'a>(header: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let num = |name: &str| header(name).and_then(|v| v.trim().parse::<i64>().ok());
+        Some(Self {
+            limit: num("x-ratelimit-limit").map(|n| n.max(0) as u64),
+            remaining: num("x-ratelimit-remaining")?.max(0) as u64,
+            reset: num("x-ratelimit-reset")?,
+        })
+    }
+
+    // No requests left and the window has not refilled yet at `now`.
+    pub fn exhausted(&self, now: i64) -> bool {
+        self.remaining == 0 && self.reset > now
+    }
+
+    // Time until the window refills, when it is exhausted at `now`.
+    pub fn wait(&self, now: i64) -> Option<Duration> {
+        self.exhausted(now)
+            .then(|| Duration::from_secs((self.reset - now) as u64))
+    }
+
+    // Delay before the next request at `now`: until the reset when the
+    // window is exhausted, else an even share of the time left (capped at
+    // MAX_PACE) once fewer than PACE_BELOW requests remain.
+    pub fn pace(&self, now: i64) -> Duration {
+        if let Some(wait) = self.wait(now) {
+            return wait;
+        }
+        if self.remaining >= PACE_BELOW || self.reset <= now {
+            return Duration::ZERO;
+        }
+        let left = Duration::from_secs((self.reset - now) as u64);
+        (left / (self.remaining as u32 + 1)).min(MAX_PACE)
+    }
+
+    // Reset time as RFC 3339, for resume hints.
+    pub fn resume_at(&self) -> String {
+        chrono::DateTime::from_timestamp(self.reset, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| self.reset.to_string())
+    }
+}
+
+// State key under which a host's last window is kept between runs.
+pub fn state_key(host: &str) -> String {
+    format!("ratelimit:{}", host)
+}
+
+// Last window persisted for `host` and when it was seen.
+pub fn saved(st: &crate::state::State, host: &str) -> Option<(Window, String)> {
+    let entry = st.entry(&state_key(host))?;
+    let w = serde_json::from_value(entry.value.clone()).ok()?;
+    Some((w, entry.updated_at.clone()))
+}
+
+// Write `w` as the latest window for `host`, touching only that key.
+pub fn persist(host: &str, w: &Window) -> anyhow::Result<()> {
+    let mut st = crate::state::State::detached(&crate::state::State::default_path());
+    st.set(&state_key(host), serde_json::to_value(w)?);
+    st.save()
+}
+
+// Structured error for a request refused locally because the window is
+// exhausted and --no-wait was given.
+pub fn exhausted_error(w: &Window) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "rate limit exhausted",
+        "remaining": w.remaining,
+        "limit": w.limit,
+        "resume_at": w.resume_at(),
+        "hint": "retry after resume_at, or drop --no-wait to wait for the reset",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "rate limit exhausted".into()))
+}
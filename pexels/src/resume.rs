@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// `--resume FILE` for --all walks: after every page the file records where
+// the walk stands, so a run cut short (Ctrl-C, --no-wait at the rate limit,
+// a network error, --soft-deadline) continues at the next page instead of
+// fetching the earlier ones again. A missing file starts a new walk.
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    // Endpoint path and query (without `page`) of the walk; only the same
+    // command may resume it
+    pub endpoint: String,
+    pub query: BTreeMap<String, String>,
+    // Next page to fetch and its leading items already returned
+    pub page: u64,
+    pub skip: usize,
+    // Items and pages returned by every run so far, counted against
+    // --limit and --max-pages
+    pub collected: u32,
+    pub pages: u32,
+    // The walk ran out of pages
+    pub done: bool,
+}
+
+impl Checkpoint {
+    // A walk not started yet.
+    pub fn start(endpoint: &str, qp: &[(String, String)]) -> Self {
+        let page = qp
+            .iter()
+            .find(|(k, _)| k == "page")
+            .and_then(|(_, v)| v.parse().ok())
+            .unwrap_or(1);
+        Self {
+            endpoint: endpoint.to_string(),
+            query: query_of(qp),
+            page,
+            skip: 0,
+            collected: 0,
+            pages: 0,
+            done: false,
+        }
+    }
+
+    // Whether this checkpoint was written by a walk over `endpoint` and `qp`.
+    pub fn matches(&self, endpoint: &str, qp: &[(String, String)]) -> bool {
+        self.endpoint == endpoint && self.query == query_of(qp)
+    }
+}
+
+fn query_of(qp: &[(String, String)]) -> BTreeMap<String, String> {
+    qp.iter()
+        .filter(|(k, _)| k != "page")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+pub fn load(path: &Path) -> Result<Option<Checkpoint>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context("read resume file"),
+    };
+    serde_json::from_str(&text).map(Some).map_err(|e| {
+        let err = serde_json::json!({
+            "error": "unreadable resume file",
+            "resume": path.display().to_string(),
+            "reason": e.to_string(),
+            "hint": "delete the file to start over from the first page",
+        });
+        anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "unreadable resume file".into())
+        )
+    })
+}
+
+// Replace the file atomically, so an interrupted run leaves the previous
+// checkpoint intact.
+pub fn save(path: &Path, cp: &Checkpoint) -> Result<()> {
+    let tmp = crate::download::part_path(path);
+    std::fs::write(&tmp, serde_json::to_string_pretty(cp)? + "\n").context("write resume file")?;
+    std::fs::rename(&tmp, path).context("write resume file")
+}
+
+pub fn done_error(path: &Path, cp: &Checkpoint) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "walk already finished",
+        "resume": path.display().to_string(),
+        "collected": cp.collected,
+        "hint": "delete the resume file to start over from the first page",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "walk already finished".into()))
+}
+
+pub fn mismatch_error(path: &Path, cp: &Checkpoint) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "resume file belongs to another walk",
+        "resume": path.display().to_string(),
+        "endpoint": cp.endpoint,
+        "query": cp.query,
+        "hint": "rerun the command that wrote it (same query, filters and --per-page), or use another file",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err)
+        .unwrap_or_else(|_| "resume file belongs to another walk".into()))
+}
@@ -0,0 +1,464 @@
+use crate::config::{Config, TokenSource, TokenStorage};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde_json::Value as JsonValue;
+
+// Registry of the keys behind `config set/get/unset/list`. Fixed keys are a
+// table of accessors; `projections.<resource>`, `hosts.<profile>.<field>`
+// and `remotes.<host>.<field>` are matched by pattern. `get` reports the
+// effective value for this run (defaults, profile, env and flags applied);
+// `set`/`unset` edit a freshly loaded config that is then written back.
+
+pub struct Key {
+    pub name: &'static str,
+    // Dotted location in config.yaml
+    path: &'static str,
+    // Value is masked in `config list`
+    secret: bool,
+    get: fn(&Config) -> String,
+    set: fn(&mut Config, &str) -> Result<()>,
+    unset: fn(&mut Config),
+}
+
+const fn key(
+    name: &'static str,
+    get: fn(&Config) -> String,
+    set: fn(&mut Config, &str) -> Result<()>,
+    unset: fn(&mut Config),
+) -> Key {
+    Key {
+        name,
+        path: name,
+        secret: false,
+        get,
+        set,
+        unset,
+    }
+}
+
+pub const KEYS: &[Key] = &[
+    Key {
+        secret: true,
+        ..key(
+            "token",
+            |c| c.token.clone().unwrap_or_default(),
+            |c, v| {
+                c.token = Some(v.to_string());
+                c.token_source = Some(TokenSource::Config);
+                Ok(())
+            },
+            |c| {
+                c.token = None;
+                c.token_source = Some(TokenSource::None);
+            },
+        )
+    },
+    key(
+        "token_storage",
+        |c| c.token_storage.as_str().to_string(),
+        |c, v| c.set_token_storage(TokenStorage::parse(v)?),
+        |c| c.token_storage = TokenStorage::File,
+    ),
+    key(
+        "audit.enabled",
+        |c| c.audit.enabled.to_string(),
+        |c, v| {
+            c.audit.enabled = parse_bool(v)?;
+            Ok(())
+        },
+        |c| c.audit.enabled = false,
+    ),
+    key(
+        "audit.path",
+        |c| c.audit.log_path().display().to_string(),
+        |c, v| {
+            c.audit.path = Some(v.into());
+            Ok(())
+        },
+        |c| c.audit.path = None,
+    ),
+    key(
+        "human",
+        |c| c.human.to_string(),
+        |c, v| {
+            c.human = parse_bool(v)?;
+            Ok(())
+        },
+        |c| c.human = false,
+    ),
+    key(
+        "watch.interval",
+        |c| c.watch.interval.map(|s| s.to_string()).unwrap_or_default(),
+        |c, v| {
+            c.watch.interval = Some(v.parse().context("expected seconds")?);
+            Ok(())
+        },
+        |c| c.watch.interval = None,
+    ),
+    key(
+        "cache.ttl",
+        |c| c.cache.ttl().as_secs().to_string(),
+        |c, v| {
+            c.cache.ttl = Some(v.parse().context("expected seconds")?);
+            Ok(())
+        },
+        |c| c.cache.ttl = None,
+    ),
+    key(
+        "web.format",
+        |c| c.web.format.unwrap_or_default().ext().to_string(),
+        |c, v| {
+            c.web.format = Some(
+                crate::optimize::WebFormat::from_str(v, true)
+                    .map_err(|_| anyhow::anyhow!("expected webp or avif"))?,
+            );
+            Ok(())
+        },
+        |c| c.web.format = None,
+    ),
+    key(
+        "web.quality",
+        |c| {
+            c.web
+                .quality
+                .unwrap_or(crate::optimize::DEFAULT_QUALITY)
+                .to_string()
+        },
+        |c, v| {
+            c.web.quality = Some(
+                v.parse::<u8>()
+                    .ok()
+                    .filter(|q| *q <= 100)
+                    .context("expected 0-100")?,
+            );
+            Ok(())
+        },
+        |c| c.web.quality = None,
+    ),
+    Key {
+        path: "output",
+        ..key(
+            "default_format",
+            |c| {
+                c.output
+                    .as_ref()
+                    .and_then(|f| f.to_possible_value())
+                    .map_or("auto".to_string(), |v| v.get_name().to_string())
+            },
+            |c, v| {
+                c.output = match v {
+                    "auto" => None,
+                    v => Some(crate::output::OutputFormat::from_str(v, true).map_err(|_| {
+                        anyhow::anyhow!("expected auto, yaml, json, raw, ndjson, csv or tsv")
+                    })?),
+                };
+                Ok(())
+            },
+            |c| c.output = None,
+        )
+    },
+    key(
+        "default_per_page",
+        |c| {
+            c.default_per_page
+                .map(|n| n.to_string())
+                .unwrap_or_default()
+        },
+        |c, v| {
+            c.default_per_page = Some(
+                v.parse::<u32>()
+                    .ok()
+                    .filter(|n| (1..=crate::api::MAX_PER_PAGE).contains(n))
+                    .context("expected 1-80")?,
+            );
+            Ok(())
+        },
+        |c| c.default_per_page = None,
+    ),
+    key(
+        "locale",
+        |c| c.locale.clone().unwrap_or_default(),
+        |c, v| {
+            c.locale = Some(v.to_string());
+            Ok(())
+        },
+        |c| c.locale = None,
+    ),
+    key(
+        "download_dir",
+        |c| {
+            c.download_dir
+                .as_ref()
+                .map(|d| d.display().to_string())
+                .unwrap_or_default()
+        },
+        |c, v| {
+            c.download_dir = Some(v.into());
+            Ok(())
+        },
+        |c| c.download_dir = None,
+    ),
+    key(
+        "max_retries",
+        |c| c.max_retries.to_string(),
+        |c, v| {
+            c.default_max_retries = Some(v.parse().context("expected a number")?);
+            Ok(())
+        },
+        |c| c.default_max_retries = None,
+    ),
+    key(
+        "timeout",
+        |c| c.timeout_secs.to_string(),
+        |c, v| {
+            c.default_timeout = Some(v.parse().context("expected seconds")?);
+            Ok(())
+        },
+        |c| c.default_timeout = None,
+    ),
+];
+
+// Older spellings still accepted by set/get/unset.
+const ALIASES: &[(&str, &str)] = &[("api_key", "token"), ("output", "default_format")];
+
+fn parse_bool(v: &str) -> Result<bool> {
+    v.parse().context("expected true or false")
+}
+
+// A key named on the command line, resolved against the registry.
+#[derive(Clone, Copy)]
+pub enum Entry<'a> {
+    Fixed(&'static Key),
+    Projection(&'a str),
+    Host(&'a str, &'a str),
+    Remote(&'a str, &'a str),
+}
+
+pub fn lookup(name: &str) -> Result<Entry<'_>> {
+    let canonical = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, k)| k);
+    if let Some(k) = KEYS.iter().find(|k| k.name == canonical) {
+        return Ok(Entry::Fixed(k));
+    }
+    if let Some(resource) = name
+        .strip_prefix("projections.")
+        .filter(|r| crate::config::PROJECTION_RESOURCES.contains(r))
+    {
+        return Ok(Entry::Projection(resource));
+    }
+    if let Some((profile, field)) = crate::config::host_profile_key(name) {
+        return Ok(Entry::Host(profile, field));
+    }
+    if let Some((host, field)) = crate::config::remote_key(name) {
+        return Ok(Entry::Remote(host, field));
+    }
+    Err(unknown(name))
+}
+
+impl Entry<'_> {
+    pub fn name(&self) -> String {
+        match self {
+            Entry::Fixed(k) => k.name.to_string(),
+            Entry::Projection(r) => format!("projections.{}", r),
+            Entry::Host(p, f) => format!("hosts.{}.{}", p, f),
+            Entry::Remote(h, f) => format!("remotes.{}.{}", h, f),
+        }
+    }
+
+    pub fn get(&self, cfg: &Config) -> String {
+        match self {
+            Entry::Fixed(k) => (k.get)(cfg),
+            Entry::Projection(r) => cfg.projection(r).join(","),
+            Entry::Host(p, f) => cfg.hosts.get(*p).and_then(|h| h.get(f)).unwrap_or_default(),
+            Entry::Remote(h, f) => cfg
+                .remotes
+                .get(*h)
+                .and_then(|r| r.get(f))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set(&self, cfg: &mut Config, value: &str) -> Result<()> {
+        match self {
+            Entry::Fixed(k) => (k.set)(cfg, value),
+            Entry::Projection(r) => {
+                let fields = value
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect();
+                cfg.projections.insert(r.to_string(), fields);
+                Ok(())
+            }
+            Entry::Host(p, f) => cfg.hosts.entry(p.to_string()).or_default().set(f, value),
+            Entry::Remote(h, f) => cfg.remotes.entry(h.to_string()).or_default().set(f, value),
+        }
+    }
+
+    // Back to the built-in default; emptied host and remote entries go too.
+    pub fn unset(&self, cfg: &mut Config) -> Result<()> {
+        match self {
+            Entry::Fixed(k) => (k.unset)(cfg),
+            Entry::Projection(r) => {
+                cfg.projections.remove(*r);
+            }
+            Entry::Host(p, f) => {
+                if let Some(h) = cfg.hosts.get_mut(*p) {
+                    h.set(f, "")?;
+                    if *h == Default::default() {
+                        cfg.hosts.remove(*p);
+                    }
+                }
+            }
+            Entry::Remote(h, f) => {
+                if let Some(r) = cfg.remotes.get_mut(*h) {
+                    r.set(f, "")?;
+                    if *r == Default::default() {
+                        cfg.remotes.remove(*h);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // How `config list` shows a secret value: the API token as a fingerprint
+    // (enough to tell which one is set), remote passwords and gateway
+    // credentials (Authorization, API keys) as a length marker -- short
+    // hand-picked strings are easy to guess back from an unsalted hash.
+    fn masked(&self, raw: &str) -> Option<JsonValue> {
+        match self {
+            Entry::Fixed(k) if k.secret => Some(crate::health::fingerprint(raw).into()),
+            Entry::Remote(_, f) if *f == "password" => Some(crate::support::redacted(raw)),
+            Entry::Host(_, f) if f.starts_with("headers.") => Some(crate::support::redacted(raw)),
+            _ => None,
+        }
+    }
+
+    // Where the key sits in config.yaml.
+    fn path(&self) -> Vec<&str> {
+        match self {
+            Entry::Fixed(k) => k.path.split('.').collect(),
+            Entry::Projection(r) => vec!["projections", r],
+            Entry::Host(p, f) => {
+                let mut path = vec!["hosts", p];
+                match f.strip_prefix("headers.") {
+                    Some(h) => path.extend(["headers", h]),
+                    None => path.push(f),
+                }
+                path
+            }
+            Entry::Remote(h, f) => vec!["remotes", h, f],
+        }
+    }
+
+    // Where this run's value comes from: `cli` or `env` (recorded while the
+    // config was resolved), `keyring`, `file` (config.yaml, including the
+    // active profile) or `default`.
+    pub fn source(&self, cfg: &Config, written: &JsonValue) -> &'static str {
+        if let Some(src) = cfg.overrides.get(&self.name()) {
+            return src;
+        }
+        if matches!(self, Entry::Fixed(k) if k.name == "token")
+            && matches!(cfg.token_source, Some(TokenSource::Keyring))
+        {
+            return "keyring";
+        }
+        let found = self
+            .path()
+            .iter()
+            .try_fold(written, |v, seg| v.get(seg))
+            .is_some_and(|v| !v.is_null());
+        if found {
+            "file"
+        } else {
+            "default"
+        }
+    }
+}
+
+// Every fixed key plus the patterned ones present in `cfg`, for `config
+// list`: `[{key, value, source}]` with secrets masked.
+pub fn list(cfg: &Config) -> Vec<JsonValue> {
+    let written = serde_json::to_value(cfg).unwrap_or_default();
+    let mut entries: Vec<Entry> = KEYS.iter().map(Entry::Fixed).collect();
+    entries.extend(
+        crate::config::PROJECTION_RESOURCES
+            .iter()
+            .map(|r| Entry::Projection(r)),
+    );
+    for (name, host) in &cfg.hosts {
+        for field in ["base", "cacert"] {
+            if host.get(field).is_some() {
+                entries.push(Entry::Host(name, field));
+            }
+        }
+    }
+    let headers: Vec<(String, String)> = cfg
+        .hosts
+        .iter()
+        .flat_map(|(name, h)| {
+            h.headers
+                .keys()
+                .map(move |k| (name.clone(), format!("headers.{}", k)))
+        })
+        .collect();
+    entries.extend(headers.iter().map(|(n, f)| Entry::Host(n, f)));
+    for (host, auth) in &cfg.remotes {
+        for field in crate::config::REMOTE_FIELDS {
+            if auth.get(field).is_some() {
+                entries.push(Entry::Remote(host, field));
+            }
+        }
+    }
+    entries
+        .iter()
+        .map(|e| {
+            let raw = e.get(cfg);
+            let value = match (raw.is_empty(), e.masked(&raw)) {
+                (true, _) => JsonValue::Null,
+                (false, Some(masked)) => masked,
+                (false, None) => JsonValue::String(raw),
+            };
+            serde_json::json!({
+                "key": e.name(),
+                "value": value,
+                "source": e.source(cfg, &written),
+            })
+        })
+        .collect()
+}
+
+// Names accepted by set/get/unset, with placeholders for the patterns.
+pub fn names() -> Vec<String> {
+    let mut keys: Vec<String> = KEYS.iter().map(|k| k.name.to_string()).collect();
+    keys.extend(
+        crate::config::PROJECTION_RESOURCES
+            .iter()
+            .map(|r| format!("projections.{}", r)),
+    );
+    keys.extend(
+        crate::config::HOST_PROFILE_FIELDS
+            .iter()
+            .map(|f| format!("hosts.<profile>.{}", f)),
+    );
+    keys.extend(
+        crate::config::REMOTE_FIELDS
+            .iter()
+            .map(|f| format!("remotes.<host>.{}", f)),
+    );
+    keys
+}
+
+fn unknown(name: &str) -> anyhow::Error {
+    let keys = names();
+    let err = serde_json::json!({
+        "error": "unsupported key",
+        "key": name,
+        "did_you_mean": crate::suggest::nearest(name, keys.iter().map(|k| k.as_str())),
+        "keys": keys,
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "unsupported key".into()))
+}
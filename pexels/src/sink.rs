@@ -0,0 +1,624 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// When an NDJSON output file is closed and a fresh one started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rotate {
+    Size(u64),
+    Daily,
+}
+
+// `--rotate size=100MB` or `--rotate daily`.
+pub fn parse_rotate(s: &str) -> Result<Rotate, String> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("daily") {
+        return Ok(Rotate::Daily);
+    }
+    match s.split_once('=') {
+        Some((k, v)) if k.trim().eq_ignore_ascii_case("size") => {
+            let n = crate::util::parse_size(v)?;
+            if n == 0 {
+                return Err("rotation size must be positive".into());
+            }
+            Ok(Rotate::Size(n))
+        }
+        _ => Err(format!(
+            "invalid rotation '{}' (use size=100MB or daily)",
+            s
+        )),
+    }
+}
+
+// Rotated name: `<stem>-<UTC timestamp>.<ext>` next to the active file.
+pub fn rotated_name(path: &Path, at: chrono::DateTime<chrono::Utc>) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "out".into());
+    let ts = at.format("%Y%m%dT%H%M%SZ");
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, ts, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, ts),
+    };
+    path.with_file_name(name)
+}
+
+// Append-only NDJSON file for long-running collectors. Reopening an existing
+// file resumes it (size and day are taken from the file), a failed write is
+// retried once on a fresh handle, and every rotation fsyncs the closed file
+// before renaming it to a timestamped name.
+pub struct NdjsonSink {
+    path: PathBuf,
+    rotate: Option<Rotate>,
+    file: Option<File>,
+    size: u64,
+    day: chrono::NaiveDate,
+    rotated: Vec<PathBuf>,
+}
+
+impl NdjsonSink {
+    pub fn open(path: &Path, rotate: Option<Rotate>) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).context("create output dir")?;
+        }
+        let mut sink = Self {
+            path: path.to_path_buf(),
+            rotate,
+            file: None,
+            size: 0,
+            day: chrono::Utc::now().date_naive(),
+            rotated: vec![],
+        };
+        sink.reopen()?;
+        if let Ok(meta) = fs::metadata(path) {
+            sink.size = meta.len();
+            if let Ok(modified) = meta.modified() {
+                let modified: chrono::DateTime<chrono::Utc> = modified.into();
+                if sink.size > 0 {
+                    sink.day = modified.date_naive();
+                }
+            }
+        }
+        Ok(sink)
+    }
+
+    pub fn write(&mut self, value: &JsonValue) -> Result<()> {
+        let mut line = serde_json::to_string(value).context("serialize record")?;
+        line.push('\n');
+        if self.due(line.len() as u64) {
+            self.rotate_now()?;
+        }
+        if self.write_raw(line.as_bytes()).is_err() {
+            self.reopen()?;
+            self.write_raw(line.as_bytes())
+                .with_context(|| format!("write {}", self.path.display()))?;
+        }
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    // Files closed by rotation so far, oldest first.
+    pub fn rotated(&self) -> &[PathBuf] {
+        &self.rotated
+    }
+
+    // Flush and fsync the active file.
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(f) = self.file.as_mut() {
+            f.flush()?;
+            f.sync_all().context("sync output file")?;
+        }
+        Ok(())
+    }
+
+    fn due(&self, next: u64) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        match self.rotate {
+            Some(Rotate::Size(max)) => self.size + next > max,
+            Some(Rotate::Daily) => chrono::Utc::now().date_naive() != self.day,
+            None => false,
+        }
+    }
+
+    fn rotate_now(&mut self) -> Result<()> {
+        self.finish()?;
+        self.file = None;
+        let mut target = rotated_name(&self.path, chrono::Utc::now());
+        let mut n = 1;
+        while target.exists() {
+            let name = format!("{}.{}", target.display(), n);
+            target = PathBuf::from(name);
+            n += 1;
+        }
+        fs::rename(&self.path, &target).context("rotate output file")?;
+        self.rotated.push(target);
+        self.size = 0;
+        self.day = chrono::Utc::now().date_naive();
+        self.reopen()
+    }
+
+    fn reopen(&mut self) -> Result<()> {
+        let f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("open {}", self.path.display()))?;
+        self.file = Some(f);
+        Ok(())
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.file.is_none() {
+            self.reopen()?;
+        }
+        if let Some(f) = self.file.as_mut() {
+            f.write_all(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+// `--sink`: where command results are delivered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Stdout,
+    File(PathBuf),
+    Http(reqwest::Url),
+    // nats://[user:pass@|token@]host[:port]/subject (cargo feature `nats`)
+    Nats(reqwest::Url),
+}
+
+// `-`/`stdout`, an http(s) or nats URL, or a file path (optionally `file:PATH`).
+pub fn parse_target(s: &str) -> Result<Target, String> {
+    let s = s.trim();
+    if s == "-" || s == "stdout" {
+        return Ok(Target::Stdout);
+    }
+    if s.starts_with("nats://") {
+        let url = reqwest::Url::parse(s).map_err(|e| format!("invalid sink URL '{}': {}", s, e))?;
+        nats_subject(&url)?;
+        return Ok(Target::Nats(url));
+    }
+    if s.starts_with("kafka://") {
+        return Err(
+            "Kafka is not supported as a sink; use nats://host/subject, or a Kafka REST \
+            proxy's https:// topic URL"
+                .into(),
+        );
+    }
+    if s.starts_with("http://") || s.starts_with("https://") {
+        return reqwest::Url::parse(s)
+            .map(Target::Http)
+            .map_err(|e| format!("invalid sink URL '{}': {}", s, e));
+    }
+    match s.strip_prefix("file:").unwrap_or(s) {
+        "" => Err("empty sink path".into()),
+        path => Ok(Target::File(PathBuf::from(path))),
+    }
+}
+
+// The sink for `target`; `headers` ("Name: value") go with every HTTP POST.
+pub fn open(target: &Target, headers: &[String]) -> Result<Box<dyn crate::output::Sink>> {
+    Ok(match target {
+        Target::Stdout => Box::new(crate::output::StdoutSink),
+        Target::File(path) => Box::new(FileSink::create(path)?),
+        Target::Http(url) => Box::new(HttpSink::new(url.clone(), headers)?),
+        #[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+        Target::Nats(url) => Box::new(NatsSink::connect(url, headers)?),
+        #[cfg(not(all(feature = "nats", not(target_arch = "wasm32"))))]
+        Target::Nats(_) => {
+            let err = serde_json::json!({
+                "error": "NATS sinks are not available in this build",
+                "hint": "rebuild with `cargo install --path pexels --features nats`",
+            });
+            anyhow::bail!(serde_yaml::to_string(&err).unwrap_or_else(|_| "no NATS support".into()))
+        }
+    })
+}
+
+// The subject a nats:// target publishes to: its path without the leading
+// slash. Wildcards are for subscribers only.
+pub fn nats_subject(url: &reqwest::Url) -> Result<String, String> {
+    let subject = url.path().trim_start_matches('/');
+    let valid = !subject.is_empty()
+        && subject
+            .split('.')
+            .all(|t| !t.is_empty() && t != "*" && t != ">")
+        && !subject
+            .chars()
+            .any(|c| c.is_whitespace() || c == '/' || c == '%');
+    if valid {
+        Ok(subject.to_string())
+    } else {
+        Err(format!(
+            "invalid NATS subject '{}' (use nats://host[:port]/events.curated)",
+            subject
+        ))
+    }
+}
+
+// Results written to a file: replaced when the run starts, appended to
+// within it. A failed write is retried once on a fresh handle.
+pub struct FileSink {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl FileSink {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).context("create output dir")?;
+        }
+        let file = File::create(path).with_context(|| format!("open {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+        })
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.file.is_none() {
+            self.file = Some(OpenOptions::new().append(true).open(&self.path)?);
+        }
+        let f = self.file.as_mut().expect("file just opened");
+        f.write_all(bytes)?;
+        f.flush()
+    }
+}
+
+impl crate::output::Sink for FileSink {
+    fn write(&mut self, bytes: &[u8], _content_type: &str) -> Result<()> {
+        if self.write_raw(bytes).is_err() {
+            self.file = None;
+            self.write_raw(bytes)
+                .with_context(|| format!("write {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+// Tries per HTTP delivery, and the time each may take.
+pub const HTTP_ATTEMPTS: u32 = 4;
+#[cfg(not(target_arch = "wasm32"))]
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Results POSTed to an HTTP endpoint, one request per delivery with the
+// output's media type. Connection errors, 408, 429 and 5xx are retried with
+// backoff (a Retry-After in seconds wins) up to HTTP_ATTEMPTS times; any
+// other status fails at once. The Pexels token is never sent.
+pub struct HttpSink {
+    url: reqwest::Url,
+    http: reqwest::Client,
+}
+
+impl HttpSink {
+    pub fn new(url: reqwest::Url, headers: &[String]) -> Result<Self> {
+        let mut map = reqwest::header::HeaderMap::new();
+        for h in headers {
+            let (name, value) = parse_header(h)?;
+            map.insert(name, value);
+        }
+        let ua = format!("pexels-cli/{}", env!("CARGO_PKG_VERSION"));
+        let builder = reqwest::Client::builder()
+            .default_headers(map)
+            .user_agent(ua);
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = builder.timeout(HTTP_TIMEOUT);
+        Ok(Self {
+            url,
+            http: builder.build().context("build sink client")?,
+        })
+    }
+
+    // One delivery, retried per the policy above.
+    pub async fn post(&self, body: &[u8], content_type: &str) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let sent = self
+                .http
+                .post(self.url.clone())
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(body.to_vec())
+                .send()
+                .await;
+            let (reason, wait) = match sent {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) if retryable(resp.status()) => {
+                    let wait = resp
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .map(std::time::Duration::from_secs);
+                    (format!("HTTP {}", resp.status().as_u16()), wait)
+                }
+                Ok(resp) => {
+                    let status = resp.status().as_u16();
+                    let body = resp.text().await.unwrap_or_default();
+                    let err = serde_json::json!({
+                        "error": "sink rejected the output",
+                        "sink": self.url.as_str(),
+                        "status": status,
+                        "body": body.chars().take(200).collect::<String>(),
+                    });
+                    return Err(anyhow::anyhow!(serde_yaml::to_string(&err)
+                        .unwrap_or_else(|_| "sink rejected the output".into())));
+                }
+                Err(e) => (e.to_string(), None),
+            };
+            if attempt >= HTTP_ATTEMPTS {
+                let err = serde_json::json!({
+                    "error": "sink unreachable",
+                    "sink": self.url.as_str(),
+                    "attempts": attempt,
+                    "reason": reason,
+                });
+                return Err(anyhow::anyhow!(
+                    serde_yaml::to_string(&err).unwrap_or_else(|_| "sink unreachable".into())
+                ));
+            }
+            tracing::warn!("sink delivery failed ({}), retrying", reason);
+            crate::util::sleep(wait.unwrap_or_else(|| crate::util::backoff_delay(attempt))).await;
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl crate::output::Sink for HttpSink {
+    // Output is rendered synchronously; the POST runs on the CLI's runtime.
+    fn write(&mut self, bytes: &[u8], content_type: &str) -> Result<()> {
+        let post = self.post(bytes, content_type);
+        match tokio::runtime::Handle::try_current() {
+            Ok(rt) => tokio::task::block_in_place(|| rt.block_on(post)),
+            Err(_) => tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("build runtime")?
+                .block_on(post),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl crate::output::Sink for HttpSink {
+    fn write(&mut self, _bytes: &[u8], _content_type: &str) -> Result<()> {
+        anyhow::bail!("HTTP sinks are not available on wasm32")
+    }
+}
+
+fn retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429
+}
+
+// `--sink-header "Name: value"`.
+fn parse_header(h: &str) -> Result<(reqwest::header::HeaderName, reqwest::header::HeaderValue)> {
+    let parsed = h.split_once(':').and_then(|(name, value)| {
+        Some((
+            reqwest::header::HeaderName::from_bytes(name.trim().as_bytes()).ok()?,
+            reqwest::header::HeaderValue::from_str(value.trim()).ok()?,
+        ))
+    });
+    parsed.ok_or_else(|| {
+        let err = serde_json::json!({
+            "error": "invalid --sink-header",
+            "header": h,
+            "hint": "use \"Name: value\", e.g. \"Authorization: Bearer <key>\"",
+        });
+        anyhow::anyhow!(
+            serde_yaml::to_string(&err).unwrap_or_else(|_| "invalid --sink-header".into())
+        )
+    })
+}
+
+// Tries per NATS delivery; each reconnects first.
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+pub const NATS_ATTEMPTS: u32 = 4;
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+const NATS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Results published to a NATS subject over the plain text protocol. NDJSON
+// output goes out one message per line (one per watch item or streamed
+// record), anything else as a single message. Each delivery ends with a
+// PING so a server -ERR fails it; connection errors reconnect and retry the
+// whole delivery with backoff up to NATS_ATTEMPTS times, so a subscriber may
+// see a message twice. `--sink-header` values travel as NATS headers, along
+// with the output's Content-Type when the server supports headers.
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+pub struct NatsSink {
+    url: reqwest::Url,
+    subject: String,
+    headers: Vec<(String, String)>,
+    conn: Option<NatsConn>,
+}
+
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+struct NatsConn {
+    reader: std::io::BufReader<std::net::TcpStream>,
+    writer: std::net::TcpStream,
+    max_payload: usize,
+    headers: bool,
+}
+
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+impl NatsSink {
+    // Connects right away so a wrong address or credentials fail the run
+    // before any work is done.
+    pub fn connect(url: &reqwest::Url, headers: &[String]) -> Result<Self> {
+        let headers = headers
+            .iter()
+            .map(|h| {
+                let (name, value) = parse_header(h)?;
+                Ok((
+                    name.to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut sink = Self {
+            url: url.clone(),
+            subject: nats_subject(url).map_err(|e| anyhow::anyhow!(e))?,
+            headers,
+            conn: None,
+        };
+        let conn = sink.open().map_err(|e| sink.unreachable(1, &e))?;
+        sink.conn = Some(conn);
+        Ok(sink)
+    }
+
+    fn open(&self) -> Result<NatsConn> {
+        use std::io::BufRead;
+        let host = self.url.host_str().unwrap_or("127.0.0.1");
+        let port = self.url.port().unwrap_or(4222);
+        let stream = std::net::TcpStream::connect((host, port))?;
+        stream.set_read_timeout(Some(NATS_TIMEOUT))?;
+        stream.set_write_timeout(Some(NATS_TIMEOUT))?;
+        let mut reader = std::io::BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let info: JsonValue = match line.trim_end().strip_prefix("INFO ") {
+            Some(json) => serde_json::from_str(json)?,
+            None => anyhow::bail!("not a NATS server: {:?}", line.trim_end()),
+        };
+        if info["tls_required"].as_bool() == Some(true) {
+            anyhow::bail!("server requires TLS, which the NATS sink does not speak");
+        }
+        let mut connect = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "pexels-cli",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "headers": true,
+        });
+        let user = self.url.username();
+        match self.url.password() {
+            Some(pass) => {
+                connect["user"] = user.into();
+                connect["pass"] = pass.into();
+            }
+            None if !user.is_empty() => connect["auth_token"] = user.into(),
+            None => {}
+        }
+        let mut conn = NatsConn {
+            reader,
+            writer: stream,
+            max_payload: info["max_payload"].as_u64().unwrap_or(1 << 20) as usize,
+            headers: info["headers"].as_bool() == Some(true),
+        };
+        conn.writer
+            .write_all(format!("CONNECT {}\r\n", connect).as_bytes())?;
+        conn.sync()?;
+        Ok(conn)
+    }
+
+    // One delivery on the current connection.
+    fn publish(&mut self, bytes: &[u8], content_type: &str) -> Result<()> {
+        if self.conn.is_none() {
+            self.conn = Some(self.open()?);
+        }
+        let conn = self.conn.as_mut().expect("connection just opened");
+        let messages: Vec<&[u8]> = if content_type == "application/x-ndjson" {
+            bytes
+                .split(|b| *b == b'\n')
+                .filter(|line| !line.is_empty())
+                .collect()
+        } else {
+            vec![bytes]
+        };
+        if !self.headers.is_empty() && !conn.headers {
+            anyhow::bail!("server does not support headers (needed for --sink-header)");
+        }
+        let mut header = String::new();
+        if conn.headers {
+            header.push_str("NATS/1.0\r\n");
+            header.push_str(&format!("Content-Type: {}\r\n", content_type));
+            for (name, value) in &self.headers {
+                header.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            header.push_str("\r\n");
+        }
+        let mut buf = vec![];
+        for msg in messages {
+            if header.len() + msg.len() > conn.max_payload {
+                anyhow::bail!(
+                    "message of {} bytes exceeds the server's max_payload of {}",
+                    header.len() + msg.len(),
+                    conn.max_payload
+                );
+            }
+            if header.is_empty() {
+                buf.extend(format!("PUB {} {}\r\n", self.subject, msg.len()).as_bytes());
+            } else {
+                let total = header.len() + msg.len();
+                let line = format!("HPUB {} {} {}\r\n", self.subject, header.len(), total);
+                buf.extend(line.as_bytes());
+                buf.extend(header.as_bytes());
+            }
+            buf.extend(msg);
+            buf.extend(b"\r\n");
+        }
+        conn.writer.write_all(&buf)?;
+        conn.sync()
+    }
+
+    fn unreachable(&self, attempts: u32, e: &anyhow::Error) -> anyhow::Error {
+        let mut url = self.url.clone();
+        let _ = url.set_password(None);
+        let _ = url.set_username("");
+        let err = serde_json::json!({
+            "error": "sink unreachable",
+            "sink": url.as_str(),
+            "attempts": attempts,
+            "reason": e.to_string(),
+        });
+        anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "sink unreachable".into()))
+    }
+}
+
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+impl NatsConn {
+    // PING and wait for the PONG, answering server PINGs on the way; a -ERR
+    // means the server refused what was sent.
+    fn sync(&mut self) -> Result<()> {
+        use std::io::BufRead;
+        self.writer.write_all(b"PING\r\n")?;
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                anyhow::bail!("connection closed by the server");
+            }
+            match line.trim_end() {
+                "PONG" => return Ok(()),
+                "PING" => self.writer.write_all(b"PONG\r\n")?,
+                l if l.starts_with("-ERR") => anyhow::bail!("server error: {}", &l[4..].trim()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "nats", not(target_arch = "wasm32")))]
+impl crate::output::Sink for NatsSink {
+    fn write(&mut self, bytes: &[u8], content_type: &str) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let e = match self.publish(bytes, content_type) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            self.conn = None;
+            if attempt >= NATS_ATTEMPTS || e.downcast_ref::<std::io::Error>().is_none() {
+                return Err(self.unreachable(attempt, &e));
+            }
+            tracing::warn!("sink delivery failed ({}), retrying", e);
+            std::thread::sleep(crate::util::backoff_delay(attempt));
+        }
+    }
+}
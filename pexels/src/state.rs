@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// Persisted CLI state (daily picks, continuation points, seen ids, quota
+// snapshots, ...) in one SQLite database under the data dir. Keys are
+// `<namespace>:<rest>`; the namespace is stored alongside for inspection
+// with `pexels state`. Kept separate from config.yaml so user settings are
+// never rewritten by routine commands.
+//
+// Entries are loaded up front and changes are staged; `save` writes only the
+// touched keys in one transaction, so concurrent processes updating
+// different keys never lose each other's writes. On wasm32 the store is
+// in-memory only.
+pub struct State {
+    path: PathBuf,
+    data: BTreeMap<String, Entry>,
+    dirty: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub value: JsonValue,
+    pub updated_at: String,
+}
+
+// Schema migrations, applied in order; `PRAGMA user_version` records how
+// many have run.
+#[cfg(not(target_arch = "wasm32"))]
+const MIGRATIONS: &[&str] = &["CREATE TABLE kv (
+        key TEXT PRIMARY KEY,
+        namespace TEXT NOT NULL,
+        value TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE INDEX kv_namespace ON kv(namespace);"];
+
+pub const SCHEMA_VERSION: usize = 1;
+
+// Namespace part of a key (`potd:curated` -> `potd`).
+pub fn namespace(key: &str) -> &str {
+    key.split(':').next().unwrap_or(key)
+}
+
+impl State {
+    pub fn default_path() -> PathBuf {
+        crate::util::data_dir().join("state.db")
+    }
+
+    // Pre-SQLite JSON store, imported once by `load`.
+    pub fn legacy_path() -> PathBuf {
+        crate::util::data_dir().join("state.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let mut st = Self::load_from(&Self::default_path())?;
+        st.import_legacy(&Self::legacy_path())?;
+        Ok(st)
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let mut st = Self {
+            path: path.to_path_buf(),
+            data: BTreeMap::new(),
+            dirty: vec![],
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let conn = st.open()?;
+            let mut stmt = conn.prepare("SELECT key, value, updated_at FROM kv")?;
+            let rows = stmt.query_map([], |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, String>(1)?,
+                    r.get::<_, String>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (key, value, updated_at) = row?;
+                let value = serde_json::from_str(&value).unwrap_or(JsonValue::String(value));
+                st.data.insert(key, Entry { value, updated_at });
+            }
+        }
+        Ok(st)
+    }
+
+    // Store that stages writes to `path` without reading existing entries,
+    // for hot paths that update a single key.
+    pub fn detached(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            data: BTreeMap::new(),
+            dirty: vec![],
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.data.get(key).map(|e| &e.value)
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&Entry> {
+        self.data.get(key)
+    }
+
+    // Entries in key order, optionally limited to one namespace.
+    pub fn entries<'a>(
+        &'a self,
+        ns: Option<&'a str>,
+    ) -> impl Iterator<Item = (&'a String, &'a Entry)> {
+        self.data
+            .iter()
+            .filter(move |(k, _)| ns.map(|n| namespace(k) == n).unwrap_or(true))
+    }
+
+    pub fn set(&mut self, key: &str, value: JsonValue) {
+        let updated_at = chrono::Utc::now().to_rfc3339();
+        self.data
+            .insert(key.to_string(), Entry { value, updated_at });
+        self.dirty.push(key.to_string());
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let old = self.data.remove(key).map(|e| e.value);
+        self.dirty.push(key.to_string());
+        old
+    }
+
+    // Write staged changes in a single transaction, under the state lock.
+    pub fn save(&self) -> Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _lock = crate::lock::FileLock::acquire_blocking(
+                &crate::lock::lock_path_for(&self.path),
+                crate::lock::default_wait(),
+            )?;
+            let mut conn = self.open()?;
+            let tx = conn.transaction()?;
+            for key in &self.dirty {
+                match self.data.get(key) {
+                    Some(e) => {
+                        tx.execute(
+                            "INSERT INTO kv (key, namespace, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+                             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                            rusqlite::params![key, namespace(key), e.value.to_string(), e.updated_at],
+                        )?;
+                    }
+                    None => {
+                        tx.execute("DELETE FROM kv WHERE key = ?1", [key])?;
+                    }
+                }
+            }
+            tx.commit().context("write state db")?;
+        }
+        Ok(())
+    }
+
+    // Move entries from the old state.json into the database, then rename
+    // the file so the import runs once.
+    fn import_legacy(&mut self, legacy: &Path) -> Result<()> {
+        if !legacy.exists() {
+            return Ok(());
+        }
+        let raw = std::fs::read_to_string(legacy).context("read legacy state")?;
+        let map: serde_json::Map<String, JsonValue> =
+            serde_json::from_str(&raw).context("parse legacy state")?;
+        for (k, v) in map {
+            if !self.data.contains_key(&k) {
+                self.set(&k, v);
+            }
+        }
+        self.save()?;
+        std::fs::rename(legacy, legacy.with_extension("json.migrated"))
+            .context("retire legacy state")?;
+        Ok(())
+    }
+
+    // Open (creating and migrating as needed) the database file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open(&self) -> Result<rusqlite::Connection> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context("create state dir")?;
+        }
+        let conn = rusqlite::Connection::open(&self.path).context("open state db")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600));
+        }
+        conn.busy_timeout(crate::lock::default_wait())?;
+        let version: usize = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+        for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(sql)
+                .with_context(|| format!("migrate state db to v{}", i + 1))?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+        }
+        Ok(conn)
+    }
+
+    // Schema version of the database on disk.
+    pub fn schema_version(&self) -> Result<usize> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let conn = self.open()?;
+            Ok(conn.query_row("PRAGMA user_version", [], |r| r.get(0))?)
+        }
+        #[cfg(target_arch = "wasm32")]
+        Ok(SCHEMA_VERSION)
+    }
+}
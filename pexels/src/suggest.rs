@@ -0,0 +1,106 @@
+use serde_json::Value as JsonValue;
+use std::collections::BTreeSet;
+
+// "Did you mean" suggestions for mistyped config keys, field paths and enum
+// values, ranked by edit distance.
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+// Up to three candidates within a third of the input's length (at least 1
+// edit) and at most one edit further than the best, closest first.
+pub fn nearest<'a, I>(input: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max = (input.chars().count() / 3).max(1);
+    let lower = input.to_lowercase();
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|c| (levenshtein(&lower, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= max)
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    // Only runners-up about as close as the best match
+    let best = scored.first().map(|(d, _)| *d).unwrap_or(0);
+    scored
+        .into_iter()
+        .take_while(|(d, _)| *d <= best + 1)
+        .take(3)
+        .map(|(_, c)| c.to_string())
+        .collect()
+}
+
+// Every dotted path in a value (`src`, `src.original`, ...); array elements
+// share their parent's path.
+pub fn paths(v: &JsonValue) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    collect_paths(v, "", &mut out);
+    out
+}
+
+fn collect_paths(v: &JsonValue, prefix: &str, out: &mut BTreeSet<String>) {
+    match v {
+        JsonValue::Object(map) => {
+            for (k, child) in map {
+                let path = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", prefix, k)
+                };
+                out.insert(path.clone());
+                collect_paths(child, &path, out);
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_paths(item, prefix, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Requested `--fields` paths that exist in none of `items`, each with its
+// nearest known paths. Sets (`@ids`) and wildcards are not checked.
+pub fn unmatched_fields(items: &[JsonValue], fields: &[String]) -> Vec<(String, Vec<String>)> {
+    if items.is_empty() {
+        return vec![];
+    }
+    let known: BTreeSet<String> = items.iter().flat_map(paths).collect();
+    fields
+        .iter()
+        .filter(|f| !f.starts_with('@') && !f.contains('*') && !known.contains(f.as_str()))
+        .map(|f| (f.clone(), nearest(f, known.iter().map(|s| s.as_str()))))
+        .collect()
+}
+
+// Structured warning for `unmatched_fields` results; None when every path
+// was found.
+pub fn fields_warning(missing: &[(String, Vec<String>)], checked: usize) -> Option<JsonValue> {
+    if missing.is_empty() {
+        return None;
+    }
+    let fields: Vec<JsonValue> = missing
+        .iter()
+        .map(|(path, near)| serde_json::json!({ "path": path, "did_you_mean": near }))
+        .collect();
+    Some(serde_json::json!({
+        "warning": "fields matched nothing",
+        "items_checked": checked,
+        "fields": fields,
+        "hint": "--fields paths are case-sensitive dot paths into each item (e.g. src.original)",
+    }))
+}
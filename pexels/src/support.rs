@@ -0,0 +1,116 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::Value as JsonValue;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+// Audit lines copied into a support bundle.
+pub const LOG_TAIL_LINES: usize = 200;
+
+// Trace of the most recent failed API call, kept for support bundles.
+pub fn last_failure_path() -> PathBuf {
+    crate::util::data_dir().join("last_failure.json")
+}
+
+// Best effort: a failure to save the trace must never mask the real error.
+pub fn record_failure(trace: &JsonValue) {
+    let mut rec = trace.clone();
+    if let Some(obj) = rec.as_object_mut() {
+        obj.insert("ts".into(), chrono::Utc::now().to_rfc3339().into());
+    }
+    let path = last_failure_path();
+    let res = path
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(&path, serde_json::to_vec_pretty(&rec).unwrap_or_default()));
+    if let Err(e) = res {
+        tracing::debug!("failure trace not saved: {}", e);
+    }
+}
+
+// Config as YAML with every token (top level and per profile), remote
+// password and gateway header value replaced by a marker (its length is
+// kept to help spot truncated keys); header names stay readable.
+pub fn redacted_config(cfg: &Config) -> Result<String> {
+    let mut v = serde_json::to_value(cfg).context("serialize config")?;
+    if let Some(token) = cfg.token.as_deref() {
+        v["token"] = redacted(token);
+    }
+    for (name, profile) in &cfg.profiles {
+        if let Some(token) = profile.token.as_deref() {
+            v["profiles"][name]["token"] = redacted(token);
+        }
+    }
+    for (host, remote) in &cfg.remotes {
+        if let Some(password) = remote.password.as_deref() {
+            v["remotes"][host]["password"] = redacted(password);
+        }
+    }
+    for (name, host) in &cfg.hosts {
+        for (header, value) in &host.headers {
+            v["hosts"][name]["headers"][header] = redacted(value);
+        }
+    }
+    serde_yaml::to_string(&v).context("serialize config")
+}
+
+pub(crate) fn redacted(secret: &str) -> JsonValue {
+    JsonValue::String(format!("<redacted: {} chars>", secret.len()))
+}
+
+// Version and build details.
+pub fn build_info() -> JsonValue {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "debug_build": cfg!(debug_assertions),
+        "features": {
+            "blocking": cfg!(feature = "blocking"),
+            "ffi": cfg!(feature = "ffi"),
+            "fault-injection": cfg!(feature = "fault-injection"),
+            "nats": cfg!(feature = "nats"),
+            "parquet": cfg!(feature = "parquet"),
+        },
+    })
+}
+
+// Last `n` lines of a text file; None when it does not exist.
+pub fn tail_lines(path: &Path, n: usize) -> Option<String> {
+    let text = fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(n);
+    Some(lines[start..].join("\n") + "\n")
+}
+
+// Write `entries` (name, contents) as a gzipped tarball under a top-level
+// `pexels-support/` directory.
+pub fn write_bundle(out: &Path, entries: &[(String, Vec<u8>)]) -> Result<u64> {
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).context("create bundle dir")?;
+    }
+    let file = File::create(out).context("create bundle")?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        tar.append_data(
+            &mut header,
+            format!("pexels-support/{}", name),
+            data.as_slice(),
+        )
+        .context("write bundle entry")?;
+    }
+    tar.into_inner()
+        .context("finish bundle")?
+        .finish()
+        .context("finish bundle")?;
+    Ok(fs::metadata(out)?.len())
+}
@@ -0,0 +1,288 @@
+use crate::api::PexelsClient;
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+
+// `pexels browse`: page through curated or search results in the terminal,
+// mark photos and hand the marked ones back for a batch download. The
+// browser state is plain data so paging and marking work without a terminal.
+
+// Photos per page when --per-page is not set.
+pub const DEFAULT_PER_PAGE: u32 = 20;
+
+#[derive(Debug, Default)]
+pub struct Browser {
+    // Items of the page on screen
+    pub items: Vec<JsonValue>,
+    pub page: u32,
+    pub has_next: bool,
+    // Highlighted row
+    pub cursor: usize,
+    // Marked items in mark order, kept across pages
+    marked: Vec<JsonValue>,
+}
+
+impl Browser {
+    // Show page `page` of a photo list response.
+    pub fn load(&mut self, page: u32, data: &JsonValue) {
+        self.items = data
+            .get("photos")
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default();
+        self.page = page;
+        self.has_next = data.get("next_page").is_some_and(|n| !n.is_null());
+        self.cursor = 0;
+    }
+
+    pub fn current(&self) -> Option<&JsonValue> {
+        self.items.get(self.cursor)
+    }
+
+    pub fn up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.cursor + 1 < self.items.len() {
+            self.cursor += 1;
+        }
+    }
+
+    pub fn is_marked(&self, item: &JsonValue) -> bool {
+        self.marked.iter().any(|m| m["id"] == item["id"])
+    }
+
+    // Mark or unmark the highlighted item.
+    pub fn toggle(&mut self) {
+        if let Some(item) = self.current().cloned() {
+            self.toggle_item(item);
+        }
+    }
+
+    // Mark every item on the page, or unmark them all when all are marked.
+    pub fn toggle_page(&mut self) {
+        let all = self.items.iter().all(|i| self.is_marked(i));
+        for item in self.items.clone() {
+            if self.is_marked(&item) == all {
+                self.toggle_item(item);
+            }
+        }
+    }
+
+    fn toggle_item(&mut self, item: JsonValue) {
+        match self.marked.iter().position(|m| m["id"] == item["id"]) {
+            Some(i) => {
+                self.marked.remove(i);
+            }
+            None => self.marked.push(item),
+        }
+    }
+
+    pub fn marked(&self) -> &[JsonValue] {
+        &self.marked
+    }
+
+    pub fn next_page(&self) -> Option<u32> {
+        self.has_next.then_some(self.page + 1)
+    }
+
+    pub fn prev_page(&self) -> Option<u32> {
+        (self.page > 1).then(|| self.page - 1)
+    }
+}
+
+// Metadata shown next to the list for the highlighted item.
+pub fn details(item: &JsonValue) -> Vec<(&'static str, String)> {
+    let n = crate::normalize::normalize(item);
+    let text = |v: &JsonValue| match v {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Null => String::new(),
+        v => v.to_string(),
+    };
+    let mut out = vec![
+        ("id", text(&n["id"])),
+        ("title", text(&n["title"])),
+        ("photographer", text(&n["creator"]["name"])),
+    ];
+    if let (Some(w), Some(h)) = (n["width"].as_u64(), n["height"].as_u64()) {
+        out.push(("size", format!("{}x{}", w, h)));
+    }
+    out.push(("avg_color", text(&item["avg_color"])));
+    out.push(("page", text(&n["urls"]["page"])));
+    out.push(("file", text(&n["urls"]["file"])));
+    out.retain(|(_, v)| !v.is_empty());
+    out
+}
+
+// Run the browser on the terminal; returns the marked items when the user
+// downloads (d/Enter), nothing when they quit (q/Esc).
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn browse(
+    client: &PexelsClient,
+    query: Option<&str>,
+    per_page: u32,
+) -> Result<Vec<JsonValue>> {
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        let err = serde_json::json!({
+            "error": "browse needs an interactive terminal",
+            "hint": "use photos download-all, or photos search --pick",
+        });
+        return Err(anyhow::anyhow!(serde_yaml::to_string(&err)?));
+    }
+    let mut browser = Browser::default();
+    browser.load(1, &client.photos_page(query, 1, per_page).await?);
+    let title = query.unwrap_or("curated");
+    let mut screen = Screen::enter()?;
+    let mut status = String::new();
+    loop {
+        screen.0.draw(|f| draw(f, &browser, title, &status))?;
+        let Event::Key(key) = tokio::task::block_in_place(event::read)? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status.clear();
+        let page = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(vec![])
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(vec![]),
+            KeyCode::Char('d') | KeyCode::Enter if !browser.marked().is_empty() => {
+                return Ok(browser.marked().to_vec())
+            }
+            KeyCode::Char('d') | KeyCode::Enter => {
+                status = "nothing marked".into();
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                browser.up();
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                browser.down();
+                None
+            }
+            KeyCode::Char(' ') => {
+                browser.toggle();
+                browser.down();
+                None
+            }
+            KeyCode::Char('a') => {
+                browser.toggle_page();
+                None
+            }
+            KeyCode::Right | KeyCode::Char('n') => {
+                if !browser.has_next {
+                    status = "last page".into();
+                }
+                browser.next_page()
+            }
+            KeyCode::Left | KeyCode::Char('p') => {
+                if browser.page <= 1 {
+                    status = "first page".into();
+                }
+                browser.prev_page()
+            }
+            _ => None,
+        };
+        if let Some(p) = page {
+            match client.photos_page(query, p, per_page).await {
+                Ok(data) => browser.load(p, &data),
+                // Keep the current page; the error's first line goes to the footer
+                Err(e) => status = e.to_string().lines().next().unwrap_or("").to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn browse(
+    _client: &PexelsClient,
+    _query: Option<&str>,
+    _per_page: u32,
+) -> Result<Vec<JsonValue>> {
+    anyhow::bail!("browse is not available on this target")
+}
+
+// Raw mode and the alternate screen on stderr, so stdout stays free for the
+// download summary; both are undone on drop, error paths included.
+#[cfg(not(target_arch = "wasm32"))]
+struct Screen(ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stderr>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Screen {
+    fn enter() -> Result<Self> {
+        use ratatui::crossterm::{execute, terminal};
+        terminal::enable_raw_mode()?;
+        let screen = Screen(ratatui::Terminal::new(
+            ratatui::backend::CrosstermBackend::new(std::io::stderr()),
+        )?);
+        execute!(std::io::stderr(), terminal::EnterAlternateScreen)?;
+        Ok(screen)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Screen {
+    fn drop(&mut self) {
+        use ratatui::crossterm::{cursor, execute, terminal};
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(
+            std::io::stderr(),
+            terminal::LeaveAlternateScreen,
+            cursor::Show
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn draw(f: &mut ratatui::Frame, b: &Browser, title: &str, status: &str) {
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+    let [body, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(f.area());
+    let [list_area, info_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(body);
+    let rows: Vec<ListItem> = b
+        .items
+        .iter()
+        .map(|i| {
+            let mark = if b.is_marked(i) { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", mark, crate::pick::label(i)))
+        })
+        .collect();
+    let list = List::new(rows)
+        .block(Block::bordered().title(format!(" {} — page {} ", title, b.page)))
+        .highlight_symbol("> ")
+        .highlight_style(Style::new().reversed());
+    let mut state = ListState::default().with_selected(b.current().map(|_| b.cursor));
+    f.render_stateful_widget(list, list_area, &mut state);
+    let lines: Vec<Line> = b
+        .current()
+        .map(details)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| Line::from(vec![Span::from(format!("{}: ", k)).bold(), Span::from(v)]))
+        .collect();
+    f.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(" details ")),
+        info_area,
+    );
+    let help = format!(
+        "{} marked · space mark · a page · n/p next/prev · d download · q quit",
+        b.marked().len()
+    );
+    let line = if status.is_empty() {
+        help
+    } else {
+        format!("{} · {}", status, help)
+    };
+    f.render_widget(Paragraph::new(line), footer);
+}
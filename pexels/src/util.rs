@@ -1,4 +1,6 @@
+use directories::ProjectDirs;
 use rand::{thread_rng, Rng};
+use std::path::PathBuf;
 use std::time::Duration;
 
 pub fn backoff_delay(attempt: u32) -> Duration {
@@ -11,3 +13,79 @@ pub fn backoff_delay(attempt: u32) -> Duration {
     let ms = (exp + jitter).min(max);
     Duration::from_millis(ms)
 }
+
+// Runtime-agnostic sleep: tokio timers natively, browser timers on wasm32.
+pub async fn sleep(d: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(d).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(d).await;
+}
+
+// Per-user data dir (audit log, locks, state); vendorless like the config dir.
+pub fn data_dir() -> PathBuf {
+    let proj = ProjectDirs::from("", "", "pexels").expect("config dirs");
+    proj.data_dir().to_path_buf()
+}
+
+// Stable 64-bit FNV-1a hash, used for file names derived from keys/paths.
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
+
+// Parse durations like `90`, `45s`, `30m`, `2h`, `1h30m` or `1d` (bare
+// numbers are seconds).
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    let mut total = 0u64;
+    let mut num = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            num.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(format!("invalid duration unit '{}' in '{}'", c, s)),
+        };
+        let n: u64 = num
+            .parse()
+            .map_err(|_| format!("invalid duration '{}'", s))?;
+        total += n * unit;
+        num.clear();
+    }
+    if !num.is_empty() || s.is_empty() {
+        return Err(format!("invalid duration '{}' (use e.g. 90s, 30m, 2h)", s));
+    }
+    Ok(Duration::from_secs(total))
+}
+
+// Parse sizes like `4096`, `512KB`, `100MB` or `1GB` (binary units, bare
+// numbers are bytes).
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let t = s.trim().to_ascii_uppercase();
+    let split = t.find(|c: char| !c.is_ascii_digit()).unwrap_or(t.len());
+    let (num, unit) = t.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (use e.g. 512KB, 100MB)", s))?;
+    let mult: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("invalid size unit '{}' in '{}'", unit, s)),
+    };
+    Ok(n.saturating_mul(mult))
+}
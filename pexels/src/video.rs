@@ -0,0 +1,193 @@
+use clap::{Args, ValueEnum};
+use serde_json::Value as JsonValue;
+
+// Rendition quality labels used in `video_files[].quality`.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum VideoQuality {
+    Sd,
+    Hd,
+    Uhd,
+}
+
+impl VideoQuality {
+    pub fn key(&self) -> &'static str {
+        match self {
+            VideoQuality::Sd => "sd",
+            VideoQuality::Hd => "hd",
+            VideoQuality::Uhd => "uhd",
+        }
+    }
+}
+
+// Rendition constraints shared by `videos url` and `videos download`.
+#[derive(Args, Clone, Debug, Default)]
+pub struct Rendition {
+    /// Rendition quality (default: best available)
+    #[arg(long, value_enum)]
+    pub quality: Option<VideoQuality>,
+    /// Widest rendition allowed, in pixels
+    #[arg(long = "max-width", value_name = "PX")]
+    pub max_width: Option<u64>,
+    /// Tallest rendition allowed, in pixels
+    #[arg(long = "max-height", value_name = "PX")]
+    pub max_height: Option<u64>,
+}
+
+// Pick a rendition from `video_files`: entries matching the quality (any
+// when unset) and within the size limits, preferring the widest and then
+// the highest frame rate.
+pub fn select_file<'a>(video: &'a JsonValue, want: &Rendition) -> Option<&'a JsonValue> {
+    let num = |f: &JsonValue, k: &str| f.get(k).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let within = |f: &JsonValue, k: &str, max: Option<u64>| match max {
+        Some(m) => num(f, k) <= m as f64,
+        None => true,
+    };
+    video
+        .get("video_files")?
+        .as_array()?
+        .iter()
+        .filter(|f| f.get("link").and_then(|l| l.as_str()).is_some())
+        .filter(|f| match want.quality {
+            Some(q) => f.get("quality").and_then(|v| v.as_str()) == Some(q.key()),
+            None => true,
+        })
+        .filter(|f| within(f, "width", want.max_width) && within(f, "height", want.max_height))
+        .max_by(|a, b| {
+            (num(a, "width"), num(a, "fps"))
+                .partial_cmp(&(num(b, "width"), num(b, "fps")))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
+// Renditions on offer, for error hints: `hd 1920x1080`, ...
+pub fn available(video: &JsonValue) -> Vec<String> {
+    video
+        .get("video_files")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|f| {
+            format!(
+                "{} {}x{}",
+                f.get("quality").and_then(|q| q.as_str()).unwrap_or("?"),
+                f.get("width").and_then(|w| w.as_u64()).unwrap_or(0),
+                f.get("height").and_then(|h| h.as_u64()).unwrap_or(0),
+            )
+        })
+        .collect()
+}
+
+pub fn no_match_error(video: &JsonValue, want: &Rendition) -> anyhow::Error {
+    let err = serde_json::json!({
+        "error": "no matching video file",
+        "id": video.get("id"),
+        "quality": want.quality.map(|q| q.key()),
+        "max_width": want.max_width,
+        "max_height": want.max_height,
+        "available": available(video),
+        "hint": "relax --quality, --max-width or --max-height",
+    });
+    anyhow::anyhow!(serde_yaml::to_string(&err).unwrap_or_else(|_| "no matching video file".into()))
+}
+
+// `--bucket duration:0-10,10-30,30+`: half-open ranges over a numeric
+// field; `N+` is open-ended. Counts land in meta.buckets in the given order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buckets {
+    pub field: String,
+    pub ranges: Vec<(u64, Option<u64>)>,
+}
+
+pub fn parse_buckets(s: &str) -> Result<Buckets, String> {
+    let (field, spec) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected FIELD:RANGES, got `{}`", s))?;
+    if field != "duration" {
+        return Err(format!(
+            "unsupported bucket field `{}` (expected duration)",
+            field
+        ));
+    }
+    let num = |t: &str| {
+        t.trim()
+            .parse::<u64>()
+            .map_err(|_| format!("invalid bound `{}`", t))
+    };
+    let mut ranges = vec![];
+    for part in spec.split(',') {
+        let part = part.trim();
+        let range = match part.strip_suffix('+') {
+            Some(lo) => (num(lo)?, None),
+            None => {
+                let (lo, hi) = part
+                    .split_once('-')
+                    .ok_or_else(|| format!("expected LO-HI or LO+, got `{}`", part))?;
+                let (lo, hi) = (num(lo)?, num(hi)?);
+                if lo >= hi {
+                    return Err(format!("empty range `{}`", part));
+                }
+                (lo, Some(hi))
+            }
+        };
+        ranges.push(range);
+    }
+    Ok(Buckets {
+        field: field.to_string(),
+        ranges,
+    })
+}
+
+impl Buckets {
+    fn label(range: &(u64, Option<u64>)) -> String {
+        match range {
+            (lo, Some(hi)) => format!("{}-{}", lo, hi),
+            (lo, None) => format!("{}+", lo),
+        }
+    }
+
+    // Per-range counts; items outside every range are reported as `unbucketed`.
+    pub fn counts(&self, items: &[JsonValue]) -> JsonValue {
+        let mut counts = vec![0u64; self.ranges.len()];
+        let mut unbucketed = 0;
+        for item in items {
+            let slot = item
+                .get(&self.field)
+                .and_then(|v| v.as_u64())
+                .and_then(|v| {
+                    self.ranges
+                        .iter()
+                        .position(|(lo, hi)| v >= *lo && hi.is_none_or(|h| v < h))
+                });
+            match slot {
+                Some(i) => counts[i] += 1,
+                None => unbucketed += 1,
+            }
+        }
+        let rows: Vec<JsonValue> = self
+            .ranges
+            .iter()
+            .zip(counts)
+            .map(|(r, n)| serde_json::json!({ "range": Self::label(r), "count": n }))
+            .collect();
+        let mut out = serde_json::json!({ "field": self.field, "counts": rows });
+        if unbucketed > 0 {
+            out["unbucketed"] = unbucketed.into();
+        }
+        out
+    }
+}
+
+// Drop videos outside [min, max] seconds. The API treats duration bounds as
+// hints on some endpoints, so results are re-checked locally. Returns how
+// many were removed.
+pub fn retain_duration(data: &mut JsonValue, min: Option<u32>, max: Option<u32>) -> usize {
+    let Some(items) = data.get_mut("videos").and_then(|v| v.as_array_mut()) else {
+        return 0;
+    };
+    let before = items.len();
+    items.retain(|v| {
+        let d = v.get("duration").and_then(|d| d.as_u64()).unwrap_or(0);
+        min.is_none_or(|m| d >= m as u64) && max.is_none_or(|m| d <= m as u64)
+    });
+    before - items.len()
+}
@@ -0,0 +1,48 @@
+use pexels::analyze::{analyze, analyze_file, sidecar_path};
+use std::path::Path;
+
+// Checkerboard (busy) bottom right, flat grey elsewhere with a red top left
+fn sample() -> image::RgbImage {
+    image::RgbImage::from_fn(64, 64, |x, y| match (x >= 32, y >= 32) {
+        (true, true) if (x / 2 + y / 2) % 2 == 0 => image::Rgb([0, 0, 0]),
+        (true, true) => image::Rgb([255, 255, 255]),
+        (false, false) => image::Rgb([200, 0, 0]),
+        _ => image::Rgb([128, 128, 128]),
+    })
+}
+
+#[test]
+fn busyness_is_highest_where_the_edges_are() {
+    let report = analyze(&image::DynamicImage::ImageRgb8(sample()));
+    let q = &report["quadrants"];
+    assert_eq!(q["top_left"]["avg_color"], "#c80000");
+    assert_eq!(q["top_right"]["avg_color"], "#808080");
+    let busy = |name: &str| q[name]["busyness"].as_f64().unwrap();
+    assert!(busy("bottom_right") > 0.5);
+    // Only the borders with its neighbours register
+    assert!(busy("top_right") < 0.1);
+    assert_ne!(report["quietest"], "bottom_right");
+    let overall = report["busyness"].as_f64().unwrap();
+    assert!(overall > 0.0 && overall < busy("bottom_right"));
+}
+
+#[test]
+fn analyze_file_writes_a_sidecar() {
+    let dir = std::env::temp_dir().join(format!("pexels-analyze-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let photo = dir.join("photo-1.png");
+    sample().save(&photo).unwrap();
+    assert_eq!(
+        sidecar_path(Path::new("d/photo-1.jpeg")),
+        Path::new("d/photo-1.analysis.json")
+    );
+    let report = analyze_file(&photo).unwrap();
+    let sidecar = dir.join("photo-1.analysis.json");
+    assert_eq!(report["sidecar"], sidecar.display().to_string());
+    let written: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&sidecar).unwrap()).unwrap();
+    assert_eq!(written["quietest"], report["quietest"]);
+    std::fs::write(dir.join("clip.png"), b"not an image").unwrap();
+    assert!(analyze_file(&dir.join("clip.png")).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
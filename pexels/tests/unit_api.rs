@@ -0,0 +1,49 @@
+use pexels::api::{effective_per_page, malformed_json_error};
+use pexels::output::error_value;
+
+#[test]
+fn malformed_json_error_captures_raw_body() {
+    let body = b"<html>502 Bad Gateway</html>";
+    let err = malformed_json_error(
+        200,
+        Some("text/html"),
+        Some("rid-1"),
+        body,
+        "expected value",
+    );
+    let v = error_value(&err);
+    assert_eq!(v["error"], "malformed JSON response");
+    assert_eq!(v["code"], 200);
+    assert_eq!(v["content_type"], "text/html");
+    assert_eq!(v["request_id"], "rid-1");
+    assert_eq!(v["bytes"], body.len());
+    let path = v["raw_path"].as_str().unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), body);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn per_page_follows_limit_unless_explicit() {
+    assert_eq!(effective_per_page(None, Some(37)), Some(37));
+    assert_eq!(effective_per_page(None, Some(200)), Some(80));
+    assert_eq!(effective_per_page(Some(10), Some(37)), Some(10));
+    assert_eq!(effective_per_page(None, None), None);
+    assert_eq!(effective_per_page(None, Some(0)), None);
+}
+
+#[test]
+fn api_urls_stay_on_the_configured_host() {
+    let client = pexels::api::PexelsClient::new(pexels::config::Config {
+        host: Some("http://localhost:8080".into()),
+        ..Default::default()
+    })
+    .unwrap();
+    let url = client.api_url("/v1/search").unwrap();
+    assert_eq!(url.as_str(), "http://localhost:8080/v1/search");
+    assert_eq!(
+        client.api_url("videos/popular").unwrap().as_str(),
+        "http://localhost:8080/videos/popular"
+    );
+    assert!(client.api_url("http://localhost:8080/v1/curated").is_ok());
+    assert!(client.api_url("https://evil.example/v1/curated").is_err());
+}
@@ -0,0 +1,97 @@
+use pexels::audit::{append, record};
+use pexels::config::Config;
+use std::fs;
+
+#[test]
+fn audit_record_shape() {
+    let qp = vec![("query".to_string(), "cats".to_string())];
+    let rec = record(
+        "default",
+        "https://api.pexels.com",
+        "GET",
+        "/v1/search",
+        &qp,
+        Some(200),
+        Some("abc"),
+        Some("199"),
+    );
+    assert_eq!(rec["profile"], "default");
+    assert_eq!(rec["endpoint"], "/v1/search");
+    assert_eq!(rec["query"]["query"], "cats");
+    assert_eq!(rec["status"], 200);
+    assert_eq!(rec["request_id"], "abc");
+    assert_eq!(rec["quota_remaining"], 199);
+    assert!(rec["ts"].is_string());
+}
+
+#[test]
+fn audit_append_is_jsonl() {
+    let dir = std::env::temp_dir().join(format!("pexels-audit-{}", std::process::id()));
+    let path = dir.join("audit.jsonl");
+    let _ = fs::remove_file(&path);
+    let rec = record("default", "h", "HEAD", "/v1/curated", &[], None, None, None);
+    append(&path, &rec).unwrap();
+    append(&path, &rec).unwrap();
+    let data = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = data.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for l in lines {
+        let v: serde_json::Value = serde_json::from_str(l).unwrap();
+        assert!(v["status"].is_null());
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn audit_disabled_by_default_and_omitted_from_yaml() {
+    let cfg: Config = serde_yaml::from_str("token: t\n").unwrap();
+    assert!(!cfg.audit.enabled);
+    let s = serde_yaml::to_string(&cfg).unwrap();
+    assert!(!s.contains("audit"));
+    let cfg: Config = serde_yaml::from_str("audit:\n  enabled: true\n").unwrap();
+    assert!(cfg.audit.enabled);
+}
+
+#[test]
+fn audit_read_select_and_diff() {
+    let dir = std::env::temp_dir().join(format!("pexels-audit-replay-{}", std::process::id()));
+    let path = dir.join("audit.jsonl");
+    assert!(pexels::audit::read(&path).unwrap().is_empty());
+    for (rid, endpoint) in [
+        ("r1", "/v1/curated"),
+        ("r2", "/v1/search"),
+        ("r3", "/v1/curated"),
+    ] {
+        let rec = record(
+            "default",
+            "https://api.pexels.com",
+            "GET",
+            endpoint,
+            &[],
+            Some(200),
+            Some(rid),
+            None,
+        );
+        append(&path, &rec).unwrap();
+    }
+    let recs = pexels::audit::read(&path).unwrap();
+    let sel = |s: &str| pexels::audit::select(&recs, s).map(|(i, r)| (i, r["request_id"].clone()));
+    assert_eq!(sel("1"), Some((1, "r1".into())));
+    assert_eq!(sel("-1"), Some((3, "r3".into())));
+    assert_eq!(sel("r2"), Some((2, "r2".into())));
+    assert_eq!(sel("0"), None);
+    assert_eq!(sel("4"), None);
+    assert_eq!(sel("-4"), None);
+    assert_eq!(sel("nope"), None);
+
+    let before = serde_json::json!({"page": 1, "photos": [{"id": 1, "alt": "a"}, {"id": 2}]});
+    let after = serde_json::json!({"page": 1, "photos": [{"id": 1, "alt": "b"}], "next_page": "x"});
+    let d = pexels::audit::diff(&before, &after, 10);
+    let paths: Vec<&str> = d.iter().map(|x| x["path"].as_str().unwrap()).collect();
+    assert_eq!(paths, ["next_page", "photos[0].alt", "photos[1]"]);
+    assert_eq!(d[1]["recorded"], "a");
+    assert_eq!(d[1]["current"], "b");
+    assert_eq!(pexels::audit::diff(&before, &after, 1).len(), 1);
+    assert!(pexels::audit::diff(&before, &before, 10).is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,169 @@
+// Own test binary: points the result cache and state at a temp dir via
+// XDG_CACHE_HOME / XDG_DATA_HOME and pages through a local HTTP server. Each
+// test searches its own query so cached pages are never shared.
+use clap::Parser;
+use pexels::api::PexelsClient;
+use pexels::cli::Cli;
+use pexels::config::{AuditConfig, Config};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+// Three search pages of one photo each for any query, linked by absolute next_page URLs
+// the way the API links them. Reports every request target it answers.
+fn serve() -> (String, std::sync::mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    let base = host.clone();
+    std::thread::spawn(move || {
+        for conn in listener.incoming() {
+            let mut conn = conn.unwrap();
+            let mut reader = BufReader::new(conn.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            let target = request_line
+                .split(' ')
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+            let url = reqwest::Url::parse(&format!("{}{}", base, target)).unwrap();
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.into_owned())
+            };
+            let page: u64 = param("page").and_then(|v| v.parse().ok()).unwrap_or(1);
+            let query = param("query").unwrap_or_default();
+            let next = (page < 3).then(|| {
+                format!(
+                    "{}/v1/search?page={}&per_page=1&query={}",
+                    base,
+                    page + 1,
+                    query
+                )
+            });
+            let body = serde_json::json!({
+                "page": page,
+                "per_page": 1,
+                "total_results": 3,
+                "next_page": next,
+                "photos": [{"id": page}],
+            })
+            .to_string();
+            tx.send(target).unwrap();
+            write!(
+                conn,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Request-Id: r{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                page,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    (host, rx)
+}
+
+// A fresh audit log in the binary's temp dir.
+fn audit_log(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pexels-audit-paging-{}", std::process::id()));
+    std::env::set_var("XDG_CACHE_HOME", dir.join("cache"));
+    std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+    let log = dir.join(format!("{}.jsonl", name));
+    let _ = std::fs::remove_file(&log);
+    log
+}
+
+fn client(host: &str, log: &Path) -> PexelsClient {
+    PexelsClient::new(Config {
+        token: Some("t".into()),
+        host: Some(host.into()),
+        timeout_secs: 5,
+        audit: AuditConfig {
+            enabled: true,
+            path: Some(log.to_path_buf()),
+        },
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn paged_runs_audit_the_followed_query() {
+    let log = audit_log("paged");
+    let (host, requests) = serve();
+    let client = client(&host, &log);
+
+    let cli = Cli::try_parse_from([
+        "pexels",
+        "photos",
+        "search",
+        "--query",
+        "cats",
+        "--all",
+        "--per-page",
+        "1",
+    ])
+    .unwrap();
+    let out = client.photos_search("cats", &[], &cli).await.unwrap();
+    assert_eq!(out["photos"].as_array().unwrap().len(), 3);
+    assert_eq!(requests.try_iter().count(), 3);
+
+    let recs = pexels::audit::read(&log).unwrap();
+    assert_eq!(recs.len(), 3);
+    for (i, rec) in recs.iter().enumerate() {
+        assert_eq!(rec["endpoint"], "/v1/search");
+        assert_eq!(rec["query"]["query"], "cats", "{}", rec);
+        assert_eq!(rec["query"]["per_page"], "1", "{}", rec);
+        if i > 0 {
+            assert_eq!(rec["query"]["page"], (i + 1).to_string(), "{}", rec);
+        }
+    }
+    let _ = std::fs::remove_file(&log);
+}
+
+#[tokio::test]
+async fn replay_resends_a_followed_page() {
+    let log = audit_log("replay");
+    let (host, requests) = serve();
+    let client = client(&host, &log);
+
+    // Page 2 was reached through page 1's next_page
+    let cli = Cli::try_parse_from([
+        "pexels",
+        "photos",
+        "search",
+        "--query",
+        "dogs",
+        "--max-pages",
+        "2",
+        "--per-page",
+        "1",
+    ])
+    .unwrap();
+    client.photos_search("dogs", &[], &cli).await.unwrap();
+    assert_eq!(requests.try_iter().count(), 2);
+
+    let out = pexels::cli::replay_request(&client, "r2").await.unwrap();
+    let sent: Vec<String> = requests.try_iter().collect();
+    assert_eq!(sent.len(), 1);
+    let url = reqwest::Url::parse(&format!("{}{}", host, sent[0])).unwrap();
+    let mut query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    query.sort();
+    assert_eq!(
+        query,
+        [("page", "2"), ("per_page", "1"), ("query", "dogs")]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+    );
+    assert_eq!(out["current"]["status"], 200);
+    assert_eq!(out["identical"], true, "{}", out);
+    let _ = std::fs::remove_file(&log);
+}
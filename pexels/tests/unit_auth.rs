@@ -58,3 +58,108 @@ fn auth_status_none_reason() {
     assert_eq!(payload["present"], false);
     assert_eq!(payload["details"]["reason"], "no token found");
 }
+
+#[test]
+fn auth_status_keyring_details() {
+    let cfg = Config {
+        token: Some("t".into()),
+        token_source: Some(TokenSource::Keyring),
+        profile: Some("work".into()),
+        ..Default::default()
+    };
+    let v = build_auth_status(&cfg);
+    assert_eq!(v["source"], "keyring");
+    assert_eq!(v["details"]["service"], "pexels");
+    assert_eq!(v["details"]["account"], "work");
+}
+
+#[test]
+fn stdin_token_is_trimmed_and_validated() {
+    use pexels::cli::parse_token_input;
+    assert_eq!(parse_token_input("abc123\n").unwrap(), "abc123");
+    assert_eq!(parse_token_input("  abc123\r\n").unwrap(), "abc123");
+    let err = parse_token_input("\n").unwrap_err();
+    assert_eq!(
+        pexels::output::error_value(&err)["error"],
+        "empty token on stdin"
+    );
+    assert!(parse_token_input("abc\nsecond line\n").is_err());
+}
+
+#[test]
+fn login_stdin_conflicts_with_positional_token() {
+    use clap::Parser;
+    use pexels::cli::Cli;
+    assert!(Cli::try_parse_from(["pexels", "auth", "login", "--stdin"]).is_ok());
+    assert!(Cli::try_parse_from(["pexels", "auth", "login", "--stdin", "TOKEN"]).is_err());
+}
+
+#[test]
+fn flag_token_is_ephemeral_and_read_from_file() {
+    use clap::Parser;
+    use pexels::cli::Cli;
+    let dir = std::env::temp_dir().join(format!("pexels-token-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("token");
+    std::fs::write(&file, "secret123\n").unwrap();
+    let path = file.to_str().unwrap();
+    let cli = Cli::try_parse_from(["pexels", "--token-file", path, "quota", "view"]).unwrap();
+    assert_eq!(cli.flag_token().unwrap().as_deref(), Some("secret123"));
+    let cli = Cli::try_parse_from(["pexels", "quota", "view", "--token", "abc"]).unwrap();
+    assert_eq!(cli.flag_token().unwrap().as_deref(), Some("abc"));
+    assert!(Cli::try_parse_from([
+        "pexels",
+        "--token",
+        "a",
+        "--token-file",
+        path,
+        "quota",
+        "view"
+    ])
+    .is_err());
+    // The global flag leaves `auth login TOKEN` alone
+    assert!(Cli::try_parse_from(["pexels", "--token", "a", "auth", "login", "b"]).is_ok());
+    std::fs::write(&file, "\n").unwrap();
+    let cli = Cli::try_parse_from(["pexels", "--token-file", path, "quota", "view"]).unwrap();
+    let err = cli.flag_token().unwrap_err();
+    assert_eq!(
+        pexels::output::error_value(&err)["error"],
+        "token file must hold exactly one token"
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut cfg = Config {
+        token: Some("saved".into()),
+        token_source: Some(TokenSource::Config),
+        ..Default::default()
+    };
+    cfg.use_flag_token("abc".into());
+    let v = build_auth_status(&cfg);
+    assert_eq!(v["source"], "flag");
+    assert_eq!(v["details"]["ephemeral"], true);
+}
+
+#[test]
+fn token_stdin_excludes_other_token_flags_and_login_stdin() {
+    use clap::Parser;
+    use pexels::cli::Cli;
+    let parse = |argv: &[&str]| Cli::try_parse_from(argv.iter().copied());
+    assert!(parse(&["pexels", "--token-stdin", "quota", "view"]).is_ok());
+    assert!(parse(&["pexels", "--token-stdin", "--token", "a", "quota", "view"]).is_err());
+    assert!(parse(&[
+        "pexels",
+        "--token-stdin",
+        "--token-file",
+        "f",
+        "quota",
+        "view"
+    ])
+    .is_err());
+    // Rejected before anything is read from stdin
+    let cli = parse(&["pexels", "--token-stdin", "auth", "login", "--stdin"]).unwrap();
+    let err = cli.flag_token().unwrap_err();
+    assert_eq!(
+        pexels::output::error_value(&err)["error"],
+        "--token-stdin and auth login --stdin both read stdin"
+    );
+}
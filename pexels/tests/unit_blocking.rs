@@ -0,0 +1,29 @@
+#![cfg(feature = "blocking")]
+
+use pexels::blocking::Client;
+use pexels::config::Config;
+
+fn unreachable_client() -> Client {
+    Client::new(Config {
+        token: Some("t".into()),
+        host: Some("http://127.0.0.1:9".into()),
+        timeout_secs: 2,
+        max_retries: 0,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn blocking_get_surfaces_transport_errors() {
+    let client = unreachable_client();
+    assert!(client.photos_get("1").is_err());
+}
+
+#[test]
+fn blocking_pages_iterator_stops_after_error() {
+    let client = unreachable_client();
+    let mut pages = client.photos().search("cats").per_page(5).pages();
+    assert!(pages.next().unwrap().is_err());
+    assert!(pages.next().is_none());
+}
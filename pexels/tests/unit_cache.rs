@@ -0,0 +1,99 @@
+use pexels::cache::{logical_key, Cache};
+use serde_json::json;
+use std::time::Duration;
+use url::Url;
+
+fn qp(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn logical_key_ignores_parameter_order_and_url_form() {
+    let search = Url::parse("https://api.pexels.com/v1/search").unwrap();
+    let a = logical_key(
+        &search,
+        &qp(&[("query", "cats"), ("color", "red")]),
+        None,
+        Some("t"),
+    );
+    let b = logical_key(
+        &search,
+        &qp(&[("color", "red"), ("query", "cats"), ("page", "1")]),
+        None,
+        Some("t"),
+    );
+    assert_eq!(a, b);
+    let next = Url::parse("https://api.pexels.com/v1/search?query=cats&color=red&page=2").unwrap();
+    let c = logical_key(
+        &search,
+        &qp(&[("page", "2"), ("color", "red"), ("query", "cats")]),
+        None,
+        Some("t"),
+    );
+    assert_eq!(logical_key(&next, &[], None, Some("t")), c);
+    assert_ne!(
+        a,
+        logical_key(
+            &search,
+            &qp(&[("query", "cats"), ("color", "red")]),
+            Some("de-DE"),
+            Some("t")
+        )
+    );
+    let other = logical_key(
+        &search,
+        &qp(&[("query", "cats"), ("color", "red")]),
+        None,
+        Some("u"),
+    );
+    assert_ne!(a, other);
+    assert!(!a.contains("|token=t"));
+}
+
+#[test]
+fn cache_roundtrip_and_ttl() {
+    let dir = std::env::temp_dir().join(format!("pexels-cache-{}", std::process::id()));
+    let cache = Cache::new(dir.clone(), Duration::from_secs(60));
+    assert!(cache.get("k").is_none());
+    cache.put("k", &json!({"photos": [1]})).unwrap();
+    assert_eq!(cache.get("k").unwrap()["photos"][0], 1);
+    assert!(cache.get("other").is_none());
+    let off = Cache::new(dir.clone(), Duration::ZERO);
+    assert!(off.get("k").is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn offline_lookup_ignores_age_and_missing_token() {
+    let dir = std::env::temp_dir().join(format!("pexels-cache-offline-{}", std::process::id()));
+    let url = Url::parse("https://api.pexels.com/v1/curated").unwrap();
+    let with_token = logical_key(&url, &qp(&[("per_page", "5")]), None, Some("t"));
+    let cache = Cache::new(dir.clone(), Duration::from_secs(300));
+    cache.put(&with_token, &json!({"photos": [1]})).unwrap();
+
+    // Disabled/expired TTL still answers offline
+    let stale = Cache::new(dir.clone(), Duration::ZERO);
+    assert!(stale.get(&with_token).is_none());
+    assert_eq!(stale.get_offline(&with_token), Some(json!({"photos": [1]})));
+
+    // No credentials: any token's entry for the same request
+    let anonymous = logical_key(&url, &qp(&[("per_page", "5")]), None, None);
+    assert_eq!(stale.get_offline(&anonymous), Some(json!({"photos": [1]})));
+    let other = logical_key(&url, &qp(&[("per_page", "6")]), None, None);
+    assert!(stale.get_offline(&other).is_none());
+    // A different token is a different account: no fallback
+    let wrong = logical_key(&url, &qp(&[("per_page", "5")]), None, Some("u"));
+    assert!(stale.get_offline(&wrong).is_none());
+
+    let miss = pexels::output::error_value(&pexels::cache::offline_miss(
+        &url,
+        &qp(&[("per_page", "6")]),
+    ));
+    assert_eq!(miss["error"], "not cached");
+    assert_eq!(miss["endpoint"], "/v1/curated");
+    assert_eq!(miss["query"]["per_page"], "6");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,170 @@
+use clap::{CommandFactory, Parser};
+use pexels::cli::{parse_color, Cli, Orientation, PhotoFilters, SearchSize, VideoFilters};
+
+#[test]
+fn color_accepts_names_and_hex() {
+    assert_eq!(parse_color("Red").unwrap(), "red");
+    assert_eq!(parse_color("#FFaa00").unwrap(), "#ffaa00");
+    assert_eq!(parse_color("ffaa00").unwrap(), "#ffaa00");
+    assert!(parse_color("mauve").is_err());
+    assert!(parse_color("#ffa").is_err());
+}
+
+#[test]
+fn filters_become_query_params() {
+    let f = PhotoFilters {
+        orientation: Some(Orientation::Portrait),
+        size: Some(SearchSize::Large),
+        color: Some("#ffffff".into()),
+    };
+    let qp = f.query_params();
+    assert_eq!(qp[0], ("orientation".to_string(), "portrait".to_string()));
+    assert_eq!(qp[1], ("size".to_string(), "large".to_string()));
+    assert_eq!(qp[2], ("color".to_string(), "#ffffff".to_string()));
+    assert!(PhotoFilters::default().query_params().is_empty());
+}
+
+#[test]
+fn search_rejects_unknown_orientation() {
+    let ok = Cli::try_parse_from([
+        "pexels",
+        "photos",
+        "search",
+        "-q",
+        "x",
+        "--orientation",
+        "square",
+    ]);
+    assert!(ok.is_ok());
+    let bad = Cli::try_parse_from([
+        "pexels",
+        "photos",
+        "search",
+        "-q",
+        "x",
+        "--orientation",
+        "wide",
+    ]);
+    assert!(bad.is_err());
+}
+
+#[test]
+fn video_filters_become_query_params() {
+    let ok = Cli::try_parse_from([
+        "pexels",
+        "videos",
+        "search",
+        "ocean",
+        "--min-width",
+        "1920",
+        "--max-duration",
+        "30",
+    ]);
+    assert!(ok.is_ok());
+    let f = VideoFilters {
+        min_width: Some(1920),
+        max_duration: Some(30),
+        ..Default::default()
+    };
+    assert_eq!(
+        f.query_params(),
+        vec![
+            ("min_width".to_string(), "1920".to_string()),
+            ("max_duration".to_string(), "30".to_string()),
+        ]
+    );
+    let bad = VideoFilters {
+        min_duration: Some(60),
+        max_duration: Some(10),
+        ..Default::default()
+    };
+    assert!(bad.validate().is_err());
+}
+
+#[test]
+fn command_definition_is_consistent() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn output_format_flag_excludes_shorthands() {
+    let cli = Cli::try_parse_from(["pexels", "photos", "curated", "--output", "csv"]).unwrap();
+    assert_eq!(cli.output, Some(pexels::output::OutputFormat::Csv));
+    assert!(
+        Cli::try_parse_from(["pexels", "photos", "curated", "--output", "tsv", "--json"]).is_err()
+    );
+    assert!(Cli::try_parse_from(["pexels", "photos", "curated", "--output", "xlsx"]).is_err());
+    // Parquet prints one row per item like csv, in every build (the writer
+    // itself needs the `parquet` feature)
+    let cli = Cli::try_parse_from(["pexels", "photos", "curated", "--output", "parquet"]).unwrap();
+    let fmt = cli.output.unwrap();
+    assert!(fmt.is_table());
+    assert_eq!(fmt.content_type(), "application/vnd.apache.parquet");
+}
+
+#[test]
+fn collections_expand_flags() {
+    assert!(Cli::try_parse_from(["pexels", "collections", "featured", "--expand-counts"]).is_ok());
+    assert!(Cli::try_parse_from([
+        "pexels",
+        "collections",
+        "list",
+        "--expand",
+        "--expand-concurrency",
+        "8"
+    ])
+    .is_ok());
+    assert!(
+        Cli::try_parse_from(["pexels", "collections", "list", "--expand-concurrency", "8"])
+            .is_err()
+    );
+}
+
+#[test]
+fn get_takes_several_ids() {
+    assert!(Cli::try_parse_from(["pexels", "photos", "get"]).is_err());
+    assert!(
+        Cli::try_parse_from(["pexels", "videos", "get", "1", "2", "--concurrency", "2"]).is_ok()
+    );
+    // Lookup counts and misses of a multi-ID get land in meta
+    let data = serde_json::json!({
+        "photos": [{"id": 1}],
+        "lookups": {"requested": 2, "found": 1, "failed": 1},
+        "errors": [{"id": "2", "code": 404}],
+    });
+    let (items, meta) = pexels::cli::shape_output(&data);
+    assert_eq!(items, serde_json::json!([{"id": 1}]));
+    assert_eq!(meta["lookups"]["failed"], 1);
+    assert_eq!(meta["errors"][0]["code"], 404);
+}
+
+#[test]
+fn api_params_split_on_first_equals() {
+    use pexels::cli::parse_param;
+    assert_eq!(
+        parse_param("query=a=b").unwrap(),
+        ("query".to_string(), "a=b".to_string())
+    );
+    assert!(parse_param("query").is_err());
+    assert!(parse_param("=x").is_err());
+    let cli = Cli::try_parse_from(["pexels", "api", "get", "/v1/curated", "-p", "per_page=5"]);
+    assert!(cli.is_ok());
+}
+
+#[test]
+fn completions_cover_subcommands_and_enum_values() {
+    let mut out = vec![];
+    pexels::cli::write_completions(clap_complete::Shell::Bash, &mut out).unwrap();
+    let script = String::from_utf8(out).unwrap();
+    for word in [
+        "download-all",
+        "--orientation",
+        "landscape",
+        "large2x",
+        "uhd",
+    ] {
+        assert!(script.contains(word), "{}", word);
+    }
+    assert!(Cli::try_parse_from(["pexels", "completions", "powershell"]).is_ok());
+    assert!(Cli::try_parse_from(["pexels", "completions", "tcsh"]).is_err());
+}
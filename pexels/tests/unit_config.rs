@@ -42,3 +42,194 @@ fn test_token_save_permissions() {
     #[cfg(unix)]
     assert_eq!(meta.permissions().mode() & 0o777, 0o600);
 }
+
+#[test]
+fn projections_fall_back_to_builtin_and_accept_overrides() {
+    let mut cfg = Config::default();
+    assert!(cfg
+        .projection("photos")
+        .contains(&"photographer".to_string()));
+    assert!(cfg.projection("unknown").is_empty());
+    cfg.projections
+        .insert("photos".into(), vec!["id".into(), "src.medium".into()]);
+    assert_eq!(cfg.projection("photos"), vec!["id", "src.medium"]);
+    // An explicit empty list disables the default projection
+    cfg.projections.insert("videos".into(), vec![]);
+    assert!(cfg.projection("videos").is_empty());
+    let yaml = serde_yaml::to_string(&cfg).unwrap();
+    let back: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(back.projection("photos"), vec!["id", "src.medium"]);
+}
+
+#[test]
+fn changed_keys_name_settings_without_values() {
+    let a = Config {
+        token: Some("old-secret".into()),
+        ..Default::default()
+    };
+    let mut b = a.clone();
+    b.token = Some("new-secret".into());
+    b.watch.interval = Some(30);
+    b.projections.insert("photos".into(), vec!["id".into()]);
+    let keys = a.changed_keys(&b);
+    assert_eq!(keys, vec!["projections.photos", "token", "watch.interval"]);
+    let mut c = b.clone();
+    c.watch.interval = Some(45);
+    c.audit.enabled = true;
+    assert_eq!(b.changed_keys(&c), vec!["audit.enabled", "watch.interval"]);
+    assert!(a.changed_keys(&a.clone()).is_empty());
+}
+
+#[test]
+fn host_profile_keys_and_selection() {
+    use pexels::config::{host_profile_key, HostProfile};
+    assert_eq!(
+        host_profile_key("hosts.internal.headers.X-Key"),
+        Some(("internal", "headers.X-Key"))
+    );
+    assert_eq!(host_profile_key("hosts.internal"), None);
+    assert_eq!(host_profile_key("projections.photos"), None);
+
+    let mut p = HostProfile::default();
+    p.set("base", "https://gw.corp/pexels/").unwrap();
+    p.set("headers.X-Key", "abc").unwrap();
+    p.set("cacert", "/etc/ssl/corp.pem").unwrap();
+    assert!(p.set("proxy", "x").is_err());
+    assert_eq!(p.get("base").as_deref(), Some("https://gw.corp/pexels"));
+    assert_eq!(p.get("headers.X-Key").as_deref(), Some("abc"));
+    p.set("headers.X-Key", "").unwrap();
+    assert!(p.headers.is_empty());
+
+    let mut cfg = Config::default();
+    cfg.hosts.insert("internal".into(), p);
+    assert!(cfg.select_host_profile("internl").is_err());
+    cfg.select_host_profile("internal").unwrap();
+    assert_eq!(cfg.host.as_deref(), Some("https://gw.corp/pexels"));
+    assert!(cfg.host_profile.is_some());
+
+    // The host profile's base wins over a config profile's host (an
+    // explicit --host is applied after both)
+    let mut cfg2 = Config {
+        host: Some("http://localhost:8080".into()),
+        hosts: cfg.hosts.clone(),
+        ..Default::default()
+    };
+    cfg2.select_host_profile("internal").unwrap();
+    assert_eq!(cfg2.host.as_deref(), Some("https://gw.corp/pexels"));
+}
+
+#[test]
+fn config_profiles_override_top_level() {
+    use pexels::config::Profile;
+    let mut cfg: Config = serde_yaml::from_str(
+        "token: top\nhuman: true\nprofiles:\n  work:\n    token: work-token\n    host: https://gw.work\n    locale: de-DE\n    output: json\n  personal:\n    human: false\n",
+    )
+    .unwrap();
+    assert_eq!(cfg.profiles.len(), 2);
+    assert!(cfg.select_profile("wrk").is_err());
+    assert_eq!(cfg.profile_name(), "default");
+    let mut work = cfg.clone();
+    work.select_profile("work").unwrap();
+    assert_eq!(work.token.as_deref(), Some("work-token"));
+    assert_eq!(work.host.as_deref(), Some("https://gw.work"));
+    assert_eq!(work.locale.as_deref(), Some("de-DE"));
+    assert_eq!(work.output, Some(pexels::output::OutputFormat::Json));
+    assert!(work.human);
+    assert_eq!(work.profile_name(), "work");
+    cfg.select_profile("personal").unwrap();
+    assert_eq!(cfg.token.as_deref(), Some("top"));
+    assert!(!cfg.human);
+    assert_eq!(cfg.host, None);
+    assert_eq!(
+        cfg.profiles["personal"],
+        Profile {
+            human: Some(false),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn token_storage_parses_and_defaults_to_file() {
+    use pexels::config::TokenStorage;
+    assert_eq!(
+        TokenStorage::parse("keyring").unwrap(),
+        TokenStorage::Keyring
+    );
+    assert_eq!(TokenStorage::parse("file").unwrap(), TokenStorage::File);
+    assert!(TokenStorage::parse("vault").is_err());
+    let cfg: Config = serde_yaml::from_str("token: t\n").unwrap();
+    assert_eq!(cfg.token_storage, TokenStorage::File);
+    // The default is not written out
+    assert!(!serde_yaml::to_string(&cfg)
+        .unwrap()
+        .contains("token_storage"));
+    let cfg: Config = serde_yaml::from_str("token_storage: keyring\n").unwrap();
+    assert_eq!(cfg.token_storage, TokenStorage::Keyring);
+    assert_eq!(cfg.token_storage.as_str(), "keyring");
+}
+
+#[test]
+fn remote_keys_split_on_the_last_dot() {
+    use pexels::config::remote_key;
+    assert_eq!(
+        remote_key("remotes.dav.example.com.user"),
+        Some(("dav.example.com", "user"))
+    );
+    assert_eq!(remote_key("remotes.user"), None);
+    assert_eq!(remote_key("hosts.x.base"), None);
+    let mut auth = pexels::config::RemoteAuth::default();
+    auth.set("identity", "/keys/id").unwrap();
+    assert_eq!(auth.get("identity").as_deref(), Some("/keys/id"));
+    assert!(auth.set("port", "22").is_err());
+}
+
+#[test]
+fn default_keys_round_trip_and_flags_win() {
+    use clap::Parser;
+    use pexels::cli::{download_dir, Cli};
+    let yaml = "default_per_page: 40\nlocale: de-DE\ndownload_dir: /srv/photos\nmax_retries: 5\ntimeout: 30\n";
+    let mut cfg: Config = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(cfg.default_per_page, Some(40));
+    assert_eq!(cfg.default_timeout, Some(30));
+    assert_eq!(cfg.default_max_retries, Some(5));
+    let written = serde_yaml::to_string(&cfg).unwrap();
+    assert!(written.ends_with(
+        "default_per_page: 40\nlocale: de-DE\ntimeout: 30\nmax_retries: 5\ndownload_dir: /srv/photos\n"
+    ));
+    cfg.timeout_secs = 30;
+    cfg.max_retries = 5;
+    cfg.apply_cli(&Cli::try_parse_from(["pexels", "photos", "curated"]).unwrap());
+    assert_eq!((cfg.timeout_secs, cfg.max_retries), (30, 5));
+    assert_eq!(cfg.locale.as_deref(), Some("de-DE"));
+    cfg.apply_cli(
+        &Cli::try_parse_from([
+            "pexels",
+            "photos",
+            "curated",
+            "--timeout",
+            "5",
+            "--max-retries",
+            "0",
+            "--locale",
+            "fr-FR",
+        ])
+        .unwrap(),
+    );
+    assert_eq!((cfg.timeout_secs, cfg.max_retries), (5, 0));
+    assert_eq!(cfg.locale.as_deref(), Some("fr-FR"));
+    assert_eq!(
+        download_dir(&cfg, &None, false).unwrap(),
+        std::path::Path::new("/srv/photos")
+    );
+    assert_eq!(
+        download_dir(&cfg, &Some("out".into()), false).unwrap(),
+        std::path::Path::new("out")
+    );
+    let bare = Config::default();
+    assert!(download_dir(&bare, &None, false).is_err());
+    assert_eq!(
+        download_dir(&bare, &None, true).unwrap(),
+        std::path::Path::new(".")
+    );
+}
@@ -0,0 +1,27 @@
+// Own test binary: these tests change process-wide config location state.
+use pexels::config::{set_path_override, Config};
+
+#[test]
+fn config_flag_and_env_move_the_config_file() {
+    let dir = std::env::temp_dir().join(format!("pexels-config-path-{}", std::process::id()));
+    let from_env = dir.join("env.yaml");
+    let from_flag = dir.join("tenant/flag.yaml");
+    std::env::set_var("PEXELS_CONFIG", &from_env);
+    assert_eq!(Config::config_path(), from_env);
+    // --config wins over PEXELS_CONFIG
+    set_path_override(Some(from_flag.clone()));
+    assert_eq!(Config::config_path(), from_flag);
+    let saved = Config::update(|c| {
+        c.locale = Some("fr-FR".into());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(saved.path(), from_flag);
+    assert_eq!(Config::load().unwrap().locale.as_deref(), Some("fr-FR"));
+    assert!(!from_env.exists());
+    set_path_override(None);
+    assert_eq!(Config::load().unwrap().locale, None);
+    std::env::remove_var("PEXELS_CONFIG");
+    assert!(Config::config_path().ends_with("pexels/config.yaml"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,48 @@
+use pexels::contrast::{luminance, parse_hex, ratio, report, Region};
+
+#[test]
+fn parse_hex_accepts_short_and_long_forms() {
+    assert_eq!(parse_hex("#fff").unwrap(), [255, 255, 255]);
+    assert_eq!(parse_hex("1A2b3C").unwrap(), [0x1a, 0x2b, 0x3c]);
+    assert!(parse_hex("#ffff").is_err());
+    assert!(parse_hex("#gggggg").is_err());
+}
+
+#[test]
+fn ratio_matches_wcag_reference_values() {
+    let (white, black) = (luminance([255, 255, 255]), luminance([0, 0, 0]));
+    assert!((ratio(white, black) - 21.0).abs() < 1e-9);
+    assert!((ratio(black, white) - 21.0).abs() < 1e-9);
+    // #777 on white is the classic 4.48:1 near-miss
+    let grey = luminance([0x77, 0x77, 0x77]);
+    assert!((ratio(white, grey) - 4.48).abs() < 0.01);
+}
+
+#[test]
+fn regions_cover_the_expected_rectangles() {
+    assert_eq!(Region::Full.rect(90, 60), (0, 0, 90, 60));
+    assert_eq!(Region::Top.rect(90, 60), (0, 0, 90, 20));
+    assert_eq!(Region::Bottom.rect(90, 60), (0, 40, 90, 20));
+    assert_eq!(Region::Right.rect(90, 60), (60, 0, 30, 60));
+    assert_eq!(Region::Center.rect(90, 60), (30, 20, 30, 20));
+}
+
+#[test]
+fn report_judges_the_region_under_the_text() {
+    // Dark top third, white below
+    let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(60, 60, |_, y| {
+        if y < 20 {
+            image::Rgb([0x10, 0x10, 0x10])
+        } else {
+            image::Rgb([255, 255, 255])
+        }
+    }));
+    let top = report(&img, [255, 255, 255], Region::Top);
+    assert_eq!(top["background"], "#101010");
+    assert_eq!(top["passes"]["aaa"], true);
+    assert_eq!(top["share"]["aa"], 1.0);
+    let bottom = report(&img, [255, 255, 255], Region::Bottom);
+    assert_eq!(bottom["ratio"]["average"], 1.0);
+    assert_eq!(bottom["passes"]["aa_large"], false);
+    assert_eq!(bottom["share"]["aa"], 0.0);
+}
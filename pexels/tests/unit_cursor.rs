@@ -0,0 +1,55 @@
+use clap::Parser;
+use pexels::cli::Cli;
+use pexels::cursor::{read, write, Cursor};
+
+#[test]
+fn cursor_file_walks_to_done() {
+    let dir = std::env::temp_dir().join(format!("pexels-cursor-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("cats.cursor");
+    assert_eq!(read(&path).unwrap(), Cursor::Start);
+    let next = "https://api.pexels.com/v1/search/?page=2&per_page=15&query=cats";
+    write(&path, Some(next)).unwrap();
+    assert_eq!(read(&path).unwrap(), Cursor::Next(next.into()));
+    write(&path, None).unwrap();
+    assert_eq!(read(&path).unwrap(), Cursor::Done);
+    assert!(!dir.join("cats.cursor.part").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cursor_file_needs_raw_single_pages() {
+    let parse = |extra: &[&str]| {
+        let mut argv = vec!["pexels", "photos", "curated", "--cursor-file", "c.txt"];
+        argv.extend(extra);
+        Cli::try_parse_from(argv)
+    };
+    assert!(parse(&["--raw"]).is_ok());
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--raw", "--all"]).is_err());
+}
+
+#[test]
+fn resume_file_only_fits_its_own_walk() {
+    use pexels::resume::Checkpoint;
+    let qp = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let cp = Checkpoint::start("/v1/search", &qp(&[("query", "cats"), ("page", "3")]));
+    assert_eq!(cp.page, 3);
+    assert!(cp.matches("/v1/search", &qp(&[("page", "7"), ("query", "cats")])));
+    assert!(!cp.matches("/v1/search", &qp(&[("query", "dogs")])));
+    assert!(!cp.matches("/v1/curated", &qp(&[("query", "cats")])));
+
+    let parse = |extra: &[&str]| {
+        let mut argv = vec!["pexels", "photos", "curated", "--resume", "r.json"];
+        argv.extend(extra);
+        Cli::try_parse_from(argv)
+    };
+    assert!(parse(&["--all", "--limit", "500"]).is_ok());
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--all", "--page", "2"]).is_err());
+}
@@ -0,0 +1,100 @@
+use pexels::dataset::{parse_split, row, write_index, Exclusions, Split, COLUMNS, LICENSE};
+use std::fs;
+
+#[test]
+fn splits_parse_and_must_total_100() {
+    assert_eq!(parse_split("80/10/10").unwrap(), Split::default());
+    assert!(parse_split("90/10").is_ok());
+    assert!(parse_split("80/10/5").is_err());
+    assert!(parse_split("80").is_err());
+    assert!(parse_split("a/b/c").is_err());
+}
+
+#[test]
+fn assignment_is_stable_and_follows_the_shares() {
+    let split = parse_split("80/10/10").unwrap();
+    let mut counts = std::collections::HashMap::new();
+    for id in 0..2000 {
+        let id = id.to_string();
+        let part = split.assign(&id);
+        assert_eq!(split.assign(&id), part);
+        *counts.entry(part).or_insert(0) += 1;
+    }
+    assert!((1500..1700).contains(&counts["train"]), "{:?}", counts);
+    assert!((120..280).contains(&counts["val"]), "{:?}", counts);
+    assert!((120..280).contains(&counts["test"]), "{:?}", counts);
+    let no_test = parse_split("90/10").unwrap();
+    assert!((0..500).all(|id| no_test.assign(&id.to_string()) != "test"));
+}
+
+#[test]
+fn index_rows_are_quoted_csv() {
+    let photo = serde_json::json!({
+        "id": 42,
+        "width": 4000,
+        "height": 3000,
+        "alt": "Street, at \"night\"",
+        "photographer": "Jo",
+        "url": "https://www.pexels.com/photo/42/",
+    });
+    let dir = std::env::temp_dir().join(format!("pexels-dataset-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("index.csv");
+    write_index(
+        &path,
+        &[row(&photo, "train", "train/photo-42.jpg", "large", "ab12")],
+    )
+    .unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next().unwrap(), COLUMNS.join(","));
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "42,train,train/photo-42.jpg,large,4000,3000,\"Street, at \"\"night\"\"\",Jo,,https://www.pexels.com/photo/42/,,{},ab12",
+            LICENSE
+        )
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn exclusions_read_indexes_and_manifests() {
+    let dir = std::env::temp_dir().join(format!("pexels-exclude-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let hash = "AB".repeat(32);
+    let index = dir.join("index.csv");
+    let photo = serde_json::json!({"id": 1, "alt": "a,\nb"});
+    write_index(
+        &index,
+        &[row(&photo, "train", "train/photo-1.jpg", "large", &hash)],
+    )
+    .unwrap();
+    let manifest = dir.join("manifest.json");
+    fs::write(
+        &manifest,
+        r#"{"collection": "c", "items": [{"id": 2, "kind": "photo"}, {"id": "3", "sha256": "sha256:CD"}]}"#,
+    )
+    .unwrap();
+    let lines = dir.join("ids.ndjson");
+    fs::write(
+        &lines,
+        format!("4\n\"{}\"\n{{\"id\": 5}}\n", "ef".repeat(32)),
+    )
+    .unwrap();
+
+    let ex = Exclusions::load(&[index, manifest, lines]).unwrap();
+    let mut ids: Vec<_> = ex.ids.iter().map(String::as_str).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["1", "2", "3", "4", "5"]);
+    assert!(ex.hashes.contains(&hash.to_lowercase()));
+    assert!(ex.hashes.contains("cd"));
+    assert!(ex.hashes.contains(&"ef".repeat(32)));
+
+    let unknown = dir.join("ids.txt");
+    fs::write(&unknown, "1").unwrap();
+    let err = Exclusions::load(&[unknown]).unwrap_err().to_string();
+    assert!(err.contains("cannot read exclude manifest"), "{}", err);
+    assert!(Exclusions::load(&[dir.join("missing.json")]).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,242 @@
+use pexels::download::{
+    open_part, part_path, write_fenced, write_fenced_if, write_fenced_stream, IfBusy, Transfer,
+    Validators,
+};
+use pexels::lock::{lock_path_for, FileLock};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn tmp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pexels-dl-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn part_path_is_sibling() {
+    assert_eq!(
+        part_path(Path::new("a/b.jpg")),
+        PathBuf::from("a/b.jpg.part")
+    );
+}
+
+#[tokio::test]
+async fn fenced_write_renames_into_place() {
+    let dir = tmp("write");
+    let dest = dir.join("x.jpg");
+    let w = write_fenced(&dest, IfBusy::Error, || async { Ok(b"abc".to_vec()) })
+        .await
+        .unwrap();
+    assert!(!w.skipped);
+    assert_eq!(w.bytes, 3);
+    assert_eq!(fs::read(&dest).unwrap(), b"abc");
+    assert!(!part_path(&dest).exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn fenced_write_respects_busy_policy() {
+    let dir = tmp("busy");
+    let dest = dir.join("y.jpg");
+    let held = FileLock::try_acquire(&lock_path_for(&dest)).unwrap();
+    assert!(held.is_some());
+
+    let skipped = write_fenced(&dest, IfBusy::Skip, || async {
+        panic!("fetch must not run while busy")
+    })
+    .await
+    .unwrap();
+    assert!(skipped.skipped);
+    assert_eq!(skipped.to_json()["reason"], "busy");
+
+    let err = write_fenced(&dest, IfBusy::Error, || async { Ok(vec![]) })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("destination busy"));
+
+    drop(held);
+    let w = write_fenced(&dest, IfBusy::Skip, || async { Ok(b"z".to_vec()) })
+        .await
+        .unwrap();
+    assert!(!w.skipped);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn blocking_lock_times_out_while_held() {
+    let dir = tmp("wait");
+    let lock = dir.join("state.lock");
+    let held = FileLock::try_acquire(&lock).unwrap().unwrap();
+    let err = FileLock::acquire_blocking(&lock, std::time::Duration::from_millis(50))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("lock busy"));
+    drop(held);
+    assert!(FileLock::acquire_blocking(&lock, std::time::Duration::ZERO).is_ok());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn not_modified_keeps_existing_file() {
+    let dir = tmp("unchanged");
+    let dest = dir.join("z.jpg");
+    fs::write(&dest, b"old!").unwrap();
+    let w = write_fenced_if(&dest, IfBusy::Error, || async { Ok(None) })
+        .await
+        .unwrap();
+    assert!(w.skipped && w.unchanged);
+    assert_eq!(w.bytes, 4);
+    assert_eq!(w.to_json()["reason"], "unchanged");
+    assert_eq!(fs::read(&dest).unwrap(), b"old!");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn streamed_write_appends_to_partial_file() {
+    let dir = tmp("resume");
+    let dest = dir.join("v.mp4");
+    fs::write(part_path(&dest), b"abc").unwrap();
+    let w = write_fenced_stream(&dest, IfBusy::Error, |part| async move {
+        let offset = fs::metadata(&part).unwrap().len();
+        let mut f = open_part(&part, true)?;
+        f.write_all(b"def")?;
+        Ok(Transfer {
+            bytes: Some(6),
+            resumed_from: offset,
+            ..Default::default()
+        })
+    })
+    .await
+    .unwrap();
+    assert_eq!(fs::read(&dest).unwrap(), b"abcdef");
+    assert_eq!(w.to_json()["resumed_from"], 3);
+    assert!(!part_path(&dest).exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn if_range_prefers_strong_etag() {
+    let v = Validators {
+        etag: Some("\"abc\"".into()),
+        last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".into()),
+    };
+    assert_eq!(v.if_range(), Some("\"abc\""));
+    let weak = Validators {
+        etag: Some("W/\"abc\"".into()),
+        ..v.clone()
+    };
+    assert_eq!(weak.if_range(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+    assert_eq!(Validators::default().if_range(), None);
+}
+
+#[test]
+fn progress_line_shows_fraction() {
+    let mut p = pexels::progress::Progress::new("x.jpg", false);
+    p.reset(500, Some(2000));
+    assert!(p
+        .line()
+        .starts_with("x.jpg [######------------------]  25% 500 B/2.0 KB"));
+    p.reset(1500, None);
+    assert_eq!(p.line(), "x.jpg 1.5 KB");
+}
+
+#[test]
+fn bulk_summary_counts_outcomes() {
+    use pexels::download::{summarize, Job, Written};
+    let job = |id: u64| Job {
+        id: id.into(),
+        url: format!("https://images.example/{}.jpg", id),
+        dest: PathBuf::from(format!("d/photo-{}.jpg", id)),
+    };
+    let written = |skipped| Written {
+        path: PathBuf::from("d/x.jpg"),
+        bytes: 3,
+        skipped,
+        unchanged: skipped,
+        resumed_from: 0,
+    };
+    let results = vec![
+        (job(1), Ok(written(false))),
+        (job(2), Ok(written(true))),
+        (job(3), Err(anyhow::anyhow!("connection reset"))),
+    ];
+    let s = summarize(&results);
+    assert_eq!(
+        (
+            s["succeeded"].as_u64(),
+            s["skipped"].as_u64(),
+            s["failed"].as_u64()
+        ),
+        (Some(1), Some(1), Some(1))
+    );
+    assert_eq!(s["items"][1]["reason"], "unchanged");
+    assert_eq!(s["items"][2]["id"], 3);
+    assert_eq!(s["items"][2]["error"], "connection reset");
+    assert_eq!(s["items"][2]["path"], "d/photo-3.jpg");
+}
+
+// Answers every request with `{}` and reports its lowercased header lines.
+fn serve_headers() -> (String, std::sync::mpsc::Receiver<Vec<String>>) {
+    use std::io::{BufRead, BufReader};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for conn in listener.incoming() {
+            let mut conn = conn.unwrap();
+            let mut reader = BufReader::new(conn.try_clone().unwrap());
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push(line);
+            }
+            tx.send(headers).unwrap();
+            write!(
+                conn,
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+            )
+            .unwrap();
+        }
+    });
+    (url, rx)
+}
+
+#[tokio::test]
+async fn gateway_headers_stay_on_the_api_host() {
+    let (api, api_seen) = serve_headers();
+    let (cdn, cdn_seen) = serve_headers();
+    let mut cfg: pexels::config::Config = serde_yaml::from_str(&format!(
+        "token: t\nhosts:\n  corp:\n    base: {}\n    headers:\n      X-Gateway-Key: gw-secret\n",
+        api
+    ))
+    .unwrap();
+    cfg.select_host_profile("corp").unwrap();
+    cfg.timeout_secs = 5;
+    let client = pexels::api::PexelsClient::new(cfg).unwrap();
+
+    client.util_ping().await.unwrap();
+    let sent = api_seen.try_iter().next().unwrap();
+    assert!(
+        sent.contains(&"x-gateway-key: gw-secret".to_string()),
+        "{:?}",
+        sent
+    );
+
+    let body = client
+        .download_url_bytes(&format!("{}/photos/1/a.jpg", cdn))
+        .await
+        .unwrap();
+    assert_eq!(body, b"{}");
+    let sent = cdn_seen.try_iter().next().unwrap();
+    assert!(
+        !sent.iter().any(|h| h.starts_with("x-gateway-key")),
+        "{:?}",
+        sent
+    );
+}
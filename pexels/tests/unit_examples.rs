@@ -0,0 +1,107 @@
+use clap::Parser;
+use pexels::cli::{command, Cli};
+use pexels::config::Config;
+use pexels::examples::{argv, fill, for_command, placeholders, recipes, EXAMPLES, RECIPES};
+
+// Leaf subcommand paths ("photos search", "continue", ...).
+fn leaves(cmd: &clap::Command, prefix: &str, out: &mut Vec<String>) {
+    for sub in cmd.get_subcommands() {
+        let path = if prefix.is_empty() {
+            sub.get_name().to_string()
+        } else {
+            format!("{} {}", prefix, sub.get_name())
+        };
+        if sub.get_subcommands().next().is_none() {
+            out.push(path);
+        } else {
+            leaves(sub, &path, out);
+        }
+    }
+}
+
+#[test]
+fn every_leaf_command_has_examples() {
+    let mut paths = vec![];
+    leaves(&command(), "", &mut paths);
+    for path in paths {
+        let n = EXAMPLES.iter().filter(|e| e.command == path).count();
+        assert!((2..=3).contains(&n), "{} has {} examples", path, n);
+    }
+}
+
+#[test]
+fn every_example_parses() {
+    for e in EXAMPLES {
+        let args = argv(e.line);
+        assert_eq!(args[0], "pexels");
+        let cli = Cli::try_parse_from(&args);
+        assert!(cli.is_ok(), "{}: {}", e.line, cli.unwrap_err());
+        assert!(
+            args[1..]
+                .join(" ")
+                .contains(e.command.rsplit(' ').next().unwrap()),
+            "{} does not run {}",
+            e.line,
+            e.command
+        );
+    }
+}
+
+#[test]
+fn help_and_lookup_share_the_registry() {
+    let mut cmd = command();
+    let help = cmd
+        .find_subcommand_mut("photos")
+        .and_then(|c| c.find_subcommand_mut("search"))
+        .unwrap()
+        .render_long_help()
+        .to_string();
+    for e in for_command(&["photos".into(), "search".into()]) {
+        assert!(help.contains(e.line), "{}", e.line);
+    }
+    assert!(for_command(&["videos".into()]).len() >= 10);
+    assert!(for_command(&["nope".into()]).is_empty());
+}
+
+// `pexels ...` invocations in a shell step, with xargs' `%` standing in for an id.
+fn invocations(step: &str) -> Vec<Vec<String>> {
+    step.split(" | ")
+        .filter_map(|seg| {
+            let args = argv(seg);
+            let start = args.iter().position(|a| a == "pexels")?;
+            Some(
+                args[start..]
+                    .iter()
+                    .take_while(|a| !a.starts_with('>'))
+                    .map(|a| if a == "%" { "1".to_string() } else { a.clone() })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn recipes_fill_from_config_and_parse() {
+    let mut cfg = Config::default();
+    cfg.projections
+        .insert("photos".into(), vec!["id".into(), "alt".into()]);
+    cfg.watch.interval = Some(900);
+    let values = placeholders(&cfg);
+    let mut categories = std::collections::BTreeSet::new();
+    for r in RECIPES {
+        categories.insert(r.category);
+        for step in r.steps {
+            let filled = fill(step, &values);
+            assert!(!filled.contains("{{"), "{}", filled);
+            for args in invocations(&filled) {
+                let cli = Cli::try_parse_from(&args);
+                assert!(cli.is_ok(), "{}: {}", filled, cli.unwrap_err());
+            }
+        }
+    }
+    assert!(categories.len() >= 3);
+    let search = fill(recipes(&["export-search".into()])[0].steps[0], &values);
+    assert!(search.contains("--fields id,alt"));
+    let watch = fill(recipes(&["wallpaper".into()])[0].steps[1], &values);
+    assert!(watch.contains("--interval 900"));
+}
@@ -0,0 +1,183 @@
+use pexels::output::{
+    flatten, parse_export_spec, render_delimited, render_export, render_markdown, ExportFormat,
+};
+use serde_json::json;
+
+#[test]
+fn export_spec_parsing() {
+    let (fmt, path) = parse_export_spec("csv=out/results.csv").unwrap();
+    assert_eq!(fmt, ExportFormat::Csv);
+    assert_eq!(path.to_str().unwrap(), "out/results.csv");
+    assert!(parse_export_spec("xml=a.xml").is_err());
+    assert!(parse_export_spec("json").is_err());
+}
+
+#[test]
+fn flatten_uses_dot_paths() {
+    let flat = flatten(&json!({"id": 1, "src": {"original": "o", "tiny": "t"}, "tags": [1, 2]}));
+    let keys: Vec<_> = flat.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, vec!["id", "src.original", "src.tiny", "tags"]);
+}
+
+#[test]
+fn csv_quotes_and_unions_columns() {
+    let data = json!([
+        {"id": 1, "alt": "a, \"quoted\" cat"},
+        {"id": 2, "photographer": "B"}
+    ]);
+    let csv = render_delimited(&data, ',');
+    let lines: Vec<_> = csv.lines().collect();
+    // object keys are sorted, new columns are appended as first seen
+    assert_eq!(lines[0], "alt,id,photographer");
+    assert_eq!(lines[1], "\"a, \"\"quoted\"\" cat\",1,");
+    assert_eq!(lines[2], ",2,B");
+}
+
+// The columns `--output csv` prints: canonical records by default, the
+// flattened API fields under --fields.
+#[test]
+fn csv_output_columns() {
+    use clap::Parser;
+    use pexels::cli::{projector_from_cli, Cli};
+    let photo = json!({
+        "id": 1, "width": 10, "height": 20, "url": "https://www.pexels.com/photo/1/",
+        "alt": "cat", "photographer": "A", "photographer_url": "pu", "photographer_id": 7,
+        "src": {"original": "o", "tiny": "t"}
+    });
+    let defaults = vec!["id".to_string(), "alt".to_string()];
+    let csv = |args: &[&str]| {
+        let mut argv = vec!["pexels", "photos", "curated", "--output", "csv"];
+        argv.extend(args);
+        let p = projector_from_cli(&Cli::try_parse_from(argv).unwrap(), &defaults);
+        render_delimited(&json!([p.apply(&photo)]), ',')
+    };
+    assert_eq!(
+        csv(&[]),
+        "id,kind,title,creator.name,creator.url,creator.id,width,height,duration,urls.page,urls.file,urls.thumb\n\
+         1,photo,cat,A,pu,7,10,20,,https://www.pexels.com/photo/1/,o,t\n"
+    );
+    assert_eq!(
+        csv(&["--fields", "id,photographer,src.original"]),
+        "id,photographer,src.original\n1,A,o\n"
+    );
+}
+
+#[test]
+fn markdown_and_envelope_formats() {
+    let env = json!({"data": [{"id": 1, "alt": "x|y"}], "meta": {"next_page": 2}});
+    let md = render_markdown(&env["data"]);
+    assert!(md.starts_with("| alt | id |\n| --- | --- |\n"));
+    assert!(md.contains("x\\|y"));
+    let js = render_export(ExportFormat::Json, &env).unwrap();
+    let back: serde_json::Value = serde_json::from_str(&js).unwrap();
+    assert_eq!(back["meta"]["next_page"], 2);
+}
+
+#[test]
+fn parquet_spec_parses_in_every_build() {
+    let (fmt, _) = parse_export_spec("parquet=out/results.parquet").unwrap();
+    assert_eq!(fmt, ExportFormat::Parquet);
+    assert!(fmt.is_table() && !ExportFormat::Json.is_table());
+    let env = json!({"data": [{"id": 1}]});
+    let bytes = pexels::output::export_bytes(ExportFormat::Parquet, &env);
+    if cfg!(feature = "parquet") {
+        assert!(bytes.unwrap().starts_with(b"PAR1"));
+    } else {
+        assert!(bytes
+            .unwrap_err()
+            .to_string()
+            .contains("--features parquet"));
+    }
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_file_has_magic_footer_and_columns() {
+    let data = json!([
+        {"id": 1, "alt": "cat", "width": 4000, "liked": true, "src": {"original": "o1"}},
+        {"id": 2, "alt": null, "width": 2.5, "tags": ["a"]}
+    ]);
+    let bytes = pexels::parquet::render(&data).unwrap();
+    assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+    let n = bytes.len();
+    let footer = u32::from_le_bytes(bytes[n - 8..n - 4].try_into().unwrap()) as usize;
+    assert!(footer > 0 && footer + 12 <= n);
+    let meta = &bytes[n - 8 - footer..n - 8];
+    let has = |needle: &[u8]| meta.windows(needle.len()).any(|w| w == needle);
+    for name in ["alt", "id", "liked", "src.original", "width", "tags"] {
+        assert!(has(name.as_bytes()), "{}", name);
+    }
+    // Values of a string column are stored as length-prefixed bytes
+    assert!(bytes.windows(7).any(|w| w == b"\x03\0\0\0cat"));
+    // No rows: no row groups, still a valid file
+    let empty = pexels::parquet::render(&json!([])).unwrap();
+    assert!(empty.starts_with(b"PAR1") && empty.ends_with(b"PAR1"));
+}
+
+// The whole file for one optional INT64 column, spelled out from
+// parquet.thrift (Thrift compact protocol field headers are
+// `delta << 4 | type`), so a change in layout shows up here byte by byte.
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_layout_follows_the_spec() {
+    let bytes = pexels::parquet::render(&json!([{"id": 1}])).unwrap();
+    let mut want: Vec<u8> = b"PAR1".to_vec();
+    // PageHeader: type DATA_PAGE, uncompressed and compressed size 14,
+    // DataPageHeader { num_values 1, encoding PLAIN, def/rep levels RLE }
+    want.extend([0x15, 0x00, 0x15, 0x1c, 0x15, 0x1c, 0x2c]);
+    want.extend([0x15, 0x02, 0x15, 0x00, 0x15, 0x06, 0x15, 0x06, 0x00, 0x00]);
+    // Page: 2 bytes of RLE definition levels (one run of 1), then PLAIN 1i64
+    want.extend([2, 0, 0, 0, 0x02, 0x01]);
+    want.extend(1i64.to_le_bytes());
+    let footer = want.len();
+    // FileMetaData: version 1, schema [root "schema" with 1 child,
+    // OPTIONAL INT64 "id"], num_rows 1
+    want.extend([0x15, 0x02, 0x19, 0x2c]);
+    want.extend([0x48, 0x06]);
+    want.extend(b"schema");
+    want.extend([0x15, 0x02, 0x00]);
+    want.extend([0x15, 0x04, 0x25, 0x02, 0x18, 0x02, b'i', b'd', 0x00]);
+    want.extend([0x16, 0x02]);
+    // row_groups: [RowGroup { columns: [ColumnChunk { file_offset 4,
+    // ColumnMetaData { INT64, [PLAIN, RLE], ["id"], UNCOMPRESSED, 1 value,
+    // 31 bytes both sizes, data_page_offset 4 } }], total_byte_size 31,
+    // num_rows 1 }]
+    want.extend([0x19, 0x1c, 0x19, 0x1c, 0x26, 0x08, 0x1c]);
+    want.extend([
+        0x15, 0x04, 0x19, 0x25, 0x00, 0x06, 0x19, 0x18, 0x02, b'i', b'd',
+    ]);
+    want.extend([
+        0x15, 0x00, 0x16, 0x02, 0x16, 0x3e, 0x16, 0x3e, 0x26, 0x08, 0x00, 0x00,
+    ]);
+    want.extend([0x16, 0x3e, 0x16, 0x02, 0x00]);
+    // created_by, end of FileMetaData
+    let created_by = format!("pexels-cli version {}", env!("CARGO_PKG_VERSION"));
+    want.extend([0x28, created_by.len() as u8]);
+    want.extend(created_by.as_bytes());
+    want.push(0x00);
+    let len = (want.len() - footer) as u32;
+    want.extend(len.to_le_bytes());
+    want.extend(b"PAR1");
+    assert_eq!(bytes, want);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_columns_read_back() {
+    let data = json!([
+        {"id": 1, "sha256": "aa"},
+        {"id": 2},
+        {"id": 3, "sha256": "cc"}
+    ]);
+    let bytes = pexels::parquet::render(&data).unwrap();
+    let read = |name| pexels::parquet::read_strings(&bytes, name).unwrap();
+    assert_eq!(read("id").unwrap(), vec!["1", "2", "3"]);
+    assert_eq!(read("sha256").unwrap(), vec!["aa", "cc"]);
+    assert!(read("alt").is_none());
+    assert!(pexels::parquet::read_strings(b"PAR1", "id").is_err());
+
+    // A literal followed by an overlapping back-reference
+    let block = [12, 0x0c, b'a', b'b', b'c', b'd', 0x11, 4];
+    assert_eq!(pexels::parquet::snappy(&block).unwrap(), b"abcdabcdabcd");
+    assert!(pexels::parquet::snappy(&[4, 0x01, 9]).is_err());
+}
@@ -0,0 +1,49 @@
+use pexels::expr::{eval, parse, parse_map};
+use pexels::proj::Projector;
+use serde_json::json;
+
+#[test]
+fn arithmetic_paths_and_functions() {
+    let item = json!({
+        "id": 7,
+        "width": 6000,
+        "height": 4000,
+        "photographer": "Ana",
+        "video_files": [{"width": 1920}],
+    });
+    let e = |s: &str| eval(&parse(s).unwrap(), &item);
+    assert_eq!(e("width*height/1e6"), json!(24));
+    assert_eq!(e("round(width / height, 2)"), json!(1.5));
+    assert_eq!(e("-(width - height) % 7"), json!(-5));
+    assert_eq!(e("video_files.0.width"), json!(1920));
+    assert_eq!(e("lower(photographer) + '-' + id"), json!("ana-7"));
+    assert_eq!(e("max(width, height, 9000)"), json!(9000));
+    assert_eq!(e("len(video_files)"), json!(1));
+}
+
+#[test]
+fn missing_values_and_bad_input() {
+    let item = json!({"width": 10, "alt": null});
+    assert_eq!(
+        eval(&parse("width * duration").unwrap(), &item),
+        json!(null)
+    );
+    assert_eq!(eval(&parse("upper(alt)").unwrap(), &item), json!(null));
+    assert_eq!(eval(&parse("width / 0").unwrap(), &item), json!(null));
+    assert!(parse("width *").is_err());
+    assert!(parse("(width").is_err());
+    assert!(parse("sqrt(width)")
+        .unwrap_err()
+        .contains("unknown function"));
+    assert!(parse_map("no equals sign").is_err());
+    assert!(parse_map("bad name = 1").is_err());
+}
+
+#[test]
+fn projector_appends_computed_fields() {
+    let m = parse_map("megapixels = width*height/1e6").unwrap();
+    assert_eq!(m.name, "megapixels");
+    let p = Projector::new(vec!["id".into()]).maps(vec![m]);
+    let out = p.apply(&json!({"id": 1, "width": 3000, "height": 2000}));
+    assert_eq!(out, json!({"id": 1, "megapixels": 6}));
+}
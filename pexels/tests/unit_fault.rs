@@ -0,0 +1,33 @@
+#![cfg(feature = "fault-injection")]
+
+use clap::Parser;
+use pexels::fault::{at, parse, Fault, FaultKind};
+
+#[test]
+fn fault_specs_parse_and_schedule() {
+    assert_eq!(
+        parse("429@2"),
+        Ok(Fault {
+            kind: FaultKind::Status(429),
+            attempt: 2
+        })
+    );
+    assert_eq!(parse("Timeout@4").unwrap().kind, FaultKind::Timeout);
+    assert_eq!(parse("connect@1").unwrap().to_string(), "connect@1");
+    assert!(parse("429").is_err());
+    assert!(parse("429@0").is_err());
+    assert!(parse("200@1").is_err());
+    assert!(parse("flaky@1").is_err());
+
+    let cli = pexels::cli::Cli::try_parse_from([
+        "pexels",
+        "--inject-fault",
+        "429@2,timeout@4",
+        "util",
+        "ping",
+    ])
+    .unwrap();
+    assert_eq!(at(&cli.inject_fault, 2), Some(FaultKind::Status(429)));
+    assert_eq!(at(&cli.inject_fault, 4), Some(FaultKind::Timeout));
+    assert_eq!(at(&cli.inject_fault, 3), None);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "ffi")]
+
+use pexels::ffi::*;
+use std::ffi::{CStr, CString};
+
+fn take_json(p: *mut std::ffi::c_char) -> serde_json::Value {
+    assert!(!p.is_null());
+    let s = unsafe { CStr::from_ptr(p) }.to_str().unwrap().to_string();
+    unsafe { pexels_string_free(p) };
+    serde_json::from_str(&s).unwrap()
+}
+
+#[test]
+fn ffi_client_requires_token() {
+    let c = unsafe { pexels_client_new(std::ptr::null(), std::ptr::null()) };
+    assert!(c.is_null());
+}
+
+#[test]
+fn ffi_null_client_returns_error_envelope() {
+    let q = CString::new("cats").unwrap();
+    let out = take_json(unsafe { pexels_search_photos(std::ptr::null(), q.as_ptr(), 1, 1) });
+    assert!(out.get("error").is_some());
+    assert!(out.get("data").is_none());
+}
+
+#[test]
+fn ffi_search_transport_error_is_json() {
+    let token = CString::new("t").unwrap();
+    let host = CString::new("http://127.0.0.1:9").unwrap();
+    let c = unsafe { pexels_client_new(token.as_ptr(), host.as_ptr()) };
+    assert!(!c.is_null());
+    let q = CString::new("cats").unwrap();
+    let out = take_json(unsafe { pexels_search_photos(c, q.as_ptr(), 0, 0) });
+    assert!(out.get("error").is_some());
+    unsafe { pexels_client_free(c) };
+}
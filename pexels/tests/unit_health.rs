@@ -0,0 +1,61 @@
+use pexels::health::{
+    due, exit_code, http_status, key, record, NotFound, TokenRejected, CHECK_EVERY_SECS,
+};
+use pexels::state::State;
+
+#[test]
+fn token_check_is_due_daily_and_after_failures() {
+    let dir = std::env::temp_dir().join(format!("pexels-health-{}", std::process::id()));
+    let mut st = State::load_from(&dir.join("state.db")).unwrap();
+    let now = 1_700_000_000;
+    assert!(due(&st, "tok", now));
+    record(&mut st, "tok", true, now);
+    assert!(!due(&st, "tok", now + 60));
+    assert!(due(&st, "tok", now + CHECK_EVERY_SECS));
+    assert!(due(&st, "other", now + 60));
+    record(&mut st, "tok", false, now);
+    assert!(due(&st, "tok", now + 60));
+    assert!(!key("tok").contains("tok:tok"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn rejected_token_is_structured_and_exits_3() {
+    let err = anyhow::Error::new(TokenRejected {
+        request_id: Some("abc".into()),
+    });
+    let v = pexels::output::error_value(&err);
+    assert_eq!(v["code"], 401);
+    assert_eq!(v["request_id"], "abc");
+    assert!(v["error"].as_str().unwrap().contains("pexels auth login"));
+    assert_eq!(exit_code(&err), 3);
+    assert_eq!(exit_code(&err.context("search")), 3);
+    assert_eq!(exit_code(&anyhow::anyhow!("boom")), 1);
+}
+
+#[test]
+fn fingerprint_is_stable_and_hides_the_token() {
+    let fp = pexels::health::fingerprint("secret-token-123");
+    assert_eq!(fp, pexels::health::fingerprint("secret-token-123"));
+    assert_ne!(fp, pexels::health::fingerprint("secret-token-124"));
+    assert!(fp.starts_with("sha256:"));
+    assert_eq!(fp.len(), "sha256:".len() + 16);
+    assert!(!fp.contains("secret"));
+}
+
+#[test]
+fn missing_media_exits_4_and_statuses_are_read_back() {
+    let err = anyhow::Error::new(NotFound {
+        kind: "photo",
+        id: "42".into(),
+    });
+    assert_eq!(exit_code(&err), 4);
+    assert!(err.to_string().contains("photo not found"));
+    let api = anyhow::anyhow!("code: 404\nreason: Not Found\n");
+    assert_eq!(http_status(&api), Some(404));
+    assert_eq!(
+        http_status(&anyhow::Error::new(TokenRejected { request_id: None })),
+        Some(401)
+    );
+    assert_eq!(http_status(&anyhow::anyhow!("dns error")), None);
+}
@@ -0,0 +1,34 @@
+use pexels::human::{format_bytes, format_count, format_duration, humanize, separators};
+use serde_json::json;
+
+#[test]
+fn formats_sizes_durations_and_counts() {
+    let en = separators(None);
+    assert_eq!(format_bytes(512, en), "512 B");
+    assert_eq!(format_bytes(12_400_000, en), "12.4 MB");
+    assert_eq!(format_duration(83), "1:23");
+    assert_eq!(format_duration(3723), "1:02:03");
+    assert_eq!(format_count(1_234_567, en), "1,234,567");
+}
+
+#[test]
+fn separators_follow_locale() {
+    let de = separators(Some("de-DE"));
+    assert_eq!(format_count(8000, de), "8.000");
+    assert_eq!(format_bytes(12_400_000, de), "12,4 MB");
+    assert_eq!(format_count(8000, separators(Some("fr_FR"))), "8\u{a0}000");
+}
+
+#[test]
+fn humanize_rewrites_known_fields_only() {
+    let v = json!({
+        "data": [{"id": 12345, "duration": 75, "width": 1920}],
+        "meta": {"total_results": 8000, "est_total_bytes": 2_500_000}
+    });
+    let h = humanize(&v, separators(Some("en-US")));
+    assert_eq!(h["data"][0]["id"], 12345);
+    assert_eq!(h["data"][0]["width"], 1920);
+    assert_eq!(h["data"][0]["duration"], "1:15");
+    assert_eq!(h["meta"]["total_results"], "8,000");
+    assert_eq!(h["meta"]["est_total_bytes"], "2.5 MB");
+}
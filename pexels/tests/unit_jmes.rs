@@ -0,0 +1,32 @@
+use pexels::jmes::{compile, search, search_items};
+use pexels::output::error_value;
+use serde_json::json;
+
+#[test]
+fn searches_envelopes_and_items() {
+    let out = json!({
+        "data": [
+            {"id": 1, "width": 6000, "src": {"original": "a"}},
+            {"id": 2, "width": 800, "src": {"original": "b"}},
+        ],
+        "meta": {"total_results": 2},
+    });
+    let e = compile("data[?width > `1000`].src.original").unwrap();
+    assert_eq!(search(&e, &out).unwrap(), json!(["a"]));
+    let e = compile("meta.total_results").unwrap();
+    assert_eq!(search(&e, &out).unwrap(), json!(2));
+
+    let items = out["data"].as_array().unwrap();
+    let e = compile("width > `1000` && {id: id} || null").unwrap();
+    assert_eq!(search_items(&e, items).unwrap(), vec![json!({"id": 1})]);
+}
+
+#[test]
+fn parse_errors_report_position() {
+    let err = compile("data[?width >").unwrap_err();
+    let v = error_value(&err);
+    assert_eq!(v["error"], "invalid --jmes expression");
+    assert_eq!(v["expression"], "data[?width >");
+    assert_eq!(v["column"], 14);
+    assert!(v["at"].as_str().unwrap().ends_with("             ^"));
+}
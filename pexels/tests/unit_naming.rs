@@ -0,0 +1,80 @@
+use pexels::naming::{
+    check_template, ext_from_content_type, ext_from_url, is_reserved_name, join_rendered,
+    render_template, sanitize_component, slug,
+};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn sanitize_reserved_chars_and_trailing_dots() {
+    assert_eq!(sanitize_component("a:b?c*d.jpg"), "a_b_c_d.jpg");
+    assert_eq!(sanitize_component("photo<1>|\"x\""), "photo_1___x_");
+    assert_eq!(sanitize_component("name. . "), "name");
+    assert_eq!(sanitize_component("..."), "_");
+    assert_eq!(sanitize_component("tab\there"), "tab_here");
+}
+
+#[test]
+fn sanitize_device_names() {
+    assert!(is_reserved_name("CON"));
+    assert!(is_reserved_name("nul.txt"));
+    assert!(is_reserved_name("Com1.jpg"));
+    assert!(!is_reserved_name("console.jpg"));
+    assert_eq!(sanitize_component("aux.jpg"), "_aux.jpg");
+}
+
+#[test]
+fn sanitize_truncates_long_names_keeping_extension() {
+    let long = format!("{}.jpeg", "é".repeat(200));
+    let out = sanitize_component(&long);
+    assert!(out.len() <= 255);
+    assert!(out.ends_with(".jpeg"));
+}
+
+#[test]
+fn join_rendered_normalizes_separators() {
+    let base = Path::new("out");
+    let p = join_rendered(base, r"Jane: Doe\2024/../a?.jpg");
+    let expected: PathBuf = ["out", "Jane_ Doe", "2024", "a_.jpg"].iter().collect();
+    assert_eq!(p, expected);
+    assert_eq!(slug("Golden Hour, Beach!"), "golden-hour-beach");
+}
+
+#[test]
+fn name_templates_render_from_media_json() {
+    assert!(check_template("{id}_{photographer_slug}.{ext}").is_ok());
+    assert!(check_template("{id}_{nope}.{ext}").is_err());
+    assert!(check_template("{id").is_err());
+    let photo = serde_json::json!({
+        "id": 42, "width": 4000, "height": 3000, "alt": "Red Fox, in snow",
+        "photographer": "Ana María", "photographer_id": 7,
+        "src": {"original": "https://images.example/photos/42/x.jpeg"}
+    });
+    assert_eq!(
+        render_template(
+            "{photographer_slug}/{id}_{width}x{height}_{alt_slug}.{ext}",
+            &photo,
+            "jpeg"
+        ),
+        "ana-mar-a/42_4000x3000_red-fox-in-snow.jpeg"
+    );
+    let video = serde_json::json!({"id": 9, "duration": 5, "user": {"name": "Bo"}});
+    assert_eq!(
+        render_template("{kind}-{id}-{photographer}.{ext}", &video, "mp4"),
+        "video-9-Bo.mp4"
+    );
+}
+
+#[test]
+fn extensions_from_url_or_content_type() {
+    assert_eq!(
+        ext_from_url("https://x.example/a/b.JPEG?w=10").as_deref(),
+        Some("jpeg")
+    );
+    assert_eq!(ext_from_url("https://x.example/video-files/123/"), None);
+    assert_eq!(
+        ext_from_content_type("image/png; charset=binary"),
+        Some("png")
+    );
+    assert_eq!(ext_from_content_type("video/mp4"), Some("mp4"));
+    assert_eq!(ext_from_content_type("text/html"), None);
+}
@@ -0,0 +1,81 @@
+use pexels::normalize::{identity, kind_of, normalize, Kind};
+use serde_json::json;
+
+#[test]
+fn normalize_photo() {
+    let p = json!({
+        "id": 1, "width": 10, "height": 20, "url": "page", "alt": "cat",
+        "photographer": "A", "photographer_url": "pu", "photographer_id": 7,
+        "src": {"original": "o", "tiny": "t"}
+    });
+    assert_eq!(kind_of(&p), Kind::Photo);
+    let n = normalize(&p);
+    assert_eq!(n["kind"], "photo");
+    assert_eq!(n["title"], "cat");
+    assert_eq!(n["creator"]["name"], "A");
+    assert_eq!(n["urls"]["file"], "o");
+    assert_eq!(n["urls"]["thumb"], "t");
+    assert!(n["duration"].is_null());
+}
+
+#[test]
+fn normalize_video_picks_widest_file() {
+    let v = json!({
+        "id": 2, "width": 1920, "height": 1080, "duration": 12, "url": "page", "image": "img",
+        "user": {"name": "B", "url": "bu", "id": 9},
+        "video_files": [{"width": 640, "link": "sd"}, {"width": 1920, "link": "hd"}]
+    });
+    let n = normalize(&v);
+    assert_eq!(n["kind"], "video");
+    assert_eq!(n["creator"]["name"], "B");
+    assert_eq!(n["duration"], 12);
+    assert_eq!(n["urls"]["file"], "hd");
+    assert_eq!(n["urls"]["thumb"], "img");
+}
+
+#[test]
+fn normalize_collection_media_and_identity() {
+    let media = json!({"type": "Video", "id": 3, "video_files": []});
+    assert_eq!(kind_of(&media), Kind::Video);
+    let coll = json!({"id": "abc", "title": "T", "media_count": 4});
+    assert_eq!(normalize(&coll)["title"], "T");
+    assert_eq!(identity(&coll).unwrap(), "collection:abc");
+    assert_eq!(identity(&json!({"id": 5})).unwrap(), "photo:5");
+}
+
+#[test]
+fn table_output_uses_canonical_records() {
+    use clap::Parser;
+    use pexels::cli::{projector_from_cli, Cli};
+    let photo = json!({"id": 1, "alt": "cat", "photographer": "A", "src": {"original": "o"}});
+    let video = json!({"id": 2, "duration": 5, "user": {"name": "B"}, "video_files": []});
+    let coll = json!({"id": "c", "title": "T", "media_count": 1});
+    let defaults = vec!["id".to_string(), "alt".to_string()];
+    let rows = |args: &[&str]| {
+        let cli = Cli::try_parse_from(args).unwrap();
+        let p = projector_from_cli(&cli, &defaults);
+        let items = json!([p.apply(&photo), p.apply(&video), p.apply(&coll)]);
+        pexels::output::to_table(&items)
+    };
+
+    // Every kind fills the same columns
+    let (headers, cells) = rows(&["pexels", "photos", "curated", "--output", "csv"]);
+    assert_eq!(headers, pexels::normalize::COLUMNS);
+    assert_eq!(cells[0][..4], ["1", "photo", "cat", "A"]);
+    assert_eq!(cells[1][..4], ["2", "video", "", "B"]);
+    assert_eq!(cells[2][..4], ["c", "collection", "T", ""]);
+
+    // --fields picks from the API shape; YAML keeps the resource defaults
+    let (headers, _) = rows(&[
+        "pexels",
+        "photos",
+        "curated",
+        "--output",
+        "csv",
+        "--fields",
+        "id,src.original",
+    ]);
+    assert_eq!(headers, ["id", "src.original"]);
+    let (headers, _) = rows(&["pexels", "photos", "curated"]);
+    assert_eq!(headers, ["alt", "id"]);
+}
@@ -0,0 +1,114 @@
+use pexels::optimize::{optimize, output_path, tally, with_xmp, Attribution, Settings, WebFormat};
+use std::path::Path;
+
+fn sample(dir: &Path) -> std::path::PathBuf {
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join("photo-1.png");
+    let img = image::RgbImage::from_fn(32, 24, |x, y| {
+        image::Rgb([(x * 8) as u8, (y * 10) as u8, 128])
+    });
+    img.save(&path).unwrap();
+    path
+}
+
+fn credit() -> Attribution {
+    Attribution::from_media(&serde_json::json!({
+        "photographer": "Ana <Lima> & Co",
+        "photographer_url": "https://www.pexels.com/@ana",
+        "url": "https://www.pexels.com/photo/1/",
+    }))
+}
+
+#[test]
+fn output_path_swaps_the_extension() {
+    assert_eq!(
+        output_path(Path::new("d/photo-1.jpeg"), WebFormat::Webp),
+        Path::new("d/photo-1.webp")
+    );
+    assert_eq!(
+        output_path(Path::new("d/photo-1.webp"), WebFormat::Webp),
+        Path::new("d/photo-1-web.webp")
+    );
+}
+
+#[test]
+fn xmp_carries_escaped_attribution() {
+    let xmp = credit().xmp();
+    assert!(xmp.contains("<rdf:li>Ana &lt;Lima&gt; &amp; Co</rdf:li>"));
+    assert!(xmp.contains("Photo by Ana &lt;Lima&gt; &amp; Co on Pexels"));
+    assert!(xmp.contains("<dc:source>https://www.pexels.com/photo/1/</dc:source>"));
+    assert!(xmp.contains("https://www.pexels.com/license/"));
+    assert!(xmp.ends_with("<?xpacket end=\"r\"?>"));
+}
+
+#[test]
+fn webp_copy_embeds_xmp_and_decodes() {
+    let dir = std::env::temp_dir().join(format!("pexels-optimize-{}", std::process::id()));
+    let src = sample(&dir);
+    let settings = Settings {
+        format: WebFormat::Webp,
+        quality: 75,
+    };
+    let report = optimize(&src, settings, &credit()).unwrap();
+    assert_eq!(report["format"], "webp");
+    assert_eq!(report["xmp"], "embedded");
+    let out = dir.join("photo-1.webp");
+    assert_eq!(report["path"], out.display().to_string());
+    let bytes = std::fs::read(&out).unwrap();
+    assert_eq!(report["bytes"], bytes.len());
+    assert_eq!(&bytes[12..16], b"VP8X");
+    // XMP flag set, canvas 32x24
+    assert_eq!(bytes[20] & 0x04, 0x04);
+    assert_eq!(&bytes[24..27], &[31, 0, 0]);
+    assert_eq!(&bytes[27..30], &[23, 0, 0]);
+    let riff = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    assert_eq!(riff + 8, bytes.len());
+    assert!(String::from_utf8_lossy(&bytes).contains("Photo by Ana"));
+    let img = image::open(&out).unwrap();
+    assert_eq!((img.width(), img.height()), (32, 24));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn with_xmp_rejects_non_webp() {
+    assert!(with_xmp(b"not a webp file at all", b"<x/>").is_err());
+}
+
+#[test]
+fn tally_sums_savings() {
+    let items = vec![
+        serde_json::json!({"web": {"original_bytes": 1000, "bytes": 400}}),
+        serde_json::json!({"web": {"original_bytes": 1000, "bytes": 350}}),
+        serde_json::json!({"web": {"error": "web optimization failed"}}),
+        serde_json::json!({"error": "download failed"}),
+    ];
+    assert_eq!(
+        tally(&items),
+        serde_json::json!({
+            "optimized": 2,
+            "failed": 1,
+            "original_bytes": 2000,
+            "bytes": 750,
+            "saved_bytes": 1250,
+            "saved_pct": 62.5,
+        })
+    );
+}
+
+#[test]
+fn avif_copy_gets_an_xmp_sidecar() {
+    let dir = std::env::temp_dir().join(format!("pexels-optimize-avif-{}", std::process::id()));
+    let src = sample(&dir);
+    let settings = Settings {
+        format: WebFormat::Avif,
+        quality: 60,
+    };
+    let report = optimize(&src, settings, &credit()).unwrap();
+    assert_eq!(report["format"], "avif");
+    let sidecar = dir.join("photo-1.xmp");
+    assert_eq!(report["xmp"], sidecar.display().to_string());
+    assert!(std::fs::read_to_string(&sidecar).unwrap().contains("Ana"));
+    let bytes = std::fs::read(dir.join("photo-1.avif")).unwrap();
+    assert_eq!(&bytes[4..8], b"ftyp");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -1,5 +1,5 @@
 use pexels::cli::shape_output;
-use pexels::output::{parse_page_number, wrap_ok};
+use pexels::output::{default_format, parse_page_number, wrap_ok, write_ndjson, OutputFormat};
 use pexels::proj::project_item_with_fallback;
 use serde_json::json;
 
@@ -49,3 +49,148 @@ fn test_single_resource_projection_fallback() {
     // ensure we didn't end up with {}
     assert!(!projected.as_object().unwrap().is_empty());
 }
+
+#[test]
+fn test_collapse_depth_and_items() {
+    use pexels::output::{collapse, collapse_data, Collapse};
+    let video = json!({
+        "id": 1,
+        "user": {"name": "A", "links": {"x": 1}},
+        "video_files": [{"link": "a"}, {"link": "b"}, {"link": "c"}, {"link": "d"}]
+    });
+    let opts = Collapse {
+        max_depth: Some(1),
+        max_items: None,
+    };
+    let out = collapse(&video, &opts);
+    assert_eq!(out["id"], 1);
+    assert_eq!(out["user"]["name"], "A");
+    assert_eq!(out["user"]["links"], "{…} (1 keys)");
+    assert_eq!(out["video_files"][0], "{…} (1 keys)");
+
+    let opts = Collapse {
+        max_depth: None,
+        max_items: Some(2),
+    };
+    let out = collapse_data(&json!([video]), &opts);
+    let files = out[0]["video_files"].as_array().unwrap();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[2], "… (2 more)");
+}
+
+#[test]
+fn test_enforce_size_abort_and_truncate() {
+    use pexels::output::enforce_size;
+    let items: Vec<_> = (0..50)
+        .map(|i| json!({"id": i, "alt": "x".repeat(100)}))
+        .collect();
+    let mut out = wrap_ok(&json!(items), Some(json!({"next_page": null})));
+    let full = serde_json::to_vec(&out).unwrap().len() as u64;
+    assert!(enforce_size(&mut out.clone(), full, false).is_ok());
+    let err = enforce_size(&mut out.clone(), 1000, false).unwrap_err();
+    assert!(err.to_string().contains("response too large"));
+
+    enforce_size(&mut out, 1000, true).unwrap();
+    assert!(serde_json::to_vec(&out).unwrap().len() <= 1000);
+    let kept = out["data"].as_array().unwrap().len();
+    assert!(kept > 0 && kept < 50);
+    assert_eq!(out["meta"]["truncated"]["dropped"], 50 - kept);
+}
+
+#[test]
+fn test_annotate_positions_ranks_across_pages() {
+    use pexels::output::annotate_positions;
+    let mut page = json!({
+        "page": 3,
+        "per_page": 2,
+        "photos": [{"id": 5}, {"id": 6}]
+    });
+    annotate_positions(&mut page);
+    assert_eq!(page["photos"][0]["_position"]["rank"], 5);
+    assert_eq!(page["photos"][1]["_position"]["page"], 3);
+    assert_eq!(page["photos"][1]["_position"]["index"], 1);
+    // already-stamped items keep their original position
+    page["page"] = json!(1);
+    annotate_positions(&mut page);
+    assert_eq!(page["photos"][1]["_position"]["rank"], 6);
+}
+
+#[test]
+fn test_sample_indices_spread() {
+    use pexels::cli::sample_indices;
+    assert_eq!(sample_indices(10, 5), vec![0, 2, 4, 6, 8]);
+    assert_eq!(sample_indices(3, 5), vec![0, 1, 2]);
+    assert!(sample_indices(0, 5).is_empty());
+}
+
+#[test]
+fn select_emit_blocks() {
+    use pexels::output::{select_emit, Emit};
+    let out = json!({"data": [{"id": 1}], "meta": {"total_results": 9}});
+    assert_eq!(select_emit(out.clone(), Emit::Both), out);
+    assert_eq!(select_emit(out.clone(), Emit::Meta)["total_results"], 9);
+    assert_eq!(select_emit(out, Emit::Data)[0]["id"], 1);
+    let single = json!({"data": {"id": 2}});
+    assert_eq!(select_emit(single, Emit::Meta), json!({}));
+}
+
+#[test]
+fn continuation_meta_and_page_args() {
+    let input = json!({"photos": [{"id": 1}], "continue": {"page": 3, "skip": 0}});
+    let (_data, meta) = shape_output(&input);
+    assert_eq!(meta["continue"]["page"], 3);
+    assert_eq!(meta["continue"]["args"], "--page 3");
+    let argv: Vec<String> = [
+        "photos", "search", "-q", "cats", "--page", "2", "--all", "--page=4",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    assert_eq!(
+        pexels::cli::strip_page_args(&argv),
+        vec!["photos", "search", "-q", "cats", "--all"]
+    );
+}
+
+#[test]
+fn ndjson_emits_one_item_per_line_without_envelope() {
+    let mut buf = vec![];
+    let list = wrap_ok(
+        &json!([{"id": 1}, {"id": 2}]),
+        Some(json!({"total_results": 2})),
+    );
+    write_ndjson(&mut buf, &list).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "{\"id\":1}\n{\"id\":2}\n");
+
+    let mut buf = vec![];
+    write_ndjson(
+        &mut buf,
+        &wrap_ok(&json!({"id": 3, "src": {"tiny": "t"}}), None),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "{\"id\":3,\"src\":{\"tiny\":\"t\"}}\n"
+    );
+}
+
+#[test]
+fn default_format_follows_terminal_unless_pinned() {
+    assert_eq!(
+        default_format(None, true, true),
+        (OutputFormat::Yaml, false)
+    );
+    assert_eq!(
+        default_format(None, false, false),
+        (OutputFormat::Json, true)
+    );
+    assert_eq!(
+        default_format(None, false, true),
+        (OutputFormat::Ndjson, true)
+    );
+    let pinned = OutputFormat::Yaml;
+    assert_eq!(
+        default_format(Some(&pinned), false, true),
+        (OutputFormat::Yaml, false)
+    );
+}
@@ -0,0 +1,229 @@
+// Own test binary: points the result cache at a temp dir via XDG_CACHE_HOME
+// and pages through it with --offline.
+use clap::Parser;
+use futures::StreamExt;
+use pexels::api::{Listing, PexelsClient};
+use pexels::cache::{default_dir, logical_key, Cache};
+use pexels::cli::Cli;
+use pexels::config::Config;
+use serde_json::json;
+use std::time::Duration;
+
+const PER_PAGE: u64 = 2;
+const TOTAL: u64 = 7;
+
+// Curated pages 1-4 of 7 photos, two per page, stored the way --offline
+// looks them up.
+fn seed() {
+    let cache = Cache::new(default_dir(), Duration::from_secs(60));
+    let url = reqwest::Url::parse("https://api.pexels.com/v1/curated").unwrap();
+    for page in 1..=TOTAL.div_ceil(PER_PAGE) {
+        let ids: Vec<u64> = (1..=TOTAL)
+            .filter(|id| (id - 1) / PER_PAGE + 1 == page)
+            .collect();
+        let next = (page * PER_PAGE < TOTAL).then(|| {
+            format!(
+                "https://api.pexels.com/v1/curated?page={}&per_page={}",
+                page + 1,
+                PER_PAGE
+            )
+        });
+        let body = json!({
+            "page": page,
+            "per_page": PER_PAGE,
+            "total_results": TOTAL,
+            "next_page": next,
+            "photos": ids.iter().map(|id| json!({"id": id})).collect::<Vec<_>>(),
+        });
+        let qp = vec![
+            ("page".to_string(), page.to_string()),
+            ("per_page".to_string(), PER_PAGE.to_string()),
+        ];
+        let key = logical_key(&url, &qp, None, Some("t"));
+        cache.put(&key, &body).unwrap();
+    }
+}
+
+// Search pages for "dups" where each page repeats the last photo of the one
+// before: [1, 2], [2, 3], [3, 4].
+fn seed_repeats() {
+    let cache = Cache::new(default_dir(), Duration::from_secs(60));
+    let url = reqwest::Url::parse("https://api.pexels.com/v1/search").unwrap();
+    for page in 1..=3u64 {
+        let next = (page < 3).then(|| {
+            format!(
+                "https://api.pexels.com/v1/search?page={}&per_page=2&query=dups",
+                page + 1
+            )
+        });
+        let body = json!({
+            "page": page,
+            "per_page": 2,
+            "total_results": 6,
+            "next_page": next,
+            "photos": [{"id": page}, {"id": page + 1}],
+        });
+        let qp = vec![
+            ("page".to_string(), page.to_string()),
+            ("per_page".to_string(), "2".to_string()),
+            ("query".to_string(), "dups".to_string()),
+        ];
+        cache
+            .put(&logical_key(&url, &qp, None, Some("t")), &body)
+            .unwrap();
+    }
+}
+
+fn parse(args: &[&str]) -> Cli {
+    let mut argv = vec![
+        "pexels",
+        "photos",
+        "curated",
+        "--per-page",
+        "2",
+        "--offline",
+    ];
+    argv.extend(args);
+    Cli::try_parse_from(argv).unwrap()
+}
+
+fn client() -> PexelsClient {
+    PexelsClient::new(Config {
+        token: Some("t".into()),
+        offline: true,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+async fn curated(args: &[&str]) -> serde_json::Value {
+    client().photos_curated(&parse(args)).await.unwrap()
+}
+
+// The walk as streamed: one chunk per page.
+async fn chunks(args: &[&str]) -> Vec<serde_json::Value> {
+    let (client, cli) = (client(), parse(args));
+    let pages = client.list_pages(Listing::PhotosCurated, &cli).unwrap();
+    pages
+        .map(|c| serde_json::Value::Object(c.unwrap()))
+        .collect()
+        .await
+}
+
+fn ids(v: &serde_json::Value) -> Vec<u64> {
+    v["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn concurrent_pages_merge_in_order_within_limits() {
+    let dir = std::env::temp_dir().join(format!("pexels-page-concurrency-{}", std::process::id()));
+    std::env::set_var("XDG_CACHE_HOME", &dir);
+    seed();
+
+    let sequential = curated(&["--all"]).await;
+    let concurrent = curated(&["--all", "--page-concurrency", "3"]).await;
+    assert_eq!(ids(&sequential), (1..=7).collect::<Vec<_>>());
+    assert_eq!(ids(&concurrent), ids(&sequential));
+    assert!(concurrent.get("continue").is_none());
+
+    // --limit stops mid-page with a resume point
+    let limited = curated(&["--limit", "5", "--page-concurrency", "4"]).await;
+    assert_eq!(ids(&limited), vec![1, 2, 3, 4, 5]);
+    assert_eq!(limited["continue"], json!({"page": 3, "skip": 1}));
+
+    // --max-pages stops after whole pages
+    let capped = curated(&["--max-pages", "2", "--page-concurrency", "4"]).await;
+    assert_eq!(ids(&capped), vec![1, 2, 3, 4]);
+    assert_eq!(capped["continue"], json!({"page": 3, "skip": 0}));
+
+    // --soft-deadline already passed after the first page: partial result
+    for concurrency in ["1", "3"] {
+        let args = [
+            "--all",
+            "--soft-deadline",
+            "0s",
+            "--page-concurrency",
+            concurrency,
+        ];
+        let partial = curated(&args).await;
+        assert_eq!(ids(&partial), vec![1, 2]);
+        assert_eq!(partial["partial"], true);
+        assert_eq!(partial["continue"], json!({"page": 2, "skip": 0}));
+        let (_, meta) = pexels::cli::shape_output(&partial);
+        assert_eq!(meta["partial"], true);
+    }
+    let relaxed = curated(&["--all", "--soft-deadline", "1h"]).await;
+    assert_eq!(ids(&relaxed).len(), 7);
+    assert!(relaxed.get("partial").is_none());
+
+    // Streamed walks hand over each page's items as they arrive
+    for concurrency in ["1", "3"] {
+        let streamed = chunks(&["--limit", "5", "--page-concurrency", concurrency]).await;
+        let per_page: Vec<Vec<u64>> = streamed.iter().map(ids).collect();
+        assert_eq!(per_page, vec![vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(streamed[0]["total_results"], TOTAL);
+        assert!(streamed[1].get("total_results").is_none());
+        assert_eq!(streamed[2]["continue"], json!({"page": 3, "skip": 1}));
+    }
+
+    // --resume picks up where the last run stopped, limits counted across runs
+    let state = dir.join("curated.resume");
+    let resume = state.to_str().unwrap();
+    let first = curated(&["--all", "--limit", "3", "--resume", resume]).await;
+    assert_eq!(ids(&first), vec![1, 2, 3]);
+    let cp = pexels::resume::load(&state).unwrap().unwrap();
+    assert_eq!((cp.page, cp.skip, cp.collected, cp.done), (2, 1, 3, false));
+    let second = curated(&["--all", "--limit", "5", "--resume", resume]).await;
+    assert_eq!(ids(&second), vec![4, 5]);
+    let rest = curated(&["--all", "--resume", resume, "--page-concurrency", "2"]).await;
+    assert_eq!(ids(&rest), vec![6, 7]);
+    assert!(pexels::resume::load(&state).unwrap().unwrap().done);
+    let again = client()
+        .photos_curated(&parse(&["--all", "--resume", resume]))
+        .await
+        .unwrap_err();
+    assert!(again.to_string().contains("walk already finished"));
+
+    // Photos repeated on a later page are dropped and counted
+    seed_repeats();
+    let search = |args: &'static [&'static str]| async move {
+        client()
+            .photos_search("dups", &[], &parse(args))
+            .await
+            .unwrap()
+    };
+    let deduped = search(&["--all"]).await;
+    assert_eq!(ids(&deduped), vec![1, 2, 3, 4]);
+    assert_eq!(deduped["duplicates_dropped"], 2);
+    let (_, meta) = pexels::cli::shape_output(&deduped);
+    assert_eq!(meta["duplicates_dropped"], 2);
+    let kept = search(&["--all", "--no-dedupe"]).await;
+    assert_eq!(ids(&kept), vec![1, 2, 2, 3, 3, 4]);
+    assert!(kept.get("duplicates_dropped").is_none());
+    // Repeats don't count against --limit
+    let limited = search(&["--limit", "3"]).await;
+    assert_eq!(ids(&limited), vec![1, 2, 3]);
+    assert_eq!(limited["continue"], json!({"page": 3, "skip": 0}));
+
+    // --count asks for a single one-item page, whatever --per-page says
+    let url = reqwest::Url::parse("https://api.pexels.com/v1/curated").unwrap();
+    let qp = vec![("per_page".to_string(), "1".to_string())];
+    Cache::new(default_dir(), Duration::from_secs(60))
+        .put(
+            &logical_key(&url, &qp, None, Some("t")),
+            &json!({"page": 1, "per_page": 1, "total_results": TOTAL, "photos": [{"id": 1}]}),
+        )
+        .unwrap();
+    let count = client()
+        .count(Listing::PhotosCurated, &parse(&["--count"]))
+        .await
+        .unwrap();
+    assert_eq!(count, TOTAL);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,69 @@
+use pexels::models::{Page, Photo};
+use serde_json::json;
+
+#[test]
+fn page_from_value_parses_items_and_links() {
+    let v = json!({
+        "page": 2,
+        "per_page": 2,
+        "total_results": 10,
+        "next_page": "https://api.pexels.com/v1/search?page=3&per_page=2&query=cats",
+        "prev_page": "https://api.pexels.com/v1/search?page=1&per_page=2&query=cats",
+        "photos": [
+            {"id": 1, "width": 10, "height": 20, "photographer": "A", "src": {"original": "o1"}},
+            {"id": 2, "alt": "a cat"}
+        ]
+    });
+    let page: Page<Photo> = Page::from_value(&v, "photos").unwrap();
+    assert_eq!(page.page, 2);
+    assert_eq!(page.total_results, Some(10));
+    assert!(page.next_page.unwrap().contains("page=3"));
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items[0].src.original, "o1");
+    assert_eq!(page.items[1].alt.as_deref(), Some("a cat"));
+}
+
+#[test]
+fn page_from_value_missing_items_is_empty() {
+    let page: Page<Photo> = Page::from_value(&json!({"page": 1}), "photos").unwrap();
+    assert!(page.items.is_empty());
+    assert!(page.next_page.is_none());
+}
+
+#[test]
+fn page_from_value_rejects_malformed_items() {
+    let v = json!({"photos": [{"id": "not-a-number"}]});
+    assert!(Page::<Photo>::from_value(&v, "photos").is_err());
+}
+
+#[test]
+fn video_page_parses_files_with_missing_sizes() {
+    use pexels::models::{SearchResult, Video};
+    let v = json!({
+        "page": 1,
+        "videos": [{
+            "id": 7, "duration": 12, "user": {"id": 3, "name": "U"},
+            "video_files": [
+                {"id": 1, "quality": "hd", "file_type": "video/mp4", "width": 1920, "height": 1080, "fps": 29.97, "link": "l1"},
+                {"id": 2, "quality": null, "file_type": "video/mp4", "width": null, "height": null, "link": "l2"}
+            ]
+        }]
+    });
+    let page: SearchResult<Video> = Page::from_value(&v, "videos").unwrap();
+    let video = &page.items[0];
+    assert_eq!(video.user.name, "U");
+    assert_eq!(video.video_files[0].width, Some(1920));
+    assert!(video.video_files[1].quality.is_none());
+}
+
+#[test]
+fn collection_media_is_tagged_by_type() {
+    use pexels::models::Media;
+    let v = json!({"media": [
+        {"type": "Photo", "id": 1, "src": {"original": "o"}},
+        {"type": "Video", "id": 2, "video_files": []}
+    ]});
+    let page: Page<Media> = Page::from_value(&v, "media").unwrap();
+    assert!(matches!(&page.items[0], Media::Photo(p) if p.src.original == "o"));
+    assert!(matches!(&page.items[1], Media::Video(v) if v.id == 2));
+}
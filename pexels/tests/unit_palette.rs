@@ -0,0 +1,51 @@
+use pexels::palette::{extract, kmeans, Swatch};
+
+// 3/4 red, 1/4 blue
+fn two_tone() -> image::DynamicImage {
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(40, 40, |x, _| {
+        if x < 30 {
+            image::Rgb([220, 20, 30])
+        } else {
+            image::Rgb([10, 40, 200])
+        }
+    }))
+}
+
+#[test]
+fn extract_finds_dominant_colors_with_proportions() {
+    let colors = extract(&two_tone(), 2);
+    assert_eq!(colors.len(), 2);
+    assert_eq!(colors[0].hex(), "#dc141e");
+    assert!((colors[0].proportion - 0.75).abs() < 0.01);
+    assert_eq!(colors[1].hex(), "#0a28c8");
+    assert!((colors[1].proportion - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn extract_is_deterministic_and_drops_empty_clusters() {
+    let a = extract(&two_tone(), 6);
+    assert_eq!(a, extract(&two_tone(), 6));
+    let total: f64 = a.iter().map(|s| s.proportion).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(a.windows(2).all(|w| w[0].proportion >= w[1].proportion));
+}
+
+#[test]
+fn kmeans_handles_fewer_pixels_than_colors() {
+    assert!(kmeans(&[], 4).is_empty());
+    let one = kmeans(&[[255.0, 255.0, 255.0]], 4);
+    assert_eq!(one.len(), 1);
+    assert_eq!(one[0].hex(), "#ffffff");
+}
+
+#[test]
+fn swatch_json_rounds_proportion() {
+    let s = Swatch {
+        rgb: [1, 2, 3],
+        proportion: 0.123456,
+    };
+    assert_eq!(
+        s.to_json(),
+        serde_json::json!({"hex": "#010203", "rgb": [1, 2, 3], "proportion": 0.123})
+    );
+}
@@ -0,0 +1,318 @@
+#![cfg(feature = "parquet")]
+// `read_strings` against files laid out the way other writers lay them out
+// (dictionary pages, several row groups, Snappy, data page v2, metadata the
+// reader has to skip), built here from parquet.thrift without going through
+// the crate's writer, plus damaged copies that must fail cleanly.
+use pexels::parquet::{read_strings, render};
+use serde_json::json;
+
+// A Thrift compact value; `Struct` fields go in ascending id order.
+enum V {
+    Bool(bool),
+    I32(i64),
+    I64(i64),
+    Bin(Vec<u8>),
+    List(Vec<V>),
+    Struct(Vec<(i16, V)>),
+}
+
+impl V {
+    fn ty(&self) -> u8 {
+        match self {
+            V::Bool(true) => 1,
+            V::Bool(false) => 2,
+            V::I32(_) => 5,
+            V::I64(_) => 6,
+            V::Bin(_) => 8,
+            V::List(_) => 9,
+            V::Struct(_) => 12,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            // Struct fields carry booleans in the field header
+            V::Bool(_) => {}
+            V::I32(v) | V::I64(v) => varint(out, ((v << 1) ^ (v >> 63)) as u64),
+            V::Bin(b) => {
+                varint(out, b.len() as u64);
+                out.extend(b);
+            }
+            V::List(items) => {
+                let ty = items.first().map_or(5, V::ty);
+                out.push(((items.len() as u8) << 4) | ty);
+                for item in items {
+                    item.write(out);
+                }
+            }
+            V::Struct(fields) => {
+                let mut last = 0;
+                for (id, v) in fields {
+                    out.push((((id - last) as u8) << 4) | v.ty());
+                    last = *id;
+                    v.write(out);
+                }
+                out.push(0);
+            }
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write(&mut out);
+        out
+    }
+}
+
+fn varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn bin(s: &str) -> V {
+    V::Bin(s.as_bytes().to_vec())
+}
+
+// PLAIN BYTE_ARRAY values.
+fn plain(values: &[&str]) -> Vec<u8> {
+    let mut out = vec![];
+    for v in values {
+        out.extend((v.len() as u32).to_le_bytes());
+        out.extend(v.as_bytes());
+    }
+    out
+}
+
+// A Snappy block of one literal (short inputs only).
+fn snappy(raw: &[u8]) -> Vec<u8> {
+    assert!(!raw.is_empty() && raw.len() <= 60);
+    let mut out = vec![];
+    varint(&mut out, raw.len() as u64);
+    out.push(((raw.len() - 1) as u8) << 2);
+    out.extend(raw);
+    out
+}
+
+const SNAPPY: i64 = 1;
+const PLAIN_DICTIONARY: i64 = 2;
+const RLE: i64 = 3;
+const RLE_DICTIONARY: i64 = 8;
+
+// PageHeader for a body of `raw` bytes stored as `stored` bytes.
+fn page_header(kind: i64, raw: usize, stored: usize, detail: (i16, V)) -> Vec<u8> {
+    V::Struct(vec![
+        (1, V::I32(kind)),
+        (2, V::I32(raw as i64)),
+        (3, V::I32(stored as i64)),
+        detail,
+    ])
+    .bytes()
+}
+
+fn dictionary_page(entries: &[&str]) -> Vec<u8> {
+    let raw = plain(entries);
+    let body = snappy(&raw);
+    let detail = V::Struct(vec![
+        (1, V::I32(entries.len() as i64)),
+        (2, V::I32(PLAIN_DICTIONARY)),
+    ]);
+    let mut page = page_header(2, raw.len(), body.len(), (7, detail));
+    page.extend(body);
+    page
+}
+
+// Data page v1: length-prefixed definition levels, then the values, all of
+// it Snappy-compressed.
+fn data_page_v1(count: usize, levels: &[u8], encoding: i64, values: &[u8]) -> Vec<u8> {
+    let mut raw = (levels.len() as u32).to_le_bytes().to_vec();
+    raw.extend(levels);
+    raw.extend(values);
+    let body = snappy(&raw);
+    let detail = V::Struct(vec![
+        (1, V::I32(count as i64)),
+        (2, V::I32(encoding)),
+        (3, V::I32(RLE)),
+        (4, V::I32(RLE)),
+    ]);
+    let mut page = page_header(0, raw.len(), body.len(), (5, detail));
+    page.extend(body);
+    page
+}
+
+// Data page v2: definition levels stored as is, values compressed when
+// `compressed`.
+fn data_page_v2(
+    (count, nulls): (usize, usize),
+    levels: &[u8],
+    encoding: i64,
+    values: &[u8],
+    compressed: bool,
+) -> Vec<u8> {
+    let stored = if compressed {
+        snappy(values)
+    } else {
+        values.to_vec()
+    };
+    let detail = V::Struct(vec![
+        (1, V::I32(count as i64)),
+        (2, V::I32(nulls as i64)),
+        (3, V::I32(count as i64)),
+        (4, V::I32(encoding)),
+        (5, V::I32(levels.len() as i64)),
+        (6, V::I32(0)),
+        (7, V::Bool(compressed)),
+    ]);
+    let mut page = page_header(
+        3,
+        levels.len() + values.len(),
+        levels.len() + stored.len(),
+        (8, detail),
+    );
+    page.extend(levels);
+    page.extend(stored);
+    page
+}
+
+// Rows, optional dictionary page, data pages.
+type Group = (i64, Option<Vec<u8>>, Vec<Vec<u8>>);
+
+// One optional UTF8 column "sha256" with a row group per entry of `groups`.
+fn file(groups: Vec<Group>) -> Vec<u8> {
+    let mut out = b"PAR1".to_vec();
+    let mut row_groups = vec![];
+    let mut total_rows = 0;
+    for (rows, dict, pages) in groups {
+        let start = out.len() as i64;
+        let dict_offset = dict.as_ref().map(|_| start);
+        if let Some(d) = dict {
+            out.extend(d);
+        }
+        let data_offset = out.len() as i64;
+        for p in pages {
+            out.extend(p);
+        }
+        let size = out.len() as i64 - start;
+        let mut meta = vec![
+            (1, V::I32(6)),
+            (
+                2,
+                V::List(vec![V::I32(0), V::I32(RLE), V::I32(RLE_DICTIONARY)]),
+            ),
+            (3, V::List(vec![bin("sha256")])),
+            (4, V::I32(SNAPPY)),
+            (5, V::I64(rows)),
+            (6, V::I64(size)),
+            (7, V::I64(size)),
+            (9, V::I64(data_offset)),
+        ];
+        if let Some(offset) = dict_offset {
+            meta.push((11, V::I64(offset)));
+        }
+        // Statistics { max_value, min_value }, which the reader skips
+        meta.push((12, V::Struct(vec![(5, bin("zz")), (6, bin("aa"))])));
+        row_groups.push(V::Struct(vec![
+            (
+                1,
+                V::List(vec![V::Struct(vec![
+                    (2, V::I64(start)),
+                    (3, V::Struct(meta)),
+                ])]),
+            ),
+            (2, V::I64(size)),
+            (3, V::I64(rows)),
+        ]));
+        total_rows += rows;
+    }
+    let footer = V::Struct(vec![
+        (1, V::I32(2)),
+        (
+            2,
+            V::List(vec![
+                V::Struct(vec![(4, bin("schema")), (5, V::I32(1))]),
+                V::Struct(vec![
+                    (1, V::I32(6)),
+                    (3, V::I32(1)),
+                    (4, bin("sha256")),
+                    (6, V::I32(0)),
+                ]),
+            ]),
+        ),
+        (3, V::I64(total_rows)),
+        (4, V::List(row_groups)),
+        (
+            5,
+            V::List(vec![V::Struct(vec![
+                (1, bin("ARROW:schema")),
+                (2, bin("/////w==")),
+            ])]),
+        ),
+        (6, bin("unit_parquet fixture")),
+    ])
+    .bytes();
+    out.extend(&footer);
+    out.extend((footer.len() as u32).to_le_bytes());
+    out.extend(b"PAR1");
+    out
+}
+
+// Rows aa, bb, aa | cc, null, cc | dd, ee across three row groups.
+fn sample() -> Vec<u8> {
+    // Dictionary indices: bit width 1, one bit-packed group holding 0, 1, 0
+    let first = data_page_v1(3, &[0x06, 0x01], RLE_DICTIONARY, &[1, 0x03, 0b010]);
+    // Bit width 0 (a single entry), an RLE run of two; levels 1, 0, 1
+    // bit-packed
+    let second = data_page_v2((3, 1), &[0x03, 0b101], RLE_DICTIONARY, &[0, 0x04], true);
+    // Two pages in the last group: PLAIN v1 and uncompressed PLAIN v2
+    let third = data_page_v1(1, &[0x02, 0x01], 0, &plain(&["dd"]));
+    let fourth = data_page_v2((1, 0), &[0x02, 0x01], 0, &plain(&["ee"]), false);
+    file(vec![
+        (3, Some(dictionary_page(&["aa", "bb"])), vec![first]),
+        (3, Some(dictionary_page(&["cc"])), vec![second]),
+        (2, None, vec![third, fourth]),
+    ])
+}
+
+#[test]
+fn reads_dictionary_pages_across_row_groups() {
+    let values = read_strings(&sample(), "sha256").unwrap().unwrap();
+    assert_eq!(values, ["aa", "bb", "aa", "cc", "cc", "dd", "ee"]);
+    assert!(read_strings(&sample(), "id").unwrap().is_none());
+}
+
+// Every truncation and a spread of byte overwrites of a valid file either
+// read or fail with an error: no panic, no runaway allocation.
+#[test]
+fn damaged_files_fail_cleanly() {
+    let ours = render(&json!([{"id": 1, "sha256": "aa"}, {"id": 2}])).unwrap();
+    for bytes in [sample(), ours] {
+        for len in 0..bytes.len() {
+            let _ = read_strings(&bytes[..len], "sha256");
+        }
+        for i in 0..bytes.len() {
+            for b in [0x00, 0x01, 0x0f, 0x7f, 0x80, 0xff] {
+                let mut broken = bytes.clone();
+                broken[i] = b;
+                let _ = read_strings(&broken, "sha256");
+                let _ = read_strings(&broken, "id");
+            }
+        }
+    }
+}
+
+#[test]
+fn hostile_lengths_are_refused() {
+    // A Snappy block claiming far more output than its tags can produce
+    assert!(pexels::parquet::snappy(&[0xff, 0xff, 0xff, 0xff, 0x0f, 0x00, b'a']).is_err());
+    // Metadata nested a thousand lists deep
+    let mut deep = b"PAR1".to_vec();
+    let footer: Vec<u8> = std::iter::once(0x19)
+        .chain(std::iter::repeat_n(0x19, 1000))
+        .collect();
+    deep.extend(&footer);
+    deep.extend((footer.len() as u32).to_le_bytes());
+    deep.extend(b"PAR1");
+    assert!(read_strings(&deep, "id").is_err());
+}
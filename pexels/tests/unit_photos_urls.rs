@@ -0,0 +1,24 @@
+use clap::Parser;
+use pexels::cli::{read_ids, Cli};
+
+#[test]
+fn ids_file_skips_comments_blanks_and_repeats() {
+    let path = std::env::temp_dir().join(format!("pexels-ids-{}.txt", std::process::id()));
+    std::fs::write(&path, "# picks\n2014422\n\n  3573351 \n2014422\n").unwrap();
+    assert_eq!(read_ids(&path).unwrap(), vec!["2014422", "3573351"]);
+    let _ = std::fs::remove_file(&path);
+    let missing = read_ids(std::path::Path::new("/nonexistent/ids.txt")).unwrap_err();
+    assert!(missing.to_string().contains("cannot read --input"));
+}
+
+#[test]
+fn photos_urls_needs_input() {
+    let parse = |extra: &[&str]| {
+        let mut argv = vec!["pexels", "photos", "urls"];
+        argv.extend(extra);
+        Cli::try_parse_from(argv)
+    };
+    assert!(parse(&["--input", "ids.txt", "--size", "large"]).is_ok());
+    assert!(parse(&["--input", "-", "--concurrency", "16"]).is_ok());
+    assert!(parse(&[]).is_err());
+}
@@ -0,0 +1,25 @@
+use pexels::pick::{file_name, label, media_url};
+use serde_json::json;
+
+#[test]
+fn label_summarizes_photo_and_video() {
+    let photo = json!({"id": 1, "alt": "Cat", "photographer": "A", "width": 10, "height": 20,
+        "src": {"original": "https://images.pexels.com/photos/1/a.jpeg"}});
+    assert_eq!(label(&photo), "photo 1 — Cat — by A — 10x20");
+    let video = json!({"id": 2, "duration": 83, "user": {"name": "B"}, "video_files": []});
+    assert_eq!(label(&video), "video 2 — by B — 1:23");
+}
+
+#[test]
+fn media_url_and_file_name() {
+    let photo =
+        json!({"id": 1, "src": {"original": "https://images.pexels.com/photos/1/a.JPEG?x=1"}});
+    assert_eq!(
+        media_url(&photo).unwrap(),
+        "https://images.pexels.com/photos/1/a.JPEG?x=1"
+    );
+    assert_eq!(file_name(&photo), "photo-1.jpeg");
+    let video =
+        json!({"id": 2, "video_files": [{"width": 1, "link": "https://v.example/play?id=2"}]});
+    assert_eq!(file_name(&video), "video-2.mp4");
+}
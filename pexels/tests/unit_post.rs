@@ -0,0 +1,52 @@
+use pexels::post::{render, resolve, run};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+fn pipelines() -> BTreeMap<String, Vec<String>> {
+    let mut p = BTreeMap::new();
+    p.insert(
+        "webp".to_string(),
+        vec!["cwebp -q 80 {path} -o {dir}/{stem}.webp".to_string()],
+    );
+    p.insert("bad".to_string(), vec!["echo {file}".to_string()]);
+    p
+}
+
+#[test]
+fn resolve_validates_names_and_placeholders() {
+    let p = pipelines();
+    assert_eq!(resolve(&p, "webp").unwrap().len(), 1);
+    let err = pexels::output::error_value(&resolve(&p, "nope").unwrap_err());
+    assert_eq!(err["error"], "unknown post-processor");
+    let err = pexels::output::error_value(&resolve(&p, "bad").unwrap_err());
+    assert_eq!(err["placeholder"], "{file}");
+}
+
+#[cfg(unix)]
+#[test]
+fn render_quotes_paths() {
+    let out = render(
+        "cp {path} {dir}/{stem}.bak",
+        Path::new("/tmp/it's here/a.jpg"),
+    );
+    assert_eq!(
+        out,
+        "cp '/tmp/it'\\''s here/a.jpg' '/tmp/it'\\''s here'/'a'.bak"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn run_executes_steps_and_reports_failure() {
+    let dir = std::env::temp_dir().join(format!("pexels-post-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("a.jpg");
+    std::fs::write(&file, b"x").unwrap();
+    let steps = vec!["cp {path} {dir}/copy.jpg".to_string()];
+    let res = run("copy", &steps, &file).unwrap();
+    assert_eq!(res["steps"].as_array().unwrap().len(), 1);
+    assert!(dir.join("copy.jpg").exists());
+    let err = run("fail", &["exit 3".to_string()], &file).unwrap_err();
+    assert_eq!(pexels::output::error_value(&err)["status"], 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
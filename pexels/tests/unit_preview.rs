@@ -0,0 +1,77 @@
+use pexels::preview::{render, Protocol};
+
+fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    move |k| {
+        vars.iter()
+            .find(|(name, _)| *name == k)
+            .map(|(_, v)| v.to_string())
+    }
+}
+
+// Left half white, right half black
+fn split(w: u32, h: u32) -> image::DynamicImage {
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(w, h, |x, _| {
+        if x < w / 2 {
+            image::Rgb([255, 255, 255])
+        } else {
+            image::Rgb([0, 0, 0])
+        }
+    }))
+}
+
+#[test]
+fn detects_protocol_from_environment() {
+    let detect = |vars: &[(&str, &str)], color| Protocol::detect(env(vars), color);
+    assert_eq!(detect(&[("KITTY_WINDOW_ID", "1")], true), Protocol::Kitty);
+    assert_eq!(
+        detect(&[("TERM_PROGRAM", "iTerm.app")], true),
+        Protocol::Iterm
+    );
+    assert_eq!(detect(&[("TERM", "foot")], true), Protocol::Sixel);
+    assert_eq!(
+        detect(&[("TERM", "xterm-256color")], true),
+        Protocol::Blocks
+    );
+    assert_eq!(
+        detect(&[("TERM", "xterm-256color")], false),
+        Protocol::Ascii
+    );
+    assert_eq!(detect(&[], true), Protocol::Blocks);
+}
+
+#[test]
+fn blocks_and_ascii_keep_the_aspect_ratio() {
+    let img = split(40, 20);
+    let blocks = String::from_utf8(render(&img, Protocol::Blocks, 8).unwrap()).unwrap();
+    // 8 columns x 4 pixel rows -> 2 lines of half blocks
+    assert_eq!(blocks.lines().count(), 2);
+    assert_eq!(
+        blocks.lines().next().unwrap().matches('\u{2580}').count(),
+        8
+    );
+    assert!(blocks.starts_with("\x1b[38;2;255;255;255m"));
+    let ascii = String::from_utf8(render(&img, Protocol::Ascii, 8).unwrap()).unwrap();
+    // 8 columns x 40/20/2 rows; the resize blends the middle columns
+    assert_eq!(ascii.lines().count(), 2);
+    for line in ascii.lines() {
+        assert_eq!(line.len(), 8);
+        assert!(
+            line.starts_with("@@@") && line.ends_with("   "),
+            "{:?}",
+            line
+        );
+    }
+}
+
+#[test]
+fn graphics_protocols_are_framed() {
+    let img = split(64, 32);
+    let sixel = render(&img, Protocol::Sixel, 4).unwrap();
+    assert!(sixel.starts_with(b"\x1bPq\"1;1;32;16"));
+    assert!(sixel.ends_with(b"\x1b\\\n"));
+    let kitty = String::from_utf8(render(&img, Protocol::Kitty, 4).unwrap()).unwrap();
+    assert!(kitty.starts_with("\x1b_Ga=T,f=100,c=4,m=0;"));
+    let iterm = String::from_utf8(render(&img, Protocol::Iterm, 4).unwrap()).unwrap();
+    assert!(iterm.starts_with("\x1b]1337;File=inline=1;"));
+    assert!(iterm.contains(";width=4;"));
+}
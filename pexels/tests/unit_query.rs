@@ -0,0 +1,65 @@
+use pexels::query::BoolQuery;
+use serde_json::json;
+
+fn plan() -> BoolQuery {
+    BoolQuery {
+        base: None,
+        any_of: vec!["cat".into(), "kitten".into()],
+        all_of: vec!["outdoor".into()],
+        none_of: vec!["studio".into()],
+    }
+}
+
+#[test]
+fn searches_expand_any_of_with_common_terms() {
+    assert_eq!(plan().searches(), vec!["outdoor cat", "outdoor kitten"]);
+    let plain = BoolQuery {
+        base: Some("dogs".into()),
+        all_of: vec!["beach".into()],
+        ..Default::default()
+    };
+    assert_eq!(plain.searches(), vec!["dogs beach"]);
+    assert!(BoolQuery::default().is_plain());
+}
+
+#[test]
+fn matches_uses_alt_text_and_url_slug() {
+    let p = plan();
+    assert!(p.matches(&json!({"alt": "Cat playing Outdoor"})));
+    assert!(p.matches(&json!({"alt": "", "url": "https://www.pexels.com/photo/cat-outdoor-123/"})));
+    assert!(!p.matches(&json!({"alt": "cat outdoor in a studio"})));
+    assert!(!p.matches(&json!({"alt": "cat indoors"})));
+}
+
+#[test]
+fn merge_filters_and_dedupes() {
+    let a = json!({"photos": [{"id": 1, "alt": "outdoor cat"}, {"id": 2, "alt": "studio outdoor cat"}]});
+    let b =
+        json!({"photos": [{"id": 1, "alt": "outdoor cat"}, {"id": 3, "alt": "outdoor kitten"}]});
+    let merged = plan().merge(&[a, b], "photos");
+    let ids: Vec<u64> = merged["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 3]);
+    assert_eq!(merged["total_results"], 2);
+    assert_eq!(merged["query_plan"]["filtered_out"], 1);
+    assert_eq!(merged["query_plan"]["duplicates"], 1);
+}
+
+#[test]
+fn cap_per_creator_limits_each_contributor() {
+    use pexels::query::cap_per_creator;
+    let mut items = vec![
+        json!({"id": 1, "photographer": "A", "photographer_id": 7}),
+        json!({"id": 2, "photographer": "A", "photographer_id": 7}),
+        json!({"id": 3, "user": {"id": 9, "name": "B"}, "video_files": []}),
+        json!({"id": 4, "photographer": "A", "photographer_id": 7}),
+        json!({"id": 5}),
+    ];
+    assert_eq!(cap_per_creator(&mut items, 1), 2);
+    let ids: Vec<u64> = items.iter().map(|i| i["id"].as_u64().unwrap()).collect();
+    assert_eq!(ids, vec![1, 3, 5]);
+}
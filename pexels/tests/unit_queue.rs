@@ -0,0 +1,37 @@
+use pexels::queue::{add, clear, items, key, MediaType, QueueItem};
+use pexels::state::State;
+use std::fs;
+
+#[test]
+fn queue_keeps_items_across_loads_and_replaces_duplicates() {
+    let dir = std::env::temp_dir().join(format!("pexels-queue-{}", std::process::id()));
+    let path = dir.join("state.db");
+    let mut st = State::load_from(&path).unwrap();
+    let mut photo = QueueItem::new(MediaType::Photo, "2014422");
+    photo.size = Some("large".into());
+    assert!(!add(&mut st, &photo));
+    let mut video = QueueItem::new(MediaType::Video, "857195");
+    video.quality = Some("hd".into());
+    video.max_width = Some(1920);
+    add(&mut st, &video);
+    st.set("potd:curated", serde_json::json!(1));
+    st.save().unwrap();
+
+    let mut st = State::load_from(&path).unwrap();
+    let queued = items(&st);
+    assert_eq!(queued, vec![photo.clone(), video.clone()]);
+    assert_eq!(key(MediaType::Video, "857195"), "queue:video:857195");
+    let r = queued[1].rendition();
+    assert_eq!(r.quality, Some(pexels::video::VideoQuality::Hd));
+    assert_eq!(r.max_width, Some(1920));
+
+    photo.size = Some("tiny".into());
+    assert!(add(&mut st, &photo));
+    assert_eq!(items(&st).len(), 2);
+    assert_eq!(clear(&mut st), 2);
+    st.save().unwrap();
+    let st = State::load_from(&path).unwrap();
+    assert!(items(&st).is_empty());
+    assert!(st.get("potd:curated").is_some());
+    let _ = fs::remove_dir_all(&dir);
+}
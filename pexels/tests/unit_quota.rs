@@ -0,0 +1,82 @@
+use pexels::quota::Window;
+use std::time::Duration;
+
+#[test]
+fn window_from_headers_and_wait() {
+    let headers = [
+        ("x-ratelimit-limit", "20000"),
+        ("x-ratelimit-remaining", "0"),
+        ("x-ratelimit-reset", "1700000060"),
+    ];
+    let get = |name: &str| headers.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+    let w = Window::from_headers(get).unwrap();
+    assert_eq!(w.limit, Some(20000));
+    assert!(w.exhausted(1_700_000_000));
+    assert_eq!(w.wait(1_700_000_000), Some(Duration::from_secs(60)));
+    assert_eq!(w.wait(1_700_000_060), None);
+    assert!(w.resume_at().starts_with("2023-11-14T22:14:20"));
+    let left = Window { remaining: 5, ..w };
+    assert_eq!(left.wait(1_700_000_000), None);
+    assert!(Window::from_headers(|_: &str| None).is_none());
+}
+
+#[test]
+fn window_pace_spreads_low_remaining() {
+    let now = 1_700_000_000;
+    let w = Window {
+        limit: Some(200),
+        remaining: 150,
+        reset: now + 60,
+    };
+    assert_eq!(w.pace(now), Duration::ZERO);
+    let low = Window { remaining: 9, ..w };
+    assert_eq!(low.pace(now), Duration::from_secs(2));
+    let short = Window {
+        remaining: 39,
+        reset: now + 20,
+        ..w
+    };
+    assert_eq!(short.pace(now), Duration::from_millis(500));
+    let empty = Window { remaining: 0, ..w };
+    assert_eq!(empty.pace(now), Duration::from_secs(60));
+    assert_eq!(empty.pace(now + 61), Duration::ZERO);
+}
+
+#[test]
+fn window_state_roundtrip() {
+    let w = Window {
+        limit: None,
+        remaining: 3,
+        reset: 42,
+    };
+    let v = serde_json::to_value(w).unwrap();
+    assert_eq!(serde_json::from_value::<Window>(v).unwrap(), w);
+    assert_eq!(
+        pexels::quota::state_key("https://api.pexels.com"),
+        "ratelimit:https://api.pexels.com"
+    );
+}
+
+#[test]
+fn saved_window_is_read_back_with_seen_at() {
+    use pexels::state::State;
+    let dir = std::env::temp_dir().join(format!("pexels-quota-{}", std::process::id()));
+    let path = dir.join("state.db");
+    let w = Window {
+        limit: Some(200),
+        remaining: 17,
+        reset: 1_700_000_000,
+    };
+    let mut st = State::detached(&path);
+    st.set(
+        &pexels::quota::state_key("https://api.pexels.com"),
+        serde_json::to_value(w).unwrap(),
+    );
+    st.save().unwrap();
+    let st = State::load_from(&path).unwrap();
+    let (back, seen_at) = pexels::quota::saved(&st, "https://api.pexels.com").unwrap();
+    assert_eq!(back, w);
+    assert!(!seen_at.is_empty());
+    assert!(pexels::quota::saved(&st, "http://localhost").is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
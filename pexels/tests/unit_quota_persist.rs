@@ -0,0 +1,59 @@
+// Own test binary: points the state db at a temp dir via XDG_DATA_HOME.
+use pexels::api::PexelsClient;
+use pexels::config::Config;
+use pexels::state::State;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+// Answers every request with an empty page and a rate-limit window.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for conn in listener.incoming() {
+            let mut conn = conn.unwrap();
+            let mut reader = BufReader::new(conn.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            let body = r#"{"page":1,"per_page":1,"photos":[]}"#;
+            write!(
+                conn,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Ratelimit-Limit: 200\r\nX-Ratelimit-Remaining: 150\r\nX-Ratelimit-Reset: 4102444800\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    host
+}
+
+#[tokio::test]
+async fn window_is_saved_by_the_caller_not_per_response() {
+    let dir = std::env::temp_dir().join(format!("pexels-quota-persist-{}", std::process::id()));
+    std::env::set_var("XDG_DATA_HOME", &dir);
+    let host = serve();
+    let client = PexelsClient::new(Config {
+        token: Some("t".into()),
+        host: Some(host.clone()),
+        timeout_secs: 5,
+        ..Default::default()
+    })
+    .unwrap();
+
+    client.util_ping().await.unwrap();
+    assert_eq!(client.quota_window().map(|w| w.remaining), Some(150));
+    // The request path keeps the window in memory only
+    assert!(!State::default_path().exists());
+
+    client.save_window().unwrap();
+    let st = State::load().unwrap();
+    let (w, _) = pexels::quota::saved(&st, &host).unwrap();
+    assert_eq!((w.limit, w.remaining), (Some(200), 150));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,107 @@
+use clap::Parser;
+use pexels::cli::Cli;
+use pexels::config::Config;
+use pexels::settings::{list, lookup, names};
+
+fn entry<'a>(items: &'a [serde_json::Value], key: &str) -> &'a serde_json::Value {
+    items.iter().find(|i| i["key"] == key).unwrap()
+}
+
+#[test]
+fn lookup_resolves_aliases_and_patterns() {
+    assert_eq!(lookup("api_key").unwrap().name(), "token");
+    assert_eq!(lookup("output").unwrap().name(), "default_format");
+    assert_eq!(
+        lookup("remotes.nas.local.user").unwrap().name(),
+        "remotes.nas.local.user"
+    );
+    assert_eq!(
+        lookup("projections.videos").unwrap().name(),
+        "projections.videos"
+    );
+    let err = lookup("audit.enable").err().unwrap().to_string();
+    assert!(err.contains("did_you_mean") && err.contains("audit.enabled"));
+    assert!(lookup("projections.users").is_err());
+    assert!(names().contains(&"hosts.<profile>.base".to_string()));
+}
+
+#[test]
+fn set_get_unset_round_trip() {
+    let mut cfg = Config::default();
+    for (key, value) in [
+        ("timeout", "30"),
+        ("default_format", "json"),
+        ("projections.photos", "id, url"),
+        ("hosts.gw.headers.X-Key", "k"),
+        ("remotes.nas.local.password", "pw"),
+    ] {
+        lookup(key).unwrap().set(&mut cfg, value).unwrap();
+    }
+    assert_eq!(cfg.default_timeout, Some(30));
+    assert_eq!(lookup("output").unwrap().get(&cfg), "json");
+    assert_eq!(lookup("projections.photos").unwrap().get(&cfg), "id,url");
+    assert!(lookup("default_per_page")
+        .unwrap()
+        .set(&mut cfg, "500")
+        .is_err());
+    for key in [
+        "timeout",
+        "output",
+        "projections.photos",
+        "hosts.gw.headers.X-Key",
+        "remotes.nas.local.password",
+    ] {
+        lookup(key).unwrap().unset(&mut cfg).unwrap();
+    }
+    assert_eq!(cfg.default_timeout, None);
+    assert_eq!(cfg.output, None);
+    assert!(cfg.projections.is_empty());
+    // Emptied entries are dropped
+    assert!(cfg.hosts.is_empty() && cfg.remotes.is_empty());
+}
+
+#[test]
+fn list_reports_sources_and_masks_secrets() {
+    let mut cfg: Config = serde_yaml::from_str(
+        "token: secret\ntoken_source: config\nlocale: de-DE\nremotes:\n  nas:\n    password: pw\n",
+    )
+    .unwrap();
+    cfg.timeout_secs = 15;
+    cfg.max_retries = 3;
+    cfg.apply_cli(
+        &Cli::try_parse_from(["pexels", "config", "list", "--max-retries", "7", "--json"]).unwrap(),
+    );
+    let items = list(&cfg);
+    let token = entry(&items, "token");
+    assert_eq!(token["source"], "file");
+    assert!(token["value"].as_str().unwrap().starts_with("sha256:"));
+    assert_eq!(entry(&items, "locale")["source"], "file");
+    assert_eq!(entry(&items, "timeout")["value"], "15");
+    assert_eq!(entry(&items, "timeout")["source"], "default");
+    assert_eq!(entry(&items, "max_retries")["value"], "7");
+    assert_eq!(entry(&items, "max_retries")["source"], "cli");
+    assert_eq!(entry(&items, "default_format")["source"], "cli");
+    assert_eq!(
+        entry(&items, "download_dir")["value"],
+        serde_json::Value::Null
+    );
+    let pw = entry(&items, "remotes.nas.password");
+    assert_eq!(pw["value"], "<redacted: 2 chars>");
+    assert_eq!(pw["source"], "file");
+}
+
+#[test]
+fn list_masks_host_header_values() {
+    let cfg: Config = serde_yaml::from_str(
+        "hosts:\n  corp:\n    base: https://gw.example\n    headers:\n      Authorization: Bearer gw-secret\n",
+    )
+    .unwrap();
+    let items = list(&cfg);
+    let header = entry(&items, "hosts.corp.headers.Authorization");
+    assert_eq!(header["value"], "<redacted: 16 chars>");
+    assert_eq!(header["source"], "file");
+    assert_eq!(
+        entry(&items, "hosts.corp.base")["value"],
+        "https://gw.example"
+    );
+}
@@ -0,0 +1,253 @@
+use pexels::output::Sink;
+use pexels::sink::{
+    parse_rotate, parse_target, rotated_name, FileSink, HttpSink, NdjsonSink, Rotate, Target,
+    HTTP_ATTEMPTS,
+};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+fn tmp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pexels-sink-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn rotate_specs_parse() {
+    assert_eq!(parse_rotate("size=100MB").unwrap(), Rotate::Size(100 << 20));
+    assert_eq!(parse_rotate("size=512").unwrap(), Rotate::Size(512));
+    assert_eq!(parse_rotate("Daily").unwrap(), Rotate::Daily);
+    assert!(parse_rotate("size=0").is_err());
+    assert!(parse_rotate("hourly").is_err());
+    let at = chrono::DateTime::parse_from_rfc3339("2024-05-01T10:20:30Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    assert_eq!(
+        rotated_name(Path::new("out/items.ndjson"), at),
+        PathBuf::from("out/items-20240501T102030Z.ndjson")
+    );
+}
+
+#[test]
+fn size_rotation_keeps_every_line() {
+    let dir = tmp("size");
+    let path = dir.join("items.ndjson");
+    let mut sink = NdjsonSink::open(&path, Some(Rotate::Size(40))).unwrap();
+    for i in 0..5 {
+        sink.write(&serde_json::json!({ "id": i, "pad": "xxxxxxxxxx" }))
+            .unwrap();
+    }
+    sink.finish().unwrap();
+    assert!(!sink.rotated().is_empty());
+    let mut lines = 0;
+    for f in sink.rotated().iter().chain(std::iter::once(&path)) {
+        let body = fs::read_to_string(f).unwrap();
+        assert!(body.len() <= 40 || body.lines().count() == 1);
+        lines += body.lines().count();
+    }
+    assert_eq!(lines, 5);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reopening_resumes_existing_file() {
+    let dir = tmp("resume");
+    let path = dir.join("items.ndjson");
+    {
+        let mut sink = NdjsonSink::open(&path, None).unwrap();
+        sink.write(&serde_json::json!({ "id": 1 })).unwrap();
+        sink.finish().unwrap();
+    }
+    let mut sink = NdjsonSink::open(&path, Some(Rotate::Size(1 << 20))).unwrap();
+    sink.write(&serde_json::json!({ "id": 2 })).unwrap();
+    sink.finish().unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"id\":1}\n{\"id\":2}\n"
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sink_targets_parse() {
+    assert_eq!(parse_target("-").unwrap(), Target::Stdout);
+    assert_eq!(
+        parse_target("file:out/cats.json").unwrap(),
+        Target::File("out/cats.json".into())
+    );
+    assert_eq!(
+        parse_target("cats.ndjson").unwrap(),
+        Target::File("cats.ndjson".into())
+    );
+    assert!(matches!(
+        parse_target("https://ingest.example/api").unwrap(),
+        Target::Http(_)
+    ));
+    assert!(parse_target("file:").is_err());
+}
+
+#[test]
+fn file_sink_replaces_then_appends() {
+    let dir = tmp("file");
+    let path = dir.join("nested").join("out.ndjson");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "stale\n").unwrap();
+    let mut sink = FileSink::create(&path).unwrap();
+    sink.write(b"{\"id\":1}\n", "application/x-ndjson").unwrap();
+    sink.write(b"{\"id\":2}\n", "application/x-ndjson").unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"id\":1}\n{\"id\":2}\n"
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+// Answers each request with the next status and reports what it received.
+fn serve(statuses: Vec<u16>) -> (String, std::sync::mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/ingest", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for status in statuses {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(conn.try_clone().unwrap());
+            let (mut len, mut content_type) = (0, String::new());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(v) = line.strip_prefix("content-length:") {
+                    len = v.trim().parse().unwrap();
+                }
+                if let Some(v) = line.strip_prefix("content-type:") {
+                    content_type = v.trim().to_string();
+                }
+            }
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+            tx.send(format!(
+                "{} {}",
+                content_type,
+                String::from_utf8_lossy(&body)
+            ))
+            .unwrap();
+            write!(
+                conn,
+                "HTTP/1.1 {} X\r\nContent-Length: 0\r\nRetry-After: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+        }
+    });
+    (url, rx)
+}
+
+#[tokio::test]
+async fn http_sink_retries_transient_failures_only() {
+    let (url, rx) = serve(vec![503, 429, 200]);
+    let sink = HttpSink::new(url.parse().unwrap(), &["X-Key: abc".into()]).unwrap();
+    sink.post(b"{\"id\":1}\n", "application/x-ndjson")
+        .await
+        .unwrap();
+    let got: Vec<String> = rx.try_iter().collect();
+    assert_eq!(got.len(), 3);
+    assert_eq!(got[2], "application/x-ndjson {\"id\":1}\n");
+
+    let (url, rx) = serve(vec![400]);
+    let sink = HttpSink::new(url.parse().unwrap(), &[]).unwrap();
+    let err = sink.post(b"{}", "application/json").await.unwrap_err();
+    assert!(err.to_string().contains("sink rejected the output"));
+    assert_eq!(rx.try_iter().count(), 1);
+
+    let (url, _rx) = serve(vec![502; HTTP_ATTEMPTS as usize]);
+    let sink = HttpSink::new(url.parse().unwrap(), &[]).unwrap();
+    let err = sink.post(b"{}", "application/json").await.unwrap_err();
+    assert!(err.to_string().contains("sink unreachable"));
+
+    assert!(HttpSink::new(url.parse().unwrap(), &["no colon".into()]).is_err());
+}
+
+#[test]
+fn nats_targets_parse() {
+    let t = parse_target("nats://user:pw@localhost:4222/pexels.curated").unwrap();
+    let Target::Nats(url) = &t else {
+        panic!("{:?}", t)
+    };
+    assert_eq!(pexels::sink::nats_subject(url).unwrap(), "pexels.curated");
+    assert!(parse_target("nats://localhost").is_err());
+    assert!(parse_target("nats://localhost/pexels.>").is_err());
+    assert!(parse_target("kafka://broker/topic")
+        .unwrap_err()
+        .contains("nats://"));
+}
+
+// A NATS server that takes one connection and reports every line it reads.
+#[cfg(feature = "nats")]
+fn serve_nats(info: &'static str) -> (String, std::sync::mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("nats://tok@{}/pexels.items", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let (mut conn, _) = listener.accept().unwrap();
+        write!(conn, "INFO {}\r\n", info).unwrap();
+        let mut reader = BufReader::new(conn.try_clone().unwrap());
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+            let line = line.trim_end().to_string();
+            let ping = line == "PING";
+            if tx.send(line).is_err() {
+                break;
+            }
+            if ping {
+                conn.write_all(b"PONG\r\n").unwrap();
+            }
+        }
+    });
+    (url, rx)
+}
+
+#[cfg(feature = "nats")]
+#[test]
+fn nats_sink_publishes_one_message_per_ndjson_line() {
+    let (url, rx) = serve_nats(r#"{"max_payload":1024}"#);
+    let Target::Nats(url) = parse_target(&url).unwrap() else {
+        unreachable!()
+    };
+    let mut sink = pexels::sink::NatsSink::connect(&url, &[]).unwrap();
+    sink.write(b"{\"id\":1}\n{\"id\":2}\n", "application/x-ndjson")
+        .unwrap();
+    let lines: Vec<String> = rx.try_iter().collect();
+    assert!(lines[0].starts_with("CONNECT ") && lines[0].contains("\"auth_token\":\"tok\""));
+    assert_eq!(
+        &lines[1..],
+        [
+            "PING",
+            "PUB pexels.items 8",
+            "{\"id\":1}",
+            "PUB pexels.items 8",
+            "{\"id\":2}",
+            "PING"
+        ]
+    );
+
+    // Headers need a server that supports them
+    let (url, rx) = serve_nats(r#"{"headers":true}"#);
+    let Target::Nats(url) = parse_target(&url).unwrap() else {
+        unreachable!()
+    };
+    let mut sink = pexels::sink::NatsSink::connect(&url, &["X-Run: 7".into()]).unwrap();
+    sink.write(b"{}", "application/json").unwrap();
+    let lines: Vec<String> = rx.try_iter().collect();
+    assert!(lines.contains(&"HPUB pexels.items 54 56".to_string()));
+    assert!(lines.contains(&"Content-Type: application/json".to_string()));
+    assert!(lines.contains(&"x-run: 7".to_string()));
+}
@@ -0,0 +1,54 @@
+use pexels::cli::potd_index;
+use pexels::state::{namespace, State, SCHEMA_VERSION};
+use serde_json::json;
+use std::fs;
+
+#[test]
+fn state_roundtrip() {
+    let dir = std::env::temp_dir().join(format!("pexels-state-{}", std::process::id()));
+    let path = dir.join("state.json");
+    let mut st = State::load_from(&path).unwrap();
+    assert!(st.get("k").is_none());
+    st.set("k", json!({"date": "2024-01-01"}));
+    st.save().unwrap();
+    let st = State::load_from(&path).unwrap();
+    assert_eq!(st.get("k").unwrap()["date"], "2024-01-01");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn potd_index_is_stable_per_day() {
+    let a = potd_index("2024-05-01", "potd:curated", 80);
+    assert_eq!(a, potd_index("2024-05-01", "potd:curated", 80));
+    assert!(a < 80);
+    let days: std::collections::HashSet<_> = (1..=28)
+        .map(|d| potd_index(&format!("2024-02-{:02}", d), "potd:curated", 80))
+        .collect();
+    assert!(days.len() > 1);
+}
+
+#[test]
+fn state_db_migrates_and_saves_only_touched_keys() {
+    let dir = std::env::temp_dir().join(format!("pexels-statedb-{}", std::process::id()));
+    let path = dir.join("state.db");
+    let mut a = State::load_from(&path).unwrap();
+    assert_eq!(a.schema_version().unwrap(), SCHEMA_VERSION);
+    let mut b = State::load_from(&path).unwrap();
+    a.set("potd:curated", json!(1));
+    b.set("continue:last", json!({"page": 2}));
+    a.save().unwrap();
+    b.save().unwrap();
+    let st = State::load_from(&path).unwrap();
+    assert_eq!(st.get("potd:curated").unwrap(), &json!(1));
+    assert_eq!(st.get("continue:last").unwrap()["page"], 2);
+    assert_eq!(st.entries(Some("potd")).count(), 1);
+    assert_eq!(namespace("potd:query:cats"), "potd");
+    let mut st = st;
+    st.remove("potd:curated");
+    st.save().unwrap();
+    assert!(State::load_from(&path)
+        .unwrap()
+        .get("potd:curated")
+        .is_none());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,64 @@
+use pexels::suggest::{fields_warning, levenshtein, nearest, unmatched_fields};
+use serde_json::json;
+
+#[test]
+fn nearest_ranks_by_edit_distance() {
+    assert_eq!(levenshtein("kitten", "sitting"), 3);
+    let keys = [
+        "audit.enabled",
+        "audit.path",
+        "projections.photos",
+        "projections.videos",
+    ];
+    assert_eq!(nearest("audit.enable", keys), vec!["audit.enabled"]);
+    assert_eq!(
+        nearest("projections.photo", keys),
+        vec!["projections.photos"]
+    );
+    assert_eq!(
+        nearest("PROJECTIONS.VIDEO", keys),
+        vec!["projections.videos"]
+    );
+    assert!(nearest("colour", keys).is_empty());
+}
+
+#[test]
+fn unmatched_fields_suggest_known_paths() {
+    let items = vec![
+        json!({"id": 1, "photographer": "a", "src": {"original": "o"}}),
+        json!({"id": 2, "video_files": [{"link": "l"}]}),
+    ];
+    let fields: Vec<String> = [
+        "id",
+        "photograper",
+        "src.orignal",
+        "video_files.link",
+        "@ids",
+        "src.*",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    let missing = unmatched_fields(&items, &fields);
+    assert_eq!(
+        missing,
+        vec![
+            ("photograper".to_string(), vec!["photographer".to_string()]),
+            ("src.orignal".to_string(), vec!["src.original".to_string()]),
+        ]
+    );
+    assert!(unmatched_fields(&[], &fields).is_empty());
+
+    let w = fields_warning(&missing, items.len()).unwrap();
+    assert_eq!(w["warning"], "fields matched nothing");
+    assert_eq!(w["items_checked"], 2);
+    assert_eq!(w["fields"][1]["path"], "src.orignal");
+    assert_eq!(w["fields"][1]["did_you_mean"], json!(["src.original"]));
+    assert!(fields_warning(&[], 2).is_none());
+}
+
+#[test]
+fn null_values_count_as_found() {
+    let items = vec![json!({"id": 1, "alt": null}), json!({"id": 2, "alt": null})];
+    assert!(unmatched_fields(&items, &["alt".to_string()]).is_empty());
+}
@@ -0,0 +1,125 @@
+use flate2::read::GzDecoder;
+use pexels::config::Config;
+use pexels::support::{redacted_config, tail_lines, write_bundle};
+use std::io::Read;
+
+#[test]
+fn redacted_config_hides_token() {
+    let cfg = Config {
+        token: Some("secret-token-value".into()),
+        ..Default::default()
+    };
+    let yaml = redacted_config(&cfg).unwrap();
+    assert!(!yaml.contains("secret-token-value"));
+    assert!(yaml.contains("<redacted: 18 chars>"));
+}
+
+#[test]
+fn redacted_config_hides_profile_tokens() {
+    let mut cfg = Config::default();
+    for (name, token) in [("work", "work-token-1"), ("home", "home-token-22")] {
+        cfg.profiles.insert(
+            name.into(),
+            pexels::config::Profile {
+                token: Some(token.into()),
+                locale: Some("de-DE".into()),
+                ..Default::default()
+            },
+        );
+    }
+    let yaml = redacted_config(&cfg).unwrap();
+    assert!(!yaml.contains("work-token-1") && !yaml.contains("home-token-22"));
+    assert!(yaml.contains("<redacted: 12 chars>"));
+    assert!(yaml.contains("<redacted: 13 chars>"));
+    assert!(yaml.contains("de-DE"));
+}
+
+#[test]
+fn redacted_config_hides_remote_passwords() {
+    let mut cfg = Config::default();
+    cfg.remotes.insert(
+        "dav.example".into(),
+        pexels::config::RemoteAuth {
+            user: Some("ana".into()),
+            password: Some("dav-password".into()),
+            ..Default::default()
+        },
+    );
+    let yaml = redacted_config(&cfg).unwrap();
+    assert!(!yaml.contains("dav-password"));
+    assert!(yaml.contains("<redacted: 12 chars>"));
+    assert!(yaml.contains("ana"));
+}
+
+#[test]
+fn redacted_config_hides_host_header_values() {
+    let mut cfg = Config::default();
+    let mut gw = pexels::config::HostProfile {
+        base: Some("https://gw.example/pexels".into()),
+        ..Default::default()
+    };
+    gw.headers
+        .insert("Authorization".into(), "Bearer gw-secret".into());
+    gw.headers.insert("X-Api-Key".into(), "k-123".into());
+    cfg.hosts.insert("corp".into(), gw);
+    let yaml = redacted_config(&cfg).unwrap();
+    assert!(!yaml.contains("gw-secret") && !yaml.contains("k-123"));
+    assert!(
+        yaml.contains("Authorization: '<redacted: 16 chars>'"),
+        "{}",
+        yaml
+    );
+    assert!(
+        yaml.contains("X-Api-Key: '<redacted: 5 chars>'"),
+        "{}",
+        yaml
+    );
+    assert!(yaml.contains("https://gw.example/pexels"));
+}
+
+#[test]
+fn tail_lines_keeps_last_n() {
+    let dir = std::env::temp_dir().join(format!("pexels-tail-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("log.jsonl");
+    std::fs::write(&path, "a\nb\nc\n").unwrap();
+    assert_eq!(tail_lines(&path, 2).unwrap(), "b\nc\n");
+    assert!(tail_lines(&dir.join("missing"), 2).is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bundle_round_trips_entries() {
+    let dir = std::env::temp_dir().join(format!("pexels-bundle-{}", std::process::id()));
+    let out = dir.join("bundle.tar.gz");
+    let entries = vec![
+        ("version.json".to_string(), b"{}".to_vec()),
+        ("doctor.json".to_string(), b"{\"ok\":true}".to_vec()),
+    ];
+    assert!(write_bundle(&out, &entries).unwrap() > 0);
+    let mut archive = tar::Archive::new(GzDecoder::new(std::fs::File::open(&out).unwrap()));
+    let mut seen = vec![];
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let name = entry.path().unwrap().display().to_string();
+        let mut body = String::new();
+        entry.read_to_string(&mut body).unwrap();
+        seen.push((name, body));
+    }
+    assert_eq!(seen[0], ("pexels-support/version.json".into(), "{}".into()));
+    assert_eq!(seen[1].0, "pexels-support/doctor.json");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn build_info_reports_every_optional_feature() {
+    let info = pexels::support::build_info();
+    let features = info["features"].as_object().unwrap();
+    let names: Vec<&str> = features.keys().map(|k| k.as_str()).collect();
+    assert_eq!(
+        names,
+        ["blocking", "fault-injection", "ffi", "nats", "parquet"]
+    );
+    assert_eq!(features["nats"], cfg!(feature = "nats"));
+    assert_eq!(features["parquet"], cfg!(feature = "parquet"));
+}
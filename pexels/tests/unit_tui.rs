@@ -0,0 +1,61 @@
+use pexels::tui::{details, Browser};
+use serde_json::json;
+
+fn page(ids: &[u64], next: bool) -> serde_json::Value {
+    let photos: Vec<_> = ids
+        .iter()
+        .map(|id| {
+            json!({"id": id, "photographer": "Ann", "width": 4000, "height": 3000,
+            "src": {"original": format!("https://images.pexels.com/photos/{id}/a.jpeg")}})
+        })
+        .collect();
+    json!({
+        "page": 1,
+        "photos": photos,
+        "next_page": if next { json!("https://api.pexels.com/v1/curated?page=2") } else { json!(null) },
+    })
+}
+
+#[test]
+fn marks_survive_paging() {
+    let mut b = Browser::default();
+    b.load(1, &page(&[1, 2, 3], true));
+    assert_eq!((b.prev_page(), b.next_page()), (None, Some(2)));
+    b.down();
+    b.toggle();
+    b.load(2, &page(&[4, 5], false));
+    assert_eq!(b.cursor, 0);
+    assert_eq!((b.prev_page(), b.next_page()), (Some(1), None));
+    b.toggle();
+    let ids: Vec<_> = b.marked().iter().map(|m| m["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(2), json!(4)]);
+    // Unmarking on the page it came back from
+    b.load(1, &page(&[1, 2, 3], true));
+    b.down();
+    b.toggle();
+    assert_eq!(b.marked().len(), 1);
+}
+
+#[test]
+fn toggle_page_marks_rest_then_clears() {
+    let mut b = Browser::default();
+    b.load(1, &page(&[1, 2, 3], false));
+    b.toggle();
+    b.toggle_page();
+    assert_eq!(b.marked().len(), 3);
+    b.toggle_page();
+    assert!(b.marked().is_empty());
+    b.down();
+    b.down();
+    b.down();
+    assert_eq!(b.cursor, 2);
+}
+
+#[test]
+fn details_lists_present_fields() {
+    let item = page(&[7], false)["photos"][0].clone();
+    let d = details(&item);
+    let keys: Vec<_> = d.iter().map(|(k, _)| *k).collect();
+    assert_eq!(keys, vec!["id", "photographer", "size", "file"]);
+    assert_eq!(d[2].1, "4000x3000");
+}
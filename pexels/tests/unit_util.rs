@@ -0,0 +1,13 @@
+use pexels::util::parse_duration;
+use std::time::Duration;
+
+#[test]
+fn parse_duration_units() {
+    assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+    assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+    assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+    assert!(parse_duration("10x").is_err());
+    assert!(parse_duration("5m3").is_err());
+    assert!(parse_duration("").is_err());
+}
@@ -0,0 +1,87 @@
+use pexels::video::{select_file, Rendition, VideoQuality};
+use serde_json::json;
+
+fn video() -> serde_json::Value {
+    json!({
+        "id": 7,
+        "video_files": [
+            {"quality": "sd", "width": 640, "height": 360, "fps": 25, "link": "sd640"},
+            {"quality": "hd", "width": 1280, "height": 720, "fps": 25, "link": "hd1280"},
+            {"quality": "hd", "width": 1920, "height": 1080, "fps": 30, "link": "hd1920"},
+            {"quality": "uhd", "width": 3840, "height": 2160, "fps": 30, "link": "uhd"},
+        ]
+    })
+}
+
+#[test]
+fn picks_widest_matching_rendition() {
+    let v = video();
+    let link = |quality, max_width| {
+        let want = Rendition {
+            quality,
+            max_width,
+            max_height: None,
+        };
+        select_file(&v, &want).map(|f| f["link"].as_str().unwrap().to_string())
+    };
+    assert_eq!(link(None, None).as_deref(), Some("uhd"));
+    assert_eq!(
+        link(Some(VideoQuality::Hd), None).as_deref(),
+        Some("hd1920")
+    );
+    assert_eq!(
+        link(Some(VideoQuality::Hd), Some(1280)).as_deref(),
+        Some("hd1280")
+    );
+    assert_eq!(link(None, Some(1000)).as_deref(), Some("sd640"));
+    assert!(link(Some(VideoQuality::Uhd), Some(1920)).is_none());
+    let short = Rendition {
+        max_height: Some(720),
+        ..Default::default()
+    };
+    assert_eq!(select_file(&v, &short).unwrap()["link"], "hd1280");
+}
+
+#[test]
+fn no_match_error_lists_renditions() {
+    let want = Rendition {
+        quality: Some(VideoQuality::Uhd),
+        max_width: Some(100),
+        max_height: None,
+    };
+    let err = pexels::video::no_match_error(&video(), &want);
+    let msg = err.to_string();
+    assert!(msg.contains("no matching video file"));
+    assert!(msg.contains("hd 1920x1080"));
+}
+
+#[test]
+fn duration_buckets_count_half_open_ranges() {
+    use pexels::video::parse_buckets;
+    let b = parse_buckets("duration:0-10,10-30,30+").unwrap();
+    let items: Vec<_> = [3, 10, 29, 30, 300]
+        .iter()
+        .map(|d| json!({ "duration": d }))
+        .chain([json!({})])
+        .collect();
+    let out = b.counts(&items);
+    let counts: Vec<u64> = out["counts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["count"].as_u64().unwrap())
+        .collect();
+    assert_eq!(counts, vec![1, 2, 2]);
+    assert_eq!(out["counts"][2]["range"], "30+");
+    assert_eq!(out["unbucketed"], 1);
+    assert!(parse_buckets("duration:10-5").is_err());
+    assert!(parse_buckets("width:0-10").is_err());
+}
+
+#[test]
+fn retain_duration_drops_out_of_range_videos() {
+    let mut data = json!({"videos": [{"duration": 5}, {"duration": 15}, {"duration": 45}]});
+    let dropped = pexels::video::retain_duration(&mut data, Some(10), Some(30));
+    assert_eq!(dropped, 2);
+    assert_eq!(data["videos"], json!([{"duration": 15}]));
+}